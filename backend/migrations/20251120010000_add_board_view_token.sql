@@ -0,0 +1,12 @@
+-- Add read-only view token to boards
+ALTER TABLE boards
+ADD COLUMN view_token VARCHAR(255);
+
+-- Backfill existing boards with a random token
+UPDATE boards
+SET view_token = substr(md5(random()::text || id::text), 1, 12)
+WHERE view_token IS NULL;
+
+ALTER TABLE boards
+ALTER COLUMN view_token SET NOT NULL,
+ADD CONSTRAINT boards_view_token_key UNIQUE (view_token);
@@ -0,0 +1,7 @@
+-- Add soft-archive support to cards
+ALTER TABLE cards
+ADD COLUMN archived_at TIMESTAMPTZ;
+
+-- Active cards are looked up by column and position
+CREATE INDEX idx_cards_column_active ON cards(column_id, position)
+WHERE archived_at IS NULL;
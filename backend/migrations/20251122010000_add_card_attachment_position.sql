@@ -0,0 +1,14 @@
+-- Add user-controlled ordering to card attachments
+ALTER TABLE card_attachments
+ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+-- Preserve the existing upload order
+UPDATE card_attachments ca
+SET position = ordered.rn - 1
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY card_id ORDER BY created_at, id) AS rn
+    FROM card_attachments
+) ordered
+WHERE ca.id = ordered.id;
+
+CREATE INDEX idx_card_attachments_card_position ON card_attachments(card_id, position);
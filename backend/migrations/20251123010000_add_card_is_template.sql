@@ -0,0 +1,3 @@
+-- Cards flagged as templates can be copied into new cards on the same board
+ALTER TABLE cards
+ADD COLUMN is_template BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,30 @@
+-- Terminal columns (e.g. "Done") mark the cards in them as completed
+ALTER TABLE columns
+ADD COLUMN is_terminal BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- When a card last reached a terminal column; NULL while it is outside one
+ALTER TABLE cards
+ADD COLUMN completed_at TIMESTAMPTZ;
+
+-- Keep completed_at in step with the card's column
+CREATE OR REPLACE FUNCTION set_card_completed_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM columns WHERE id = NEW.column_id AND is_terminal) THEN
+        IF TG_OP = 'INSERT' THEN
+            NEW.completed_at = NOW();
+        ELSE
+            -- Moving between terminal columns keeps the original completion time
+            NEW.completed_at = COALESCE(OLD.completed_at, NOW());
+        END IF;
+    ELSE
+        NEW.completed_at = NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_cards_completed_at
+    BEFORE INSERT OR UPDATE OF column_id ON cards
+    FOR EACH ROW
+    EXECUTE FUNCTION set_card_completed_at();
@@ -0,0 +1,14 @@
+-- Add user-controlled ordering to board labels
+ALTER TABLE board_labels
+ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+-- Preserve the existing creation order
+UPDATE board_labels bl
+SET position = ordered.rn - 1
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY board_id ORDER BY created_at, id) AS rn
+    FROM board_labels
+) ordered
+WHERE bl.id = ordered.id;
+
+CREATE INDEX idx_board_labels_board_position ON board_labels(board_id, position);
@@ -0,0 +1,10 @@
+-- Users subscribed to changes on a card
+CREATE TABLE card_watchers (
+    card_id UUID NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (card_id, user_id)
+);
+
+-- Look up the cards a user watches
+CREATE INDEX idx_card_watchers_user_id ON card_watchers(user_id);
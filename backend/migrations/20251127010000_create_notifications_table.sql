@@ -0,0 +1,12 @@
+-- In-app notifications addressed to a single user
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- List a user's notifications, newest first
+CREATE INDEX idx_notifications_user_id_created_at ON notifications(user_id, created_at DESC);
@@ -0,0 +1,3 @@
+-- Add soft-archive support to columns; their cards are kept as they are
+ALTER TABLE columns
+ADD COLUMN archived_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- Add an optional accent color to cards, independent of their labels
+ALTER TABLE cards
+ADD COLUMN color VARCHAR(20);
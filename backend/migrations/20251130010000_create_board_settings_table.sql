@@ -0,0 +1,6 @@
+-- Per-board feature flags; boards without a row use the defaults
+CREATE TABLE board_settings (
+    board_id UUID PRIMARY KEY REFERENCES boards(id) ON DELETE CASCADE,
+    settings JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+-- Stable IDs boards are known by in the presence protocol, which carries a u32
+--
+-- A board keeps the ID it is first given. Boards get the hash of their UUID
+-- that clients derived before IDs were stored, or the next free value when
+-- another board holds it. Boards created before this table are mapped at
+-- startup, oldest first, so existing presence rooms and peak statistics keep
+-- their ID.
+CREATE TABLE board_presence_ids (
+    board_id UUID PRIMARY KEY REFERENCES boards(id) ON DELETE CASCADE,
+    presence_id BIGINT NOT NULL UNIQUE CHECK (presence_id BETWEEN 0 AND 4294967295),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,137 @@
+use actix_web::{
+    Error,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::Method,
+    middleware::Next,
+    web,
+};
+
+use crate::config::Config;
+use crate::error::AppError;
+
+/// Non-GET routes that do not change board data and stay open in read-only mode
+///
+/// Sign-in, token refresh and sign-out only touch sessions, so users stay
+/// signed in through a maintenance window.
+const READ_ONLY_ALLOWED_PATHS: &[&str] = &[
+    "/api/auth/login",
+    "/api/auth/refresh",
+    "/api/auth/logout",
+    "/api/boards/summaries",
+    "/api/cards/ai/generate-description",
+];
+
+/// Whether a request may be served while `READ_ONLY` is set
+///
+/// # Arguments
+/// * `method` - Request method
+/// * `path` - Request path
+///
+/// # Returns
+/// * `bool` - True for reads and for the writes in `READ_ONLY_ALLOWED_PATHS`
+pub fn allowed_when_read_only(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || READ_ONLY_ALLOWED_PATHS.contains(&path)
+}
+
+/// Reject writes with 503 while the server is in read-only mode
+///
+/// Wrapped around the whole app; does nothing unless `READ_ONLY` is set.
+pub async fn reject_writes_when_read_only(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let read_only = req
+        .app_data::<web::Data<Config>>()
+        .is_some_and(|config| config.read_only);
+
+    if read_only && !allowed_when_read_only(req.method(), req.path()) {
+        return Err(AppError::ServiceUnavailable(
+            "Fluxboard is in read-only mode for maintenance. Changes cannot be saved right now"
+                .to_string(),
+        )
+        .into());
+    }
+
+    next.call(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::{App, HttpResponse, middleware, test};
+
+    fn config(read_only: bool) -> Config {
+        Config {
+            read_only,
+            ..Config::for_tests()
+        }
+    }
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    macro_rules! app {
+        ($read_only:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data(web::Data::new(config($read_only)))
+                    .wrap(middleware::from_fn(reject_writes_when_read_only))
+                    .route("/api/boards", web::get().to(ok))
+                    .route("/api/boards", web::post().to(ok))
+                    .route("/api/auth/login", web::post().to(ok)),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn create_is_rejected_with_503_in_read_only_mode() {
+        let app = app!(true);
+
+        let req = test::TestRequest::post().uri("/api/boards").to_request();
+        let Err(err) = test::try_call_service(&app, req).await else {
+            panic!("create was served in read-only mode");
+        };
+
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[actix_web::test]
+    async fn get_still_works_in_read_only_mode() {
+        let app = app!(true);
+
+        let req = test::TestRequest::get().uri("/api/boards").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn sign_in_stays_open_in_read_only_mode() {
+        let app = app!(true);
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn create_is_served_outside_read_only_mode() {
+        let app = app!(false);
+
+        let req = test::TestRequest::post().uri("/api/boards").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
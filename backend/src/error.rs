@@ -21,6 +21,10 @@ pub enum AppError {
     ValidationError(String),
     /// Internal server error
     InternalError(String),
+    /// Temporarily unavailable (e.g., read-only maintenance mode)
+    ServiceUnavailable(String),
+    /// Request body over the configured size limit
+    PayloadTooLarge(String),
 }
 
 impl fmt::Display for AppError {
@@ -34,6 +38,8 @@ impl fmt::Display for AppError {
             AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             AppError::InternalError(msg) => write!(f, "Internal error: {}", msg),
+            AppError::ServiceUnavailable(msg) => write!(f, "Service unavailable: {}", msg),
+            AppError::PayloadTooLarge(msg) => write!(f, "Payload too large: {}", msg),
         }
     }
 }
@@ -67,6 +73,8 @@ impl ResponseError for AppError {
                 log::error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
             }
+            AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
         };
 
         HttpResponse::build(status).json(ErrorResponse {
@@ -85,6 +93,8 @@ impl ResponseError for AppError {
             AppError::Conflict(_) => StatusCode::CONFLICT,
             AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
             AppError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
         }
     }
 }
@@ -0,0 +1,114 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::{AppError, AppResult};
+use crate::services::{BoardService, CardService, S3Service};
+
+/// Header carrying the admin token
+const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// Query parameters for purging archived items
+#[derive(Debug, Deserialize)]
+pub struct PurgeArchivedQuery {
+    /// Items archived more than this many days ago are purged
+    pub days: u32,
+}
+
+/// What a purge of archived items deleted
+#[derive(Debug, Serialize)]
+pub struct PurgeArchivedResponse {
+    /// Archived columns deleted, with their cards
+    pub purged_columns: u64,
+    /// Archived cards deleted
+    pub purged_cards: u64,
+    /// Attachments deleted along with them
+    pub purged_attachments: usize,
+}
+
+/// Check the request carries the configured admin token
+///
+/// Admin routes do not exist unless `ADMIN_TOKEN` is set.
+fn require_admin(config: &Config, req: &HttpRequest) -> AppResult<()> {
+    let Some(ref admin_token) = config.admin_token else {
+        return Err(AppError::NotFound(
+            "Admin endpoints are disabled. Configure ADMIN_TOKEN to enable them".to_string(),
+        ));
+    };
+
+    let provided = req
+        .headers()
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    // Compare digests so the comparison time does not depend on the token
+    if Sha256::digest(provided.as_bytes()) != Sha256::digest(admin_token.as_bytes()) {
+        return Err(AppError::Unauthorized("Invalid admin token".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Permanently delete columns and cards archived longer than `older_than`
+///
+/// Shared by the admin endpoint and the scheduled purge. Attachments go with
+/// their cards; their S3 objects are deleted best effort afterwards.
+pub async fn purge_archived_items(
+    pool: &PgPool,
+    s3_service: &S3Service,
+    older_than: Duration,
+) -> AppResult<PurgeArchivedResponse> {
+    let columns = BoardService::purge_archived(pool, older_than).await?;
+    let cards = CardService::purge_archived(pool, older_than).await?;
+
+    // Delete from S3 (best effort - the rows are already gone)
+    let s3_keys: Vec<&String> = columns.s3_keys.iter().chain(&cards.s3_keys).collect();
+    for s3_key in &s3_keys {
+        if let Err(e) = s3_service.delete_object(s3_key).await {
+            log::error!("Failed to delete S3 object {}: {}", s3_key, e);
+        }
+    }
+
+    log::info!(
+        "Purged {} archived columns, {} archived cards and {} attachments",
+        columns.deleted,
+        cards.deleted,
+        s3_keys.len()
+    );
+
+    Ok(PurgeArchivedResponse {
+        purged_columns: columns.deleted,
+        purged_cards: cards.deleted,
+        purged_attachments: s3_keys.len(),
+    })
+}
+
+/// Permanently delete columns and cards archived more than `days` days ago
+/// POST /admin/purge-archived?days=
+pub async fn purge_archived(
+    pool: web::Data<PgPool>,
+    s3_service: web::Data<Arc<S3Service>>,
+    config: web::Data<Config>,
+    query: web::Query<PurgeArchivedQuery>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_admin(config.get_ref(), &req)?;
+
+    if query.days == 0 {
+        return Err(AppError::BadRequest("days must be at least 1".to_string()));
+    }
+
+    let response = purge_archived_items(
+        pool.get_ref(),
+        s3_service.get_ref(),
+        Duration::days(query.days.into()),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
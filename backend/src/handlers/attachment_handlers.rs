@@ -1,3 +1,5 @@
+use actix_web::body::SizedStream;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
 use actix_web::{HttpRequest, HttpResponse, web};
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -7,26 +9,47 @@ use validator::Validate;
 use crate::auth_middleware::auth::AuthenticatedUser;
 use crate::config::Config;
 use crate::error::{AppError, AppResult};
-use crate::models::{Board, Card, CardAttachment, Column, UploadUrlRequest, UploadUrlResponse};
+use crate::models::{
+    Board, Card, CardAttachment, Column, DownloadDisposition, DownloadQuery,
+    ReorderAttachmentsRequest, UploadUrlRequest, UploadUrlResponse,
+};
 use crate::services::S3Service;
 use crate::sse::events::SseEvent;
 use crate::sse::manager::SseManager;
 
 /// Helper function to check if a board operation is allowed
-fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
+async fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     if !board.is_locked {
         return true;
     }
 
     if let Some(password_header) = req.headers().get("X-Board-Password") {
         if let Ok(password_str) = password_header.to_str() {
-            return password_str == board.password;
+            return board.verify_password(password_str).await;
         }
     }
 
     false
 }
 
+/// Build the Content-Disposition header for an attachment download
+fn content_disposition(
+    attachment: &CardAttachment,
+    disposition: DownloadDisposition,
+) -> ContentDisposition {
+    let disposition = match disposition {
+        DownloadDisposition::Inline => DispositionType::Inline,
+        DownloadDisposition::Attachment => DispositionType::Attachment,
+    };
+
+    ContentDisposition {
+        disposition,
+        parameters: vec![DispositionParam::Filename(
+            attachment.original_filename.clone(),
+        )],
+    }
+}
+
 /// Helper function to get board from card_id
 async fn get_board_from_card(pool: &PgPool, card_id: Uuid) -> AppResult<Board> {
     let card = Card::find_by_id(pool, card_id)
@@ -63,7 +86,7 @@ pub async fn generate_upload_url(
     // Check if card exists and get board for password verification
     let board = get_board_from_card(pool.get_ref(), card_id).await?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot upload attachments to a locked board".to_string(),
         ));
@@ -160,6 +183,19 @@ pub async fn confirm_attachment(
         attachment_id
     );
 
+    // Fast path: a retried confirmation returns the attachment without
+    // re-verifying S3 or re-broadcasting AttachmentCreated
+    if let Some(existing) = CardAttachment::find_by_id(pool.get_ref(), attachment_id).await?
+        && existing.card_id == card_id
+        && existing.is_confirmed
+    {
+        log::info!(
+            "[Confirm] Attachment {} already confirmed, skipping",
+            attachment_id
+        );
+        return Ok(HttpResponse::Ok().json(existing));
+    }
+
     // Check if card exists and get board for password verification
     let board = match get_board_from_card(pool.get_ref(), card_id).await {
         Ok(b) => {
@@ -176,7 +212,7 @@ pub async fn confirm_attachment(
         }
     };
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         log::error!(
             "[Confirm] Board operation not allowed for board_id={}",
             board.id
@@ -230,15 +266,27 @@ pub async fn confirm_attachment(
 
     // Confirm attachment
     log::info!("[Confirm] Marking attachment as confirmed");
-    let confirmed_attachment = CardAttachment::confirm(pool.get_ref(), attachment_id)
-        .await?
-        .ok_or_else(|| {
-            log::error!(
-                "[Confirm] Failed to confirm attachment with id={}",
+    let confirmed_attachment = match CardAttachment::confirm(pool.get_ref(), attachment_id).await? {
+        Some(confirmed) => confirmed,
+        None => {
+            // A concurrent request confirmed it first and has already broadcast the event
+            let existing = CardAttachment::find_by_id(pool.get_ref(), attachment_id)
+                .await?
+                .filter(|a| a.is_confirmed)
+                .ok_or_else(|| {
+                    log::error!(
+                        "[Confirm] Failed to confirm attachment with id={}",
+                        attachment_id
+                    );
+                    AppError::NotFound("Attachment not found".to_string())
+                })?;
+            log::info!(
+                "[Confirm] Attachment {} confirmed concurrently, skipping broadcast",
                 attachment_id
             );
-            AppError::NotFound("Attachment not found".to_string())
-        })?;
+            return Ok(HttpResponse::Ok().json(existing));
+        }
+    };
 
     log::info!("[Confirm] Attachment confirmed successfully");
 
@@ -280,11 +328,63 @@ pub async fn list_card_attachments(
     Ok(HttpResponse::Ok().json(attachments))
 }
 
+/// Reorder a card's attachments
+pub async fn reorder_attachments(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    card_id: web::Path<Uuid>,
+    input: web::Json<ReorderAttachmentsRequest>,
+    _user: AuthenticatedUser,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let card_id = card_id.into_inner();
+    let input = input.into_inner();
+
+    // Check board permissions
+    let board = get_board_from_card(pool.get_ref(), card_id).await?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot reorder attachments on a locked board".to_string(),
+        ));
+    }
+
+    // Every listed attachment must belong to this card
+    let existing = CardAttachment::find_by_card_id(pool.get_ref(), card_id).await?;
+    if let Some((unknown_id, _)) = input
+        .attachment_positions
+        .iter()
+        .find(|(id, _)| !existing.iter().any(|attachment| attachment.id == *id))
+    {
+        return Err(AppError::BadRequest(format!(
+            "Attachment {} does not belong to this card",
+            unknown_id
+        )));
+    }
+
+    let attachments =
+        CardAttachment::reorder(pool.get_ref(), card_id, input.attachment_positions).await?;
+
+    // Broadcast SSE event
+    sse_manager
+        .broadcast(
+            board.id,
+            SseEvent::AttachmentsReordered {
+                card_id,
+                attachment_ids: attachments.iter().map(|attachment| attachment.id).collect(),
+            },
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(attachments))
+}
+
 /// Generate a pre-signed download URL for an attachment
 pub async fn generate_download_url(
     pool: web::Data<PgPool>,
     s3_service: web::Data<Arc<S3Service>>,
     attachment_id: web::Path<Uuid>,
+    query: web::Query<DownloadQuery>,
 ) -> AppResult<HttpResponse> {
     let attachment_id = attachment_id.into_inner();
 
@@ -301,13 +401,64 @@ pub async fn generate_download_url(
     }
 
     // Generate pre-signed download URL
-    let download_url = s3_service.generate_download_url(&attachment.s3_key).await?;
+    let disposition = content_disposition(&attachment, attachment.disposition(query.disposition));
+    let download_url = s3_service
+        .generate_download_url(&attachment.s3_key, &disposition.to_string())
+        .await?;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "download_url": download_url
     })))
 }
 
+/// Stream an attachment through the backend
+///
+/// Alternative to pre-signed URLs for deployments where clients cannot
+/// reach S3 directly. Only available when `S3_DOWNLOAD_PROXY_ENABLED` is set.
+pub async fn download_attachment(
+    pool: web::Data<PgPool>,
+    s3_service: web::Data<Arc<S3Service>>,
+    config: web::Data<Config>,
+    attachment_id: web::Path<Uuid>,
+    query: web::Query<DownloadQuery>,
+) -> AppResult<HttpResponse> {
+    if !config.s3_download_proxy_enabled {
+        return Err(AppError::NotFound(
+            "Attachment download proxy is disabled".to_string(),
+        ));
+    }
+
+    let attachment_id = attachment_id.into_inner();
+
+    // Get attachment
+    let attachment = CardAttachment::find_by_id(pool.get_ref(), attachment_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+
+    // Only allow confirmed attachments
+    if !attachment.is_confirmed {
+        return Err(AppError::BadRequest(
+            "Attachment is not confirmed yet".to_string(),
+        ));
+    }
+
+    let object = s3_service.get_object(&attachment.s3_key).await?;
+    let disposition = content_disposition(&attachment, attachment.disposition(query.disposition));
+
+    let mut response = HttpResponse::Ok();
+    response
+        .content_type(attachment.content_type.as_str())
+        .insert_header(disposition);
+
+    // Forward the object size when S3 reports it so clients can show progress
+    match object.content_length {
+        Some(content_length) if content_length >= 0 => {
+            Ok(response.body(SizedStream::new(content_length as u64, object.body)))
+        }
+        _ => Ok(response.streaming(object.body)),
+    }
+}
+
 /// Delete an attachment
 pub async fn delete_attachment(
     pool: web::Data<PgPool>,
@@ -334,7 +485,7 @@ pub async fn delete_attachment(
     // Check board permissions
     let board = get_board_from_card(pool.get_ref(), attachment.card_id).await?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot delete attachments from a locked board".to_string(),
         ));
@@ -367,3 +518,273 @@ pub async fn delete_attachment(
 
     Ok(HttpResponse::NoContent().finish())
 }
+
+/// Delete all confirmed attachments of a card
+///
+/// Only allowed when the user uploaded every one of them.
+pub async fn delete_card_attachments(
+    pool: web::Data<PgPool>,
+    s3_service: web::Data<Arc<S3Service>>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    card_id: web::Path<Uuid>,
+    user: AuthenticatedUser,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let card_id = card_id.into_inner();
+
+    // Check board permissions
+    let board = get_board_from_card(pool.get_ref(), card_id).await?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot delete attachments from a locked board".to_string(),
+        ));
+    }
+
+    // Verify user owns every attachment
+    let attachments = CardAttachment::find_by_card_id(pool.get_ref(), card_id).await?;
+    if attachments
+        .iter()
+        .any(|attachment| attachment.uploaded_by != user.user_id)
+    {
+        return Err(AppError::Forbidden(
+            "You can only delete your own attachments".to_string(),
+        ));
+    }
+
+    if attachments.is_empty() {
+        return Ok(HttpResponse::NoContent().finish());
+    }
+
+    // Delete from database
+    let s3_keys = CardAttachment::delete_by_card_id(pool.get_ref(), card_id).await?;
+
+    // Delete from S3 (best effort - don't fail if S3 deletion fails)
+    for s3_key in &s3_keys {
+        if let Err(e) = s3_service.delete_object(s3_key).await {
+            log::error!("Failed to delete S3 object {}: {}", s3_key, e);
+        }
+    }
+
+    // Broadcast SSE event
+    sse_manager
+        .broadcast(
+            board.id,
+            SseEvent::AttachmentsDeleted {
+                card_id,
+                attachment_ids: attachments.iter().map(|attachment| attachment.id).collect(),
+            },
+        )
+        .await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fake_s3::FakeS3;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{TestRequest, call_service, init_service, read_body};
+    use actix_web::{App, HttpMessage};
+
+    /// Insert a card on a new board and return its id
+    async fn create_card(pool: &PgPool) -> Uuid {
+        let board_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title) VALUES ('sharetoken01', 'viewtoken001', 'Board') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let column_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO columns (board_id, title, position) VALUES ($1, 'To do', 0) RETURNING id",
+        )
+        .bind(board_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        sqlx::query_scalar(
+            "INSERT INTO cards (column_id, title, position) VALUES ($1, 'Card', 0) RETURNING id",
+        )
+        .bind(column_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn create_user(pool: &PgPool) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash) VALUES ('user@example.com', 'x') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn create_attachment(
+        pool: &PgPool,
+        card_id: Uuid,
+        user_id: Uuid,
+        s3_key: &str,
+        is_confirmed: bool,
+    ) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO card_attachments (card_id, uploaded_by, filename, original_filename, \
+             content_type, file_size, s3_key, s3_bucket, is_confirmed, position) \
+             VALUES ($1, $2, 'report.pdf', 'Q3 report.pdf', 'application/pdf', 1, $3, 'fluxboard', $4, 0) \
+             RETURNING id",
+        )
+        .bind(card_id)
+        .bind(user_id)
+        .bind(s3_key)
+        .bind(is_confirmed)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn proxy_config() -> Config {
+        Config {
+            s3_download_proxy_enabled: true,
+            ..Config::for_tests()
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn download_streams_the_object_body(pool: PgPool) {
+        let card_id = create_card(&pool).await;
+        let user_id = create_user(&pool).await;
+        let s3_key = format!("attachments/{}/report.pdf", card_id);
+        let attachment_id = create_attachment(&pool, card_id, user_id, &s3_key, true).await;
+
+        let config = proxy_config();
+        let s3 = FakeS3::start(&config).await;
+        let object: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+        s3.put(&s3_key, &object);
+
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .app_data(web::Data::new(Arc::new(s3.service(&config))))
+                .app_data(web::Data::new(config))
+                .route(
+                    "/attachments/{attachment_id}/download",
+                    web::get().to(download_attachment),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri(&format!("/attachments/{}/download", attachment_id))
+            .to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/pdf"
+        );
+        let disposition = resp
+            .headers()
+            .get("content-disposition")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(disposition.starts_with("attachment"));
+        assert!(disposition.contains("Q3 report.pdf"));
+        assert_eq!(read_body(resp).await, object);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn download_rejects_unconfirmed_attachments(pool: PgPool) {
+        let card_id = create_card(&pool).await;
+        let user_id = create_user(&pool).await;
+        let s3_key = format!("attachments/{}/report.pdf", card_id);
+        let attachment_id = create_attachment(&pool, card_id, user_id, &s3_key, false).await;
+
+        let config = proxy_config();
+        let s3 = FakeS3::start(&config).await;
+        s3.put(&s3_key, b"not yet confirmed");
+
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .app_data(web::Data::new(Arc::new(s3.service(&config))))
+                .app_data(web::Data::new(config))
+                .route(
+                    "/attachments/{attachment_id}/download",
+                    web::get().to(download_attachment),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri(&format!("/attachments/{}/download", attachment_id))
+            .to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn bulk_delete_removes_every_attachment_and_emits_one_event(pool: PgPool) {
+        let card_id = create_card(&pool).await;
+        let user_id = create_user(&pool).await;
+        let board_id = get_board_from_card(&pool, card_id).await.unwrap().id;
+
+        let config = Config::for_tests();
+        let s3 = FakeS3::start(&config).await;
+        let mut attachment_ids = Vec::new();
+        let mut s3_keys = Vec::new();
+        for n in 0..3 {
+            let s3_key = format!("attachments/{}/{}.pdf", card_id, n);
+            s3.put(&s3_key, b"%PDF");
+            attachment_ids.push(create_attachment(&pool, card_id, user_id, &s3_key, true).await);
+            s3_keys.push(s3_key);
+        }
+
+        let sse_manager = Arc::new(SseManager::new());
+        let (_, mut events) = sse_manager.subscribe(board_id, None).await;
+
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(Arc::new(s3.service(&config))))
+                .app_data(web::Data::new(sse_manager.clone()))
+                .route(
+                    "/cards/{card_id}/attachments",
+                    web::delete().to(delete_card_attachments),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::delete()
+            .uri(&format!("/cards/{}/attachments", card_id))
+            .to_request();
+        req.extensions_mut().insert(AuthenticatedUser { user_id });
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let remaining = CardAttachment::find_by_card_id(&pool, card_id)
+            .await
+            .unwrap();
+        assert!(remaining.is_empty());
+        assert!(s3_keys.iter().all(|s3_key| !s3.contains(s3_key)));
+
+        let event = events.recv().await.unwrap().unwrap();
+        match event.event() {
+            SseEvent::AttachmentsDeleted {
+                card_id: event_card_id,
+                attachment_ids: event_ids,
+            } => {
+                assert_eq!(*event_card_id, card_id);
+                assert_eq!(event_ids, &attachment_ids);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(events.try_recv().is_err());
+    }
+}
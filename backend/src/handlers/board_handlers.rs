@@ -1,27 +1,43 @@
+use actix_web::http::header::{
+    ContentDisposition, DispositionParam, DispositionType, ETag, EntityTag, Header, IfMatch,
+    IfNoneMatch,
+};
 use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use sqlx::PgPool;
 use std::sync::Arc;
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
-use crate::models::{Board, CreateBoardInput, SetLockStateInput, UpdateBoardInput};
-use crate::services::BoardService;
+use crate::models::board::BoardPublic;
+use crate::models::{
+    Board, BoardSummariesInput, CreateBoardInput, RegenerateShareTokenInput, SetLockStateInput,
+    UpdateBoardInput, UpdateBoardSettingsInput,
+};
+use crate::services::presence_service::{BoardStateChange, MAX_PEAK_PRESENCE_DAYS};
+use crate::services::{BoardService, ExportService, PresenceService, S3Service};
 use crate::sse::events::SseEvent;
 use crate::sse::manager::SseManager;
+use crate::utils::is_valid_share_token;
+
+/// Bytes buffered between the ZIP writer and the response of a board export
+const EXPORT_PIPE_SIZE: usize = 64 * 1024;
 
 /// Helper function to check if a board operation is allowed
 ///
 /// For locked boards, only requests with the correct password in X-Board-Password header are allowed
-fn check_board_password(is_locked: bool, password: &str, req: &HttpRequest) -> bool {
+async fn check_board_password(board: &Board, req: &HttpRequest) -> bool {
     // If board is not locked, allow all operations
-    if !is_locked {
+    if !board.is_locked {
         return true;
     }
 
     // Board is locked - check if request has correct password
     if let Some(password_header) = req.headers().get("X-Board-Password") {
         if let Ok(password_str) = password_header.to_str() {
-            return password_str == password;
+            return board.verify_password(password_str).await;
         }
     }
 
@@ -29,6 +45,26 @@ fn check_board_password(is_locked: bool, password: &str, req: &HttpRequest) -> b
     false
 }
 
+/// Query parameters for loading a board by share token
+#[derive(Deserialize)]
+pub struct BoardQuery {
+    /// Also return archived columns and their cards
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// Query parameters for peak presence metrics
+#[derive(Deserialize)]
+pub struct PeakPresenceQuery {
+    /// Number of days to report, today included
+    #[serde(default = "default_peak_presence_days")]
+    pub days: u32,
+}
+
+fn default_peak_presence_days() -> u32 {
+    7
+}
+
 /// Create a new board
 pub async fn create_board(
     pool: web::Data<PgPool>,
@@ -44,18 +80,210 @@ pub async fn list_boards(pool: web::Data<PgPool>) -> AppResult<HttpResponse> {
     Ok(HttpResponse::Ok().json(boards))
 }
 
-/// Get a board by ID
+/// Get a board by ID, without its password or share token
 pub async fn get_board(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> AppResult<HttpResponse> {
-    let board = BoardService::get_board_by_id(pool.get_ref(), id.into_inner()).await?;
+    let board = BoardService::get_board_public(pool.get_ref(), id.into_inner()).await?;
     Ok(HttpResponse::Ok().json(board))
 }
 
+/// Get summaries for several boards in one request
+///
+/// POST /boards/summaries with `{ "ids": [...] }`
+pub async fn get_board_summaries(
+    pool: web::Data<PgPool>,
+    input: web::Json<BoardSummariesInput>,
+) -> AppResult<HttpResponse> {
+    let summaries =
+        BoardService::get_board_summaries(pool.get_ref(), input.into_inner().ids).await?;
+    Ok(HttpResponse::Ok().json(summaries))
+}
+
+/// Get cycle time statistics for a board
+///
+/// GET /boards/{id}/metrics/cycle-time
+pub async fn get_cycle_time_metrics(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+    let stats = BoardService::get_cycle_time(pool.get_ref(), board_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "board_id": board_id,
+        "completed_cards": stats.completed_cards,
+        "average_cycle_time_seconds": stats.average_seconds,
+    })))
+}
+
+/// Get column and card counts for a board
+///
+/// GET /boards/{id}/metrics/counts
+pub async fn get_count_metrics(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+    let counts = BoardService::get_counts(pool.get_ref(), board_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "board_id": board_id,
+        "column_count": counts.column_count,
+        "card_count": counts.card_count,
+        "columns": counts.columns,
+    })))
+}
+
+/// Get the most users on a board at once, per day
+///
+/// GET /boards/{id}/metrics/peak-presence?days=
+pub async fn get_peak_presence_metrics(
+    pool: web::Data<PgPool>,
+    presence_service: Option<web::Data<Arc<PresenceService>>>,
+    id: web::Path<Uuid>,
+    query: web::Query<PeakPresenceQuery>,
+) -> AppResult<HttpResponse> {
+    let presence_service = presence_service.ok_or_else(|| {
+        AppError::NotFound(
+            "Presence statistics are disabled. Configure REDIS_URL to enable them".to_string(),
+        )
+    })?;
+    if query.days == 0 || query.days > MAX_PEAK_PRESENCE_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_PEAK_PRESENCE_DAYS
+        )));
+    }
+
+    let board_id = id.into_inner();
+    BoardService::get_board_by_id(pool.get_ref(), board_id).await?;
+    let presence_id = PresenceService::get_or_assign_presence_id(pool.get_ref(), board_id).await?;
+
+    let today = Utc::now().date_naive();
+    let days = presence_service
+        .get_peak_presence(presence_id, query.days, today)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "board_id": board_id,
+        "presence_board_id": presence_id,
+        "days": days,
+    })))
+}
+
+/// Check whether an `If-None-Match` header matches the current ETag
+fn etag_matches(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
+}
+
+/// Get a board by ID with columns, cards, labels and attachments
+///
+/// Responds with the board's fingerprint as an `ETag`, and with
+/// 304 Not Modified when `If-None-Match` already matches it.
+pub async fn get_board_with_relations(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+
+    // Fingerprint before loading, so a concurrent change yields a stale tag, never a stale body
+    let etag =
+        EntityTag::new_strong(BoardService::board_fingerprint(pool.get_ref(), board_id).await?);
+    if etag_matches(&req, &etag) {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .finish());
+    }
+
+    let board =
+        BoardService::get_board_by_id_with_relations(pool.get_ref(), board_id, false).await?;
+    Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(board))
+}
+
+/// Download a board as a ZIP archive with its attachments
+///
+/// GET /boards/{id}/export.zip
+///
+/// The archive is written while it is sent, through a bounded in-memory pipe,
+/// so large attachments are never held in memory. Archived columns are
+/// included. A failure after the response has started can only cut the
+/// download short; it is logged.
+pub async fn export_board_zip(
+    pool: web::Data<PgPool>,
+    s3_service: web::Data<Arc<S3Service>>,
+    id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+    let board =
+        BoardService::get_board_by_id_with_relations(pool.get_ref(), board_id, true).await?;
+
+    let (writer, reader) = tokio::io::duplex(EXPORT_PIPE_SIZE);
+    let s3_service = s3_service.get_ref().clone();
+    tokio::spawn(async move {
+        if let Err(e) = ExportService::write_board_zip(&s3_service, &board, writer).await {
+            log::error!("Export of board {} failed: {}", board.id, e);
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!(
+                "board-{}.zip",
+                board_id
+            ))],
+        })
+        .streaming(ReaderStream::new(reader)))
+}
+
+/// Reject share or view tokens that cannot have been generated
+///
+/// Malformed tokens fail here instead of costing a database lookup
+fn validate_token_format(token: &str) -> AppResult<()> {
+    if is_valid_share_token(token) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(
+            "Invalid board token format".to_string(),
+        ))
+    }
+}
+
 /// Get a board by share token
+/// GET /boards/share/{token}?include_archived=true
 pub async fn get_board_by_share_token(
     pool: web::Data<PgPool>,
     token: web::Path<String>,
+    query: web::Query<BoardQuery>,
 ) -> AppResult<HttpResponse> {
-    let board = BoardService::get_board_by_share_token(pool.get_ref(), &token.into_inner()).await?;
+    let share_token = token.into_inner();
+    validate_token_format(&share_token)?;
+
+    let board = BoardService::get_board_by_share_token(
+        pool.get_ref(),
+        &share_token,
+        query.include_archived,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(board))
+}
+
+/// Get a read-only view of a board by view token
+///
+/// The view carries no ids or share token, so viewers cannot address edit routes
+pub async fn get_board_by_view_token(
+    pool: web::Data<PgPool>,
+    token: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let view_token = token.into_inner();
+    validate_token_format(&view_token)?;
+
+    let board = BoardService::get_board_by_view_token(pool.get_ref(), &view_token).await?;
     Ok(HttpResponse::Ok().json(board))
 }
 
@@ -68,11 +296,14 @@ pub async fn update_board_by_share_token(
     req: HttpRequest,
 ) -> AppResult<HttpResponse> {
     let share_token = token.into_inner();
+    validate_token_format(&share_token)?;
 
     // Get board first to check lock status
-    let existing_board = BoardService::get_board_by_share_token(pool.get_ref(), &share_token).await?;
+    let existing_board = Board::find_by_share_token(pool.get_ref(), &share_token)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !check_board_password(existing_board.is_locked, &existing_board.password, &req) {
+    if !check_board_password(&existing_board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot update a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -88,7 +319,7 @@ pub async fn update_board_by_share_token(
         .broadcast(
             board.id,
             SseEvent::BoardUpdated {
-                board: board.clone(),
+                board: board.clone().into(),
             },
         )
         .await;
@@ -109,14 +340,17 @@ pub async fn update_board(
     // Get board first to check lock status
     let existing_board = BoardService::get_board_by_id(pool.get_ref(), board_id).await?;
 
-    if !check_board_password(existing_board.is_locked, &existing_board.password, &req) {
+    if !check_board_password(&existing_board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot update a locked board. Only the board owner can edit locked boards."
                 .to_string(),
         ));
     }
 
-    let board = BoardService::update_board(pool.get_ref(), board_id, input.into_inner()).await?;
+    let board: BoardPublic =
+        BoardService::update_board(pool.get_ref(), board_id, input.into_inner())
+            .await?
+            .into();
 
     // Broadcast board update via SSE
     sse_manager
@@ -131,20 +365,130 @@ pub async fn update_board(
     Ok(HttpResponse::Ok().json(board))
 }
 
+/// Get a board's feature flags
+pub async fn get_board_settings(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+    BoardService::get_board_by_id(pool.get_ref(), board_id).await?;
+
+    let settings = BoardService::get_settings(pool.get_ref(), board_id).await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// Update a board's feature flags
+pub async fn update_board_settings(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    input: web::Json<UpdateBoardSettingsInput>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+
+    // Get board first to check lock status
+    let existing_board = BoardService::get_board_by_id(pool.get_ref(), board_id).await?;
+
+    if !check_board_password(&existing_board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot change settings of a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let settings =
+        BoardService::update_settings(pool.get_ref(), board_id, input.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// Tell the presence service about a board state change, logging failures
+///
+/// Best effort: the change has already been saved, and presence is optional.
+async fn publish_board_state_change(
+    presence_service: Option<&web::Data<Arc<PresenceService>>>,
+    presence_id: u32,
+    event: BoardStateChange,
+) {
+    let Some(presence_service) = presence_service else {
+        return;
+    };
+    if let Err(e) = presence_service
+        .publish_board_state_change(presence_id, event)
+        .await
+    {
+        log::warn!(
+            "Failed to publish {:?} for presence board {}: {}",
+            event,
+            presence_id,
+            e
+        );
+    }
+}
+
 /// Delete a board
-pub async fn delete_board(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> AppResult<HttpResponse> {
-    BoardService::delete_board(pool.get_ref(), id.into_inner()).await?;
+///
+/// Closes the board's presence room when presence is configured.
+pub async fn delete_board(
+    pool: web::Data<PgPool>,
+    presence_service: Option<web::Data<Arc<PresenceService>>>,
+    id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let board_id = id.into_inner();
+    // Read before deleting: the presence ID row goes with the board
+    let presence_id = PresenceService::find_presence_id(pool.get_ref(), board_id).await?;
+
+    BoardService::delete_board(pool.get_ref(), board_id).await?;
+
+    if let Some(presence_id) = presence_id {
+        publish_board_state_change(
+            presence_service.as_ref(),
+            presence_id,
+            BoardStateChange::Deleted,
+        )
+        .await;
+    }
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// A board's version as an entity tag: its `updated_at` timestamp
+fn board_version_tag(board: &Board) -> EntityTag {
+    EntityTag::new_strong(board.updated_at.to_rfc3339())
+}
+
+/// Read the board version a request expects from its `If-Match` header
+fn expected_board_version(req: &HttpRequest) -> AppResult<DateTime<Utc>> {
+    let missing = || {
+        AppError::BadRequest("If-Match header with the board's updated_at is required".to_string())
+    };
+
+    match IfMatch::parse(req) {
+        Ok(IfMatch::Items(tags)) => match tags.as_slice() {
+            [tag] => DateTime::parse_from_rfc3339(tag.tag())
+                .map(|version| version.with_timezone(&Utc))
+                .map_err(|_| missing()),
+            _ => Err(missing()),
+        },
+        _ => Err(missing()),
+    }
+}
+
 /// Lock or unlock a board
+///
+/// Requires the board version the caller last saw in `If-Match`, so two
+/// owners toggling at once cannot silently overwrite each other's decision.
+/// Responds with the new version as an `ETag`. Locking closes the board's
+/// presence room when presence is configured.
 pub async fn set_board_lock_state(
     pool: web::Data<PgPool>,
     sse_manager: web::Data<Arc<SseManager>>,
+    presence_service: Option<web::Data<Arc<PresenceService>>>,
     token: web::Path<String>,
     input: web::Json<SetLockStateInput>,
+    req: HttpRequest,
 ) -> AppResult<HttpResponse> {
     let share_token = token.into_inner();
+    validate_token_format(&share_token)?;
+    let expected_version = expected_board_version(&req)?;
     let lock_input = input.into_inner();
 
     let board = BoardService::set_board_lock_state(
@@ -152,6 +496,7 @@ pub async fn set_board_lock_state(
         &share_token,
         &lock_input.password,
         lock_input.is_locked,
+        expected_version,
     )
     .await?;
 
@@ -160,10 +505,116 @@ pub async fn set_board_lock_state(
         .broadcast(
             board.id,
             SseEvent::BoardUpdated {
-                board: board.clone(),
+                board: board.clone().into(),
             },
         )
         .await;
 
-    Ok(HttpResponse::Ok().json(board))
+    if board.is_locked && presence_service.is_some() {
+        let presence_id =
+            PresenceService::get_or_assign_presence_id(pool.get_ref(), board.id).await?;
+        publish_board_state_change(
+            presence_service.as_ref(),
+            presence_id,
+            BoardStateChange::Locked,
+        )
+        .await;
+    }
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(board_version_tag(&board)))
+        .json(board))
+}
+
+/// Regenerate a board's share token, invalidating the old one
+pub async fn regenerate_share_token(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    input: web::Json<RegenerateShareTokenInput>,
+) -> AppResult<HttpResponse> {
+    let share_token =
+        BoardService::regenerate_share_token(pool.get_ref(), id.into_inner(), &input.password)
+            .await?;
+
+    // Not broadcast over SSE: subscribers who only know the old token must not learn the new one
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "share_token": share_token
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::App;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{TestRequest, call_service, init_service, read_body_json};
+
+    async fn create_board(pool: &PgPool, share_token: &str, view_token: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title) VALUES ($1, $2, 'Board') RETURNING id",
+        )
+        .bind(share_token)
+        .bind(view_token)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn view_token_reads_but_cannot_write(pool: PgPool) {
+        let board_id = create_board(&pool, "sharetoken01", "viewtoken001").await;
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(Arc::new(SseManager::new())))
+                .route(
+                    "/boards/view/{token}",
+                    web::get().to(get_board_by_view_token),
+                )
+                .route(
+                    "/boards/share/{token}",
+                    web::put().to(update_board_by_share_token),
+                )
+                .route(
+                    "/boards/share/{token}/lock",
+                    web::post().to(set_board_lock_state),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/boards/view/viewtoken001")
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let view: serde_json::Value = read_body_json(resp).await;
+        assert_eq!(view["title"], "Board");
+        assert!(view.get("id").is_none());
+        assert!(view.get("share_token").is_none());
+
+        let req = TestRequest::put()
+            .uri("/boards/share/viewtoken001")
+            .set_json(serde_json::json!({ "title": "Renamed" }))
+            .to_request();
+        assert_eq!(
+            call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let board = Board::find_by_id(&pool, board_id).await.unwrap().unwrap();
+        let req = TestRequest::post()
+            .uri("/boards/share/viewtoken001/lock")
+            .insert_header(IfMatch::Items(vec![board_version_tag(&board)]))
+            .set_json(serde_json::json!({ "password": "secret", "is_locked": true }))
+            .to_request();
+        assert_eq!(
+            call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let board = Board::find_by_id(&pool, board_id).await.unwrap().unwrap();
+        assert_eq!(board.title, "Board");
+        assert!(!board.is_locked);
+    }
 }
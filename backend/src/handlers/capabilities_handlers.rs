@@ -0,0 +1,44 @@
+use actix_web::{HttpResponse, web};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::AppResult;
+
+/// Longest username the presence service accepts, in UTF-8 bytes
+///
+/// Mirrors `MAX_USERNAME_LENGTH` in `presence-service/src/protocol/types.rs`.
+pub const PRESENCE_MAX_USERNAME_LENGTH: usize = 32;
+
+/// Optional features available on this server
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    /// AI description generation is available
+    pub ai_enabled: bool,
+    /// Attachments can be streamed through `GET /attachments/{id}/download`
+    pub download_proxy_enabled: bool,
+    /// Longest presence username in UTF-8 bytes; longer names are truncated
+    pub presence_max_username_length: usize,
+    /// Longest card description in bytes
+    pub max_card_description_length: usize,
+    /// Writes are rejected for maintenance; boards can be viewed but not edited
+    pub read_only: bool,
+}
+
+impl Capabilities {
+    /// Derive capabilities from configuration
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ai_enabled: config.ai_enabled(),
+            download_proxy_enabled: config.s3_download_proxy_enabled,
+            presence_max_username_length: PRESENCE_MAX_USERNAME_LENGTH,
+            max_card_description_length: config.max_card_description_length,
+            read_only: config.read_only,
+        }
+    }
+}
+
+/// Get the optional features enabled on this server
+/// GET /capabilities
+pub async fn get_capabilities(config: web::Data<Config>) -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(Capabilities::from_config(config.get_ref())))
+}
@@ -4,16 +4,21 @@ use sqlx::PgPool;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::auth_middleware::auth::AuthenticatedUser;
+use crate::config::Config;
 use crate::error::{AppError, AppResult};
-use crate::models::{Board, Column, UpdateCardInput};
-use crate::services::{AiService, CardService};
-use crate::sse::events::SseEvent;
+use crate::models::{Board, BoardLabel, CardSearchQuery, Column, Notification, UpdateCardInput};
+use crate::services::{AiService, BoardService, CardService, NotificationService, WatcherService};
+use crate::sse::events::{CardChange, SseEvent};
 use crate::sse::manager::SseManager;
 
+/// Response header carrying a created card's committed position
+const CARD_POSITION_HEADER: &str = "X-Card-Position";
+
 /// Helper function to check if a board operation is allowed
 ///
 /// For locked boards, only requests with the correct password in X-Board-Password header are allowed
-fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
+async fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     // If board is not locked, allow all operations
     if !board.is_locked {
         return true;
@@ -22,7 +27,7 @@ fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     // Board is locked - check if request has correct password
     if let Some(password_header) = req.headers().get("X-Board-Password") {
         if let Ok(password_str) = password_header.to_str() {
-            return password_str == board.password;
+            return board.verify_password(password_str).await;
         }
     }
 
@@ -30,12 +35,70 @@ fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     false
 }
 
+/// Notify the users watching a card that it changed
+///
+/// Each watcher gets an in-app notification and a `CardChanged` event on their
+/// notification stream. Best effort: the change is already committed, so
+/// failures are only logged.
+async fn notify_card_watchers(
+    pool: &PgPool,
+    sse_manager: &SseManager,
+    card_id: Uuid,
+    board_id: Uuid,
+    change: CardChange,
+) {
+    let user_ids = match WatcherService::get_watcher_ids(pool, card_id).await {
+        Ok(user_ids) => user_ids,
+        Err(e) => {
+            log::warn!("Failed to load watchers of card {}: {}", card_id, e);
+            return;
+        }
+    };
+
+    let payload = serde_json::json!({
+        "card_id": card_id,
+        "board_id": board_id,
+        "change": change,
+    });
+    for user_id in &user_ids {
+        if let Err(e) = NotificationService::create(
+            pool,
+            *user_id,
+            Notification::KIND_CARD_CHANGED,
+            payload.clone(),
+        )
+        .await
+        {
+            log::warn!(
+                "Failed to notify user {} about card {}: {}",
+                user_id,
+                card_id,
+                e
+            );
+        }
+    }
+
+    sse_manager
+        .notify_users(
+            &user_ids,
+            SseEvent::CardChanged {
+                card_id,
+                board_id,
+                change,
+            },
+        )
+        .await;
+}
+
 /// Request body for creating a card
 #[derive(Deserialize)]
 pub struct CreateCardRequest {
     pub title: String,
     pub description: Option<String>,
-    pub position: i32,
+    /// Accent color (hex or color name); omit for none
+    pub color: Option<String>,
+    /// Omit to append the card to the end of the column
+    pub position: Option<i32>,
 }
 
 /// Request body for moving a card
@@ -57,6 +120,10 @@ pub struct GenerateDescriptionRequest {
     pub title: String,
     pub context: Option<String>,
     pub format: DescriptionFormat,
+    /// Overrides the configured persona for this request
+    pub persona: Option<String>,
+    /// Board the card belongs to, so its `ai_enabled` setting is honored
+    pub board_id: Option<Uuid>,
 }
 
 /// Description format type
@@ -65,6 +132,10 @@ pub struct GenerateDescriptionRequest {
 pub enum DescriptionFormat {
     Bullets,
     Long,
+    /// Client-supplied prompt with `{title}`/`{context}` placeholders
+    Custom {
+        template: String,
+    },
 }
 
 /// Response for AI generation
@@ -77,6 +148,7 @@ pub struct GenerateDescriptionResponse {
 pub async fn create_card(
     pool: web::Data<PgPool>,
     sse_manager: web::Data<Arc<SseManager>>,
+    config: web::Data<Config>,
     column_id: web::Path<Uuid>,
     input: web::Json<CreateCardRequest>,
     req: HttpRequest,
@@ -93,21 +165,27 @@ pub async fn create_card(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot create cards on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
         ));
     }
 
-    let card = CardService::create_card(
+    let settings = BoardService::get_settings(pool.get_ref(), board.id).await?;
+    CardService::check_wip_limit(pool.get_ref(), col_id, settings.wip_limit).await?;
+
+    let (card, shifted) = CardService::create_card(
         pool.get_ref(),
         col_id,
         input.title,
         input.description,
+        input.color,
         input.position,
+        config.max_card_description_length,
     )
     .await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast card creation via SSE
     sse_manager
@@ -117,7 +195,82 @@ pub async fn create_card(
         )
         .await;
 
-    Ok(HttpResponse::Created().json(card))
+    // Let clients reconcile cards that moved down to make room
+    for shifted_card in shifted {
+        sse_manager
+            .broadcast_with(
+                column.board_id,
+                SseEvent::CardReordered {
+                    card_id: shifted_card.id,
+                    column_id: shifted_card.column_id,
+                    new_position: shifted_card.position,
+                },
+                settings.coalesce_reorders,
+            )
+            .await;
+    }
+
+    Ok(HttpResponse::Created()
+        .insert_header((CARD_POSITION_HEADER, card.position.to_string()))
+        .json(card))
+}
+
+/// Create a new card from a template card
+///
+/// POST /columns/{column_id}/cards/from-template/{template_id}
+pub async fn create_card_from_template(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let (col_id, template_id) = path.into_inner();
+
+    let column = Column::find_by_id(pool.get_ref(), col_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Column not found".to_string()))?;
+
+    let board = Board::find_by_id(pool.get_ref(), column.board_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot create cards on a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let settings = BoardService::get_settings(pool.get_ref(), board.id).await?;
+    CardService::check_wip_limit(pool.get_ref(), col_id, settings.wip_limit).await?;
+
+    let card = CardService::create_from_template(pool.get_ref(), template_id, col_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    sse_manager
+        .broadcast(
+            column.board_id,
+            SseEvent::CardCreated { card: card.clone() },
+        )
+        .await;
+
+    // Labels copied from the template
+    let labels = BoardLabel::find_by_card_id(pool.get_ref(), card.id).await?;
+    for label in labels {
+        sse_manager
+            .broadcast(
+                column.board_id,
+                SseEvent::CardLabelAssigned {
+                    card_id: card.id,
+                    label,
+                },
+            )
+            .await;
+    }
+
+    Ok(HttpResponse::Created()
+        .insert_header((CARD_POSITION_HEADER, card.position.to_string()))
+        .json(card))
 }
 
 /// Get a card by ID
@@ -126,10 +279,41 @@ pub async fn get_card(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> AppResult
     Ok(HttpResponse::Ok().json(card))
 }
 
+/// Search a board's cards
+///
+/// GET /boards/{board_id}/cards/search?q=...&label_ids=id1,id2
+///
+/// Results carry a `score`; cards with any of `label_ids` rank higher, and
+/// recently updated cards get a small boost.
+pub async fn search_cards(
+    pool: web::Data<PgPool>,
+    board_id: web::Path<Uuid>,
+    query: web::Query<CardSearchQuery>,
+) -> AppResult<HttpResponse> {
+    let query = query.into_inner();
+    let label_ids = query
+        .label_ids
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            Uuid::parse_str(id)
+                .map_err(|_| AppError::BadRequest(format!("Invalid label ID '{}'", id)))
+        })
+        .collect::<AppResult<Vec<Uuid>>>()?;
+
+    let cards =
+        CardService::search(pool.get_ref(), board_id.into_inner(), &query.q, &label_ids).await?;
+    Ok(HttpResponse::Ok().json(cards))
+}
+
 /// Update a card
 pub async fn update_card(
     pool: web::Data<PgPool>,
     sse_manager: web::Data<Arc<SseManager>>,
+    config: web::Data<Config>,
     id: web::Path<Uuid>,
     input: web::Json<UpdateCardInput>,
     req: HttpRequest,
@@ -149,14 +333,29 @@ pub async fn update_card(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot update cards on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
         ));
     }
 
-    let card = CardService::update_card(pool.get_ref(), card_id, input.into_inner()).await?;
+    let input = input.into_inner();
+    if let Some(target_column_id) = input.column_id
+        && target_column_id != existing_card.column_id
+    {
+        let settings = BoardService::get_settings(pool.get_ref(), board.id).await?;
+        CardService::check_wip_limit(pool.get_ref(), target_column_id, settings.wip_limit).await?;
+    }
+
+    let card = CardService::update_card(
+        pool.get_ref(),
+        card_id,
+        input,
+        config.max_card_description_length,
+    )
+    .await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Get the column to find the board_id
     if let Ok(Some(column)) = Column::find_by_id(pool.get_ref(), card.column_id).await {
@@ -167,6 +366,14 @@ pub async fn update_card(
                 SseEvent::CardUpdated { card: card.clone() },
             )
             .await;
+        notify_card_watchers(
+            pool.get_ref(),
+            &sse_manager,
+            card.id,
+            column.board_id,
+            CardChange::Updated,
+        )
+        .await;
     }
 
     Ok(HttpResponse::Ok().json(card))
@@ -194,7 +401,7 @@ pub async fn delete_card(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot delete cards on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -202,6 +409,7 @@ pub async fn delete_card(
     }
 
     CardService::delete_card(pool.get_ref(), card_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast card deletion via SSE
     sse_manager
@@ -211,6 +419,82 @@ pub async fn delete_card(
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// Merge the source card into the target card
+///
+/// POST /cards/{target_id}/merge/{source_id}
+pub async fn merge_cards(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    config: web::Data<Config>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let (target_id, source_id) = path.into_inner();
+
+    let target = crate::models::Card::find_by_id(pool.get_ref(), target_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Card not found".to_string()))?;
+
+    let column = Column::find_by_id(pool.get_ref(), target.column_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Column not found".to_string()))?;
+
+    let board = Board::find_by_id(pool.get_ref(), column.board_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot merge cards on a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let labels_before = BoardLabel::find_by_card_id(pool.get_ref(), target_id).await?;
+
+    let card = CardService::merge_cards(
+        pool.get_ref(),
+        source_id,
+        target_id,
+        config.max_card_description_length,
+    )
+    .await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    sse_manager
+        .broadcast(
+            column.board_id,
+            SseEvent::CardUpdated { card: card.clone() },
+        )
+        .await;
+
+    // Labels the target picked up from the source
+    let labels_after = BoardLabel::find_by_card_id(pool.get_ref(), target_id).await?;
+    for label in labels_after {
+        if labels_before.iter().any(|l| l.id == label.id) {
+            continue;
+        }
+        sse_manager
+            .broadcast(
+                column.board_id,
+                SseEvent::CardLabelAssigned {
+                    card_id: target_id,
+                    label,
+                },
+            )
+            .await;
+    }
+
+    sse_manager
+        .broadcast(
+            column.board_id,
+            SseEvent::CardDeleted { card_id: source_id },
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(card))
+}
+
 /// Move a card to a different column
 pub async fn move_card(
     pool: web::Data<PgPool>,
@@ -237,15 +521,22 @@ pub async fn move_card(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot move cards on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
         ));
     }
 
+    // Reordering within a column never changes its card count
+    if input.column_id != from_column_id {
+        let settings = BoardService::get_settings(pool.get_ref(), board.id).await?;
+        CardService::check_wip_limit(pool.get_ref(), input.column_id, settings.wip_limit).await?;
+    }
+
     let card =
         CardService::move_card(pool.get_ref(), card_id, input.column_id, input.position).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Get the column to find the board_id
     if let Ok(Some(column)) = Column::find_by_id(pool.get_ref(), card.column_id).await {
@@ -261,6 +552,14 @@ pub async fn move_card(
                 },
             )
             .await;
+        notify_card_watchers(
+            pool.get_ref(),
+            &sse_manager,
+            card.id,
+            column.board_id,
+            CardChange::Moved,
+        )
+        .await;
     }
 
     Ok(HttpResponse::Ok().json(card))
@@ -286,7 +585,7 @@ pub async fn reorder_cards(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot reorder cards on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -294,17 +593,21 @@ pub async fn reorder_cards(
     }
 
     CardService::reorder_cards(pool.get_ref(), col_id, card_positions.clone()).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    let settings = BoardService::get_settings(pool.get_ref(), board.id).await?;
 
     // Broadcast SSE events for each reordered card
     for (card_id, new_position) in card_positions {
         sse_manager
-            .broadcast(
+            .broadcast_with(
                 column.board_id,
                 SseEvent::CardReordered {
                     card_id,
                     column_id: col_id,
                     new_position,
                 },
+                settings.coalesce_reorders,
             )
             .await;
     }
@@ -312,33 +615,248 @@ pub async fn reorder_cards(
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Archive all cards in a column
+pub async fn archive_column_cards(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    column_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let col_id = column_id.into_inner();
+
+    // Get the board_id from the column and check lock status
+    let column = Column::find_by_id(pool.get_ref(), col_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Column not found".to_string()))?;
+
+    let board = Board::find_by_id(pool.get_ref(), column.board_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot archive cards on a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let card_ids = CardService::archive_column_cards(pool.get_ref(), col_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    // Broadcast a single event for the whole column
+    if !card_ids.is_empty() {
+        sse_manager
+            .broadcast(
+                column.board_id,
+                SseEvent::ColumnCardsArchived {
+                    column_id: col_id,
+                    card_ids: card_ids.clone(),
+                },
+            )
+            .await;
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "column_id": col_id,
+        "card_ids": card_ids,
+    })))
+}
+
 /// Generate AI description for a card
 pub async fn generate_description(
+    pool: web::Data<PgPool>,
     ai_service: Option<web::Data<Arc<AiService>>>,
+    config: web::Data<Config>,
     input: web::Json<GenerateDescriptionRequest>,
 ) -> AppResult<HttpResponse> {
     // Check if AI service is available
-    let ai_service = ai_service.ok_or_else(|| {
-        AppError::BadRequest(
-            "AI service not configured. Please add GEMINI_API_KEY to .env".to_string(),
-        )
-    })?;
+    let ai_service = ai_service.ok_or_else(ai_disabled_error)?;
 
     let input = input.into_inner();
+    if let Some(board_id) = input.board_id {
+        let settings = BoardService::get_settings(pool.get_ref(), board_id).await?;
+        if !settings.ai_enabled {
+            return Err(AppError::Forbidden(
+                "AI description generation is disabled for this board".to_string(),
+            ));
+        }
+    }
     let context = input.context.unwrap_or_default();
+    CardService::validate_description(Some(&context), config.max_card_description_length)?;
+
+    // Blank overrides fall back to the configured persona
+    let persona = input
+        .persona
+        .as_deref()
+        .map(str::trim)
+        .filter(|persona| !persona.is_empty());
+    if let Some(persona) = persona {
+        AiService::validate_persona(persona)?;
+    }
+
+    if let DescriptionFormat::Custom { template } = &input.format {
+        AiService::validate_template(template)?;
+    }
 
     let description = match input.format {
         DescriptionFormat::Bullets => {
             ai_service
-                .generate_bullet_points(&input.title, &context)
+                .generate_bullet_points(&input.title, &context, persona)
                 .await?
         }
         DescriptionFormat::Long => {
             ai_service
-                .generate_long_description(&input.title, &context)
+                .generate_long_description(&input.title, &context, persona)
+                .await?
+        }
+        DescriptionFormat::Custom { template } => {
+            ai_service
+                .generate_from_template(&template, &input.title, &context, persona)
                 .await?
         }
     };
 
     Ok(HttpResponse::Ok().json(GenerateDescriptionResponse { description }))
 }
+
+/// Fallback for AI routes when no Gemini API key is configured
+pub async fn ai_feature_disabled() -> AppResult<HttpResponse> {
+    Err(ai_disabled_error())
+}
+
+fn ai_disabled_error() -> AppError {
+    AppError::NotFound("AI feature is disabled. Configure GEMINI_API_KEY to enable it".to_string())
+}
+
+/// Watch a card to be notified when it changes
+/// POST /cards/{card_id}/watch
+pub async fn watch_card(
+    pool: web::Data<PgPool>,
+    card_id: web::Path<Uuid>,
+    user: AuthenticatedUser,
+) -> AppResult<HttpResponse> {
+    WatcherService::watch_card(pool.get_ref(), card_id.into_inner(), user.user_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Stop watching a card
+/// DELETE /cards/{card_id}/watch
+pub async fn unwatch_card(
+    pool: web::Data<PgPool>,
+    card_id: web::Path<Uuid>,
+    user: AuthenticatedUser,
+) -> AppResult<HttpResponse> {
+    WatcherService::unwatch_card(pool.get_ref(), card_id.into_inner(), user.user_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::App;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{TestRequest, call_service, init_service};
+
+    /// Insert a board last active a day ago with one column, and return their ids
+    async fn create_column(pool: &PgPool) -> (Uuid, Uuid) {
+        let board_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title, updated_at) \
+             VALUES ('sharetoken01', 'viewtoken001', 'Board', NOW() - INTERVAL '1 day') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let column_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO columns (board_id, title, position) VALUES ($1, 'To do', 0) RETURNING id",
+        )
+        .bind(board_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        (board_id, column_id)
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn creating_a_card_bumps_board_updated_at(pool: PgPool) {
+        let (board_id, column_id) = create_column(&pool).await;
+        let before = Board::find_by_id(&pool, board_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .updated_at;
+
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(Arc::new(SseManager::new())))
+                .app_data(web::Data::new(Config::for_tests()))
+                .route("/columns/{column_id}/cards", web::post().to(create_card)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri(&format!("/columns/{}/cards", column_id))
+            .set_json(serde_json::json!({ "title": "New card" }))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let after = Board::find_by_id(&pool, board_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .updated_at;
+        assert!(after > before + chrono::Duration::hours(23));
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn wip_limit_setting_toggles_card_creation(pool: PgPool) {
+        let (board_id, column_id) = create_column(&pool).await;
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .app_data(web::Data::new(Arc::new(SseManager::new())))
+                .app_data(web::Data::new(Config::for_tests()))
+                .route("/columns/{column_id}/cards", web::post().to(create_card))
+                .route(
+                    "/boards/{id}/settings",
+                    web::patch().to(crate::handlers::board_handlers::update_board_settings),
+                ),
+        )
+        .await;
+
+        let create = || {
+            TestRequest::post()
+                .uri(&format!("/columns/{}/cards", column_id))
+                .set_json(serde_json::json!({ "title": "Card" }))
+                .to_request()
+        };
+        let set_wip_limit = |wip_limit: Option<u32>| {
+            TestRequest::patch()
+                .uri(&format!("/boards/{}/settings", board_id))
+                .set_json(serde_json::json!({ "wip_limit": wip_limit }))
+                .to_request()
+        };
+
+        assert_eq!(
+            call_service(&app, create()).await.status(),
+            StatusCode::CREATED
+        );
+
+        let resp = call_service(&app, set_wip_limit(Some(1))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            call_service(&app, create()).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        let resp = call_service(&app, set_wip_limit(None)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            call_service(&app, create()).await.status(),
+            StatusCode::CREATED
+        );
+    }
+}
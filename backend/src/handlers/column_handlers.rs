@@ -6,14 +6,14 @@ use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
 use crate::models::{Board, UpdateColumnInput};
-use crate::services::ColumnService;
+use crate::services::{BoardService, ColumnService};
 use crate::sse::events::SseEvent;
 use crate::sse::manager::SseManager;
 
 /// Helper function to check if a board operation is allowed
 ///
 /// For locked boards, only requests with the correct password in X-Board-Password header are allowed
-fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
+async fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     // If board is not locked, allow all operations
     if !board.is_locked {
         return true;
@@ -22,7 +22,7 @@ fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     // Board is locked - check if request has correct password
     if let Some(password_header) = req.headers().get("X-Board-Password") {
         if let Ok(password_str) = password_header.to_str() {
-            return password_str == board.password;
+            return board.verify_password(password_str).await;
         }
     }
 
@@ -35,6 +35,8 @@ fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
 pub struct CreateColumnRequest {
     pub title: String,
     pub position: i32,
+    #[serde(default)]
+    pub is_terminal: bool,
 }
 
 /// Request body for reordering columns
@@ -59,15 +61,22 @@ pub async fn create_column(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot create columns on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
         ));
     }
 
-    let column =
-        ColumnService::create_column(pool.get_ref(), b_id, input.title, input.position).await?;
+    let column = ColumnService::create_column(
+        pool.get_ref(),
+        b_id,
+        input.title,
+        input.position,
+        input.is_terminal,
+    )
+    .await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast column creation via SSE
     sse_manager
@@ -101,7 +110,7 @@ pub async fn update_column(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot update columns on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -109,6 +118,7 @@ pub async fn update_column(
     }
 
     let column = ColumnService::update_column(pool.get_ref(), column_id, input.into_inner()).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast column update via SSE
     sse_manager
@@ -141,7 +151,7 @@ pub async fn delete_column(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot delete columns on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -149,6 +159,7 @@ pub async fn delete_column(
     }
 
     ColumnService::delete_column(pool.get_ref(), column_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast column deletion via SSE
     sse_manager
@@ -158,6 +169,85 @@ pub async fn delete_column(
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// Archive a column, hiding it and its cards from the board
+/// POST /columns/{id}/archive
+pub async fn archive_column(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let column_id = id.into_inner();
+
+    // Get column to find board_id, then check board lock status
+    let existing_column = crate::models::Column::find_by_id(pool.get_ref(), column_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Column not found".to_string()))?;
+
+    let board = Board::find_by_id(pool.get_ref(), existing_column.board_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot archive columns on a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let column = ColumnService::archive_column(pool.get_ref(), column_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    // Broadcast column archive via SSE
+    sse_manager
+        .broadcast(column.board_id, SseEvent::ColumnArchived { column_id })
+        .await;
+
+    Ok(HttpResponse::Ok().json(column))
+}
+
+/// Restore an archived column together with its cards
+/// POST /columns/{id}/restore
+pub async fn restore_column(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let column_id = id.into_inner();
+
+    // Get column to find board_id, then check board lock status
+    let existing_column = crate::models::Column::find_by_id(pool.get_ref(), column_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Column not found".to_string()))?;
+
+    let board = Board::find_by_id(pool.get_ref(), existing_column.board_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
+
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(AppError::Unauthorized(
+            "Cannot restore columns on a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let column = ColumnService::restore_column(pool.get_ref(), column_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    // Broadcast column restore via SSE, with the cards it brings back
+    sse_manager
+        .broadcast(
+            column.board_id,
+            SseEvent::ColumnRestored {
+                column: column.clone(),
+            },
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(column))
+}
+
 /// Reorder columns within a board
 pub async fn reorder_columns(
     pool: web::Data<PgPool>,
@@ -174,7 +264,7 @@ pub async fn reorder_columns(
         .await?
         .ok_or_else(|| AppError::NotFound("Board not found".to_string()))?;
 
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(AppError::Unauthorized(
             "Cannot reorder columns on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -182,16 +272,20 @@ pub async fn reorder_columns(
     }
 
     ColumnService::reorder_columns(pool.get_ref(), b_id, column_positions.clone()).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    let settings = BoardService::get_settings(pool.get_ref(), board.id).await?;
 
     // Broadcast column reordering via SSE for each column
     for (column_id, new_position) in column_positions {
         sse_manager
-            .broadcast(
+            .broadcast_with(
                 b_id,
                 SseEvent::ColumnReordered {
                     column_id,
                     new_position,
                 },
+                settings.coalesce_reorders,
             )
             .await;
     }
@@ -6,14 +6,14 @@ use uuid::Uuid;
 
 use crate::error::AppResult;
 use crate::models::{Board, Card, UpdateBoardLabelInput};
-use crate::services::BoardLabelService;
+use crate::services::{BoardLabelService, BoardService};
 use crate::sse::events::SseEvent;
 use crate::sse::manager::SseManager;
 
 /// Helper function to check if a board operation is allowed
 ///
 /// For locked boards, only requests with the correct password in X-Board-Password header are allowed
-fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
+async fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     // If board is not locked, allow all operations
     if !board.is_locked {
         return true;
@@ -22,7 +22,7 @@ fn is_board_operation_allowed(board: &Board, req: &HttpRequest) -> bool {
     // Board is locked - check if request has correct password
     if let Some(password_header) = req.headers().get("X-Board-Password") {
         if let Ok(password_str) = password_header.to_str() {
-            return password_str == board.password;
+            return board.verify_password(password_str).await;
         }
     }
 
@@ -44,6 +44,12 @@ pub struct UpdateBoardLabelRequest {
     pub color: Option<String>,
 }
 
+/// Request body for reordering a board's labels
+#[derive(Deserialize)]
+pub struct ReorderBoardLabelsRequest {
+    pub label_positions: Vec<(Uuid, i32)>,
+}
+
 // ============================================================================
 // Board Label Management Endpoints
 // ============================================================================
@@ -58,6 +64,15 @@ pub async fn list_board_labels(
     Ok(HttpResponse::Ok().json(labels))
 }
 
+/// GET /boards/:boardId/labels/usage - List board labels with card counts
+pub async fn list_board_label_usage(
+    pool: web::Data<PgPool>,
+    board_id: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let usage = BoardLabelService::label_usage(pool.get_ref(), board_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(usage))
+}
+
 /// POST /boards/:boardId/labels - Create a new label for a board
 pub async fn create_board_label(
     pool: web::Data<PgPool>,
@@ -77,7 +92,7 @@ pub async fn create_board_label(
         })?;
 
     // Check if board operation is allowed (locked boards require password)
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(crate::error::AppError::Unauthorized(
             "Cannot create labels on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -86,6 +101,7 @@ pub async fn create_board_label(
 
     let label =
         BoardLabelService::create_label(pool.get_ref(), b_id, input.name, input.color).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast label creation via SSE
     sse_manager
@@ -125,7 +141,7 @@ pub async fn update_board_label(
         })?;
 
     // Check if board operation is allowed (locked boards require password)
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(crate::error::AppError::Unauthorized(
             "Cannot update labels on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -138,6 +154,7 @@ pub async fn update_board_label(
     };
 
     let label = BoardLabelService::update_label(pool.get_ref(), l_id, update_input).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast label update via SSE
     sse_manager
@@ -173,7 +190,7 @@ pub async fn delete_board_label(
         })?;
 
     // Check if board operation is allowed (locked boards require password)
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(crate::error::AppError::Unauthorized(
             "Cannot delete labels on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -181,6 +198,7 @@ pub async fn delete_board_label(
     }
 
     BoardLabelService::delete_label(pool.get_ref(), l_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast label deletion via SSE
     sse_manager
@@ -190,6 +208,48 @@ pub async fn delete_board_label(
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// PATCH /boards/:boardId/labels/reorder - Reorder a board's labels
+pub async fn reorder_board_labels(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    board_id: web::Path<Uuid>,
+    input: web::Json<ReorderBoardLabelsRequest>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let b_id = board_id.into_inner();
+    let label_positions = input.into_inner().label_positions;
+
+    // Verify board exists and check if locked
+    let board = Board::find_by_id(pool.get_ref(), b_id)
+        .await?
+        .ok_or_else(|| {
+            crate::error::AppError::NotFound(format!("Board with ID {} not found", b_id))
+        })?;
+
+    // Check if board operation is allowed (locked boards require password)
+    if !is_board_operation_allowed(&board, &req).await {
+        return Err(crate::error::AppError::Unauthorized(
+            "Cannot reorder labels on a locked board. Only the board owner can edit locked boards."
+                .to_string(),
+        ));
+    }
+
+    let labels = BoardLabelService::reorder_labels(pool.get_ref(), b_id, label_positions).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
+
+    // Broadcast the new label order via SSE
+    sse_manager
+        .broadcast(
+            b_id,
+            SseEvent::LabelsReordered {
+                label_ids: labels.iter().map(|label| label.id).collect(),
+            },
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(labels))
+}
+
 // ============================================================================
 // Card Label Assignment Endpoints
 // ============================================================================
@@ -227,7 +287,7 @@ pub async fn assign_label_to_card(
         })?;
 
     // Check if board operation is allowed (locked boards require password)
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(crate::error::AppError::Unauthorized(
             "Cannot assign labels on a locked board. Only the board owner can edit locked boards."
                 .to_string(),
@@ -235,6 +295,7 @@ pub async fn assign_label_to_card(
     }
 
     BoardLabelService::assign_label_to_card(pool.get_ref(), card_id, label_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast label assignment via SSE
     sse_manager
@@ -272,13 +333,14 @@ pub async fn unassign_label_from_card(
         })?;
 
     // Check if board operation is allowed (locked boards require password)
-    if !is_board_operation_allowed(&board, &req) {
+    if !is_board_operation_allowed(&board, &req).await {
         return Err(crate::error::AppError::Unauthorized(
             "Cannot unassign labels on a locked board. Only the board owner can edit locked boards.".to_string(),
         ));
     }
 
     BoardLabelService::unassign_label_from_card(pool.get_ref(), card_id, label_id).await?;
+    BoardService::touch_board(pool.get_ref(), board.id).await;
 
     // Broadcast label unassignment via SSE
     sse_manager
@@ -3,12 +3,15 @@
 //! This module contains all HTTP request handlers for the REST API.
 //! Handlers are organized by resource type.
 
+pub mod admin_handlers;
 pub mod attachment_handlers;
 pub mod auth_handlers;
 pub mod board_handlers;
+pub mod capabilities_handlers;
 pub mod card_handlers;
 pub mod column_handlers;
 pub mod label_handlers;
+pub mod notification_handlers;
 pub mod sse_handlers;
 
 use crate::auth_middleware::auth::{OptionalAuth, RequireAuth};
@@ -17,8 +20,21 @@ use actix_web::web;
 
 /// Configure all API routes
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    // AI routes short-circuit with 404 when no API key is configured
+    let ai_enabled = Config::from_env().ai_enabled();
+
     cfg.service(
         web::scope("/api")
+            // Admin routes (require ADMIN_TOKEN)
+            .route(
+                "/admin/purge-archived",
+                web::post().to(admin_handlers::purge_archived),
+            )
+            // Feature discovery
+            .route(
+                "/capabilities",
+                web::get().to(capabilities_handlers::get_capabilities),
+            )
             // Auth routes (public)
             .route("/auth/register", web::post().to(auth_handlers::register))
             .route("/auth/login", web::post().to(auth_handlers::login))
@@ -30,19 +46,75 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(auth_handlers::get_current_user))
                     .wrap(RequireAuth::new(Config::from_env())),
             )
-            // SSE route
+            // SSE routes
             .route(
                 "/sse/{share_token}",
                 web::get().to(sse_handlers::board_events_stream),
             )
+            .route(
+                "/sse/board/{id}",
+                web::get().to(sse_handlers::board_events_stream_by_id),
+            )
+            .service(
+                web::resource("/me/events")
+                    .route(web::get().to(sse_handlers::user_events_stream))
+                    .wrap(RequireAuth::new(Config::from_env())),
+            )
+            // Notification routes (require auth)
+            .service(
+                web::resource("/me/notifications")
+                    .route(web::get().to(notification_handlers::list_notifications))
+                    .wrap(RequireAuth::new(Config::from_env())),
+            )
+            .service(
+                web::resource("/me/notifications/{id}/read")
+                    .route(web::post().to(notification_handlers::mark_notification_read))
+                    .wrap(RequireAuth::new(Config::from_env())),
+            )
             // Board routes
             .route("/boards", web::post().to(board_handlers::create_board))
+            .route(
+                "/boards/summaries",
+                web::post().to(board_handlers::get_board_summaries),
+            )
             .route("/boards/{id}", web::get().to(board_handlers::get_board))
             .route("/boards/{id}", web::put().to(board_handlers::update_board))
             .route(
                 "/boards/{id}",
                 web::delete().to(board_handlers::delete_board),
             )
+            .route(
+                "/boards/{id}/settings",
+                web::get().to(board_handlers::get_board_settings),
+            )
+            .route(
+                "/boards/{id}/settings",
+                web::patch().to(board_handlers::update_board_settings),
+            )
+            .route(
+                "/boards/{id}/export.zip",
+                web::get().to(board_handlers::export_board_zip),
+            )
+            .route(
+                "/boards/{id}/full",
+                web::get().to(board_handlers::get_board_with_relations),
+            )
+            .route(
+                "/boards/{id}/metrics/cycle-time",
+                web::get().to(board_handlers::get_cycle_time_metrics),
+            )
+            .route(
+                "/boards/{id}/metrics/counts",
+                web::get().to(board_handlers::get_count_metrics),
+            )
+            .route(
+                "/boards/{id}/metrics/peak-presence",
+                web::get().to(board_handlers::get_peak_presence_metrics),
+            )
+            .route(
+                "/boards/{id}/regenerate-token",
+                web::post().to(board_handlers::regenerate_share_token),
+            )
             .route(
                 "/boards/share/{token}",
                 web::get().to(board_handlers::get_board_by_share_token),
@@ -51,6 +123,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 "/boards/share/{token}",
                 web::put().to(board_handlers::update_board_by_share_token),
             )
+            .route(
+                "/boards/view/{token}",
+                web::get().to(board_handlers::get_board_by_view_token),
+            )
             .route(
                 "/boards/share/{token}/lock",
                 web::post().to(board_handlers::set_board_lock_state),
@@ -60,6 +136,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 "/boards/{board_id}/columns",
                 web::post().to(column_handlers::create_column),
             )
+            .route(
+                "/boards/{board_id}/cards/search",
+                web::get().to(card_handlers::search_cards),
+            )
             .route(
                 "/boards/{board_id}/columns/reorder",
                 web::patch().to(column_handlers::reorder_columns),
@@ -72,15 +152,31 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 "/columns/{id}",
                 web::delete().to(column_handlers::delete_column),
             )
+            .route(
+                "/columns/{id}/archive",
+                web::post().to(column_handlers::archive_column),
+            )
+            .route(
+                "/columns/{id}/restore",
+                web::post().to(column_handlers::restore_column),
+            )
             // Card routes
             .route(
                 "/columns/{column_id}/cards",
                 web::post().to(card_handlers::create_card),
             )
+            .route(
+                "/columns/{column_id}/cards/from-template/{template_id}",
+                web::post().to(card_handlers::create_card_from_template),
+            )
             .route(
                 "/columns/{column_id}/cards/reorder",
                 web::patch().to(card_handlers::reorder_cards),
             )
+            .route(
+                "/columns/{column_id}/archive-cards",
+                web::post().to(card_handlers::archive_column_cards),
+            )
             .route("/cards/{id}", web::get().to(card_handlers::get_card))
             .route("/cards/{id}", web::put().to(card_handlers::update_card))
             .route("/cards/{id}", web::delete().to(card_handlers::delete_card))
@@ -88,10 +184,25 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 "/cards/{id}/move",
                 web::patch().to(card_handlers::move_card),
             )
+            .route(
+                "/cards/{target_id}/merge/{source_id}",
+                web::post().to(card_handlers::merge_cards),
+            )
+            // Card watch routes (require auth)
+            .service(
+                web::resource("/cards/{card_id}/watch")
+                    .route(web::post().to(card_handlers::watch_card))
+                    .route(web::delete().to(card_handlers::unwatch_card))
+                    .wrap(RequireAuth::new(Config::from_env())),
+            )
             // AI generation route
             .route(
                 "/cards/ai/generate-description",
-                web::post().to(card_handlers::generate_description),
+                if ai_enabled {
+                    web::post().to(card_handlers::generate_description)
+                } else {
+                    web::post().to(card_handlers::ai_feature_disabled)
+                },
             )
             // Board label management routes
             .route(
@@ -102,6 +213,14 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 "/boards/{board_id}/labels",
                 web::post().to(label_handlers::create_board_label),
             )
+            .route(
+                "/boards/{board_id}/labels/usage",
+                web::get().to(label_handlers::list_board_label_usage),
+            )
+            .route(
+                "/boards/{board_id}/labels/reorder",
+                web::patch().to(label_handlers::reorder_board_labels),
+            )
             .route(
                 "/boards/labels/{label_id}",
                 web::put().to(label_handlers::update_board_label),
@@ -135,16 +254,28 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::delete().to(attachment_handlers::delete_attachment))
                     .wrap(RequireAuth::new(Config::from_env())),
             )
-            // Attachment routes (optional auth for viewing)
+            .service(
+                web::resource("/cards/{card_id}/attachments/reorder")
+                    .route(web::patch().to(attachment_handlers::reorder_attachments))
+                    .wrap(RequireAuth::new(Config::from_env())),
+            )
+            // Attachment routes (optional auth for viewing; bulk delete still
+            // requires a signed-in user through its AuthenticatedUser extractor)
             .service(
                 web::resource("/cards/{card_id}/attachments")
                     .route(web::get().to(attachment_handlers::list_card_attachments))
+                    .route(web::delete().to(attachment_handlers::delete_card_attachments))
                     .wrap(OptionalAuth::new(Config::from_env())),
             )
             .service(
                 web::resource("/attachments/{attachment_id}/download-url")
                     .route(web::get().to(attachment_handlers::generate_download_url))
                     .wrap(OptionalAuth::new(Config::from_env())),
+            )
+            .service(
+                web::resource("/attachments/{attachment_id}/download")
+                    .route(web::get().to(attachment_handlers::download_attachment))
+                    .wrap(OptionalAuth::new(Config::from_env())),
             ),
     );
 }
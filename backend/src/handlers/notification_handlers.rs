@@ -0,0 +1,32 @@
+use actix_web::{HttpResponse, web};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth_middleware::auth::AuthenticatedUser;
+use crate::error::AppResult;
+use crate::models::NotificationsQuery;
+use crate::services::NotificationService;
+
+/// List the current user's notifications, newest first
+/// GET /me/notifications?unread=true
+pub async fn list_notifications(
+    pool: web::Data<PgPool>,
+    query: web::Query<NotificationsQuery>,
+    user: AuthenticatedUser,
+) -> AppResult<HttpResponse> {
+    let notifications =
+        NotificationService::list(pool.get_ref(), user.user_id, query.unread).await?;
+    Ok(HttpResponse::Ok().json(notifications))
+}
+
+/// Mark one of the current user's notifications as read
+/// POST /me/notifications/{id}/read
+pub async fn mark_notification_read(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    user: AuthenticatedUser,
+) -> AppResult<HttpResponse> {
+    let notification =
+        NotificationService::mark_read(pool.get_ref(), id.into_inner(), user.user_id).await?;
+    Ok(HttpResponse::Ok().json(notification))
+}
@@ -1,23 +1,102 @@
 use actix_web::{Error, HttpRequest, HttpResponse, web};
 use futures::stream::{self, Stream};
+use serde::Deserialize;
 use std::convert::Infallible;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use tokio_stream::StreamExt as _;
 use tokio_stream::wrappers::ReceiverStream;
+use uuid::Uuid;
 
+use crate::auth_middleware::auth::AuthenticatedUser;
 use crate::error::AppError;
+use crate::models::Board;
 use crate::sse::SseManager;
+use crate::sse::events::SseEvent;
+use crate::sse::manager::SseEventWrapper;
 use sqlx::PgPool;
 
+/// SSE event name of the frame carrying replayed events in batch mode
+const BATCH_EVENT_NAME: &str = "batch";
+
+/// How often idle streams get a `ping` event
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+pub struct EventStreamQuery {
+    /// Deliver replayed events as one JSON array frame
+    #[serde(default)]
+    pub batch: bool,
+    /// Fallback for `Last-Event-ID` when the client cannot set headers
+    pub last_event_id: Option<u64>,
+}
+
+/// Id of the last event the client saw, from the `Last-Event-ID` header or
+/// the `last_event_id` query parameter
+fn last_event_id(req: &HttpRequest, query: &EventStreamQuery) -> Option<u64> {
+    req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .or(query.last_event_id)
+}
+
+/// Format replayed events for the stream
+///
+/// In batch mode every event goes into a single `batch` frame whose data is a
+/// JSON array of event payloads and whose id is the last event's id.
+/// Otherwise each event gets its own frame, as live events do.
+fn replay_frames(events: &[SseEventWrapper], batch: bool) -> Vec<String> {
+    let Some(last) = events.last() else {
+        return Vec::new();
+    };
+
+    if !batch {
+        return events.iter().map(|event| event.to_string()).collect();
+    }
+
+    let payloads: Vec<&SseEvent> = events.iter().map(|event| event.event()).collect();
+    match serde_json::to_string(&payloads) {
+        Ok(json) => vec![format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            last.id(),
+            BATCH_EVENT_NAME,
+            json
+        )],
+        Err(e) => {
+            log::error!("Failed to serialize SSE replay batch: {}", e);
+            vec![": error\n\n".to_string()]
+        }
+    }
+}
+
+/// Format the keep-alive `ping` frame
+///
+/// Unlike other events it has no `id`, so it never moves the client's
+/// `Last-Event-ID` and resuming after a ping replays nothing extra.
+fn ping_frame() -> String {
+    match SseEvent::Ping.to_json() {
+        Ok(json) => format!("event: {}\ndata: {}\n\n", SseEvent::Ping.event_name(), json),
+        Err(e) => {
+            log::error!("Failed to serialize SSE ping: {}", e);
+            ": keep-alive\n\n".to_string()
+        }
+    }
+}
+
 /// SSE endpoint for board updates
-/// GET /sse/{share_token}
+/// GET /sse/{share_token}?batch=true
+///
+/// Clients resuming with `Last-Event-ID` first receive the buffered events
+/// they missed, then live events one per frame.
 pub async fn board_events_stream(
     pool: web::Data<PgPool>,
     sse_manager: web::Data<Arc<SseManager>>,
     path: web::Path<String>,
-    _req: HttpRequest,
+    query: web::Query<EventStreamQuery>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let share_token = path.into_inner();
 
@@ -41,19 +120,95 @@ pub async fn board_events_stream(
         AppError::NotFound("Board not found".to_string())
     })?;
 
-    let board_id = board.id;
+    Ok(subscribe_board(&sse_manager, board.id, &req, &query).await)
+}
+
+/// SSE endpoint for board updates, addressed by board UUID
+/// GET /sse/board/{id}?batch=true
+///
+/// Same stream as `/sse/{share_token}`, for tooling that only knows the ID.
+pub async fn board_events_stream_by_id(
+    pool: web::Data<PgPool>,
+    sse_manager: web::Data<Arc<SseManager>>,
+    path: web::Path<Uuid>,
+    query: web::Query<EventStreamQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let board_id = path.into_inner();
+
+    // Validate the board exists
+    Board::find_by_id(pool.get_ref(), board_id)
+        .await
+        .map_err(|e| {
+            log::error!("Database error fetching board: {}", e);
+            AppError::DatabaseError(e)
+        })?
+        .ok_or_else(|| {
+            log::warn!("Board not found for id: {}", board_id);
+            AppError::NotFound("Board not found".to_string())
+        })?;
+
+    Ok(subscribe_board(&sse_manager, board_id, &req, &query).await)
+}
+
+/// Subscribe to a board and stream its events, replaying what the client
+/// missed since its `Last-Event-ID`
+async fn subscribe_board(
+    sse_manager: &SseManager,
+    board_id: Uuid,
+    req: &HttpRequest,
+    query: &EventStreamQuery,
+) -> HttpResponse {
+    let last_event_id = last_event_id(req, query);
 
     log::info!("New SSE connection for board: {}", board_id);
 
-    // Subscribe to board updates
-    let receiver = sse_manager.subscribe(board_id).await;
+    // Subscribe to board updates, catching up on anything missed
+    let (replayed, receiver) = sse_manager.subscribe(board_id, last_event_id).await;
+    if !replayed.is_empty() {
+        log::info!(
+            "Replaying {} SSE events for board {} (batch: {})",
+            replayed.len(),
+            board_id,
+            query.batch
+        );
+    }
+
+    event_stream_response(replay_frames(&replayed, query.batch), receiver)
+}
+
+/// SSE endpoint for the authenticated user's notifications
+/// GET /me/events
+///
+/// Carries events addressed to the user rather than to a board, such as
+/// `card:changed` for watched cards. Missed notifications are not replayed.
+pub async fn user_events_stream(
+    sse_manager: web::Data<Arc<SseManager>>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, Error> {
+    log::info!("New SSE notification connection for user: {}", user.user_id);
+
+    let receiver = sse_manager.subscribe_user(user.user_id).await;
+    Ok(event_stream_response(Vec::new(), receiver))
+}
+
+/// Build the streaming response: replayed frames first, then live events
+/// merged with keep-alive pings
+fn event_stream_response(
+    replayed: Vec<String>,
+    receiver: mpsc::Receiver<Result<SseEventWrapper, Infallible>>,
+) -> HttpResponse {
+    let replay_stream =
+        stream::iter(replayed.into_iter().map(|frame| {
+            Ok::<actix_web::web::Bytes, Infallible>(actix_web::web::Bytes::from(frame))
+        }));
     let event_stream = ReceiverStream::new(receiver);
 
-    // Create a heartbeat stream that sends keep-alive comments every 30 seconds
+    // Create a heartbeat stream that sends a ping event every 30 seconds
     let heartbeat = stream::repeat_with(|| {
-        Ok::<actix_web::web::Bytes, Infallible>(actix_web::web::Bytes::from(": keep-alive\n\n"))
+        Ok::<actix_web::web::Bytes, Infallible>(actix_web::web::Bytes::from(ping_frame()))
     })
-    .throttle(Duration::from_secs(30));
+    .throttle(PING_INTERVAL);
 
     // Convert SSE events to Bytes
     let event_bytes_stream = event_stream.map(|event_result| {
@@ -70,16 +225,183 @@ pub async fn board_events_stream(
         }
     });
 
-    // Merge the event stream with the heartbeat stream
+    // Send the replay first, then merge live events with the heartbeat stream
     let merged_stream: Pin<
         Box<dyn Stream<Item = Result<actix_web::web::Bytes, Infallible>> + Send>,
-    > = Box::pin(stream::select(event_bytes_stream, heartbeat));
+    > = Box::pin(replay_stream.chain(stream::select(event_bytes_stream, heartbeat)));
 
     // Create the SSE response with proper headers
-    Ok(HttpResponse::Ok()
+    HttpResponse::Ok()
         .content_type("text/event-stream")
         .insert_header(("Cache-Control", "no-cache"))
         .insert_header(("X-Accel-Buffering", "no"))
         .insert_header(("Connection", "keep-alive"))
-        .streaming(merged_stream))
+        .streaming(merged_stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::App;
+    use actix_web::body::{BoxBody, MessageBody};
+    use actix_web::http::StatusCode;
+    use actix_web::test::{TestRequest, call_service, init_service};
+
+    fn card_deleted(id: u64) -> SseEventWrapper {
+        SseEventWrapper::new(
+            id,
+            SseEvent::CardDeleted {
+                card_id: Uuid::new_v4(),
+            },
+        )
+    }
+
+    #[test]
+    fn batch_replay_is_one_array_frame() {
+        let events: Vec<_> = (1..=3).map(card_deleted).collect();
+
+        let frames = replay_frames(&events, true);
+        assert_eq!(frames.len(), 1);
+
+        let frame = &frames[0];
+        assert!(frame.starts_with("id: 3\nevent: batch\ndata: "));
+        assert!(frame.ends_with("\n\n"));
+        let data = frame
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap();
+        let payloads: Vec<serde_json::Value> = serde_json::from_str(data).unwrap();
+        assert_eq!(payloads.len(), 3);
+    }
+
+    #[test]
+    fn replay_without_batch_is_one_frame_per_event() {
+        let events: Vec<_> = (1..=3).map(card_deleted).collect();
+
+        let frames = replay_frames(&events, false);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].starts_with("id: 1\nevent: card:deleted\n"));
+        assert!(frames[2].starts_with("id: 3\n"));
+    }
+
+    #[test]
+    fn empty_replay_has_no_frames() {
+        assert!(replay_frames(&[], true).is_empty());
+        assert!(replay_frames(&[], false).is_empty());
+    }
+
+    /// Next chunk written to a streaming response body
+    async fn next_frame(body: &mut BoxBody) -> String {
+        let chunk = std::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        String::from_utf8(chunk.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn ping_frame_is_typed_and_has_no_id() {
+        let frame = ping_frame();
+        assert!(frame.starts_with("event: ping\ndata: "));
+        assert!(!frame.contains("id: "));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_stream_sends_pings() {
+        let (_tx, rx) = mpsc::channel(1);
+        let mut body = event_stream_response(Vec::new(), rx).into_body();
+
+        assert_eq!(next_frame(&mut body).await, ping_frame());
+
+        let started = tokio::time::Instant::now();
+        assert_eq!(next_frame(&mut body).await, ping_frame());
+        assert!(started.elapsed() >= PING_INTERVAL);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn replayed_frames_come_before_pings() {
+        let (_tx, rx) = mpsc::channel(1);
+        let replayed = replay_frames(&[card_deleted(1)], false);
+        let mut body = event_stream_response(replayed.clone(), rx).into_body();
+
+        assert_eq!(next_frame(&mut body).await, replayed[0]);
+        assert_eq!(next_frame(&mut body).await, ping_frame());
+    }
+
+    /// Next frame on the stream that is not a keep-alive ping
+    async fn next_event_frame(body: &mut BoxBody) -> String {
+        loop {
+            let frame = next_frame(body).await;
+            if frame != ping_frame() {
+                return frame;
+            }
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn uuid_route_delivers_same_events_as_share_token_route(pool: PgPool) {
+        let board_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title) VALUES ('share-sse', 'view-sse', 'Board') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let sse_manager = Arc::new(SseManager::with_reorder_window(Duration::ZERO));
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .app_data(web::Data::new(sse_manager.clone()))
+                .route("/sse/{share_token}", web::get().to(board_events_stream))
+                .route("/sse/board/{id}", web::get().to(board_events_stream_by_id)),
+        )
+        .await;
+
+        let by_token =
+            call_service(&app, TestRequest::get().uri("/sse/share-sse").to_request()).await;
+        let by_id = call_service(
+            &app,
+            TestRequest::get()
+                .uri(&format!("/sse/board/{}", board_id))
+                .to_request(),
+        )
+        .await;
+        assert!(by_token.status().is_success());
+        assert!(by_id.status().is_success());
+
+        let mut by_token = by_token.into_body().boxed();
+        let mut by_id = by_id.into_body().boxed();
+
+        sse_manager
+            .broadcast(
+                board_id,
+                SseEvent::CardDeleted {
+                    card_id: Uuid::new_v4(),
+                },
+            )
+            .await;
+
+        let frame = next_event_frame(&mut by_token).await;
+        assert!(frame.contains("event: card:deleted\n"));
+        assert_eq!(next_event_frame(&mut by_id).await, frame);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn uuid_route_is_not_found_for_unknown_board(pool: PgPool) {
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(pool))
+                .app_data(web::Data::new(Arc::new(SseManager::new())))
+                .route("/sse/board/{id}", web::get().to(board_events_stream_by_id)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri(&format!("/sse/board/{}", Uuid::new_v4()))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
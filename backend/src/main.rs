@@ -1,7 +1,9 @@
-use actix_web::{App, HttpServer, web};
+use actix_web::error::JsonPayloadError;
+use actix_web::{App, HttpRequest, HttpServer, web};
 use log::info;
 use std::io;
 use std::sync::Arc;
+use std::time::Duration;
 
 mod auth_middleware;
 mod config;
@@ -15,7 +17,11 @@ mod utils;
 
 use config::Config;
 use db::init_pool;
-use services::{AiService, S3Service};
+use error::AppError;
+use services::{AiService, BoardService, PresenceService, S3Service};
+
+/// How often archived items are checked for purging
+const ARCHIVE_PURGE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
 
 #[actix_web::main]
 async fn main() -> io::Result<()> {
@@ -28,6 +34,7 @@ async fn main() -> io::Result<()> {
     // Load configuration
     let config = Config::from_env();
     info!("Starting Fluxboard backend server...");
+    config.log_effective();
     info!(
         "Server will run on {}:{}",
         config.server_host, config.server_port
@@ -48,14 +55,44 @@ async fn main() -> io::Result<()> {
         .expect("Failed to run database migrations");
     info!("Database migrations completed successfully");
 
+    // Hash board passwords left in plaintext by earlier versions
+    let hashed = BoardService::hash_plaintext_passwords(&pool)
+        .await
+        .expect("Failed to hash plaintext board passwords");
+    if hashed > 0 {
+        info!("Hashed {} plaintext board passwords", hashed);
+    }
+
+    let mapped = PresenceService::assign_missing_presence_ids(&pool)
+        .await
+        .expect("Failed to assign board presence IDs");
+    if mapped > 0 {
+        info!("Assigned presence IDs to {} boards", mapped);
+    }
+
     // Initialize SSE manager
-    let sse_manager = Arc::new(sse::SseManager::new());
-    info!("SSE manager initialized");
+    let sse_manager = Arc::new(sse::SseManager::with_reorder_window(
+        Duration::from_millis(config.sse_reorder_coalesce_ms),
+    ));
+    info!(
+        "SSE manager initialized (reorder events coalesced over {}ms)",
+        config.sse_reorder_coalesce_ms
+    );
 
     // Initialize AI service if API key is configured
+    if let Some(ref persona) = config.ai_persona {
+        AiService::validate_persona(persona).expect("AI_PERSONA is too long");
+    }
     let ai_service = config.gemini_api_key.clone().map(|key| {
         info!("AI service initialized with Gemini API");
-        Arc::new(AiService::new(key))
+        Arc::new(AiService::new(key, config.ai_persona.clone()))
+    });
+
+    // Initialize presence statistics if Redis is configured
+    let presence_service = config.redis_url.as_deref().map(|url| {
+        let service = PresenceService::new(url).expect("REDIS_URL must be a valid Redis URL");
+        info!("Presence statistics enabled");
+        Arc::new(service)
     });
 
     // Initialize S3 service
@@ -65,6 +102,31 @@ async fn main() -> io::Result<()> {
     let s3_service = Arc::new(s3_service);
     info!("S3 service initialized");
 
+    // Purge long-archived columns and cards in the background if configured
+    if config.read_only {
+        info!("Read-only mode: writes are rejected and the archive purge is paused");
+    } else if config.archive_purge_after_days > 0 {
+        let older_than = chrono::Duration::days(config.archive_purge_after_days.into());
+        let pool = pool.clone();
+        let s3_service = s3_service.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ARCHIVE_PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) =
+                    handlers::admin_handlers::purge_archived_items(&pool, &s3_service, older_than)
+                        .await
+                {
+                    log::error!("Scheduled archive purge failed: {}", e);
+                }
+            }
+        });
+        info!(
+            "Archived items older than {} days will be purged daily",
+            config.archive_purge_after_days
+        );
+    }
+
     // Start HTTP server
     let config_clone = config.clone();
     HttpServer::new(move || {
@@ -76,8 +138,14 @@ async fn main() -> io::Result<()> {
                 actix_web::http::header::AUTHORIZATION,
                 actix_web::http::header::ACCEPT,
                 actix_web::http::header::CONTENT_TYPE,
+                actix_web::http::header::IF_MATCH,
+                actix_web::http::header::IF_NONE_MATCH,
                 actix_web::http::header::HeaderName::from_static("x-board-password"),
             ])
+            .expose_headers(vec![
+                actix_web::http::header::ETAG,
+                actix_web::http::header::HeaderName::from_static("x-card-position"),
+            ])
             .max_age(3600);
 
         // Add additional CORS origin if configured
@@ -96,20 +164,37 @@ async fn main() -> io::Result<()> {
             // Share config across all handlers
             .app_data(web::Data::new(config_clone.clone()))
             // Share S3 service across all handlers
-            .app_data(web::Data::new(s3_service.clone()));
+            .app_data(web::Data::new(s3_service.clone()))
+            // Reject oversized JSON bodies before they are buffered
+            .app_data(
+                web::JsonConfig::default()
+                    .limit(config_clone.json_payload_limit())
+                    .error_handler(json_error_handler),
+            );
 
         // Add AI service if available
         if let Some(ref ai_svc) = ai_service {
             app = app.app_data(web::Data::new(ai_svc.clone()));
         }
 
+        // Add presence statistics if available
+        if let Some(ref presence_svc) = presence_service {
+            app = app.app_data(web::Data::new(presence_svc.clone()));
+        }
+
         app
+            // Reject writes while in read-only mode (no-op unless READ_ONLY is set)
+            .wrap(actix_web::middleware::from_fn(
+                auth_middleware::read_only::reject_writes_when_read_only,
+            ))
             // Enable logger middleware
             .wrap(actix_web::middleware::Logger::default())
             // CORS middleware
             .wrap(cors)
             // Health check endpoint
             .route("/health", web::get().to(health_check))
+            // Readiness endpoint (checks dependencies)
+            .route("/ready", web::get().to(readiness_check))
             // Configure API routes (including SSE and auth)
             .configure(handlers::configure_routes)
     })
@@ -118,6 +203,26 @@ async fn main() -> io::Result<()> {
     .await
 }
 
+/// Turn oversized JSON bodies into a structured 413
+///
+/// Other JSON errors keep actix's default response.
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        JsonPayloadError::OverflowKnownLength { length, limit } => {
+            AppError::PayloadTooLarge(format!(
+                "Request body is {} bytes; the limit is {} bytes",
+                length, limit
+            ))
+            .into()
+        }
+        JsonPayloadError::Overflow { limit } => {
+            AppError::PayloadTooLarge(format!("Request body exceeds the limit of {} bytes", limit))
+                .into()
+        }
+        err => err.into(),
+    }
+}
+
 /// Health check endpoint
 async fn health_check() -> actix_web::Result<impl actix_web::Responder> {
     Ok(web::Json(serde_json::json!({
@@ -125,3 +230,107 @@ async fn health_check() -> actix_web::Result<impl actix_web::Responder> {
         "message": "Fluxboard backend is running"
     })))
 }
+
+/// Readiness endpoint
+///
+/// Fails with 503 when the database is unreachable. When AI is enabled the
+/// provider check is reported too, but a failing provider only marks the
+/// backend as degraded: the rest of the API still works without it.
+async fn readiness_check(
+    pool: web::Data<sqlx::PgPool>,
+    ai_service: Option<web::Data<Arc<AiService>>>,
+) -> actix_web::HttpResponse {
+    let database = match db::test_connection(pool.get_ref()).await {
+        Ok(_) => "ok".to_string(),
+        Err(e) => {
+            log::error!("Readiness check: database unreachable: {}", e);
+            "unreachable".to_string()
+        }
+    };
+
+    let ai = match ai_service {
+        Some(ai_service) => match ai_service.health_check().await {
+            Ok(()) => "ok".to_string(),
+            Err(reason) => reason,
+        },
+        None => "disabled".to_string(),
+    };
+
+    let status = if database != "ok" {
+        "unavailable"
+    } else if ai != "ok" && ai != "disabled" {
+        "degraded"
+    } else {
+        "ready"
+    };
+
+    let body = serde_json::json!({
+        "status": status,
+        "checks": {
+            "database": database,
+            "ai": ai,
+        }
+    });
+
+    if database == "ok" {
+        actix_web::HttpResponse::Ok().json(body)
+    } else {
+        actix_web::HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::{HttpResponse, test};
+
+    async fn echo(body: web::Json<serde_json::Value>) -> HttpResponse {
+        HttpResponse::Ok().json(body.into_inner())
+    }
+
+    fn json_config(limit: usize) -> web::JsonConfig {
+        web::JsonConfig::default()
+            .limit(limit)
+            .error_handler(json_error_handler)
+    }
+
+    #[actix_web::test]
+    async fn oversized_json_body_gets_structured_413() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(16))
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(serde_json::json!({ "text": "a".repeat(64) }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "413 Payload Too Large");
+        assert!(body["message"].as_str().unwrap().contains("16 bytes"));
+    }
+
+    #[actix_web::test]
+    async fn json_body_within_limit_is_accepted() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config(1024))
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(serde_json::json!({ "text": "short" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -15,8 +15,15 @@ pub struct CardAttachment {
     pub content_type: String,
     pub file_size: i32,
     pub s3_key: String,
+    /// Storage bucket is an infrastructure detail, never sent to clients
+    ///
+    /// Recorded at upload only; object operations always use the configured bucket.
+    #[allow(dead_code)]
+    #[serde(skip_serializing)]
     pub s3_bucket: String,
     pub is_confirmed: bool,
+    /// Display order within the card (ascending)
+    pub position: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -40,7 +47,47 @@ pub struct UploadUrlResponse {
     pub s3_key: String,
 }
 
+/// Request to reorder a card's attachments
+#[derive(Debug, Deserialize)]
+pub struct ReorderAttachmentsRequest {
+    pub attachment_positions: Vec<(Uuid, i32)>,
+}
+
+/// How a downloaded attachment should be presented by the browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadDisposition {
+    /// Render in the browser (used for images by default)
+    Inline,
+    /// Save as a file
+    Attachment,
+}
+
+impl DownloadDisposition {
+    /// Default disposition for a MIME type: images render inline, everything else downloads
+    pub fn for_content_type(content_type: &str) -> Self {
+        let content_type = content_type.trim().to_ascii_lowercase();
+        if content_type.starts_with("image/") {
+            DownloadDisposition::Inline
+        } else {
+            DownloadDisposition::Attachment
+        }
+    }
+}
+
+/// Query parameters for attachment downloads
+#[derive(Debug, Deserialize)]
+pub struct DownloadQuery {
+    /// Overrides the disposition inferred from the content type
+    pub disposition: Option<DownloadDisposition>,
+}
+
 impl CardAttachment {
+    /// Resolve the download disposition, preferring an explicit request
+    pub fn disposition(&self, requested: Option<DownloadDisposition>) -> DownloadDisposition {
+        requested.unwrap_or_else(|| DownloadDisposition::for_content_type(&self.content_type))
+    }
+
     /// Create a new attachment record (unconfirmed) with a pre-generated ID
     ///
     /// # Arguments
@@ -74,11 +121,12 @@ impl CardAttachment {
             r#"
             INSERT INTO card_attachments
                 (id, card_id, uploaded_by, filename, original_filename, content_type,
-                 file_size, s3_key, s3_bucket, is_confirmed)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, false)
+                 file_size, s3_key, s3_bucket, is_confirmed, position)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, false,
+                    (SELECT COALESCE(MAX(position) + 1, 0) FROM card_attachments WHERE card_id = $2))
             RETURNING id, card_id, uploaded_by, filename, original_filename,
                       content_type, file_size, s3_key, s3_bucket,
-                      is_confirmed as "is_confirmed!",
+                      is_confirmed as "is_confirmed!", position,
                       created_at as "created_at!",
                       updated_at as "updated_at!"
             "#,
@@ -130,11 +178,12 @@ impl CardAttachment {
             r#"
             INSERT INTO card_attachments
                 (card_id, uploaded_by, filename, original_filename, content_type,
-                 file_size, s3_key, s3_bucket, is_confirmed)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, false)
+                 file_size, s3_key, s3_bucket, is_confirmed, position)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, false,
+                    (SELECT COALESCE(MAX(position) + 1, 0) FROM card_attachments WHERE card_id = $1))
             RETURNING id, card_id, uploaded_by, filename, original_filename,
                       content_type, file_size, s3_key, s3_bucket,
-                      is_confirmed as "is_confirmed!",
+                      is_confirmed as "is_confirmed!", position,
                       created_at as "created_at!",
                       updated_at as "updated_at!"
             "#,
@@ -167,7 +216,7 @@ impl CardAttachment {
             r#"
             SELECT id, card_id, uploaded_by, filename, original_filename,
                    content_type, file_size, s3_key, s3_bucket,
-                   is_confirmed as "is_confirmed!",
+                   is_confirmed as "is_confirmed!", position,
                    created_at as "created_at!",
                    updated_at as "updated_at!"
             FROM card_attachments
@@ -195,12 +244,12 @@ impl CardAttachment {
             r#"
             SELECT id, card_id, uploaded_by, filename, original_filename,
                    content_type, file_size, s3_key, s3_bucket,
-                   is_confirmed as "is_confirmed!",
+                   is_confirmed as "is_confirmed!", position,
                    created_at as "created_at!",
                    updated_at as "updated_at!"
             FROM card_attachments
             WHERE card_id = $1 AND is_confirmed = true
-            ORDER BY created_at ASC
+            ORDER BY position ASC, created_at ASC
             "#,
             card_id
         )
@@ -212,22 +261,25 @@ impl CardAttachment {
 
     /// Confirm an attachment (mark as confirmed after successful S3 upload)
     ///
+    /// Only transitions unconfirmed attachments, so concurrent confirmations
+    /// of the same attachment succeed exactly once.
+    ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `id` - Attachment UUID
     ///
     /// # Returns
-    /// * `Result<Option<CardAttachment>, sqlx::Error>` - Updated attachment or None
+    /// * `Result<Option<CardAttachment>, sqlx::Error>` - Updated attachment, or None if not found or already confirmed
     pub async fn confirm(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         let attachment = sqlx::query_as!(
             CardAttachment,
             r#"
             UPDATE card_attachments
             SET is_confirmed = true, updated_at = NOW()
-            WHERE id = $1
+            WHERE id = $1 AND is_confirmed = false
             RETURNING id, card_id, uploaded_by, filename, original_filename,
                       content_type, file_size, s3_key, s3_bucket,
-                      is_confirmed as "is_confirmed!",
+                      is_confirmed as "is_confirmed!", position,
                       created_at as "created_at!",
                       updated_at as "updated_at!"
             "#,
@@ -239,6 +291,62 @@ impl CardAttachment {
         Ok(attachment)
     }
 
+    /// Reorder a card's attachments
+    ///
+    /// Attachments not listed keep their position; IDs belonging to other
+    /// cards are ignored.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    /// * `attachment_positions` - Pairs of (attachment_id, new_position)
+    ///
+    /// # Returns
+    /// * `Result<Vec<CardAttachment>, sqlx::Error>` - The card's attachments in their new order
+    pub async fn reorder(
+        pool: &PgPool,
+        card_id: Uuid,
+        attachment_positions: Vec<(Uuid, i32)>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        for (attachment_id, position) in attachment_positions {
+            sqlx::query!(
+                r#"
+                UPDATE card_attachments
+                SET position = $1, updated_at = NOW()
+                WHERE id = $2 AND card_id = $3
+                "#,
+                position,
+                attachment_id,
+                card_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let attachments = sqlx::query_as!(
+            CardAttachment,
+            r#"
+            SELECT id, card_id, uploaded_by, filename, original_filename,
+                   content_type, file_size, s3_key, s3_bucket,
+                   is_confirmed as "is_confirmed!", position,
+                   created_at as "created_at!",
+                   updated_at as "updated_at!"
+            FROM card_attachments
+            WHERE card_id = $1 AND is_confirmed = true
+            ORDER BY position ASC, created_at ASC
+            "#,
+            card_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(attachments)
+    }
+
     /// Delete an attachment
     ///
     /// # Arguments
@@ -260,4 +368,33 @@ impl CardAttachment {
 
         Ok(result.rows_affected() > 0)
     }
+
+    /// Delete all confirmed attachments of a card
+    ///
+    /// Pending uploads are left alone. The rows go in a single statement, so
+    /// either all of them are deleted or none are.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>, sqlx::Error>` - S3 keys of the deleted attachments
+    pub async fn delete_by_card_id(
+        pool: &PgPool,
+        card_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let s3_keys = sqlx::query_scalar!(
+            r#"
+            DELETE FROM card_attachments
+            WHERE card_id = $1 AND is_confirmed = true
+            RETURNING s3_key
+            "#,
+            card_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(s3_keys)
+    }
 }
@@ -1,8 +1,13 @@
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordVerifier},
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use super::board_presence_id::BoardPresenceId;
 use super::{BoardLabel, Card, CardAttachment, Column};
 
 /// Board model representing a Kanban board
@@ -12,29 +17,108 @@ pub struct Board {
     pub share_token: String,
     pub title: String,
     pub description: Option<String>,
+    /// Argon2 hash of the owner password
     #[serde(skip_serializing)]
     pub password: String,
     pub is_locked: bool,
+    /// Token for read-only share links
+    pub view_token: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Response for a newly created board
+///
+/// The only response that includes the plaintext password, so the creator
+/// can store it to lock and edit the board later. Only its hash is stored.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedBoard {
+    #[serde(flatten)]
+    pub board: Board,
+    pub password: String,
+}
+
+/// Board fields safe to send to every subscriber of a board
+///
+/// Omits the password and the edit-capable share token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardPublic {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub is_locked: bool,
+    pub view_token: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Board> for BoardPublic {
+    fn from(board: Board) -> Self {
+        Self {
+            id: board.id,
+            title: board.title,
+            description: board.description,
+            is_locked: board.is_locked,
+            view_token: board.view_token,
+            created_at: board.created_at,
+            updated_at: board.updated_at,
+        }
+    }
+}
+
 /// Board with all related data (columns, cards, labels)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoardWithRelations {
     pub id: Uuid,
+    /// Edit-capable share token (empty and omitted when not loaded by it)
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub share_token: String,
     pub title: String,
     pub description: Option<String>,
-    #[serde(skip_serializing)]
-    pub password: String,
+    /// Whether the board has an owner password (required for edits while locked)
+    pub is_protected: bool,
     pub is_locked: bool,
+    pub view_token: String,
+    /// ID the board is known by in the presence protocol (omitted until assigned)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_id: Option<u32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub columns: Vec<ColumnWithCards>,
     pub labels: Vec<BoardLabel>, // Board-level labels
 }
 
+/// Lightweight board overview for dashboards listing many boards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardSummary {
+    pub id: Uuid,
+    pub title: String,
+    pub is_locked: bool,
+    /// Columns that are not archived
+    pub column_count: i64,
+    /// Cards that are not archived, outside archived columns
+    pub card_count: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Column and card counts of a board, computed without loading its relations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardCounts {
+    /// Columns that are not archived
+    pub column_count: i64,
+    /// Active cards, outside archived columns
+    pub card_count: i64,
+    /// Active cards per column, in column order
+    pub columns: Vec<ColumnCardCount>,
+}
+
+/// Number of active cards in one column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnCardCount {
+    pub column_id: Uuid,
+    pub card_count: i64,
+}
+
 /// Column with cards
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnWithCards {
@@ -42,6 +126,8 @@ pub struct ColumnWithCards {
     pub board_id: Uuid,
     pub title: String,
     pub position: i32,
+    /// Only set on columns loaded with `include_archived`
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub cards: Vec<CardWithLabels>,
@@ -54,6 +140,7 @@ pub struct CardWithLabels {
     pub column_id: Uuid,
     pub title: String,
     pub description: Option<String>,
+    pub color: Option<String>,
     pub position: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -61,6 +148,129 @@ pub struct CardWithLabels {
     pub attachments: Vec<CardAttachment>,
 }
 
+/// Read-only view of a board, served to view token holders
+///
+/// Carries no ids or tokens: edit routes are keyed by board, column, card,
+/// label and attachment ids, so viewers must not learn any of them.
+#[derive(Debug, Clone, Serialize)]
+pub struct BoardView {
+    pub title: String,
+    pub description: Option<String>,
+    pub is_locked: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub columns: Vec<ColumnView>,
+    pub labels: Vec<LabelView>,
+}
+
+/// Column of a `BoardView`
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnView {
+    pub title: String,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub cards: Vec<CardView>,
+}
+
+/// Card of a `BoardView`
+#[derive(Debug, Clone, Serialize)]
+pub struct CardView {
+    pub title: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub labels: Vec<LabelView>,
+    pub attachments: Vec<AttachmentView>,
+}
+
+/// Label of a `BoardView`
+#[derive(Debug, Clone, Serialize)]
+pub struct LabelView {
+    pub name: String,
+    pub color: String,
+    pub position: i32,
+}
+
+/// Attachment of a `BoardView`, listed without a way to download it
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentView {
+    pub original_filename: String,
+    pub content_type: String,
+    pub file_size: i32,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<BoardWithRelations> for BoardView {
+    fn from(board: BoardWithRelations) -> Self {
+        Self {
+            title: board.title,
+            description: board.description,
+            is_locked: board.is_locked,
+            created_at: board.created_at,
+            updated_at: board.updated_at,
+            columns: board.columns.into_iter().map(ColumnView::from).collect(),
+            labels: board.labels.into_iter().map(LabelView::from).collect(),
+        }
+    }
+}
+
+impl From<ColumnWithCards> for ColumnView {
+    fn from(column: ColumnWithCards) -> Self {
+        Self {
+            title: column.title,
+            position: column.position,
+            created_at: column.created_at,
+            updated_at: column.updated_at,
+            cards: column.cards.into_iter().map(CardView::from).collect(),
+        }
+    }
+}
+
+impl From<CardWithLabels> for CardView {
+    fn from(card: CardWithLabels) -> Self {
+        Self {
+            title: card.title,
+            description: card.description,
+            color: card.color,
+            position: card.position,
+            created_at: card.created_at,
+            updated_at: card.updated_at,
+            labels: card.labels.into_iter().map(LabelView::from).collect(),
+            attachments: card
+                .attachments
+                .into_iter()
+                .map(AttachmentView::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<BoardLabel> for LabelView {
+    fn from(label: BoardLabel) -> Self {
+        Self {
+            name: label.name,
+            color: label.color,
+            position: label.position,
+        }
+    }
+}
+
+impl From<CardAttachment> for AttachmentView {
+    fn from(attachment: CardAttachment) -> Self {
+        Self {
+            original_filename: attachment.original_filename,
+            content_type: attachment.content_type,
+            file_size: attachment.file_size,
+            position: attachment.position,
+            created_at: attachment.created_at,
+        }
+    }
+}
+
 /// Input data for creating a new board
 #[derive(Debug, Deserialize)]
 pub struct CreateBoardInput {
@@ -82,31 +292,49 @@ pub struct SetLockStateInput {
     pub is_locked: bool,
 }
 
+/// Input data for fetching several board summaries at once
+#[derive(Debug, Deserialize)]
+pub struct BoardSummariesInput {
+    pub ids: Vec<Uuid>,
+}
+
+/// Input data for regenerating a board's share token
+#[derive(Debug, Deserialize)]
+pub struct RegenerateShareTokenInput {
+    pub password: String,
+}
+
 impl Board {
     /// Create a new board
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `input` - Board creation data
+    /// * `password_hash` - Argon2 hash of the owner password
     ///
     /// # Returns
     /// * `Result<Board, sqlx::Error>` - Created board or error
-    pub async fn create(pool: &PgPool, input: CreateBoardInput) -> Result<Self, sqlx::Error> {
+    pub async fn create(
+        pool: &PgPool,
+        input: CreateBoardInput,
+        password_hash: &str,
+    ) -> Result<Self, sqlx::Error> {
         let share_token = Self::generate_share_token();
-        let password = Self::generate_password();
+        let view_token = Self::generate_share_token();
 
         let board = sqlx::query_as!(
             Board,
             r#"
-            INSERT INTO boards (share_token, title, description, password, is_locked)
-            VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, share_token, title, description, password, is_locked, created_at, updated_at
+            INSERT INTO boards (share_token, title, description, password, is_locked, view_token)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
             "#,
             share_token,
             input.title,
             input.description,
-            password,
-            false
+            password_hash,
+            false,
+            view_token
         )
         .fetch_one(pool)
         .await?;
@@ -126,7 +354,7 @@ impl Board {
         let board = sqlx::query_as!(
             Board,
             r#"
-            SELECT id, share_token, title, description, password, is_locked, created_at, updated_at
+            SELECT id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
             FROM boards
             WHERE id = $1
             "#,
@@ -138,6 +366,46 @@ impl Board {
         Ok(board)
     }
 
+    /// Find summaries for a set of boards in one query
+    ///
+    /// Unknown IDs are skipped, so the result may be shorter than `ids`.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `ids` - Board UUIDs
+    ///
+    /// # Returns
+    /// * `Result<Vec<BoardSummary>, sqlx::Error>` - Summaries, most recently updated first
+    pub async fn find_summaries(
+        pool: &PgPool,
+        ids: &[Uuid],
+    ) -> Result<Vec<BoardSummary>, sqlx::Error> {
+        let summaries = sqlx::query_as!(
+            BoardSummary,
+            r#"
+            SELECT
+                b.id,
+                b.title,
+                b.is_locked,
+                (SELECT COUNT(*) FROM columns col
+                    WHERE col.board_id = b.id AND col.archived_at IS NULL) as "column_count!",
+                (SELECT COUNT(*) FROM cards c
+                    JOIN columns col ON c.column_id = col.id
+                    WHERE col.board_id = b.id AND col.archived_at IS NULL
+                      AND c.archived_at IS NULL) as "card_count!",
+                b.updated_at
+            FROM boards b
+            WHERE b.id = ANY($1)
+            ORDER BY b.updated_at DESC
+            "#,
+            ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
     /// Find a board by share token
     ///
     /// # Arguments
@@ -153,7 +421,7 @@ impl Board {
         let board = sqlx::query_as!(
             Board,
             r#"
-            SELECT id, share_token, title, description, password, is_locked, created_at, updated_at
+            SELECT id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
             FROM boards
             WHERE share_token = $1
             "#,
@@ -165,17 +433,74 @@ impl Board {
         Ok(board)
     }
 
+    /// Find a board by read-only view token
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `view_token` - Unique view token
+    ///
+    /// # Returns
+    /// * `Result<Option<Board>, sqlx::Error>` - Found board or None
+    pub async fn find_by_view_token(
+        pool: &PgPool,
+        view_token: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let board = sqlx::query_as!(
+            Board,
+            r#"
+            SELECT id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
+            FROM boards
+            WHERE view_token = $1
+            "#,
+            view_token
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(board)
+    }
+
+    /// Find a board by ID with all relations
+    ///
+    /// The share token is cleared: knowing a board's id must not hand out the
+    /// edit link.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    /// * `include_archived` - Also load archived columns and their cards
+    ///
+    /// # Returns
+    /// * `Result<Option<BoardWithRelations>, sqlx::Error>` - Found board with relations or None
+    pub async fn find_by_id_with_relations(
+        pool: &PgPool,
+        id: Uuid,
+        include_archived: bool,
+    ) -> Result<Option<BoardWithRelations>, sqlx::Error> {
+        let board = match Self::find_by_id(pool, id).await? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+
+        let mut board = Self::load_relations(pool, board, include_archived).await?;
+        board.share_token.clear();
+
+        Ok(Some(board))
+    }
+
     /// Find a board by share token with all relations
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `share_token` - Unique share token
+    /// * `include_archived` - Also load archived columns and their cards
     ///
     /// # Returns
     /// * `Result<Option<BoardWithRelations>, sqlx::Error>` - Found board with relations or None
     pub async fn find_by_share_token_with_relations(
         pool: &PgPool,
         share_token: &str,
+        include_archived: bool,
     ) -> Result<Option<BoardWithRelations>, sqlx::Error> {
         // First get the board
         let board = Self::find_by_share_token(pool, share_token).await?;
@@ -185,63 +510,124 @@ impl Board {
             None => return Ok(None),
         };
 
+        Ok(Some(
+            Self::load_relations(pool, board, include_archived).await?,
+        ))
+    }
+
+    /// Find a board by view token with all relations, as a read-only view
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `view_token` - Unique view token
+    ///
+    /// # Returns
+    /// * `Result<Option<BoardView>, sqlx::Error>` - Found board view or None
+    pub async fn find_by_view_token_with_relations(
+        pool: &PgPool,
+        view_token: &str,
+    ) -> Result<Option<BoardView>, sqlx::Error> {
+        let board = match Self::find_by_view_token(pool, view_token).await? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+
+        let board = Self::load_relations(pool, board, false).await?;
+        Ok(Some(board.into()))
+    }
+
+    /// Load columns, cards, labels and attachments for a board
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board` - Board to load relations for
+    /// * `include_archived` - Also load archived columns and their cards
+    ///
+    /// # Returns
+    /// * `Result<BoardWithRelations, sqlx::Error>` - Board with relations
+    async fn load_relations(
+        pool: &PgPool,
+        board: Board,
+        include_archived: bool,
+    ) -> Result<BoardWithRelations, sqlx::Error> {
         // Get all columns for this board
         let columns = Column::find_by_board_id(pool, board.id).await?;
 
-        // Build columns with cards
+        // Build columns with cards, skipping archived ones unless requested
         let mut columns_with_cards = Vec::new();
         for column in columns {
-            // Get all cards for this column
-            let cards = Card::find_by_column_id(pool, column.id).await?;
-
-            // Build cards with labels and attachments
-            let mut cards_with_labels = Vec::new();
-            for card in cards {
-                // Get all labels for this card
-                let labels = BoardLabel::find_by_card_id(pool, card.id).await?;
-
-                // Get all attachments for this card
-                let attachments = CardAttachment::find_by_card_id(pool, card.id).await?;
-
-                cards_with_labels.push(CardWithLabels {
-                    id: card.id,
-                    column_id: card.column_id,
-                    title: card.title,
-                    description: card.description,
-                    position: card.position,
-                    created_at: card.created_at,
-                    updated_at: card.updated_at,
-                    labels,
-                    attachments,
-                });
+            if column.archived_at.is_some() && !include_archived {
+                continue;
             }
-
-            columns_with_cards.push(ColumnWithCards {
-                id: column.id,
-                board_id: column.board_id,
-                title: column.title,
-                position: column.position,
-                created_at: column.created_at,
-                updated_at: column.updated_at,
-                cards: cards_with_labels,
-            });
+            columns_with_cards.push(ColumnWithCards::load(pool, column).await?);
         }
 
         // Get all board labels
         let labels = BoardLabel::find_by_board_id(pool, board.id).await?;
 
-        Ok(Some(BoardWithRelations {
+        let presence_id = BoardPresenceId::find_by_board_id(pool, board.id).await?;
+
+        Ok(BoardWithRelations {
             id: board.id,
             share_token: board.share_token,
             title: board.title,
             description: board.description,
-            password: board.password,
+            is_protected: !board.password.is_empty(),
             is_locked: board.is_locked,
+            view_token: board.view_token,
+            presence_id,
             created_at: board.created_at,
             updated_at: board.updated_at,
             columns: columns_with_cards,
             labels,
-        }))
+        })
+    }
+
+    /// Summarize when a board and everything shown with it last changed
+    ///
+    /// Combines the latest `updated_at` and the row count of the board's columns,
+    /// cards, labels, label assignments and attachments, so deletions change the
+    /// result as well as edits.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<Option<String>, sqlx::Error>` - Change summary or None if not found
+    pub async fn change_state(pool: &PgPool, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        let state = sqlx::query_scalar!(
+            r#"
+            SELECT concat_ws('|',
+                b.updated_at,
+                (SELECT MAX(col.updated_at) FROM columns col WHERE col.board_id = b.id),
+                (SELECT COUNT(*) FROM columns col WHERE col.board_id = b.id),
+                (SELECT MAX(c.updated_at) FROM cards c
+                    JOIN columns col ON c.column_id = col.id WHERE col.board_id = b.id),
+                (SELECT COUNT(*) FROM cards c
+                    JOIN columns col ON c.column_id = col.id WHERE col.board_id = b.id),
+                (SELECT MAX(bl.updated_at) FROM board_labels bl WHERE bl.board_id = b.id),
+                (SELECT COUNT(*) FROM board_labels bl WHERE bl.board_id = b.id),
+                (SELECT MAX(cl.created_at) FROM card_labels cl
+                    JOIN board_labels bl ON cl.label_id = bl.id WHERE bl.board_id = b.id),
+                (SELECT COUNT(*) FROM card_labels cl
+                    JOIN board_labels bl ON cl.label_id = bl.id WHERE bl.board_id = b.id),
+                (SELECT MAX(ca.updated_at) FROM card_attachments ca
+                    JOIN cards c ON ca.card_id = c.id
+                    JOIN columns col ON c.column_id = col.id WHERE col.board_id = b.id),
+                (SELECT COUNT(*) FROM card_attachments ca
+                    JOIN cards c ON ca.card_id = c.id
+                    JOIN columns col ON c.column_id = col.id WHERE col.board_id = b.id)
+            ) as "state!"
+            FROM boards b
+            WHERE b.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(state)
     }
 
     /// List all boards
@@ -255,7 +641,7 @@ impl Board {
         let boards = sqlx::query_as!(
             Board,
             r#"
-            SELECT id, share_token, title, description, password, is_locked, created_at, updated_at
+            SELECT id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
             FROM boards
             ORDER BY created_at DESC
             "#
@@ -289,7 +675,7 @@ impl Board {
                 description = COALESCE($3, description),
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, share_token, title, description, password, is_locked, created_at, updated_at
+            RETURNING id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
             "#,
             id,
             input.title,
@@ -323,29 +709,112 @@ impl Board {
         Ok(result.rows_affected() > 0)
     }
 
-    /// Generate a unique share token
+    /// Bump a board's `updated_at` after a change to its columns, cards or labels
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<bool, sqlx::Error>` - True if the board exists
+    pub async fn touch(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE boards
+            SET updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Check whether a share token is already in use
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `share_token` - Share token to check
+    ///
+    /// # Returns
+    /// * `Result<bool, sqlx::Error>` - True if a board uses this token
+    pub async fn share_token_exists(pool: &PgPool, share_token: &str) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM boards WHERE share_token = $1) as "exists!"
+            "#,
+            share_token
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    /// Replace a board's share token
+    ///
+    /// The caller verifies the board password first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    /// * `share_token` - New share token
+    ///
+    /// # Returns
+    /// * `Result<Option<Board>, sqlx::Error>` - Updated board or None if not found
+    pub async fn set_share_token(
+        pool: &PgPool,
+        id: Uuid,
+        share_token: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let board = sqlx::query_as!(
+            Board,
+            r#"
+            UPDATE boards
+            SET
+                share_token = $2,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
+            "#,
+            id,
+            share_token
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(board)
+    }
+
+    /// Generate a random share token
+    ///
+    /// Uniqueness is not guaranteed; callers replacing an existing token
+    /// should check it with `share_token_exists`.
     ///
     /// # Returns
     /// * `String` - Random alphanumeric share token
-    fn generate_share_token() -> String {
+    pub fn generate_share_token() -> String {
+        use crate::utils::share_token::{SHARE_TOKEN_CHARSET, SHARE_TOKEN_LENGTH};
         use rand::Rng;
-        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-        const TOKEN_LEN: usize = 12;
 
         let mut rng = rand::thread_rng();
-        (0..TOKEN_LEN)
+        (0..SHARE_TOKEN_LENGTH)
             .map(|_| {
-                let idx = rng.gen_range(0..CHARSET.len());
-                CHARSET[idx] as char
+                let idx = rng.gen_range(0..SHARE_TOKEN_CHARSET.len());
+                SHARE_TOKEN_CHARSET[idx] as char
             })
             .collect()
     }
 
     /// Generate a random password for board protection
     ///
+    /// Shown to the owner once on creation; only its hash is stored.
+    ///
     /// # Returns
     /// * `String` - Random alphanumeric password
-    fn generate_password() -> String {
+    pub fn generate_password() -> String {
         use rand::Rng;
         const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
         const PASSWORD_LEN: usize = 16;
@@ -359,35 +828,139 @@ impl Board {
             .collect()
     }
 
-    /// Lock or unlock a board with password verification
+    /// Check a candidate password against the stored hash
+    ///
+    /// Argon2 is slow by design, so the check runs on the blocking thread pool
+    /// instead of stalling the worker serving other requests.
+    ///
+    /// # Arguments
+    /// * `candidate` - Password to verify
+    ///
+    /// # Returns
+    /// * `bool` - True if the password matches (never for boards without a password)
+    pub async fn verify_password(&self, candidate: &str) -> bool {
+        if self.password.is_empty() {
+            return false;
+        }
+
+        let id = self.id;
+        let hash = self.password.clone();
+        let candidate = candidate.to_string();
+        let verified = tokio::task::spawn_blocking(move || {
+            let parsed_hash = match PasswordHash::new(&hash) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    log::error!("Password hash of board {} is invalid: {:?}", id, e);
+                    return false;
+                }
+            };
+
+            Argon2::default()
+                .verify_password(candidate.as_bytes(), &parsed_hash)
+                .is_ok()
+        })
+        .await;
+
+        verified.unwrap_or_else(|e| {
+            log::error!("Password verification of board {} failed: {:?}", id, e);
+            false
+        })
+    }
+
+    /// Find boards whose password is still stored in plaintext
+    ///
+    /// Boards created before passwords were hashed store the password itself;
+    /// anything not in Argon2's PHC format is one of those. Boards without a
+    /// password (an empty string) are left out.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    ///
+    /// # Returns
+    /// * `Result<Vec<(Uuid, String)>, sqlx::Error>` - Board IDs with their plaintext passwords
+    pub async fn find_plaintext_passwords(
+        pool: &PgPool,
+    ) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, password
+            FROM boards
+            WHERE password <> '' AND password NOT LIKE '$argon2%'
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row.password)).collect())
+    }
+
+    /// Replace a plaintext board password with its hash
+    ///
+    /// The board's version (`updated_at`) is left alone. Nothing is written
+    /// if the stored password is no longer `plaintext`.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    /// * `plaintext` - Password currently stored
+    /// * `password_hash` - Argon2 hash to store instead
+    ///
+    /// # Returns
+    /// * `Result<bool, sqlx::Error>` - True if the password was replaced
+    pub async fn replace_plaintext_password(
+        pool: &PgPool,
+        id: Uuid,
+        plaintext: &str,
+        password_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE boards
+            SET password = $3
+            WHERE id = $1 AND password = $2
+            "#,
+            id,
+            plaintext,
+            password_hash
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Lock or unlock a board
+    ///
+    /// The caller verifies the board password first.
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `id` - Board UUID
-    /// * `password` - Password to verify
     /// * `is_locked` - New lock state
+    /// * `expected_updated_at` - Version the board must still be at
     ///
     /// # Returns
-    /// * `Result<Option<Board>, sqlx::Error>` - Updated board or None if password is incorrect
+    /// * `Result<Option<Board>, sqlx::Error>` - Updated board or None if the
+    ///   board is gone or has changed since `expected_updated_at`
     pub async fn set_lock_state(
         pool: &PgPool,
         id: Uuid,
-        password: &str,
         is_locked: bool,
+        expected_updated_at: DateTime<Utc>,
     ) -> Result<Option<Self>, sqlx::Error> {
         let board = sqlx::query_as!(
             Board,
             r#"
             UPDATE boards
             SET
-                is_locked = $3,
+                is_locked = $2,
                 updated_at = NOW()
-            WHERE id = $1 AND password = $2
-            RETURNING id, share_token, title, description, password, is_locked, created_at, updated_at
+            WHERE id = $1 AND updated_at = $3
+            RETURNING id, share_token, title, description, password, is_locked, view_token, created_at, updated_at
             "#,
             id,
-            password,
-            is_locked
+            is_locked,
+            expected_updated_at
         )
         .fetch_optional(pool)
         .await?;
@@ -395,3 +968,230 @@ impl Board {
         Ok(board)
     }
 }
+
+impl ColumnWithCards {
+    /// Load a column's active cards with their labels and attachments
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `column` - Column to load cards for
+    ///
+    /// # Returns
+    /// * `Result<ColumnWithCards, sqlx::Error>` - Column with cards
+    pub async fn load(pool: &PgPool, column: Column) -> Result<Self, sqlx::Error> {
+        // Get all cards for this column
+        let cards = Card::find_by_column_id(pool, column.id).await?;
+
+        // Build cards with labels and attachments
+        let mut cards_with_labels = Vec::new();
+        for card in cards {
+            // Get all labels for this card
+            let labels = BoardLabel::find_by_card_id(pool, card.id).await?;
+
+            // Get all attachments for this card
+            let attachments = CardAttachment::find_by_card_id(pool, card.id).await?;
+
+            cards_with_labels.push(CardWithLabels {
+                id: card.id,
+                column_id: card.column_id,
+                title: card.title,
+                description: card.description,
+                color: card.color,
+                position: card.position,
+                created_at: card.created_at,
+                updated_at: card.updated_at,
+                labels,
+                attachments,
+            });
+        }
+
+        Ok(ColumnWithCards {
+            id: column.id,
+            board_id: column.board_id,
+            title: column.title,
+            position: column.position,
+            archived_at: column.archived_at,
+            created_at: column.created_at,
+            updated_at: column.updated_at,
+            cards: cards_with_labels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every object key in `value`, at any depth
+    fn keys(value: &serde_json::Value) -> Vec<String> {
+        match value {
+            serde_json::Value::Object(map) => map
+                .iter()
+                .flat_map(|(key, value)| std::iter::once(key.clone()).chain(keys(value)))
+                .collect(),
+            serde_json::Value::Array(items) => items.iter().flat_map(keys).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// A board with an Argon2-looking password, as loaded from the database
+    fn protected_board() -> Board {
+        let now = Utc::now();
+        Board {
+            id: Uuid::new_v4(),
+            share_token: "sharetoken01".to_string(),
+            title: "Roadmap".to_string(),
+            description: None,
+            password: "$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA".to_string(),
+            is_locked: true,
+            view_token: "viewtoken001".to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn board_updated_event_carries_no_password() {
+        let event = crate::sse::events::SseEvent::BoardUpdated {
+            board: protected_board().into(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], "board_updated");
+        assert_eq!(json["board"]["title"], "Roadmap");
+        for key in keys(&json) {
+            assert!(
+                !key.contains("password") && key != "share_token",
+                "board event exposes `{}`",
+                key
+            );
+        }
+        assert!(!json.to_string().contains("$argon2"));
+    }
+
+    #[test]
+    fn share_token_board_reports_protection_without_password() {
+        let board = protected_board();
+        let relations = BoardWithRelations {
+            id: board.id,
+            share_token: board.share_token,
+            title: board.title,
+            description: board.description,
+            is_protected: !board.password.is_empty(),
+            is_locked: board.is_locked,
+            view_token: board.view_token,
+            presence_id: None,
+            created_at: board.created_at,
+            updated_at: board.updated_at,
+            columns: Vec::new(),
+            labels: Vec::new(),
+        };
+
+        let json = serde_json::to_value(&relations).unwrap();
+
+        assert_eq!(json["is_protected"], true);
+        assert_eq!(json["share_token"], "sharetoken01");
+        assert!(keys(&json).iter().all(|key| !key.contains("password")));
+        assert!(!json.to_string().contains("$argon2"));
+    }
+
+    #[test]
+    fn public_board_carries_no_password() {
+        let board = protected_board();
+
+        // `get_board` responds with the public view
+        let json = serde_json::to_value(BoardPublic::from(board.clone())).unwrap();
+        assert_eq!(json["id"], board.id.to_string());
+        assert_eq!(json["is_locked"], true);
+        for key in keys(&json) {
+            assert!(
+                !key.contains("password") && key != "share_token",
+                "public board exposes `{}`",
+                key
+            );
+        }
+
+        // The internal model never serializes the hash either
+        let json = serde_json::to_value(&board).unwrap();
+        assert!(keys(&json).iter().all(|key| !key.contains("password")));
+        assert!(!json.to_string().contains("$argon2"));
+    }
+
+    #[test]
+    fn board_view_carries_no_ids_or_tokens() {
+        let now = Utc::now();
+        let board_id = Uuid::new_v4();
+        let column_id = Uuid::new_v4();
+        let card_id = Uuid::new_v4();
+        let label = BoardLabel {
+            id: Uuid::new_v4(),
+            board_id,
+            name: "Bug".to_string(),
+            color: "#ff0000".to_string(),
+            position: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        let board = BoardWithRelations {
+            id: board_id,
+            share_token: "share".to_string(),
+            title: "Roadmap".to_string(),
+            description: None,
+            is_protected: true,
+            is_locked: false,
+            view_token: "view".to_string(),
+            presence_id: Some(42),
+            created_at: now,
+            updated_at: now,
+            columns: vec![ColumnWithCards {
+                id: column_id,
+                board_id,
+                title: "To do".to_string(),
+                position: 0,
+                archived_at: None,
+                created_at: now,
+                updated_at: now,
+                cards: vec![CardWithLabels {
+                    id: card_id,
+                    column_id,
+                    title: "Fix login".to_string(),
+                    description: Some("Steps inside".to_string()),
+                    color: None,
+                    position: 0,
+                    created_at: now,
+                    updated_at: now,
+                    labels: vec![label.clone()],
+                    attachments: vec![CardAttachment {
+                        id: Uuid::new_v4(),
+                        card_id,
+                        uploaded_by: Uuid::new_v4(),
+                        filename: "a.png".to_string(),
+                        original_filename: "screenshot.png".to_string(),
+                        content_type: "image/png".to_string(),
+                        file_size: 1024,
+                        s3_key: "attachments/a.png".to_string(),
+                        s3_bucket: "bucket".to_string(),
+                        is_confirmed: true,
+                        position: 0,
+                        created_at: now,
+                        updated_at: now,
+                    }],
+                }],
+            }],
+            labels: vec![label],
+        };
+
+        let json = serde_json::to_value(BoardView::from(board)).unwrap();
+
+        assert_eq!(json["columns"][0]["cards"][0]["title"], "Fix login");
+        assert_eq!(json["labels"][0]["name"], "Bug");
+        for key in keys(&json) {
+            assert!(
+                key != "id" && !key.ends_with("_id") && !key.ends_with("_token"),
+                "read-only view exposes `{}`",
+                key
+            );
+        }
+    }
+}
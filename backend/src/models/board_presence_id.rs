@@ -0,0 +1,119 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Mapping from board UUIDs to presence IDs, stored in `board_presence_ids`
+///
+/// Presence frames carry boards as a u32. Postgres has no unsigned integers,
+/// so IDs are stored as BIGINT and converted with `to_db`/`from_db`.
+pub struct BoardPresenceId;
+
+impl BoardPresenceId {
+    /// Find the presence ID of a board
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<Option<u32>, sqlx::Error>` - Presence ID, or None if the board has none yet
+    pub async fn find_by_board_id(
+        pool: &PgPool,
+        board_id: Uuid,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let presence_id = sqlx::query_scalar!(
+            r#"
+            SELECT presence_id
+            FROM board_presence_ids
+            WHERE board_id = $1
+            "#,
+            board_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(presence_id.map(from_db))
+    }
+
+    /// Give a board a presence ID
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    /// * `presence_id` - Presence ID to give it
+    ///
+    /// # Returns
+    /// * `Result<bool, sqlx::Error>` - True if stored; false if the board already
+    ///   has an ID or another board holds this one
+    pub async fn insert(
+        pool: &PgPool,
+        board_id: Uuid,
+        presence_id: u32,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO board_presence_ids (board_id, presence_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            board_id,
+            to_db(presence_id)
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Find the boards that have no presence ID yet, oldest first
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    ///
+    /// # Returns
+    /// * `Result<Vec<Uuid>, sqlx::Error>` - Board UUIDs
+    pub async fn find_unmapped_board_ids(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT b.id
+            FROM boards b
+            LEFT JOIN board_presence_ids p ON p.board_id = b.id
+            WHERE p.board_id IS NULL
+            ORDER BY b.created_at, b.id
+            "#
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// Store a presence ID in a BIGINT column
+pub fn to_db(presence_id: u32) -> i64 {
+    presence_id.into()
+}
+
+/// Read a presence ID from a BIGINT column
+///
+/// The column's CHECK constraint keeps values within u32; anything else
+/// would be a corrupt row and is clamped rather than wrapped.
+pub fn from_db(presence_id: i64) -> u32 {
+    presence_id.clamp(0, u32::MAX.into()) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversion_round_trips_full_u32_range() {
+        for presence_id in [0, 1, 65_535, 65_536, u32::MAX / 2, u32::MAX - 1, u32::MAX] {
+            assert_eq!(from_db(to_db(presence_id)), presence_id);
+        }
+        assert_eq!(to_db(u32::MAX), 4_294_967_295);
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        assert_eq!(from_db(-1), 0);
+        assert_eq!(from_db(i64::from(u32::MAX) + 1), u32::MAX);
+    }
+}
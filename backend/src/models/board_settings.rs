@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::utils::serde_helpers::deserialize_null_default;
+
+/// Per-board feature flags, stored as JSONB in `board_settings`
+///
+/// Boards without a row, and keys missing from a stored row, use the
+/// defaults, so new flags do not need a data migration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoardSettings {
+    /// Whether AI description generation may be used for this board
+    pub ai_enabled: bool,
+    /// Most active cards a column may hold; `None` for no limit
+    pub wip_limit: Option<u32>,
+    /// Whether reorder events are coalesced before reaching SSE clients
+    pub coalesce_reorders: bool,
+}
+
+impl Default for BoardSettings {
+    fn default() -> Self {
+        Self {
+            ai_enabled: true,
+            wip_limit: None,
+            coalesce_reorders: true,
+        }
+    }
+}
+
+/// Input data for updating board settings; omitted flags keep their value
+#[derive(Debug, Deserialize)]
+pub struct UpdateBoardSettingsInput {
+    pub ai_enabled: Option<bool>,
+    /// `null` removes the limit
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub wip_limit: Option<Option<u32>>,
+    pub coalesce_reorders: Option<bool>,
+}
+
+impl BoardSettings {
+    /// Apply an update, keeping the flags it omits
+    pub fn apply(&mut self, input: UpdateBoardSettingsInput) {
+        if let Some(ai_enabled) = input.ai_enabled {
+            self.ai_enabled = ai_enabled;
+        }
+        if let Some(wip_limit) = input.wip_limit {
+            self.wip_limit = wip_limit;
+        }
+        if let Some(coalesce_reorders) = input.coalesce_reorders {
+            self.coalesce_reorders = coalesce_reorders;
+        }
+    }
+
+    /// Find the settings of a board
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<Option<BoardSettings>, sqlx::Error>` - Stored settings, or None if the
+    ///   board has never changed them
+    pub async fn find_by_board_id(
+        pool: &PgPool,
+        board_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let settings = sqlx::query_scalar!(
+            r#"
+            SELECT settings AS "settings: Json<BoardSettings>"
+            FROM board_settings
+            WHERE board_id = $1
+            "#,
+            board_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(settings.map(|Json(settings)| settings))
+    }
+
+    /// Store the settings of a board, replacing any stored before
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    /// * `settings` - Settings to store
+    ///
+    /// # Returns
+    /// * `Result<(), sqlx::Error>` - Success or error
+    pub async fn save(pool: &PgPool, board_id: Uuid, settings: &Self) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO board_settings (board_id, settings)
+            VALUES ($1, $2)
+            ON CONFLICT (board_id) DO UPDATE
+            SET settings = EXCLUDED.settings, updated_at = NOW()
+            "#,
+            board_id,
+            Json(settings) as _
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
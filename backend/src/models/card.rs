@@ -5,6 +5,26 @@ use uuid::Uuid;
 
 use crate::utils::serde_helpers::deserialize_null_default;
 
+/// What a purge of archived items deleted
+#[derive(Debug, Default)]
+pub struct PurgedArchive {
+    /// Number of cards or columns deleted
+    pub deleted: u64,
+    /// S3 keys of the attachments deleted with them
+    pub s3_keys: Vec<String>,
+}
+
+/// Outcome of `Card::merge`
+#[derive(Debug)]
+pub enum MergeOutcome {
+    /// The updated target card
+    Merged(Card),
+    /// Either card was not found
+    NotFound,
+    /// The combined description is longer than allowed; nothing was changed
+    DescriptionTooLong,
+}
+
 /// Card model representing a card in a column
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Card {
@@ -12,9 +32,50 @@ pub struct Card {
     pub column_id: Uuid,
     pub title: String,
     pub description: Option<String>,
+    /// Accent color (hex or color name), independent of the card's labels
+    pub color: Option<String>,
+    pub position: i32,
+    /// Template cards can be copied into new cards with `create_from_template`
+    pub is_template: bool,
+    /// Set when the card has been archived; archived cards are hidden from boards
+    pub archived_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Average time from card creation to reaching a terminal column
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CycleTimeStats {
+    /// Cards currently in a terminal column, including archived ones
+    pub completed_cards: i64,
+    /// Average cycle time in seconds, or None if no card is completed
+    pub average_seconds: Option<f64>,
+}
+
+/// A card search hit with its relevance score
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ScoredCard {
+    pub id: Uuid,
+    pub column_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
     pub position: i32,
+    pub is_template: bool,
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Higher is more relevant; only meaningful relative to other hits of the same search
+    pub score: f64,
+}
+
+/// Query parameters for card search
+#[derive(Debug, Deserialize)]
+pub struct CardSearchQuery {
+    /// Text to find in card titles and descriptions
+    pub q: String,
+    /// Comma-separated label IDs; cards carrying them rank higher
+    pub label_ids: Option<String>,
 }
 
 /// Input data for creating a new card
@@ -23,7 +84,9 @@ pub struct CreateCardInput {
     pub column_id: Uuid,
     pub title: String,
     pub description: Option<String>,
-    pub position: i32,
+    pub color: Option<String>,
+    /// Target position; `None` (or a position past the end) appends the card
+    pub position: Option<i32>,
 }
 
 /// Input data for updating a card
@@ -32,36 +95,96 @@ pub struct UpdateCardInput {
     pub title: Option<String>,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub color: Option<Option<String>>,
     pub position: Option<i32>,
     pub column_id: Option<Uuid>,
+    pub is_template: Option<bool>,
 }
 
 impl Card {
     /// Create a new card
     ///
+    /// Runs in a transaction: if the target position is already taken, every
+    /// card at or after it in the column is shifted down by one first.
+    /// Without a position, or with one past the last card, the card is appended.
+    /// The returned card carries the committed position.
+    ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `input` - Card creation data
     ///
     /// # Returns
-    /// * `Result<Card, sqlx::Error>` - Created card or error
-    pub async fn create(pool: &PgPool, input: CreateCardInput) -> Result<Self, sqlx::Error> {
+    /// * `Result<(Card, Vec<Card>), sqlx::Error>` - Created card and the cards that were shifted
+    pub async fn create(
+        pool: &PgPool,
+        input: CreateCardInput,
+    ) -> Result<(Self, Vec<Self>), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Serialize concurrent inserts into the same column
+        sqlx::query!(
+            r#"
+            SELECT id FROM columns WHERE id = $1 FOR UPDATE
+            "#,
+            input.column_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let append_position = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(MAX(position) + 1, 0) AS "position!"
+            FROM cards
+            WHERE column_id = $1 AND archived_at IS NULL
+            "#,
+            input.column_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let position = input
+            .position
+            .map_or(append_position, |position| position.min(append_position));
+
+        let shifted = sqlx::query_as!(
+            Card,
+            r#"
+            UPDATE cards
+            SET position = position + 1, updated_at = NOW()
+            WHERE column_id = $1
+              AND position >= $2
+              AND archived_at IS NULL
+              AND EXISTS (
+                  SELECT 1 FROM cards
+                  WHERE column_id = $1 AND position = $2 AND archived_at IS NULL
+              )
+            RETURNING id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
+            "#,
+            input.column_id,
+            position
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
         let card = sqlx::query_as!(
             Card,
             r#"
-            INSERT INTO cards (column_id, title, description, position)
-            VALUES ($1, $2, $3, $4)
-            RETURNING id, column_id, title, description, position, created_at, updated_at
+            INSERT INTO cards (column_id, title, description, color, position)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
             "#,
             input.column_id,
             input.title,
             input.description,
-            input.position
+            input.color,
+            position
         )
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(card)
+        tx.commit().await?;
+
+        Ok((card, shifted))
     }
 
     /// Find a card by ID
@@ -76,7 +199,7 @@ impl Card {
         let card = sqlx::query_as!(
             Card,
             r#"
-            SELECT id, column_id, title, description, position, created_at, updated_at
+            SELECT id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
             FROM cards
             WHERE id = $1
             "#,
@@ -88,7 +211,7 @@ impl Card {
         Ok(card)
     }
 
-    /// Find all cards for a column
+    /// Find all active (non-archived) cards for a column
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
@@ -103,9 +226,9 @@ impl Card {
         let cards = sqlx::query_as!(
             Card,
             r#"
-            SELECT id, column_id, title, description, position, created_at, updated_at
+            SELECT id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
             FROM cards
-            WHERE column_id = $1
+            WHERE column_id = $1 AND archived_at IS NULL
             ORDER BY position ASC
             "#,
             column_id
@@ -116,7 +239,7 @@ impl Card {
         Ok(cards)
     }
 
-    /// Find all cards for a board
+    /// Find all active (non-archived) cards for a board
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
@@ -128,10 +251,11 @@ impl Card {
         let cards = sqlx::query_as!(
             Card,
             r#"
-            SELECT c.id, c.column_id, c.title, c.description, c.position, c.created_at, c.updated_at
+            SELECT c.id, c.column_id, c.title, c.description, c.color, c.position, c.is_template,
+                   c.archived_at, c.created_at, c.updated_at
             FROM cards c
             INNER JOIN columns col ON c.column_id = col.id
-            WHERE col.board_id = $1
+            WHERE col.board_id = $1 AND c.archived_at IS NULL
             ORDER BY col.position ASC, c.position ASC
             "#,
             board_id
@@ -142,6 +266,141 @@ impl Card {
         Ok(cards)
     }
 
+    /// Search a board's active cards by title and description
+    ///
+    /// Scoring (see `ScoredCard::score`):
+    /// * 1 for a description-only match, 2 when the title matches
+    /// * +3 per requested label the card carries, so a label match always
+    ///   outranks text and recency alone
+    /// * up to +1 for recency, halving with each day since the last update
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    /// * `pattern` - `ILIKE` pattern to match (wildcards in user input already escaped)
+    /// * `label_ids` - Labels that boost a card's score
+    /// * `limit` - Maximum number of results
+    ///
+    /// # Returns
+    /// * `Result<Vec<ScoredCard>, sqlx::Error>` - Matching cards, best first
+    pub async fn search(
+        pool: &PgPool,
+        board_id: Uuid,
+        pattern: &str,
+        label_ids: &[Uuid],
+        limit: i64,
+    ) -> Result<Vec<ScoredCard>, sqlx::Error> {
+        let cards = sqlx::query_as!(
+            ScoredCard,
+            r#"
+            SELECT c.id, c.column_id, c.title, c.description, c.color, c.position, c.is_template,
+                   c.archived_at, c.created_at, c.updated_at,
+                   (
+                       CASE WHEN c.title ILIKE $2 THEN 2.0 ELSE 1.0 END
+                       + 3.0 * (SELECT COUNT(*) FROM card_labels cl
+                                WHERE cl.card_id = c.id AND cl.label_id = ANY($3))
+                       + POWER(0.5, EXTRACT(EPOCH FROM (NOW() - c.updated_at)) / 86400.0)
+                   )::FLOAT8 AS "score!"
+            FROM cards c
+            INNER JOIN columns col ON c.column_id = col.id
+            WHERE col.board_id = $1
+              AND c.archived_at IS NULL
+              AND (c.title ILIKE $2 OR c.description ILIKE $2)
+            ORDER BY "score!" DESC, c.updated_at DESC
+            LIMIT $4
+            "#,
+            board_id,
+            pattern,
+            label_ids,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    /// Compute cycle time statistics for a board
+    ///
+    /// A card's cycle time runs from its creation until it reached the
+    /// terminal column it is in (`completed_at`, kept by a trigger).
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<CycleTimeStats, sqlx::Error>` - Completed card count and average cycle time
+    pub async fn cycle_time_by_board_id(
+        pool: &PgPool,
+        board_id: Uuid,
+    ) -> Result<CycleTimeStats, sqlx::Error> {
+        let stats = sqlx::query_as!(
+            CycleTimeStats,
+            r#"
+            SELECT COUNT(*) AS "completed_cards!",
+                   AVG(EXTRACT(EPOCH FROM (c.completed_at - c.created_at)))::FLOAT8 AS average_seconds
+            FROM cards c
+            INNER JOIN columns col ON c.column_id = col.id
+            WHERE col.board_id = $1 AND c.completed_at IS NOT NULL
+            "#,
+            board_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Count the active cards shown on a board
+    ///
+    /// Archived cards and cards in archived columns are not counted.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<i64, sqlx::Error>` - Number of active cards
+    pub async fn count_by_board(pool: &PgPool, board_id: Uuid) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM cards c
+            INNER JOIN columns col ON c.column_id = col.id
+            WHERE col.board_id = $1 AND col.archived_at IS NULL AND c.archived_at IS NULL
+            "#,
+            board_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Count the active cards in a column
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `column_id` - Column UUID
+    ///
+    /// # Returns
+    /// * `Result<i64, sqlx::Error>` - Number of active cards
+    pub async fn count_by_column(pool: &PgPool, column_id: Uuid) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM cards
+            WHERE column_id = $1 AND archived_at IS NULL
+            "#,
+            column_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
     /// Update a card
     ///
     /// # Arguments
@@ -156,10 +415,12 @@ impl Card {
         id: Uuid,
         input: UpdateCardInput,
     ) -> Result<Option<Self>, sqlx::Error> {
-        // Flatten the Option<Option<String>> for description
+        // Flatten the Option<Option<String>> for description and color
         // None = don't update, Some(None) = set to NULL, Some(Some(v)) = set to v
         let update_description = input.description.is_some();
         let description_value = input.description.clone().flatten();
+        let update_color = input.color.is_some();
+        let color_value = input.color.clone().flatten();
 
         let card = sqlx::query_as!(
             Card,
@@ -170,16 +431,21 @@ impl Card {
                 description = CASE WHEN $6 THEN $3 ELSE description END,
                 position = COALESCE($4, position),
                 column_id = COALESCE($5, column_id),
+                is_template = COALESCE($7, is_template),
+                color = CASE WHEN $9 THEN $8 ELSE color END,
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, column_id, title, description, position, created_at, updated_at
+            RETURNING id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
             "#,
             id,
             input.title,
             description_value,
             input.position,
             input.column_id,
-            update_description
+            update_description,
+            input.is_template,
+            color_value,
+            update_color
         )
         .fetch_optional(pool)
         .await?;
@@ -234,7 +500,7 @@ impl Card {
                 position = $3,
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, column_id, title, description, position, created_at, updated_at
+            RETURNING id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
             "#,
             id,
             new_column_id,
@@ -246,6 +512,274 @@ impl Card {
         Ok(card)
     }
 
+    /// Archive every active card in a column
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `column_id` - Column UUID
+    ///
+    /// # Returns
+    /// * `Result<Vec<Uuid>, sqlx::Error>` - IDs of the cards that were archived
+    pub async fn archive_by_column_id(
+        pool: &PgPool,
+        column_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Serialize with concurrent inserts into the same column
+        sqlx::query!(
+            r#"
+            SELECT id FROM columns WHERE id = $1 FOR UPDATE
+            "#,
+            column_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let card_ids = sqlx::query_scalar!(
+            r#"
+            UPDATE cards
+            SET archived_at = NOW(), updated_at = NOW()
+            WHERE column_id = $1 AND archived_at IS NULL
+            RETURNING id
+            "#,
+            column_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(card_ids)
+    }
+
+    /// Permanently delete cards archived before a cutoff
+    ///
+    /// Attachment rows are deleted first, in the same transaction, so the keys
+    /// of their S3 objects can be returned before the cascade would drop them.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `cutoff` - Cards archived before this time are deleted
+    ///
+    /// # Returns
+    /// * `Result<PurgedArchive, sqlx::Error>` - Number of cards deleted and their attachments' S3 keys
+    pub async fn purge_archived(
+        pool: &PgPool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<PurgedArchive, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let s3_keys = sqlx::query_scalar!(
+            r#"
+            DELETE FROM card_attachments
+            WHERE card_id IN (SELECT id FROM cards WHERE archived_at < $1)
+            RETURNING s3_key
+            "#,
+            cutoff
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM cards
+            WHERE archived_at < $1
+            "#,
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(PurgedArchive { deleted, s3_keys })
+    }
+
+    /// Merge one card into another
+    ///
+    /// Runs in a transaction: the source's description is appended to the
+    /// target's, the source's labels and attachments move to the target, and
+    /// the source card is deleted. The transaction is rolled back if the
+    /// combined description exceeds `max_description_length` bytes.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `source_id` - UUID of the card merged away
+    /// * `target_id` - UUID of the card that remains
+    /// * `max_description_length` - Longest description the target may end up with
+    ///
+    /// # Returns
+    /// * `Result<MergeOutcome, sqlx::Error>` - Updated target card, or why nothing was merged
+    pub async fn merge(
+        pool: &PgPool,
+        source_id: Uuid,
+        target_id: Uuid,
+        max_description_length: usize,
+    ) -> Result<MergeOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Lock both cards so neither changes mid-merge
+        let locked = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM cards WHERE id = $1 OR id = $2 FOR UPDATE
+            "#,
+            source_id,
+            target_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if locked.len() != 2 {
+            return Ok(MergeOutcome::NotFound);
+        }
+
+        let card = sqlx::query_as!(
+            Card,
+            r#"
+            UPDATE cards AS target
+            SET
+                description = CASE
+                    WHEN source.description IS NULL OR source.description = '' THEN target.description
+                    WHEN target.description IS NULL OR target.description = '' THEN source.description
+                    ELSE target.description || E'\n\n' || source.description
+                END,
+                updated_at = NOW()
+            FROM cards AS source
+            WHERE target.id = $1 AND source.id = $2
+            RETURNING target.id, target.column_id, target.title, target.description, target.color,
+                      target.position, target.is_template, target.archived_at,
+                      target.created_at, target.updated_at
+            "#,
+            target_id,
+            source_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Dropping the transaction rolls the update back
+        if card
+            .description
+            .as_ref()
+            .is_some_and(|description| description.len() > max_description_length)
+        {
+            return Ok(MergeOutcome::DescriptionTooLong);
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO card_labels (card_id, label_id)
+            SELECT $1, label_id FROM card_labels WHERE card_id = $2
+            ON CONFLICT (card_id, label_id) DO NOTHING
+            "#,
+            target_id,
+            source_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Keep the source's attachments after the target's own, in their existing order
+        sqlx::query!(
+            r#"
+            UPDATE card_attachments
+            SET
+                card_id = $1,
+                position = position + (
+                    SELECT COALESCE(MAX(position) + 1, 0) FROM card_attachments WHERE card_id = $1
+                ),
+                updated_at = NOW()
+            WHERE card_id = $2
+            "#,
+            target_id,
+            source_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM cards
+            WHERE id = $1
+            "#,
+            source_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(MergeOutcome::Merged(card))
+    }
+
+    /// Create a new card from a template card
+    ///
+    /// Runs in a transaction: the template's title, description, color and labels
+    /// are copied into a new, non-template card appended to the column.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `template_id` - UUID of the template card
+    /// * `column_id` - Column UUID for the new card
+    ///
+    /// # Returns
+    /// * `Result<Option<Card>, sqlx::Error>` - Created card or None if the template was not found
+    pub async fn create_from_template(
+        pool: &PgPool,
+        template_id: Uuid,
+        column_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Serialize concurrent inserts into the same column
+        sqlx::query!(
+            r#"
+            SELECT id FROM columns WHERE id = $1 FOR UPDATE
+            "#,
+            column_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let card = sqlx::query_as!(
+            Card,
+            r#"
+            INSERT INTO cards (column_id, title, description, color, position)
+            SELECT $2, template.title, template.description, template.color, (
+                SELECT COALESCE(MAX(position) + 1, 0)
+                FROM cards
+                WHERE column_id = $2 AND archived_at IS NULL
+            )
+            FROM cards AS template
+            WHERE template.id = $1 AND template.is_template
+            RETURNING id, column_id, title, description, color, position, is_template, archived_at, created_at, updated_at
+            "#,
+            template_id,
+            column_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(card) = card else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO card_labels (card_id, label_id)
+            SELECT $1, label_id FROM card_labels WHERE card_id = $2
+            "#,
+            card.id,
+            template_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(card))
+    }
+
     /// Reorder cards within a column
     ///
     /// # Arguments
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::models::card::PurgedArchive;
+
 /// Column model representing a column in a Kanban board
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Column {
@@ -10,6 +12,11 @@ pub struct Column {
     pub board_id: Uuid,
     pub title: String,
     pub position: i32,
+    /// Cards in a terminal column (e.g. "Done") count as completed
+    pub is_terminal: bool,
+    /// Set when the column has been archived; archived columns and their cards
+    /// are hidden from boards
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -20,6 +27,7 @@ pub struct CreateColumnInput {
     pub board_id: Uuid,
     pub title: String,
     pub position: i32,
+    pub is_terminal: bool,
 }
 
 /// Input data for updating a column
@@ -27,6 +35,7 @@ pub struct CreateColumnInput {
 pub struct UpdateColumnInput {
     pub title: Option<String>,
     pub position: Option<i32>,
+    pub is_terminal: Option<bool>,
 }
 
 impl Column {
@@ -42,13 +51,14 @@ impl Column {
         let column = sqlx::query_as!(
             Column,
             r#"
-            INSERT INTO columns (board_id, title, position)
-            VALUES ($1, $2, $3)
-            RETURNING id, board_id, title, position, created_at, updated_at
+            INSERT INTO columns (board_id, title, position, is_terminal)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, board_id, title, position, is_terminal, archived_at, created_at, updated_at
             "#,
             input.board_id,
             input.title,
-            input.position
+            input.position,
+            input.is_terminal
         )
         .fetch_one(pool)
         .await?;
@@ -68,7 +78,7 @@ impl Column {
         let column = sqlx::query_as!(
             Column,
             r#"
-            SELECT id, board_id, title, position, created_at, updated_at
+            SELECT id, board_id, title, position, is_terminal, archived_at, created_at, updated_at
             FROM columns
             WHERE id = $1
             "#,
@@ -92,7 +102,7 @@ impl Column {
         let columns = sqlx::query_as!(
             Column,
             r#"
-            SELECT id, board_id, title, position, created_at, updated_at
+            SELECT id, board_id, title, position, is_terminal, archived_at, created_at, updated_at
             FROM columns
             WHERE board_id = $1
             ORDER BY position ASC
@@ -105,6 +115,31 @@ impl Column {
         Ok(columns)
     }
 
+    /// Count the columns shown on a board
+    ///
+    /// Archived columns are not counted.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<i64, sqlx::Error>` - Number of columns that are not archived
+    pub async fn count_by_board(pool: &PgPool, board_id: Uuid) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM columns
+            WHERE board_id = $1 AND archived_at IS NULL
+            "#,
+            board_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
     /// Update a column
     ///
     /// # Arguments
@@ -126,13 +161,51 @@ impl Column {
             SET 
                 title = COALESCE($2, title),
                 position = COALESCE($3, position),
+                is_terminal = COALESCE($4, is_terminal),
                 updated_at = NOW()
             WHERE id = $1
-            RETURNING id, board_id, title, position, created_at, updated_at
+            RETURNING id, board_id, title, position, is_terminal, archived_at, created_at, updated_at
             "#,
             id,
             input.title,
-            input.position
+            input.position,
+            input.is_terminal
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(column)
+    }
+
+    /// Archive or restore a column
+    ///
+    /// Archiving an already archived column keeps its original `archived_at`.
+    /// The column's cards are left untouched.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Column UUID
+    /// * `archived` - True to archive, false to restore
+    ///
+    /// # Returns
+    /// * `Result<Option<Column>, sqlx::Error>` - Updated column or None if not found
+    pub async fn set_archived(
+        pool: &PgPool,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let column = sqlx::query_as!(
+            Column,
+            r#"
+            UPDATE columns
+            SET
+                archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) ELSE NULL END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, board_id, title, position, is_terminal, archived_at, created_at, updated_at
+            "#,
+            id,
+            archived
         )
         .fetch_optional(pool)
         .await?;
@@ -197,4 +270,53 @@ impl Column {
 
         Ok(())
     }
+
+    /// Permanently delete columns archived before a cutoff, with their cards
+    ///
+    /// Attachment rows of the cards are deleted first, in the same
+    /// transaction, so the keys of their S3 objects can be returned before the
+    /// cascade would drop them.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `cutoff` - Columns archived before this time are deleted
+    ///
+    /// # Returns
+    /// * `Result<PurgedArchive, sqlx::Error>` - Number of columns deleted and their attachments' S3 keys
+    pub async fn purge_archived(
+        pool: &PgPool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<PurgedArchive, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let s3_keys = sqlx::query_scalar!(
+            r#"
+            DELETE FROM card_attachments
+            WHERE card_id IN (
+                SELECT c.id FROM cards c
+                JOIN columns col ON col.id = c.column_id
+                WHERE col.archived_at < $1
+            )
+            RETURNING s3_key
+            "#,
+            cutoff
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM columns
+            WHERE archived_at < $1
+            "#,
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(PurgedArchive { deleted, s3_keys })
+    }
 }
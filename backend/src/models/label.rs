@@ -10,6 +10,7 @@ pub struct BoardLabel {
     pub board_id: Uuid,
     pub name: String,
     pub color: String,
+    pub position: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -22,6 +23,19 @@ pub struct CardLabel {
     pub created_at: DateTime<Utc>,
 }
 
+/// Board label with the number of cards it is assigned to
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BoardLabelUsage {
+    pub id: Uuid,
+    pub board_id: Uuid,
+    pub name: String,
+    pub color: String,
+    pub position: i32,
+    pub card_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 /// Input data for creating a new board label
 #[derive(Debug, Deserialize)]
 pub struct CreateBoardLabelInput {
@@ -50,9 +64,12 @@ impl BoardLabel {
         let label = sqlx::query_as!(
             BoardLabel,
             r#"
-            INSERT INTO board_labels (board_id, name, color)
-            VALUES ($1, $2, $3)
-            RETURNING id, board_id, name, color, created_at, updated_at
+            INSERT INTO board_labels (board_id, name, color, position)
+            VALUES (
+                $1, $2, $3,
+                (SELECT COALESCE(MAX(position) + 1, 0) FROM board_labels WHERE board_id = $1)
+            )
+            RETURNING id, board_id, name, color, position, created_at, updated_at
             "#,
             input.board_id,
             input.name,
@@ -76,7 +93,7 @@ impl BoardLabel {
         let label = sqlx::query_as!(
             BoardLabel,
             r#"
-            SELECT id, board_id, name, color, created_at, updated_at
+            SELECT id, board_id, name, color, position, created_at, updated_at
             FROM board_labels
             WHERE id = $1
             "#,
@@ -100,10 +117,10 @@ impl BoardLabel {
         let labels = sqlx::query_as!(
             BoardLabel,
             r#"
-            SELECT id, board_id, name, color, created_at, updated_at
+            SELECT id, board_id, name, color, position, created_at, updated_at
             FROM board_labels
             WHERE board_id = $1
-            ORDER BY created_at ASC
+            ORDER BY position ASC, created_at ASC
             "#,
             board_id
         )
@@ -125,11 +142,12 @@ impl BoardLabel {
         let labels = sqlx::query_as!(
             BoardLabel,
             r#"
-            SELECT bl.id, bl.board_id, bl.name, bl.color, bl.created_at, bl.updated_at
+            SELECT bl.id, bl.board_id, bl.name, bl.color, bl.position,
+                   bl.created_at, bl.updated_at
             FROM board_labels bl
             INNER JOIN card_labels cl ON bl.id = cl.label_id
             WHERE cl.card_id = $1
-            ORDER BY bl.created_at ASC
+            ORDER BY bl.position ASC, bl.created_at ASC
             "#,
             card_id
         )
@@ -139,6 +157,38 @@ impl BoardLabel {
         Ok(labels)
     }
 
+    /// Find all labels for a board with their card assignment counts
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `Result<Vec<BoardLabelUsage>, sqlx::Error>` - Labels with card counts (zero if unused)
+    pub async fn usage_by_board_id(
+        pool: &PgPool,
+        board_id: Uuid,
+    ) -> Result<Vec<BoardLabelUsage>, sqlx::Error> {
+        let usage = sqlx::query_as!(
+            BoardLabelUsage,
+            r#"
+            SELECT bl.id, bl.board_id, bl.name, bl.color, bl.position,
+                   COUNT(cl.card_id) AS "card_count!",
+                   bl.created_at, bl.updated_at
+            FROM board_labels bl
+            LEFT JOIN card_labels cl ON bl.id = cl.label_id
+            WHERE bl.board_id = $1
+            GROUP BY bl.id
+            ORDER BY bl.position ASC, bl.created_at ASC
+            "#,
+            board_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(usage)
+    }
+
     /// Update a board label
     ///
     /// # Arguments
@@ -161,7 +211,7 @@ impl BoardLabel {
                 name = COALESCE($2, name),
                 color = COALESCE($3, color)
             WHERE id = $1
-            RETURNING id, board_id, name, color, created_at, updated_at
+            RETURNING id, board_id, name, color, position, created_at, updated_at
             "#,
             id,
             input.name,
@@ -194,6 +244,44 @@ impl BoardLabel {
 
         Ok(result.rows_affected() > 0)
     }
+
+    /// Reorder a board's labels
+    ///
+    /// Labels that do not belong to the board are left untouched.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    /// * `label_positions` - Vec of (label_id, new_position) tuples
+    ///
+    /// # Returns
+    /// * `Result<Vec<BoardLabel>, sqlx::Error>` - All of the board's labels in their new order
+    pub async fn reorder(
+        pool: &PgPool,
+        board_id: Uuid,
+        label_positions: Vec<(Uuid, i32)>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        for (label_id, position) in label_positions {
+            sqlx::query!(
+                r#"
+                UPDATE board_labels
+                SET position = $1, updated_at = NOW()
+                WHERE id = $2 AND board_id = $3
+                "#,
+                position,
+                label_id,
+                board_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Self::find_by_board_id(pool, board_id).await
+    }
 }
 
 impl CardLabel {
@@ -5,15 +5,32 @@
 
 pub mod attachment;
 pub mod board;
+pub mod board_presence_id;
+pub mod board_settings;
 pub mod card;
 pub mod column;
 pub mod label;
+pub mod notification;
 pub mod user;
+pub mod watcher;
 
 // Re-export models for easier imports
-pub use attachment::{CardAttachment, UploadUrlRequest, UploadUrlResponse};
-pub use board::{Board, BoardWithRelations, CreateBoardInput, SetLockStateInput, UpdateBoardInput};
-pub use card::{Card, CreateCardInput, UpdateCardInput};
+pub use attachment::{
+    CardAttachment, DownloadDisposition, DownloadQuery, ReorderAttachmentsRequest,
+    UploadUrlRequest, UploadUrlResponse,
+};
+pub use board::{
+    Board, BoardSummariesInput, BoardSummary, BoardWithRelations, CreateBoardInput, CreatedBoard, RegenerateShareTokenInput, SetLockStateInput,
+    UpdateBoardInput,
+};
+pub use board_settings::{BoardSettings, UpdateBoardSettingsInput};
+pub use card::{
+    Card, CardSearchQuery, CreateCardInput, CycleTimeStats, ScoredCard, UpdateCardInput,
+};
 pub use column::{Column, CreateColumnInput, UpdateColumnInput};
-pub use label::{BoardLabel, CardLabel, CreateBoardLabelInput, UpdateBoardLabelInput};
+pub use label::{
+    BoardLabel, BoardLabelUsage, CardLabel, CreateBoardLabelInput, UpdateBoardLabelInput,
+};
+pub use notification::{Notification, NotificationsQuery};
 pub use user::{Claims, LoginRequest, LoginResponse, RegisterRequest, User, UserInfo, UserSession};
+pub use watcher::CardWatcher;
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// In-app notification addressed to a single user
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// What the notification is about, e.g. `card_changed`
+    pub kind: String,
+    /// Kind-specific details
+    pub payload: serde_json::Value,
+    /// When the user marked it read, None while unread
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for listing notifications
+#[derive(Debug, Deserialize)]
+pub struct NotificationsQuery {
+    /// Only return notifications that have not been read
+    #[serde(default)]
+    pub unread: bool,
+}
+
+impl Notification {
+    /// Kind of the notification sent when a watched card changes
+    pub const KIND_CARD_CHANGED: &'static str = "card_changed";
+
+    /// Create a notification
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - Recipient's UUID
+    /// * `kind` - Notification kind
+    /// * `payload` - Kind-specific details
+    ///
+    /// # Returns
+    /// * `Result<Notification, sqlx::Error>` - Created notification or error
+    pub async fn create(
+        pool: &PgPool,
+        user_id: Uuid,
+        kind: &str,
+        payload: &serde_json::Value,
+    ) -> Result<Self, sqlx::Error> {
+        let notification = sqlx::query_as!(
+            Notification,
+            r#"
+            INSERT INTO notifications (user_id, kind, payload)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, kind, payload, read_at, created_at
+            "#,
+            user_id,
+            kind,
+            payload
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(notification)
+    }
+
+    /// Find a user's notifications, newest first
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - Recipient's UUID
+    /// * `unread_only` - Skip notifications that were already read
+    /// * `limit` - Maximum number of results
+    ///
+    /// # Returns
+    /// * `Result<Vec<Notification>, sqlx::Error>` - List of notifications
+    pub async fn find_by_user_id(
+        pool: &PgPool,
+        user_id: Uuid,
+        unread_only: bool,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let notifications = sqlx::query_as!(
+            Notification,
+            r#"
+            SELECT id, user_id, kind, payload, read_at, created_at
+            FROM notifications
+            WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+            user_id,
+            unread_only,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(notifications)
+    }
+
+    /// Mark a user's notification as read
+    ///
+    /// Marking an already read notification keeps its original `read_at`.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Notification UUID
+    /// * `user_id` - Recipient's UUID
+    ///
+    /// # Returns
+    /// * `Result<Option<Notification>, sqlx::Error>` - Updated notification, or
+    ///   None if the user has no such notification
+    pub async fn mark_read(
+        pool: &PgPool,
+        id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let notification = sqlx::query_as!(
+            Notification,
+            r#"
+            UPDATE notifications
+            SET read_at = COALESCE(read_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, kind, payload, read_at, created_at
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(notification)
+    }
+}
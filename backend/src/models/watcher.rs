@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A user subscribed to changes on a card (junction table)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CardWatcher {
+    pub card_id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CardWatcher {
+    /// Start watching a card
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    /// * `user_id` - User UUID
+    ///
+    /// # Returns
+    /// * `Result<Option<CardWatcher>, sqlx::Error>` - New subscription, or None if
+    ///   the user was already watching
+    pub async fn watch(
+        pool: &PgPool,
+        card_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let watcher = sqlx::query_as!(
+            CardWatcher,
+            r#"
+            INSERT INTO card_watchers (card_id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT (card_id, user_id) DO NOTHING
+            RETURNING card_id, user_id, created_at
+            "#,
+            card_id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(watcher)
+    }
+
+    /// Stop watching a card
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    /// * `user_id` - User UUID
+    ///
+    /// # Returns
+    /// * `Result<bool, sqlx::Error>` - True if the user was watching
+    pub async fn unwatch(pool: &PgPool, card_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM card_watchers
+            WHERE card_id = $1 AND user_id = $2
+            "#,
+            card_id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Find the users watching a card
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    ///
+    /// # Returns
+    /// * `Result<Vec<Uuid>, sqlx::Error>` - IDs of the watching users
+    pub async fn find_user_ids_by_card_id(
+        pool: &PgPool,
+        card_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let user_ids = sqlx::query_scalar!(
+            r#"
+            SELECT user_id
+            FROM card_watchers
+            WHERE card_id = $1
+            ORDER BY created_at ASC
+            "#,
+            card_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(user_ids)
+    }
+}
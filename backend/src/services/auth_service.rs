@@ -38,7 +38,7 @@ impl AuthService {
         }
 
         // Hash password using Argon2id
-        let password_hash = Self::hash_password(&input.password)?;
+        let password_hash = Self::hash_password(&input.password).await?;
 
         // Create user
         let user = User::create(
@@ -100,7 +100,7 @@ impl AuthService {
         }
 
         // Verify password
-        Self::verify_password(&input.password, &user.password_hash)?;
+        Self::verify_password(&input.password, &user.password_hash).await?;
 
         // Generate tokens
         let (access_token, access_expires_at) =
@@ -215,33 +215,55 @@ impl AuthService {
     }
 
     /// Hash password using Argon2id
-    fn hash_password(password: &str) -> AppResult<String> {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-
-        let password_hash = argon2
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| {
-                log::error!("Password hashing failed: {:?}", e);
-                AppError::InternalError("Password hashing failed".to_string())
-            })?
-            .to_string();
-
-        Ok(password_hash)
+    ///
+    /// Argon2 is slow by design, so it runs on the blocking thread pool
+    /// instead of stalling the worker serving other requests.
+    async fn hash_password(password: &str) -> AppResult<String> {
+        let password = password.to_string();
+        tokio::task::spawn_blocking(move || {
+            let salt = SaltString::generate(&mut OsRng);
+            let argon2 = Argon2::default();
+
+            let password_hash = argon2
+                .hash_password(password.as_bytes(), &salt)
+                .map_err(|e| {
+                    log::error!("Password hashing failed: {:?}", e);
+                    AppError::InternalError("Password hashing failed".to_string())
+                })?
+                .to_string();
+
+            Ok(password_hash)
+        })
+        .await
+        .map_err(|e| {
+            log::error!("Password hashing task failed: {:?}", e);
+            AppError::InternalError("Password hashing failed".to_string())
+        })?
     }
 
     /// Verify password against hash
-    fn verify_password(password: &str, hash: &str) -> AppResult<()> {
-        let parsed_hash = PasswordHash::new(hash).map_err(|e| {
-            log::error!("Password hash parsing failed: {:?}", e);
+    ///
+    /// Runs on the blocking thread pool, like `hash_password`.
+    async fn verify_password(password: &str, hash: &str) -> AppResult<()> {
+        let password = password.to_string();
+        let hash = hash.to_string();
+        tokio::task::spawn_blocking(move || {
+            let parsed_hash = PasswordHash::new(&hash).map_err(|e| {
+                log::error!("Password hash parsing failed: {:?}", e);
+                AppError::InternalError("Password verification failed".to_string())
+            })?;
+
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed_hash)
+                .map_err(|_| AppError::Unauthorized("Invalid email or password".to_string()))?;
+
+            Ok(())
+        })
+        .await
+        .map_err(|e| {
+            log::error!("Password verification task failed: {:?}", e);
             AppError::InternalError("Password verification failed".to_string())
-        })?;
-
-        Argon2::default()
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .map_err(|_| AppError::Unauthorized("Invalid email or password".to_string()))?;
-
-        Ok(())
+        })?
     }
 
     /// Generate JWT access token
@@ -1,8 +1,14 @@
 use crate::error::{AppError, AppResult};
-use crate::models::{BoardLabel, CardLabel, CreateBoardLabelInput, UpdateBoardLabelInput};
+use crate::models::{
+    BoardLabel, BoardLabelUsage, CardLabel, CreateBoardLabelInput, UpdateBoardLabelInput,
+};
+use crate::utils::is_valid_color;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// Error returned for a label color that is neither hex nor a known name
+const INVALID_LABEL_COLOR: &str = "Label color must be a #rgb or #rrggbb hex value or a color name";
+
 /// Service for board label-related business logic
 pub struct BoardLabelService;
 
@@ -13,7 +19,7 @@ impl BoardLabelService {
     /// * `pool` - Database connection pool
     /// * `board_id` - Board UUID
     /// * `name` - Label name
-    /// * `color` - Label color (hex)
+    /// * `color` - Label color (hex or color name)
     ///
     /// # Returns
     /// * `AppResult<BoardLabel>` - Created label or error
@@ -36,16 +42,8 @@ impl BoardLabelService {
             ));
         }
 
-        if color.trim().is_empty() {
-            return Err(AppError::BadRequest(
-                "Label color cannot be empty".to_string(),
-            ));
-        }
-
-        if color.len() > 20 {
-            return Err(AppError::BadRequest(
-                "Label color cannot exceed 20 characters".to_string(),
-            ));
+        if !is_valid_color(&color) {
+            return Err(AppError::BadRequest(INVALID_LABEL_COLOR.to_string()));
         }
 
         let input = CreateBoardLabelInput {
@@ -88,6 +86,19 @@ impl BoardLabelService {
         Ok(labels)
     }
 
+    /// Get all labels for a board with the number of cards each is assigned to
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<BoardLabelUsage>>` - Labels with card counts (zero if unused)
+    pub async fn label_usage(pool: &PgPool, board_id: Uuid) -> AppResult<Vec<BoardLabelUsage>> {
+        let usage = BoardLabel::usage_by_board_id(pool, board_id).await?;
+        Ok(usage)
+    }
+
     /// Get all labels assigned to a card
     ///
     /// # Arguments
@@ -131,15 +142,8 @@ impl BoardLabelService {
 
         // Validate color if provided
         if let Some(ref color) = input.color {
-            if color.trim().is_empty() {
-                return Err(AppError::BadRequest(
-                    "Label color cannot be empty".to_string(),
-                ));
-            }
-            if color.len() > 20 {
-                return Err(AppError::BadRequest(
-                    "Label color cannot exceed 20 characters".to_string(),
-                ));
+            if !is_valid_color(color) {
+                return Err(AppError::BadRequest(INVALID_LABEL_COLOR.to_string()));
             }
         }
 
@@ -168,6 +172,45 @@ impl BoardLabelService {
         }
     }
 
+    /// Reorder a board's labels
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    /// * `label_positions` - Vec of (label_id, new_position) tuples
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<BoardLabel>>` - All of the board's labels in their new order
+    pub async fn reorder_labels(
+        pool: &PgPool,
+        board_id: Uuid,
+        label_positions: Vec<(Uuid, i32)>,
+    ) -> AppResult<Vec<BoardLabel>> {
+        // Validate positions
+        for (_, position) in &label_positions {
+            if *position < 0 {
+                return Err(AppError::BadRequest(
+                    "Label position cannot be negative".to_string(),
+                ));
+            }
+        }
+
+        // Every listed label must belong to this board
+        let existing = BoardLabel::find_by_board_id(pool, board_id).await?;
+        if let Some((unknown_id, _)) = label_positions
+            .iter()
+            .find(|(id, _)| !existing.iter().any(|label| label.id == *id))
+        {
+            return Err(AppError::BadRequest(format!(
+                "Label {} does not belong to this board",
+                unknown_id
+            )));
+        }
+
+        let labels = BoardLabel::reorder(pool, board_id, label_positions).await?;
+        Ok(labels)
+    }
+
     /// Assign a label to a card
     ///
     /// # Arguments
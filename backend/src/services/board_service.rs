@@ -1,8 +1,26 @@
 use crate::error::{AppError, AppResult};
-use crate::models::{Board, BoardWithRelations, CreateBoardInput, UpdateBoardInput};
+use crate::models::board::{BoardCounts, BoardPublic, BoardView, ColumnCardCount};
+use crate::models::card::PurgedArchive;
+use crate::models::{
+    Board, BoardSettings, BoardSummary, BoardWithRelations, Card, Column, CreateBoardInput,
+    CreatedBoard, CycleTimeStats, UpdateBoardInput, UpdateBoardSettingsInput,
+};
+use crate::services::PresenceService;
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
+};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// Attempts at generating a share token that is not already in use
+const MAX_SHARE_TOKEN_ATTEMPTS: usize = 5;
+
+/// Most boards a single summaries request may ask for
+pub const MAX_SUMMARY_IDS: usize = 100;
+
 /// Service for board-related business logic
 pub struct BoardService;
 
@@ -14,8 +32,8 @@ impl BoardService {
     /// * `input` - Board creation data
     ///
     /// # Returns
-    /// * `AppResult<Board>` - Created board or error
-    pub async fn create_board(pool: &PgPool, input: CreateBoardInput) -> AppResult<Board> {
+    /// * `AppResult<CreatedBoard>` - Created board with its plaintext password, or error
+    pub async fn create_board(pool: &PgPool, input: CreateBoardInput) -> AppResult<CreatedBoard> {
         // Validate input
         if input.title.trim().is_empty() {
             return Err(AppError::BadRequest(
@@ -29,9 +47,62 @@ impl BoardService {
             ));
         }
 
+        // Only the hash is stored; the password is shown to the owner once
+        let password = Board::generate_password();
+        let password_hash = Self::hash_password(&password).await?;
+
         // Create board using model
-        let board = Board::create(pool, input).await?;
-        Ok(board)
+        let board = Board::create(pool, input, &password_hash).await?;
+        PresenceService::get_or_assign_presence_id(pool, board.id).await?;
+        Ok(CreatedBoard { board, password })
+    }
+
+    /// Hash every board password still stored in plaintext
+    ///
+    /// Run at startup so boards created before passwords were hashed keep
+    /// their password.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    ///
+    /// # Returns
+    /// * `AppResult<usize>` - Number of passwords hashed or error
+    pub async fn hash_plaintext_passwords(pool: &PgPool) -> AppResult<usize> {
+        let mut hashed = 0;
+        for (id, plaintext) in Board::find_plaintext_passwords(pool).await? {
+            let password_hash = Self::hash_password(&plaintext).await?;
+            if Board::replace_plaintext_password(pool, id, &plaintext, &password_hash).await? {
+                hashed += 1;
+            }
+        }
+        Ok(hashed)
+    }
+
+    /// Hash a board password using Argon2id
+    ///
+    /// Runs on the blocking thread pool so the hash does not stall other
+    /// requests on the same worker.
+    async fn hash_password(password: &str) -> AppResult<String> {
+        let password = password.to_string();
+        tokio::task::spawn_blocking(move || {
+            let salt = SaltString::generate(&mut OsRng);
+            let argon2 = Argon2::default();
+
+            let password_hash = argon2
+                .hash_password(password.as_bytes(), &salt)
+                .map_err(|e| {
+                    log::error!("Board password hashing failed: {:?}", e);
+                    AppError::InternalError("Password hashing failed".to_string())
+                })?
+                .to_string();
+
+            Ok(password_hash)
+        })
+        .await
+        .map_err(|e| {
+            log::error!("Board password hashing task failed: {:?}", e);
+            AppError::InternalError("Password hashing failed".to_string())
+        })?
     }
 
     /// Get board by ID
@@ -48,19 +119,161 @@ impl BoardService {
             .ok_or_else(|| AppError::NotFound(format!("Board with ID {} not found", id)))
     }
 
+    /// Get the public view of a board by ID
+    ///
+    /// For responses; use `get_board_by_id` when the password is needed for
+    /// an authorization check.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<BoardPublic>` - Found board without its password or share token, or error
+    pub async fn get_board_public(pool: &PgPool, id: Uuid) -> AppResult<BoardPublic> {
+        Ok(Self::get_board_by_id(pool, id).await?.into())
+    }
+
+    /// Get summaries for several boards at once
+    ///
+    /// Duplicate IDs are ignored and unknown ones are left out of the result.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `ids` - Board UUIDs (at most `MAX_SUMMARY_IDS` distinct)
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<BoardSummary>>` - Found summaries or error
+    pub async fn get_board_summaries(
+        pool: &PgPool,
+        mut ids: Vec<Uuid>,
+    ) -> AppResult<Vec<BoardSummary>> {
+        ids.sort_unstable();
+        ids.dedup();
+
+        if ids.len() > MAX_SUMMARY_IDS {
+            return Err(AppError::BadRequest(format!(
+                "At most {} board IDs can be requested at once",
+                MAX_SUMMARY_IDS
+            )));
+        }
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let summaries = Board::find_summaries(pool, &ids).await?;
+        Ok(summaries)
+    }
+
+    /// Get board by ID with all relations
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    /// * `include_archived` - Also load archived columns and their cards
+    ///
+    /// # Returns
+    /// * `AppResult<BoardWithRelations>` - Found board with relations or error
+    pub async fn get_board_by_id_with_relations(
+        pool: &PgPool,
+        id: Uuid,
+        include_archived: bool,
+    ) -> AppResult<BoardWithRelations> {
+        Board::find_by_id_with_relations(pool, id, include_archived)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Board with ID {} not found", id)))
+    }
+
+    /// Compute a fingerprint of a board and everything in its full view
+    ///
+    /// Changes whenever a column, card, label, label assignment or attachment
+    /// of the board is added, edited or removed. Cheap enough to check on every
+    /// poll, so clients can cache the full view and revalidate with it.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<String>` - Hex-encoded SHA-256 fingerprint or error
+    pub async fn board_fingerprint(pool: &PgPool, id: Uuid) -> AppResult<String> {
+        let state = Board::change_state(pool, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Board with ID {} not found", id)))?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(state.as_bytes());
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Count a board's columns and active cards
+    ///
+    /// Uses `COUNT(*)` queries instead of loading the board's relations.
+    /// Archived columns, and the cards in them, are left out.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<BoardCounts>` - Column and card counts or error
+    pub async fn get_counts(pool: &PgPool, id: Uuid) -> AppResult<BoardCounts> {
+        Self::get_board_by_id(pool, id).await?;
+
+        let column_count = Column::count_by_board(pool, id).await?;
+        let card_count = Card::count_by_board(pool, id).await?;
+
+        let mut columns = Vec::new();
+        for column in Column::find_by_board_id(pool, id).await? {
+            if column.archived_at.is_some() {
+                continue;
+            }
+            columns.push(ColumnCardCount {
+                column_id: column.id,
+                card_count: Card::count_by_column(pool, column.id).await?,
+            });
+        }
+
+        Ok(BoardCounts {
+            column_count,
+            card_count,
+            columns,
+        })
+    }
+
+    /// Get cycle time statistics for a board
+    ///
+    /// Only cards that reached a terminal column count, so boards without a
+    /// terminal column report no completed cards.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<CycleTimeStats>` - Completed card count and average cycle time or error
+    pub async fn get_cycle_time(pool: &PgPool, id: Uuid) -> AppResult<CycleTimeStats> {
+        Self::get_board_by_id(pool, id).await?;
+
+        let stats = Card::cycle_time_by_board_id(pool, id).await?;
+        Ok(stats)
+    }
+
     /// Get board by share token with all relations
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `share_token` - Share token string
+    /// * `include_archived` - Also include archived columns and their cards
     ///
     /// # Returns
     /// * `AppResult<BoardWithRelations>` - Found board with relations or error
     pub async fn get_board_by_share_token(
         pool: &PgPool,
         share_token: &str,
+        include_archived: bool,
     ) -> AppResult<BoardWithRelations> {
-        Board::find_by_share_token_with_relations(pool, share_token)
+        Board::find_by_share_token_with_relations(pool, share_token, include_archived)
             .await?
             .ok_or_else(|| {
                 AppError::NotFound(format!(
@@ -70,6 +283,22 @@ impl BoardService {
             })
     }
 
+    /// Get a read-only view of a board by view token with all relations
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `view_token` - View token string
+    ///
+    /// # Returns
+    /// * `AppResult<BoardView>` - Read-only board view or error
+    pub async fn get_board_by_view_token(pool: &PgPool, view_token: &str) -> AppResult<BoardView> {
+        Board::find_by_view_token_with_relations(pool, view_token)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Board with view token '{}' not found", view_token))
+            })
+    }
+
     /// Update board by share token
     ///
     /// # Arguments
@@ -85,7 +314,7 @@ impl BoardService {
         input: UpdateBoardInput,
     ) -> AppResult<Board> {
         // First get the board by share token to get its ID
-        let board = Self::get_board_by_share_token(pool, share_token).await?;
+        let board = Self::get_board_by_share_token(pool, share_token, false).await?;
 
         // Then update using the ID
         Self::update_board(pool, board.id, input).await
@@ -136,6 +365,45 @@ impl BoardService {
             .ok_or_else(|| AppError::NotFound(format!("Board with ID {} not found", id)))
     }
 
+    /// Get the feature flags of a board
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<BoardSettings>` - Stored settings, the defaults if none are stored, or error
+    pub async fn get_settings(pool: &PgPool, id: Uuid) -> AppResult<BoardSettings> {
+        let settings = BoardSettings::find_by_board_id(pool, id).await?;
+        Ok(settings.unwrap_or_default())
+    }
+
+    /// Update the feature flags of a board
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    /// * `input` - Flags to change; omitted ones keep their value
+    ///
+    /// # Returns
+    /// * `AppResult<BoardSettings>` - Settings after the update or error
+    pub async fn update_settings(
+        pool: &PgPool,
+        id: Uuid,
+        input: UpdateBoardSettingsInput,
+    ) -> AppResult<BoardSettings> {
+        if input.wip_limit == Some(Some(0)) {
+            return Err(AppError::BadRequest(
+                "WIP limit must be at least 1; use null to remove it".to_string(),
+            ));
+        }
+
+        let mut settings = Self::get_settings(pool, id).await?;
+        settings.apply(input);
+        BoardSettings::save(pool, id, &settings).await?;
+        Ok(settings)
+    }
+
     /// Delete a board
     ///
     /// # Arguments
@@ -156,6 +424,38 @@ impl BoardService {
         }
     }
 
+    /// Record activity on a board so recency sorting reflects it
+    ///
+    /// Called after a column, card or label change has been committed. A
+    /// failure is only logged: the change itself succeeded, and failing the
+    /// request would invite the client to repeat it.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    pub async fn touch_board(pool: &PgPool, id: Uuid) {
+        if let Err(e) = Board::touch(pool, id).await {
+            log::warn!("Failed to update activity time of board {}: {}", id, e);
+        }
+    }
+
+    /// Permanently delete columns that have been archived longer than `older_than`
+    ///
+    /// Boards themselves are never archived; what accumulates on them is
+    /// archived columns, which are deleted here together with their cards.
+    /// The caller is responsible for deleting the returned S3 objects.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `older_than` - How long a column must have been archived to be purged
+    ///
+    /// # Returns
+    /// * `AppResult<PurgedArchive>` - Number of columns deleted and their attachments' S3 keys
+    pub async fn purge_archived(pool: &PgPool, older_than: Duration) -> AppResult<PurgedArchive> {
+        let purged = Column::purge_archived(pool, Utc::now() - older_than).await?;
+        Ok(purged)
+    }
+
     /// Lock or unlock a board with password verification
     ///
     /// # Arguments
@@ -163,14 +463,17 @@ impl BoardService {
     /// * `share_token` - Board share token
     /// * `password` - Password to verify
     /// * `is_locked` - New lock state
+    /// * `expected_updated_at` - Board version the caller last saw
     ///
     /// # Returns
-    /// * `AppResult<Board>` - Updated board or error
+    /// * `AppResult<Board>` - Updated board or error (`Conflict` if the board
+    ///   changed since `expected_updated_at`)
     pub async fn set_board_lock_state(
         pool: &PgPool,
         share_token: &str,
         password: &str,
         is_locked: bool,
+        expected_updated_at: DateTime<Utc>,
     ) -> AppResult<Board> {
         // First get the board by share token to get its ID
         let board = Board::find_by_share_token(pool, share_token)
@@ -182,11 +485,334 @@ impl BoardService {
                 ))
             })?;
 
-        // Attempt to set lock state with password verification
-        let updated_board = Board::set_lock_state(pool, board.id, password, is_locked)
+        if board.updated_at != expected_updated_at {
+            return Err(stale_lock_toggle());
+        }
+
+        if !board.verify_password(password).await {
+            return Err(AppError::Unauthorized("Invalid password".to_string()));
+        }
+
+        // The board may still change between the check above and the update
+        Board::set_lock_state(pool, board.id, is_locked, expected_updated_at)
             .await?
-            .ok_or_else(|| AppError::Unauthorized("Invalid password".to_string()))?;
+            .ok_or_else(stale_lock_toggle)
+    }
+
+    /// Regenerate a board's share token with password verification
+    ///
+    /// The previous token stops working immediately.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Board UUID
+    /// * `password` - Password to verify
+    ///
+    /// # Returns
+    /// * `AppResult<String>` - New share token or error
+    pub async fn regenerate_share_token(
+        pool: &PgPool,
+        id: Uuid,
+        password: &str,
+    ) -> AppResult<String> {
+        // Ensure the board exists so a missing board is not reported as a bad password
+        let board = Self::get_board_by_id(pool, id).await?;
+        if !board.verify_password(password).await {
+            return Err(AppError::Unauthorized("Invalid password".to_string()));
+        }
+
+        let mut share_token = None;
+        for _ in 0..MAX_SHARE_TOKEN_ATTEMPTS {
+            let candidate = Board::generate_share_token();
+            if !Board::share_token_exists(pool, &candidate).await? {
+                share_token = Some(candidate);
+                break;
+            }
+        }
+
+        let share_token = share_token.ok_or_else(|| {
+            AppError::InternalError("Failed to generate a unique share token".to_string())
+        })?;
+
+        let board = Board::set_share_token(pool, id, &share_token)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Board with ID {} not found", id)))?;
+
+        Ok(board.share_token)
+    }
+}
+
+/// Error for a lock toggle based on an outdated board version
+fn stale_lock_toggle() -> AppError {
+    AppError::Conflict("Board has changed since it was loaded; reload it and try again".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board with `password` stored as is, as loaded from the database
+    fn board_with_password(password: String) -> Board {
+        let now = Utc::now();
+        Board {
+            id: Uuid::new_v4(),
+            share_token: "sharetoken01".to_string(),
+            title: "Board".to_string(),
+            description: None,
+            password,
+            is_locked: false,
+            view_token: "viewtoken001".to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Insert a board storing `password` as is and return its id
+    async fn insert_board(pool: &PgPool, token: &str, password: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title, password) VALUES ($1, $2, 'Board', $3) RETURNING id",
+        )
+        .bind(format!("share-{}", token))
+        .bind(format!("view-{}", token))
+        .bind(password)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn stored_password(pool: &PgPool, id: Uuid) -> String {
+        sqlx::query_scalar("SELECT password FROM boards WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn hashed_password_verifies() {
+        let hash = BoardService::hash_password("correct horse").await.unwrap();
+        assert!(hash.starts_with("$argon2"));
+
+        let board = board_with_password(hash);
+
+        assert!(board.verify_password("correct horse").await);
+    }
+
+    #[tokio::test]
+    async fn wrong_password_is_rejected() {
+        let hash = BoardService::hash_password("correct horse").await.unwrap();
+        let board = board_with_password(hash);
+
+        assert!(!board.verify_password("battery staple").await);
+        assert!(!board.verify_password("").await);
+    }
+
+    #[tokio::test]
+    async fn empty_stored_password_never_verifies() {
+        let board = board_with_password(String::new());
+
+        assert!(!board.verify_password("").await);
+        assert!(!board.verify_password("anything").await);
+    }
+
+    #[tokio::test]
+    async fn plaintext_stored_password_does_not_verify() {
+        let board = board_with_password("secret".to_string());
+
+        assert!(!board.verify_password("secret").await);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn hash_plaintext_passwords_leaves_hashed_rows_alone(pool: PgPool) {
+        let hash = BoardService::hash_password("already hashed").await.unwrap();
+        let hashed = insert_board(&pool, "a", &hash).await;
+        let plaintext = insert_board(&pool, "b", "secret").await;
+        let unprotected = insert_board(&pool, "c", "").await;
+
+        let migrated = BoardService::hash_plaintext_passwords(&pool).await.unwrap();
+
+        assert_eq!(migrated, 1);
+        assert_eq!(stored_password(&pool, hashed).await, hash);
+        assert_eq!(stored_password(&pool, unprotected).await, "");
+        let board = board_with_password(stored_password(&pool, plaintext).await);
+        assert!(board.password.starts_with("$argon2"));
+        assert!(board.verify_password("secret").await);
+
+        // A second run finds nothing left to hash
+        assert_eq!(
+            BoardService::hash_plaintext_passwords(&pool).await.unwrap(),
+            0
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn regenerate_share_token_rejects_wrong_password(pool: PgPool) {
+        let hash = BoardService::hash_password("secret").await.unwrap();
+        let id = insert_board(&pool, "a", &hash).await;
+
+        let result = BoardService::regenerate_share_token(&pool, id, "wrong").await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+        assert!(
+            BoardService::get_board_by_share_token(&pool, "share-a", false)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn old_share_token_not_found_after_regeneration(pool: PgPool) {
+        let hash = BoardService::hash_password("secret").await.unwrap();
+        let id = insert_board(&pool, "a", &hash).await;
+
+        let share_token = BoardService::regenerate_share_token(&pool, id, "secret")
+            .await
+            .unwrap();
+
+        assert_ne!(share_token, "share-a");
+        let result = BoardService::get_board_by_share_token(&pool, "share-a", false).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        let board = BoardService::get_board_by_share_token(&pool, &share_token, false)
+            .await
+            .unwrap();
+        assert_eq!(board.id, id);
+    }
+
+    /// Insert a column on `board_id` and return its id
+    async fn insert_column(pool: &PgPool, board_id: Uuid, archived: bool) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO columns (board_id, title, position, archived_at) \
+             VALUES ($1, 'To do', 0, CASE WHEN $2 THEN NOW() END) RETURNING id",
+        )
+        .bind(board_id)
+        .bind(archived)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn insert_cards(pool: &PgPool, column_id: Uuid, count: i32) {
+        for position in 0..count {
+            sqlx::query("INSERT INTO cards (column_id, title, position) VALUES ($1, 'Card', $2)")
+                .bind(column_id)
+                .bind(position)
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn summaries_for_several_boards_at_once(pool: PgPool) {
+        let busy = insert_board(&pool, "a", "").await;
+        let todo = insert_column(&pool, busy, false).await;
+        insert_cards(&pool, todo, 3).await;
+        let done = insert_column(&pool, busy, false).await;
+        insert_cards(&pool, done, 1).await;
+        let archived = insert_column(&pool, busy, true).await;
+        insert_cards(&pool, archived, 5).await;
+
+        let empty = insert_board(&pool, "b", "").await;
+        let not_requested = insert_board(&pool, "c", "").await;
+
+        let summaries =
+            BoardService::get_board_summaries(&pool, vec![busy, empty, busy, Uuid::new_v4()])
+                .await
+                .unwrap();
+
+        assert_eq!(summaries.len(), 2);
+        assert!(summaries.iter().all(|summary| summary.id != not_requested));
+        let busy = summaries.iter().find(|summary| summary.id == busy).unwrap();
+        assert_eq!((busy.column_count, busy.card_count), (2, 4));
+        let empty = summaries
+            .iter()
+            .find(|summary| summary.id == empty)
+            .unwrap();
+        assert_eq!((empty.column_count, empty.card_count), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn summaries_reject_too_many_ids() {
+        // Rejected before any query, so the pool never connects
+        let pool = PgPool::connect_lazy("postgres://localhost/fluxboard").unwrap();
+        let ids = (0..=MAX_SUMMARY_IDS).map(|_| Uuid::new_v4()).collect();
+
+        let result = BoardService::get_board_summaries(&pool, ids).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn counts_on_a_seeded_board(pool: PgPool) {
+        let board_id = insert_board(&pool, "a", "").await;
+        let todo = insert_column(&pool, board_id, false).await;
+        insert_cards(&pool, todo, 3).await;
+        let done = insert_column(&pool, board_id, false).await;
+        insert_cards(&pool, done, 2).await;
+        sqlx::query("UPDATE cards SET archived_at = NOW() WHERE column_id = $1 AND position = 0")
+            .bind(done)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let archived = insert_column(&pool, board_id, true).await;
+        insert_cards(&pool, archived, 4).await;
+
+        assert_eq!(Column::count_by_board(&pool, board_id).await.unwrap(), 2);
+        assert_eq!(Card::count_by_board(&pool, board_id).await.unwrap(), 4);
+        assert_eq!(Card::count_by_column(&pool, todo).await.unwrap(), 3);
+        assert_eq!(Card::count_by_column(&pool, done).await.unwrap(), 1);
+
+        let counts = BoardService::get_counts(&pool, board_id).await.unwrap();
+        assert_eq!((counts.column_count, counts.card_count), (2, 4));
+        let per_column: Vec<(Uuid, i64)> = counts
+            .columns
+            .iter()
+            .map(|column| (column.column_id, column.card_count))
+            .collect();
+        assert_eq!(per_column.len(), 2);
+        assert!(per_column.contains(&(todo, 3)));
+        assert!(per_column.contains(&(done, 1)));
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn purge_archived_keeps_recent_and_active_columns(pool: PgPool) {
+        let board_id = insert_board(&pool, "a", "").await;
+        let old = insert_column(&pool, board_id, true).await;
+        insert_cards(&pool, old, 2).await;
+        sqlx::query("UPDATE columns SET archived_at = NOW() - INTERVAL '10 days' WHERE id = $1")
+            .bind(old)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let recent = insert_column(&pool, board_id, true).await;
+        let active = insert_column(&pool, board_id, false).await;
+
+        let purged = BoardService::purge_archived(&pool, Duration::days(7))
+            .await
+            .unwrap();
+        assert_eq!(purged.deleted, 1);
+
+        let mut remaining: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM columns WHERE board_id = $1")
+                .bind(board_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        remaining.sort();
+        let mut expected = vec![recent, active];
+        expected.sort();
+        assert_eq!(remaining, expected);
 
-        Ok(updated_board)
+        let orphaned: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE column_id = $1")
+            .bind(old)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(orphaned, 0);
     }
 }
@@ -1,8 +1,17 @@
 use crate::error::{AppError, AppResult};
-use crate::models::{Card, CreateCardInput, UpdateCardInput};
+use crate::models::card::{MergeOutcome, PurgedArchive};
+use crate::models::{Card, Column, CreateCardInput, ScoredCard, UpdateCardInput};
+use crate::utils::is_valid_color;
+use chrono::{Duration, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// Longest search query accepted
+const MAX_SEARCH_QUERY_LENGTH: usize = 200;
+
+/// Most results a card search returns
+const MAX_SEARCH_RESULTS: i64 = 50;
+
 /// Service for card-related business logic
 pub struct CardService;
 
@@ -14,17 +23,22 @@ impl CardService {
     /// * `column_id` - Column UUID
     /// * `title` - Card title
     /// * `description` - Optional card description
-    /// * `position` - Card position
+    /// * `color` - Optional accent color (hex or color name)
+    /// * `position` - Card position, or `None` to append to the column
+    /// * `max_description_length` - Longest description allowed, in bytes
     ///
     /// # Returns
-    /// * `AppResult<Card>` - Created card or error
+    /// * `AppResult<(Card, Vec<Card>)>` - Created card (with its committed position) and any
+    ///   cards shifted to make room, or error
     pub async fn create_card(
         pool: &PgPool,
         column_id: Uuid,
         title: String,
         description: Option<String>,
-        position: i32,
-    ) -> AppResult<Card> {
+        color: Option<String>,
+        position: Option<i32>,
+        max_description_length: usize,
+    ) -> AppResult<(Card, Vec<Card>)> {
         // Validate input
         if title.trim().is_empty() {
             return Err(AppError::BadRequest(
@@ -38,21 +52,73 @@ impl CardService {
             ));
         }
 
-        if position < 0 {
+        if position.is_some_and(|position| position < 0) {
             return Err(AppError::BadRequest(
                 "Card position cannot be negative".to_string(),
             ));
         }
 
+        Self::validate_description(description.as_deref(), max_description_length)?;
+        Self::validate_color(color.as_deref())?;
+
         let input = CreateCardInput {
             column_id,
             title,
             description,
+            color,
             position,
         };
 
-        let card = Card::create(pool, input).await?;
-        Ok(card)
+        let created = Card::create(pool, input).await?;
+        Ok(created)
+    }
+
+    /// Reject adding a card to a column that already holds `wip_limit` active cards
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `column_id` - Column the card would be added to
+    /// * `wip_limit` - Board's WIP limit, or `None` for no limit
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success, or a conflict error if the column is full
+    pub async fn check_wip_limit(
+        pool: &PgPool,
+        column_id: Uuid,
+        wip_limit: Option<u32>,
+    ) -> AppResult<()> {
+        let Some(wip_limit) = wip_limit else {
+            return Ok(());
+        };
+
+        if Card::count_by_column(pool, column_id).await? >= i64::from(wip_limit) {
+            return Err(AppError::Conflict(format!(
+                "Column has reached the board's WIP limit of {} cards",
+                wip_limit
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Reject card descriptions (or AI context) longer than `max_length` bytes
+    pub fn validate_description(description: Option<&str>, max_length: usize) -> AppResult<()> {
+        match description {
+            Some(description) if description.len() > max_length => Err(AppError::BadRequest(
+                format!("Card description cannot exceed {} bytes", max_length),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject card colors that are neither hex nor a known color name
+    pub fn validate_color(color: Option<&str>) -> AppResult<()> {
+        match color {
+            Some(color) if !is_valid_color(color) => Err(AppError::BadRequest(
+                "Card color must be a #rgb or #rrggbb hex value or a color name".to_string(),
+            )),
+            _ => Ok(()),
+        }
     }
 
     /// Get card by ID
@@ -95,16 +161,62 @@ impl CardService {
         Ok(cards)
     }
 
+    /// Search a board's cards, ranking those with the requested labels first
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    /// * `query` - Text to find in card titles and descriptions
+    /// * `label_ids` - Labels that boost matching cards
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<ScoredCard>>` - Matching cards with scores, best first
+    pub async fn search(
+        pool: &PgPool,
+        board_id: Uuid,
+        query: &str,
+        label_ids: &[Uuid],
+    ) -> AppResult<Vec<ScoredCard>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(AppError::BadRequest(
+                "Search query cannot be empty".to_string(),
+            ));
+        }
+        if query.chars().count() > MAX_SEARCH_QUERY_LENGTH {
+            return Err(AppError::BadRequest(format!(
+                "Search query cannot exceed {} characters",
+                MAX_SEARCH_QUERY_LENGTH
+            )));
+        }
+
+        // Match the query literally: escape LIKE wildcards before wrapping it
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+
+        let cards = Card::search(pool, board_id, &pattern, label_ids, MAX_SEARCH_RESULTS).await?;
+        Ok(cards)
+    }
+
     /// Update a card
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `id` - Card UUID
     /// * `input` - Card update data
+    /// * `max_description_length` - Longest description allowed, in bytes
     ///
     /// # Returns
     /// * `AppResult<Card>` - Updated card or error
-    pub async fn update_card(pool: &PgPool, id: Uuid, input: UpdateCardInput) -> AppResult<Card> {
+    pub async fn update_card(
+        pool: &PgPool,
+        id: Uuid,
+        input: UpdateCardInput,
+        max_description_length: usize,
+    ) -> AppResult<Card> {
         // Validate title if provided
         if let Some(ref title) = input.title {
             if title.trim().is_empty() {
@@ -128,6 +240,16 @@ impl CardService {
             }
         }
 
+        // Validate description if provided
+        if let Some(description) = &input.description {
+            Self::validate_description(description.as_deref(), max_description_length)?;
+        }
+
+        // Validate color if provided
+        if let Some(color) = &input.color {
+            Self::validate_color(color.as_deref())?;
+        }
+
         Card::update(pool, id, input)
             .await?
             .ok_or_else(|| AppError::NotFound(format!("Card with ID {} not found", id)))
@@ -204,4 +326,330 @@ impl CardService {
         Card::reorder(pool, column_id, card_positions).await?;
         Ok(())
     }
+
+    /// Merge a source card into a target card
+    ///
+    /// Both cards must belong to the same board, and their combined
+    /// description must fit in `max_description_length` bytes. The source
+    /// card is deleted.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `source_id` - UUID of the card merged away
+    /// * `target_id` - UUID of the card that remains
+    /// * `max_description_length` - Longest description the merged card may have
+    ///
+    /// # Returns
+    /// * `AppResult<Card>` - Updated target card or error
+    pub async fn merge_cards(
+        pool: &PgPool,
+        source_id: Uuid,
+        target_id: Uuid,
+        max_description_length: usize,
+    ) -> AppResult<Card> {
+        if source_id == target_id {
+            return Err(AppError::BadRequest(
+                "Cannot merge a card into itself".to_string(),
+            ));
+        }
+
+        let source = Self::get_card_by_id(pool, source_id).await?;
+        let target = Self::get_card_by_id(pool, target_id).await?;
+
+        if source.column_id != target.column_id {
+            let source_column = Column::find_by_id(pool, source.column_id).await?;
+            let target_column = Column::find_by_id(pool, target.column_id).await?;
+
+            let same_board = matches!(
+                (source_column, target_column),
+                (Some(s), Some(t)) if s.board_id == t.board_id
+            );
+            if !same_board {
+                return Err(AppError::BadRequest(
+                    "Cards must belong to the same board to be merged".to_string(),
+                ));
+            }
+        }
+
+        match Card::merge(pool, source_id, target_id, max_description_length).await? {
+            MergeOutcome::Merged(card) => Ok(card),
+            MergeOutcome::NotFound => Err(AppError::NotFound("Card not found".to_string())),
+            MergeOutcome::DescriptionTooLong => Err(AppError::BadRequest(format!(
+                "Merged card description cannot exceed {} bytes",
+                max_description_length
+            ))),
+        }
+    }
+
+    /// Create a new card from a template card
+    ///
+    /// The template must be flagged `is_template` and belong to the same
+    /// board as the target column.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `template_id` - UUID of the template card
+    /// * `column_id` - Column UUID for the new card
+    ///
+    /// # Returns
+    /// * `AppResult<Card>` - Created card or error
+    pub async fn create_from_template(
+        pool: &PgPool,
+        template_id: Uuid,
+        column_id: Uuid,
+    ) -> AppResult<Card> {
+        let template = Self::get_card_by_id(pool, template_id).await?;
+
+        if !template.is_template {
+            return Err(AppError::BadRequest(format!(
+                "Card with ID {} is not a template",
+                template_id
+            )));
+        }
+
+        if template.column_id != column_id {
+            let template_column = Column::find_by_id(pool, template.column_id).await?;
+            let target_column = Column::find_by_id(pool, column_id).await?.ok_or_else(|| {
+                AppError::NotFound(format!("Column with ID {} not found", column_id))
+            })?;
+
+            if template_column.map(|c| c.board_id) != Some(target_column.board_id) {
+                return Err(AppError::BadRequest(
+                    "Template must belong to the same board as the column".to_string(),
+                ));
+            }
+        }
+
+        Card::create_from_template(pool, template_id, column_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Card with ID {} not found", template_id)))
+    }
+
+    /// Archive every card in a column
+    ///
+    /// The column itself is left in place.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `column_id` - Column UUID
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Uuid>>` - IDs of the archived cards or error
+    pub async fn archive_column_cards(pool: &PgPool, column_id: Uuid) -> AppResult<Vec<Uuid>> {
+        let card_ids = Card::archive_by_column_id(pool, column_id).await?;
+        Ok(card_ids)
+    }
+
+    /// Permanently delete cards that have been archived longer than `older_than`
+    ///
+    /// The caller is responsible for deleting the returned S3 objects.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `older_than` - How long a card must have been archived to be purged
+    ///
+    /// # Returns
+    /// * `AppResult<PurgedArchive>` - Number of cards deleted and their attachments' S3 keys
+    pub async fn purge_archived(pool: &PgPool, older_than: Duration) -> AppResult<PurgedArchive> {
+        let purged = Card::purge_archived(pool, Utc::now() - older_than).await?;
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Insert a board with one column and return their ids
+    async fn create_column(pool: &PgPool) -> (Uuid, Uuid) {
+        let board_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title) VALUES ($1, $2, 'Board') RETURNING id",
+        )
+        .bind("sharetoken01")
+        .bind("viewtoken001")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let column_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO columns (board_id, title, position) VALUES ($1, 'To do', 0) RETURNING id",
+        )
+        .bind(board_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        (board_id, column_id)
+    }
+
+    async fn create_card(pool: &PgPool, column_id: Uuid, position: i32, description: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO cards (column_id, title, description, position) VALUES ($1, 'Card', $2, $3) RETURNING id",
+        )
+        .bind(column_id)
+        .bind(description)
+        .bind(position)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn create_label(pool: &PgPool, board_id: Uuid, name: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO board_labels (board_id, name, color) VALUES ($1, $2, '#f00') RETURNING id",
+        )
+        .bind(board_id)
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn assign_label(pool: &PgPool, card_id: Uuid, label_id: Uuid) {
+        sqlx::query("INSERT INTO card_labels (card_id, label_id) VALUES ($1, $2)")
+            .bind(card_id)
+            .bind(label_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn create_attachment(pool: &PgPool, card_id: Uuid, user_id: Uuid, position: i32) {
+        sqlx::query(
+            "INSERT INTO card_attachments (card_id, uploaded_by, filename, original_filename, \
+             content_type, file_size, s3_key, s3_bucket, is_confirmed, position) \
+             VALUES ($1, $2, 'a.png', 'a.png', 'image/png', 1, 'attachments/a.png', 'bucket', TRUE, $3)",
+        )
+        .bind(card_id)
+        .bind(user_id)
+        .bind(position)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn create_user(pool: &PgPool) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO users (email, password_hash) VALUES ('user@example.com', 'x') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn merge_cards_moves_labels_and_attachments(pool: PgPool) {
+        let (board_id, column_id) = create_column(&pool).await;
+        let user_id = create_user(&pool).await;
+        let target_id = create_card(&pool, column_id, 0, "Target notes").await;
+        let source_id = create_card(&pool, column_id, 1, "Source notes").await;
+
+        let shared = create_label(&pool, board_id, "Shared").await;
+        let source_only = create_label(&pool, board_id, "Source only").await;
+        assign_label(&pool, target_id, shared).await;
+        assign_label(&pool, source_id, shared).await;
+        assign_label(&pool, source_id, source_only).await;
+
+        create_attachment(&pool, target_id, user_id, 0).await;
+        create_attachment(&pool, source_id, user_id, 0).await;
+        create_attachment(&pool, source_id, user_id, 1).await;
+
+        let card = CardService::merge_cards(&pool, source_id, target_id, 1000)
+            .await
+            .unwrap();
+        assert_eq!(card.id, target_id);
+        assert_eq!(
+            card.description.as_deref(),
+            Some("Target notes\n\nSource notes")
+        );
+
+        let mut labels: Vec<Uuid> =
+            sqlx::query_scalar("SELECT label_id FROM card_labels WHERE card_id = $1")
+                .bind(target_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        labels.sort();
+        let mut expected = vec![shared, source_only];
+        expected.sort();
+        assert_eq!(labels, expected);
+
+        let positions: Vec<i32> = sqlx::query_scalar(
+            "SELECT position FROM card_attachments WHERE card_id = $1 ORDER BY position",
+        )
+        .bind(target_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(positions, vec![0, 1, 2]);
+
+        let source_left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE id = $1")
+            .bind(source_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(source_left, 0);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn merge_cards_rejects_over_long_description(pool: PgPool) {
+        let (board_id, column_id) = create_column(&pool).await;
+        let target_id = create_card(&pool, column_id, 0, &"a".repeat(60)).await;
+        let source_id = create_card(&pool, column_id, 1, &"b".repeat(60)).await;
+        let label = create_label(&pool, board_id, "Source only").await;
+        assign_label(&pool, source_id, label).await;
+
+        let result = CardService::merge_cards(&pool, source_id, target_id, 100).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        // Nothing was merged: the source card and its label are untouched
+        let target = CardService::get_card_by_id(&pool, target_id).await.unwrap();
+        assert_eq!(target.description, Some("a".repeat(60)));
+        assert!(CardService::get_card_by_id(&pool, source_id).await.is_ok());
+        let target_labels: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM card_labels WHERE card_id = $1")
+                .bind(target_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(target_labels, 0);
+    }
+
+    async fn archive_card(pool: &PgPool, card_id: Uuid, days_ago: i32) {
+        sqlx::query(
+            "UPDATE cards SET archived_at = NOW() - make_interval(days => $2) WHERE id = $1",
+        )
+        .bind(card_id)
+        .bind(days_ago)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn purge_archived_keeps_recent_and_active_cards(pool: PgPool) {
+        let (_, column_id) = create_column(&pool).await;
+        let user_id = create_user(&pool).await;
+        let old = create_card(&pool, column_id, 0, "").await;
+        let recent = create_card(&pool, column_id, 1, "").await;
+        let active = create_card(&pool, column_id, 2, "").await;
+        archive_card(&pool, old, 10).await;
+        archive_card(&pool, recent, 1).await;
+        create_attachment(&pool, old, user_id, 0).await;
+
+        let purged = CardService::purge_archived(&pool, Duration::days(7))
+            .await
+            .unwrap();
+        assert_eq!(purged.deleted, 1);
+        assert_eq!(purged.s3_keys, vec!["attachments/a.png".to_string()]);
+
+        let remaining: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM cards WHERE column_id = $1 ORDER BY position")
+                .bind(column_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(remaining, vec![recent, active]);
+    }
 }
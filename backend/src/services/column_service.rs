@@ -1,4 +1,5 @@
 use crate::error::{AppError, AppResult};
+use crate::models::board::ColumnWithCards;
 use crate::models::{Column, CreateColumnInput, UpdateColumnInput};
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -14,6 +15,7 @@ impl ColumnService {
     /// * `board_id` - Board UUID
     /// * `title` - Column title
     /// * `position` - Column position
+    /// * `is_terminal` - Whether cards in the column count as completed
     ///
     /// # Returns
     /// * `AppResult<Column>` - Created column or error
@@ -22,6 +24,7 @@ impl ColumnService {
         board_id: Uuid,
         title: String,
         position: i32,
+        is_terminal: bool,
     ) -> AppResult<Column> {
         // Validate input
         if title.trim().is_empty() {
@@ -46,6 +49,7 @@ impl ColumnService {
             board_id,
             title,
             position,
+            is_terminal,
         };
 
         let column = Column::create(pool, input).await?;
@@ -121,6 +125,40 @@ impl ColumnService {
             .ok_or_else(|| AppError::NotFound(format!("Column with ID {} not found", id)))
     }
 
+    /// Archive a column, hiding it and its cards from the board
+    ///
+    /// The cards themselves are not archived, so restoring the column brings
+    /// them back as they were.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Column UUID
+    ///
+    /// # Returns
+    /// * `AppResult<Column>` - Archived column or error
+    pub async fn archive_column(pool: &PgPool, id: Uuid) -> AppResult<Column> {
+        Column::set_archived(pool, id, true)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Column with ID {} not found", id)))
+    }
+
+    /// Restore an archived column together with its cards
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Column UUID
+    ///
+    /// # Returns
+    /// * `AppResult<ColumnWithCards>` - Restored column with its cards or error
+    pub async fn restore_column(pool: &PgPool, id: Uuid) -> AppResult<ColumnWithCards> {
+        let column = Column::set_archived(pool, id, false)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Column with ID {} not found", id)))?;
+
+        let column = ColumnWithCards::load(pool, column).await?;
+        Ok(column)
+    }
+
     /// Delete a column
     ///
     /// # Arguments
@@ -168,3 +206,85 @@ impl ColumnService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::BoardService;
+
+    /// Insert a board with two columns holding `cards` cards each, and return
+    /// the board id and column ids
+    async fn create_board(pool: &PgPool, cards: i32) -> (Uuid, Vec<Uuid>) {
+        let board_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title) VALUES ('sharetoken01', 'viewtoken001', 'Board') RETURNING id",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let mut column_ids = Vec::new();
+        for position in 0..2 {
+            let column_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO columns (board_id, title, position) VALUES ($1, 'Column', $2) RETURNING id",
+            )
+            .bind(board_id)
+            .bind(position)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+            for card_position in 0..cards {
+                sqlx::query(
+                    "INSERT INTO cards (column_id, title, position) VALUES ($1, 'Card', $2)",
+                )
+                .bind(column_id)
+                .bind(card_position)
+                .execute(pool)
+                .await
+                .unwrap();
+            }
+            column_ids.push(column_id);
+        }
+        (board_id, column_ids)
+    }
+
+    /// Ids of the columns in the board view and how many cards it shows
+    async fn board_view(pool: &PgPool, include_archived: bool) -> (Vec<Uuid>, usize) {
+        let board = BoardService::get_board_by_share_token(pool, "sharetoken01", include_archived)
+            .await
+            .unwrap();
+        let column_ids = board.columns.iter().map(|column| column.id).collect();
+        let cards = board.columns.iter().map(|column| column.cards.len()).sum();
+        (column_ids, cards)
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn archived_column_and_its_cards_are_hidden_from_board_view(pool: PgPool) {
+        let (_, columns) = create_board(&pool, 3).await;
+
+        let archived = ColumnService::archive_column(&pool, columns[0])
+            .await
+            .unwrap();
+        assert!(archived.archived_at.is_some());
+
+        assert_eq!(board_view(&pool, false).await, (vec![columns[1]], 3));
+        assert_eq!(board_view(&pool, true).await, (columns.clone(), 6));
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn restored_column_returns_with_its_cards(pool: PgPool) {
+        let (_, columns) = create_board(&pool, 2).await;
+        ColumnService::archive_column(&pool, columns[0])
+            .await
+            .unwrap();
+
+        let restored = ColumnService::restore_column(&pool, columns[0])
+            .await
+            .unwrap();
+        assert!(restored.archived_at.is_none());
+        assert_eq!(restored.cards.len(), 2);
+
+        assert_eq!(board_view(&pool, false).await, (columns.clone(), 4));
+    }
+}
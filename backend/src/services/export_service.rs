@@ -0,0 +1,296 @@
+use crate::error::{AppError, AppResult};
+use crate::models::board::ColumnWithCards;
+use crate::models::{BoardLabel, BoardWithRelations, CardAttachment};
+use crate::services::S3Service;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use chrono::{DateTime, Utc};
+use futures::{AsyncWriteExt, StreamExt};
+use serde::Serialize;
+use std::collections::HashSet;
+use tokio::io::AsyncWrite;
+use uuid::Uuid;
+
+/// Name of the board JSON inside an export archive
+pub const BOARD_JSON_ENTRY: &str = "board.json";
+
+/// Board as written to `board.json`
+///
+/// Leaves out the share and view tokens, so a shared backup does not
+/// hand out access to the live board.
+#[derive(Debug, Serialize)]
+struct BoardExport<'a> {
+    id: Uuid,
+    title: &'a str,
+    description: Option<&'a str>,
+    is_protected: bool,
+    is_locked: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    columns: &'a [ColumnWithCards],
+    labels: &'a [BoardLabel],
+}
+
+impl<'a> From<&'a BoardWithRelations> for BoardExport<'a> {
+    fn from(board: &'a BoardWithRelations) -> Self {
+        Self {
+            id: board.id,
+            title: &board.title,
+            description: board.description.as_deref(),
+            is_protected: board.is_protected,
+            is_locked: board.is_locked,
+            created_at: board.created_at,
+            updated_at: board.updated_at,
+            columns: &board.columns,
+            labels: &board.labels,
+        }
+    }
+}
+
+/// Service for exporting whole boards
+pub struct ExportService;
+
+impl ExportService {
+    /// Write a board and its attachments to `writer` as a ZIP archive
+    ///
+    /// The archive holds `board.json` (the board with relations, without its
+    /// share and view tokens) and every confirmed attachment, named by `attachment_entry_name`.
+    /// Attachments are copied from S3 chunk by chunk, so memory use does not
+    /// grow with their size.
+    ///
+    /// # Arguments
+    /// * `s3_service` - S3 service the attachments are read from
+    /// * `board` - Board to export, with its columns, cards and attachments loaded
+    /// * `writer` - Destination of the archive
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error; on error the archive is incomplete
+    pub async fn write_board_zip<W>(
+        s3_service: &S3Service,
+        board: &BoardWithRelations,
+        writer: W,
+    ) -> AppResult<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut zip = ZipFileWriter::with_tokio(writer);
+
+        let json = serde_json::to_vec_pretty(&BoardExport::from(board))
+            .map_err(|e| AppError::InternalError(format!("Failed to serialize board: {}", e)))?;
+        let entry = ZipEntryBuilder::new(BOARD_JSON_ENTRY.to_string().into(), Compression::Deflate);
+        zip.write_entry_whole(entry, &json)
+            .await
+            .map_err(zip_error)?;
+
+        let attachments = board
+            .columns
+            .iter()
+            .flat_map(|column| &column.cards)
+            .flat_map(|card| &card.attachments)
+            .filter(|attachment| attachment.is_confirmed);
+
+        // Attachments come in card and position order, so repeated names resolve the same way
+        let mut used_names = HashSet::new();
+        for attachment in attachments {
+            let mut name = attachment_entry_name(attachment, true);
+            if !used_names.insert(name.clone()) {
+                name = attachment_entry_name(attachment, false);
+            }
+
+            let mut object = s3_service.get_object(&attachment.s3_key).await?;
+
+            // Attachments are mostly images and documents that are already compressed
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Stored);
+            let mut entry_writer = zip.write_entry_stream(entry).await.map_err(zip_error)?;
+
+            while let Some(chunk) = object.body.next().await {
+                let chunk = chunk.map_err(|e| {
+                    AppError::InternalError(format!("Failed to read S3 object: {}", e))
+                })?;
+                entry_writer
+                    .write_all(&chunk)
+                    .await
+                    .map_err(|e| AppError::InternalError(format!("Failed to write ZIP: {}", e)))?;
+            }
+
+            entry_writer.close().await.map_err(zip_error)?;
+        }
+
+        zip.close().await.map_err(zip_error)?;
+        Ok(())
+    }
+}
+
+/// Name of an attachment inside an export archive
+///
+/// `attachments/{card_id}/{original_filename}`, or with the attachment ID in
+/// between when `unique` is false because the card has several files of that
+/// name. Path separators and `.`/`..` filenames are replaced so every entry
+/// stays in its card's folder.
+pub fn attachment_entry_name(attachment: &CardAttachment, unique: bool) -> String {
+    let mut filename = attachment.original_filename.replace(['/', '\\'], "_");
+    if matches!(filename.as_str(), "" | "." | "..") {
+        filename = "attachment".to_string();
+    }
+    if unique {
+        format!("attachments/{}/{}", attachment.card_id, filename)
+    } else {
+        format!(
+            "attachments/{}/{}/{}",
+            attachment.card_id, attachment.id, filename
+        )
+    }
+}
+
+fn zip_error(e: async_zip::error::ZipError) -> AppError {
+    AppError::InternalError(format!("Failed to write ZIP: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::board::CardWithLabels;
+    use crate::services::fake_s3::FakeS3;
+    use async_zip::base::read::mem::ZipFileReader;
+
+    fn attachment(card_id: Uuid, original_filename: &str, is_confirmed: bool) -> CardAttachment {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        CardAttachment {
+            id,
+            card_id,
+            uploaded_by: Uuid::new_v4(),
+            filename: format!("{}.png", id),
+            original_filename: original_filename.to_string(),
+            content_type: "image/png".to_string(),
+            file_size: 4,
+            s3_key: format!("attachments/{}/{}.png", card_id, id),
+            s3_bucket: "fluxboard".to_string(),
+            is_confirmed,
+            position: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// A board with one card holding `attachments`
+    fn board_with_attachments(attachments: Vec<CardAttachment>) -> BoardWithRelations {
+        let now = Utc::now();
+        let card = CardWithLabels {
+            id: attachments[0].card_id,
+            column_id: Uuid::new_v4(),
+            title: "Card".to_string(),
+            description: None,
+            color: None,
+            position: 0,
+            created_at: now,
+            updated_at: now,
+            labels: Vec::new(),
+            attachments,
+        };
+        let column = ColumnWithCards {
+            id: card.column_id,
+            board_id: Uuid::new_v4(),
+            title: "To do".to_string(),
+            position: 0,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+            cards: vec![card],
+        };
+        BoardWithRelations {
+            id: column.board_id,
+            share_token: String::new(),
+            title: "Roadmap".to_string(),
+            description: None,
+            is_protected: false,
+            is_locked: false,
+            view_token: "viewtoken001".to_string(),
+            presence_id: None,
+            created_at: now,
+            updated_at: now,
+            columns: vec![column],
+            labels: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn zip_holds_board_json_and_confirmed_attachments() {
+        let card_id = Uuid::new_v4();
+        let confirmed = attachment(card_id, "diagram.png", true);
+        let unconfirmed = attachment(card_id, "pending.png", false);
+        let board = board_with_attachments(vec![confirmed.clone(), unconfirmed.clone()]);
+
+        let config = Config::for_tests();
+        let s3 = FakeS3::start(&config).await;
+        s3.put(&confirmed.s3_key, b"\x89PNG");
+        s3.put(&unconfirmed.s3_key, b"partial");
+
+        let mut archive = Vec::new();
+        ExportService::write_board_zip(&s3.service(&config), &board, &mut archive)
+            .await
+            .unwrap();
+
+        let zip = ZipFileReader::new(archive).await.unwrap();
+        let names: Vec<&str> = zip
+            .file()
+            .entries()
+            .iter()
+            .map(|entry| entry.filename().as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                BOARD_JSON_ENTRY.to_string(),
+                format!("attachments/{}/diagram.png", card_id)
+            ]
+        );
+
+        let mut json = String::new();
+        zip.reader_with_entry(0)
+            .await
+            .unwrap()
+            .read_to_string_checked(&mut json)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["title"], "Roadmap");
+        assert_eq!(json["columns"][0]["cards"][0]["id"], card_id.to_string());
+
+        let mut body = Vec::new();
+        zip.reader_with_entry(1)
+            .await
+            .unwrap()
+            .read_to_end_checked(&mut body)
+            .await
+            .unwrap();
+        assert_eq!(body, b"\x89PNG");
+    }
+
+    #[test]
+    fn board_export_omits_tokens() {
+        let now = Utc::now();
+        let board = BoardWithRelations {
+            id: Uuid::new_v4(),
+            share_token: "share-token".to_string(),
+            title: "Roadmap".to_string(),
+            description: Some("Q3".to_string()),
+            is_protected: false,
+            is_locked: true,
+            view_token: "view-token".to_string(),
+            presence_id: Some(42),
+            created_at: now,
+            updated_at: now,
+            columns: Vec::new(),
+            labels: Vec::new(),
+        };
+
+        let json = serde_json::to_value(BoardExport::from(&board)).unwrap();
+
+        assert_eq!(json["id"], board.id.to_string());
+        assert_eq!(json["title"], "Roadmap");
+        assert!(json.get("share_token").is_none());
+        assert!(json.get("view_token").is_none());
+    }
+}
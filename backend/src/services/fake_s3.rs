@@ -0,0 +1,132 @@
+//! In-process stand-in for S3, for tests that stream or delete objects.
+//!
+//! Serves path-style `GET`, `HEAD` and `DELETE` requests for one bucket from
+//! an in-memory map over plain HTTP on a local port, so an `S3Service` can be
+//! pointed at it without credentials or network access.
+
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::Client as S3Client;
+use aws_sdk_s3::config::{Credentials, Region};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::S3Service;
+use crate::config::Config;
+
+/// Objects held by the fake, by key
+type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// Fake S3 endpoint serving one bucket
+pub struct FakeS3 {
+    objects: Objects,
+    endpoint_url: String,
+    bucket: String,
+}
+
+impl FakeS3 {
+    /// Start serving `config`'s bucket on a free local port
+    pub async fn start(config: &Config) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint_url = format!("http://{}", listener.local_addr().unwrap());
+        let objects = Objects::default();
+        let bucket = config.aws_s3_bucket.clone();
+
+        let served = objects.clone();
+        let prefix = format!("/{}/", bucket);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, served.clone(), prefix.clone()));
+            }
+        });
+
+        Self {
+            objects,
+            endpoint_url,
+            bucket,
+        }
+    }
+
+    /// Store an object
+    pub fn put(&self, key: &str, body: &[u8]) {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), body.to_vec());
+    }
+
+    /// Whether an object is stored under `key`
+    pub fn contains(&self, key: &str) -> bool {
+        self.objects.lock().unwrap().contains_key(key)
+    }
+
+    /// S3 service for `config` that talks to this fake
+    pub fn service(&self, config: &Config) -> S3Service {
+        assert_eq!(config.aws_s3_bucket, self.bucket);
+
+        let client_config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(config.aws_region.clone()))
+            .credentials_provider(Credentials::new("test", "test", None, None, "test"))
+            .endpoint_url(&self.endpoint_url)
+            .force_path_style(true)
+            .build();
+
+        S3Service::with_client(S3Client::from_conf(client_config), config)
+    }
+}
+
+/// Answer one request on `stream`, then close it
+async fn serve(mut stream: TcpStream, objects: Objects, prefix: String) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or_default();
+    let key = target
+        .split('?')
+        .next()
+        .and_then(|path| path.strip_prefix(&prefix))
+        .unwrap_or_default()
+        .to_string();
+
+    let (status, body) = match method {
+        "GET" | "HEAD" => match objects.lock().unwrap().get(&key) {
+            Some(object) => ("200 OK", object.clone()),
+            None => ("404 Not Found", not_found_body()),
+        },
+        "DELETE" => {
+            objects.lock().unwrap().remove(&key);
+            ("204 No Content", Vec::new())
+        }
+        _ => ("405 Method Not Allowed", Vec::new()),
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    )
+    .into_bytes();
+    if method != "HEAD" {
+        response.extend_from_slice(&body);
+    }
+    let _ = stream.write_all(&response).await;
+    let _ = stream.shutdown().await;
+}
+
+/// S3 error body for a missing key
+fn not_found_body() -> Vec<u8> {
+    b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+      <Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>"
+        .to_vec()
+}
@@ -9,7 +9,13 @@ pub mod board_label_service;
 pub mod board_service;
 pub mod card_service;
 pub mod column_service;
+pub mod export_service;
+#[cfg(test)]
+pub(crate) mod fake_s3;
+pub mod notification_service;
+pub mod presence_service;
 pub mod s3_service;
+pub mod watcher_service;
 
 // Re-export services for easier imports
 pub use ai_service::AiService;
@@ -18,4 +24,8 @@ pub use board_label_service::BoardLabelService;
 pub use board_service::BoardService;
 pub use card_service::CardService;
 pub use column_service::ColumnService;
+pub use export_service::ExportService;
+pub use notification_service::NotificationService;
+pub use presence_service::PresenceService;
 pub use s3_service::S3Service;
+pub use watcher_service::WatcherService;
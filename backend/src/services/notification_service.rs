@@ -0,0 +1,150 @@
+use crate::error::{AppError, AppResult};
+use crate::models::Notification;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Most notifications returned by one listing
+const MAX_LISTED_NOTIFICATIONS: i64 = 100;
+
+/// Service for notification-related business logic
+pub struct NotificationService;
+
+impl NotificationService {
+    /// Create a notification for a user
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - Recipient's UUID
+    /// * `kind` - Notification kind
+    /// * `payload` - Kind-specific details
+    ///
+    /// # Returns
+    /// * `AppResult<Notification>` - Created notification or error
+    pub async fn create(
+        pool: &PgPool,
+        user_id: Uuid,
+        kind: &str,
+        payload: serde_json::Value,
+    ) -> AppResult<Notification> {
+        let notification = Notification::create(pool, user_id, kind, &payload).await?;
+        Ok(notification)
+    }
+
+    /// List a user's most recent notifications, newest first
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - Recipient's UUID
+    /// * `unread_only` - Skip notifications that were already read
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Notification>>` - List of notifications or error
+    pub async fn list(
+        pool: &PgPool,
+        user_id: Uuid,
+        unread_only: bool,
+    ) -> AppResult<Vec<Notification>> {
+        let notifications =
+            Notification::find_by_user_id(pool, user_id, unread_only, MAX_LISTED_NOTIFICATIONS)
+                .await?;
+        Ok(notifications)
+    }
+
+    /// Mark one of a user's notifications as read
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Notification UUID
+    /// * `user_id` - Recipient's UUID
+    ///
+    /// # Returns
+    /// * `AppResult<Notification>` - Updated notification or error
+    pub async fn mark_read(pool: &PgPool, id: Uuid, user_id: Uuid) -> AppResult<Notification> {
+        Notification::mark_read(pool, id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Notification with ID {} not found", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_user(pool: &PgPool, email: &str) -> Uuid {
+        sqlx::query_scalar("INSERT INTO users (email, password_hash) VALUES ($1, 'x') RETURNING id")
+            .bind(email)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn notify(pool: &PgPool, user_id: Uuid, n: i32) -> Notification {
+        NotificationService::create(
+            pool,
+            user_id,
+            Notification::KIND_CARD_CHANGED,
+            serde_json::json!({ "n": n }),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn list_unread_skips_read_and_other_users(pool: PgPool) {
+        let user_id = create_user(&pool, "user@example.com").await;
+        let other_id = create_user(&pool, "other@example.com").await;
+        let read = notify(&pool, user_id, 1).await;
+        let unread = notify(&pool, user_id, 2).await;
+        notify(&pool, other_id, 3).await;
+
+        NotificationService::mark_read(&pool, read.id, user_id)
+            .await
+            .unwrap();
+
+        let listed = NotificationService::list(&pool, user_id, true)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = listed.iter().map(|notification| notification.id).collect();
+        assert_eq!(ids, vec![unread.id]);
+
+        let all = NotificationService::list(&pool, user_id, false)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn mark_read_sets_read_at_once(pool: PgPool) {
+        let user_id = create_user(&pool, "user@example.com").await;
+        let notification = notify(&pool, user_id, 1).await;
+        assert!(notification.read_at.is_none());
+
+        let read = NotificationService::mark_read(&pool, notification.id, user_id)
+            .await
+            .unwrap();
+        let read_at = read.read_at.expect("marked read");
+
+        let again = NotificationService::mark_read(&pool, notification.id, user_id)
+            .await
+            .unwrap();
+        assert_eq!(again.read_at, Some(read_at));
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn mark_read_of_another_users_notification_is_not_found(pool: PgPool) {
+        let user_id = create_user(&pool, "user@example.com").await;
+        let other_id = create_user(&pool, "other@example.com").await;
+        let notification = notify(&pool, user_id, 1).await;
+
+        let result = NotificationService::mark_read(&pool, notification.id, other_id).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let unread = NotificationService::list(&pool, user_id, true)
+            .await
+            .unwrap();
+        assert_eq!(unread.len(), 1);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::error::{AppError, AppResult};
+use crate::models::board_presence_id::BoardPresenceId;
+use chrono::{DateTime, Days, NaiveDate};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Prefix of the per-day peak presence keys written by the presence service
+const PEAK_KEY_PREFIX: &str = "presence:peak:";
+
+/// Candidate IDs tried after a board's hash before giving up on assigning one
+const MAX_PRESENCE_ID_PROBES: u32 = 64;
+
+/// Most days of peak presence that can be read (the presence service keeps 90)
+pub const MAX_PEAK_PRESENCE_DAYS: u32 = 90;
+
+/// Channel the presence service listens on for board state changes
+const CONTROL_CHANNEL: &str = "presence:control";
+
+/// Board state change that closes the board's presence room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardStateChange {
+    Deleted,
+    Locked,
+}
+
+/// Most users on a board at once during one UTC day
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyPeakPresence {
+    pub date: NaiveDate,
+    /// Zero when nobody joined the board that day
+    pub peak: u32,
+}
+
+/// Service reading presence statistics that the presence service keeps in Redis
+pub struct PresenceService {
+    client: redis::Client,
+}
+
+impl PresenceService {
+    /// Create a presence service reading from the Redis at `redis_url`
+    ///
+    /// The URL is only parsed here; connections are opened per request.
+    pub fn new(redis_url: &str) -> Result<Self, redis::RedisError> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+        })
+    }
+
+    /// Preferred presence ID of a board
+    ///
+    /// The 32-bit hash clients joined presence rooms with before IDs were
+    /// stored: `hash * 31 + byte` over the hyphenated UUID, wrapping. Boards
+    /// get it unless another board already holds it; see
+    /// `get_or_assign_presence_id` for the ID a board actually has.
+    pub fn presence_board_id(board_id: Uuid) -> u32 {
+        board_id
+            .to_string()
+            .bytes()
+            .fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b.into()))
+    }
+
+    /// Get the presence ID of a board, assigning one if it has none yet
+    ///
+    /// New boards get their hash. When another board holds it, the next
+    /// values are tried in turn, so two boards never share a presence room.
+    /// Once stored, a board's ID never changes.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<u32>` - Presence ID or error
+    pub async fn get_or_assign_presence_id(pool: &PgPool, board_id: Uuid) -> AppResult<u32> {
+        if let Some(presence_id) = BoardPresenceId::find_by_board_id(pool, board_id).await? {
+            return Ok(presence_id);
+        }
+
+        let mut candidate = Self::presence_board_id(board_id);
+        for _ in 0..MAX_PRESENCE_ID_PROBES {
+            if BoardPresenceId::insert(pool, board_id, candidate).await? {
+                return Ok(candidate);
+            }
+            // Either the candidate is taken or a concurrent call mapped the board
+            if let Some(presence_id) = BoardPresenceId::find_by_board_id(pool, board_id).await? {
+                return Ok(presence_id);
+            }
+            candidate = candidate.wrapping_add(1);
+        }
+
+        log::error!("No free presence ID found for board {}", board_id);
+        Err(AppError::InternalError(
+            "Failed to assign a presence ID".to_string(),
+        ))
+    }
+
+    /// Get the presence ID stored for a board, without assigning one
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `board_id` - Board UUID
+    ///
+    /// # Returns
+    /// * `AppResult<Option<u32>>` - Presence ID, None if the board has none, or error
+    pub async fn find_presence_id(pool: &PgPool, board_id: Uuid) -> AppResult<Option<u32>> {
+        let presence_id = BoardPresenceId::find_by_board_id(pool, board_id).await?;
+        Ok(presence_id)
+    }
+
+    /// Assign a presence ID to every board that has none
+    ///
+    /// Run at startup. Boards are mapped oldest first, so when two existing
+    /// boards share a hash, the one whose room has been in use longest keeps it.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    ///
+    /// # Returns
+    /// * `AppResult<usize>` - Number of boards mapped or error
+    pub async fn assign_missing_presence_ids(pool: &PgPool) -> AppResult<usize> {
+        let board_ids = BoardPresenceId::find_unmapped_board_ids(pool).await?;
+        for board_id in &board_ids {
+            Self::get_or_assign_presence_id(pool, *board_id).await?;
+        }
+        Ok(board_ids.len())
+    }
+
+    /// Get a board's daily peak presence for the `days` days up to `today`
+    ///
+    /// # Arguments
+    /// * `presence_id` - Presence ID of the board
+    /// * `days` - Number of days, `today` included
+    /// * `today` - Last day to report (UTC)
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<DailyPeakPresence>>` - One entry per day, oldest first, or error
+    pub async fn get_peak_presence(
+        &self,
+        presence_id: u32,
+        days: u32,
+        today: NaiveDate,
+    ) -> AppResult<Vec<DailyPeakPresence>> {
+        let dates: Vec<NaiveDate> = (0..days)
+            .rev()
+            .filter_map(|ago| today.checked_sub_days(Days::new(ago.into())))
+            .collect();
+
+        let mut pipe = redis::pipe();
+        for date in &dates {
+            pipe.zscore(peak_key(*date), presence_id);
+        }
+
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(presence_unavailable)?;
+        let peaks: Vec<Option<u32>> = pipe
+            .query_async(&mut conn)
+            .await
+            .map_err(presence_unavailable)?;
+
+        Ok(dates
+            .into_iter()
+            .zip(peaks)
+            .map(|(date, peak)| DailyPeakPresence {
+                date,
+                peak: peak.unwrap_or(0),
+            })
+            .collect())
+    }
+
+    /// Tell the presence service a board was deleted or locked
+    ///
+    /// The presence service announces the change to the board's room and
+    /// removes everyone in it.
+    ///
+    /// # Arguments
+    /// * `presence_id` - Presence ID of the board
+    /// * `event` - What happened to the board
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    pub async fn publish_board_state_change(
+        &self,
+        presence_id: u32,
+        event: BoardStateChange,
+    ) -> AppResult<()> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(presence_unavailable)?;
+        let _: () = redis::cmd("PUBLISH")
+            .arg(CONTROL_CHANNEL)
+            .arg(control_payload(presence_id, event))
+            .query_async(&mut conn)
+            .await
+            .map_err(presence_unavailable)?;
+        Ok(())
+    }
+}
+
+/// Control channel message, e.g. `{"board_id":1234,"event":"deleted"}`
+fn control_payload(presence_id: u32, event: BoardStateChange) -> String {
+    serde_json::json!({ "board_id": presence_id, "event": event }).to_string()
+}
+
+/// Key holding the peaks of `date`, which the presence service names by days since the epoch
+fn peak_key(date: NaiveDate) -> String {
+    let day = date
+        .signed_duration_since(DateTime::UNIX_EPOCH.date_naive())
+        .num_days();
+    format!("{}{}", PEAK_KEY_PREFIX, day)
+}
+
+fn presence_unavailable(e: redis::RedisError) -> AppError {
+    AppError::ServiceUnavailable(format!("Presence statistics are unavailable: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_board(pool: &PgPool, token: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO boards (share_token, view_token, title) VALUES ($1, $2, 'Board') RETURNING id",
+        )
+        .bind(format!("share-{}", token))
+        .bind(format!("view-{}", token))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn new_board_gets_its_hash(pool: PgPool) {
+        let board_id = create_board(&pool, "a").await;
+
+        let presence_id = PresenceService::get_or_assign_presence_id(&pool, board_id)
+            .await
+            .unwrap();
+
+        assert_eq!(presence_id, PresenceService::presence_board_id(board_id));
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn presence_id_is_stable(pool: PgPool) {
+        let board_id = create_board(&pool, "a").await;
+
+        let first = PresenceService::get_or_assign_presence_id(&pool, board_id)
+            .await
+            .unwrap();
+        let second = PresenceService::get_or_assign_presence_id(&pool, board_id)
+            .await
+            .unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn colliding_board_gets_next_free_id(pool: PgPool) {
+        let holder = create_board(&pool, "a").await;
+        let board_id = create_board(&pool, "b").await;
+        let hash = PresenceService::presence_board_id(board_id);
+        assert!(BoardPresenceId::insert(&pool, holder, hash).await.unwrap());
+
+        let presence_id = PresenceService::get_or_assign_presence_id(&pool, board_id)
+            .await
+            .unwrap();
+
+        assert_eq!(presence_id, hash.wrapping_add(1));
+        assert_eq!(
+            BoardPresenceId::find_by_board_id(&pool, holder)
+                .await
+                .unwrap(),
+            Some(hash)
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn missing_presence_ids_are_assigned(pool: PgPool) {
+        let first = create_board(&pool, "a").await;
+        let second = create_board(&pool, "b").await;
+
+        let mapped = PresenceService::assign_missing_presence_ids(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(mapped, 2);
+        for board_id in [first, second] {
+            assert!(
+                BoardPresenceId::find_by_board_id(&pool, board_id)
+                    .await
+                    .unwrap()
+                    .is_some()
+            );
+        }
+        assert_eq!(
+            PresenceService::assign_missing_presence_ids(&pool)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn control_payload_matches_presence_service_format() {
+        assert_eq!(
+            control_payload(1234, BoardStateChange::Deleted),
+            r#"{"board_id":1234,"event":"deleted"}"#
+        );
+        assert_eq!(
+            control_payload(u32::MAX, BoardStateChange::Locked),
+            r#"{"board_id":4294967295,"event":"locked"}"#
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore] // Requires running Postgres (DATABASE_URL)
+    async fn presence_id_is_found_without_assigning(pool: PgPool) {
+        let board_id = create_board(&pool, "find").await;
+        assert_eq!(
+            PresenceService::find_presence_id(&pool, board_id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        let assigned = PresenceService::get_or_assign_presence_id(&pool, board_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            PresenceService::find_presence_id(&pool, board_id)
+                .await
+                .unwrap(),
+            Some(assigned)
+        );
+    }
+}
@@ -4,10 +4,23 @@ use aws_config::BehaviorVersion;
 use aws_config::meta::region::RegionProviderChain;
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_s3::presigning::PresigningConfig;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream};
 use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
+/// Longest object key S3 accepts, in bytes
+const MAX_S3_KEY_LENGTH: usize = 1024;
+
+/// Body of an S3 object streamed in chunks
+pub struct S3ObjectStream {
+    /// Object size in bytes, if reported by S3
+    pub content_length: Option<i64>,
+    /// Object body chunks
+    pub body: BoxStream<'static, Result<Bytes, std::io::Error>>,
+}
+
 /// Service for S3-related operations
 #[derive(Clone)]
 pub struct S3Service {
@@ -15,6 +28,8 @@ pub struct S3Service {
     bucket: String,
     upload_url_expiry_minutes: i64,
     download_url_expiry_days: i64,
+    /// Every key passed to S3 must start with one of these
+    allowed_key_prefixes: Vec<String>,
 }
 
 impl S3Service {
@@ -49,14 +64,67 @@ impl S3Service {
         }
 
         let aws_config = aws_config_builder.load().await;
-        let client = S3Client::new(&aws_config);
 
-        Ok(Self {
+        Ok(Self::with_client(S3Client::new(&aws_config), config))
+    }
+
+    /// Create a service around an already configured S3 client
+    ///
+    /// # Arguments
+    /// * `client` - S3 client
+    /// * `config` - Application configuration
+    ///
+    /// # Returns
+    /// * `S3Service` - New service instance
+    pub fn with_client(client: S3Client, config: &Config) -> Self {
+        Self {
             client: Arc::new(client),
             bucket: config.aws_s3_bucket.clone(),
             upload_url_expiry_minutes: config.s3_upload_url_expiry_minutes,
             download_url_expiry_days: config.s3_download_url_expiry_days,
-        })
+            allowed_key_prefixes: config
+                .s3_allowed_key_prefixes
+                .split(',')
+                .map(|prefix| prefix.trim().to_string())
+                .filter(|prefix| !prefix.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Reject keys outside the allowed prefixes before they reach S3
+    ///
+    /// Keys are built from request input (card and attachment IDs, file
+    /// extensions), so a bug there must not let a request read, overwrite or
+    /// delete other objects in the bucket. Besides the prefix check, keys with
+    /// `.` or `..` segments, empty segments, backslashes or control characters
+    /// are refused so the prefix cannot be escaped, as are keys longer than S3
+    /// accepts.
+    ///
+    /// # Arguments
+    /// * `s3_key` - S3 object key
+    /// * `allowed_prefixes` - Key prefixes the service may touch
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success, or `BadRequest` for a disallowed key
+    pub fn validate_key(s3_key: &str, allowed_prefixes: &[String]) -> AppResult<()> {
+        let well_formed = !s3_key.is_empty()
+            && s3_key.len() <= MAX_S3_KEY_LENGTH
+            && !s3_key.contains('\\')
+            && !s3_key.chars().any(char::is_control)
+            && s3_key
+                .split('/')
+                .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+
+        let allowed = allowed_prefixes
+            .iter()
+            .any(|prefix| s3_key.starts_with(prefix.as_str()));
+
+        if well_formed && allowed {
+            Ok(())
+        } else {
+            log::warn!("Rejected S3 key outside the allowed prefixes: {:?}", s3_key);
+            Err(AppError::BadRequest("Invalid attachment key".to_string()))
+        }
     }
 
     /// Generate a pre-signed PUT URL for uploading a file
@@ -68,6 +136,8 @@ impl S3Service {
     /// # Returns
     /// * `AppResult<String>` - Pre-signed URL or error
     pub async fn generate_upload_url(&self, s3_key: &str, content_type: &str) -> AppResult<String> {
+        Self::validate_key(s3_key, &self.allowed_key_prefixes)?;
+
         log::info!(
             "Generating pre-signed upload URL for s3_key: {}, content_type: {}",
             s3_key,
@@ -111,10 +181,17 @@ impl S3Service {
     ///
     /// # Arguments
     /// * `s3_key` - S3 object key
+    /// * `content_disposition` - `Content-Disposition` header S3 should send with the object
     ///
     /// # Returns
     /// * `AppResult<String>` - Pre-signed URL or error
-    pub async fn generate_download_url(&self, s3_key: &str) -> AppResult<String> {
+    pub async fn generate_download_url(
+        &self,
+        s3_key: &str,
+        content_disposition: &str,
+    ) -> AppResult<String> {
+        Self::validate_key(s3_key, &self.allowed_key_prefixes)?;
+
         let expiry_duration = Duration::from_secs((self.download_url_expiry_days * 86400) as u64);
 
         let presigning_config = PresigningConfig::builder()
@@ -129,6 +206,7 @@ impl S3Service {
             .get_object()
             .bucket(&self.bucket)
             .key(s3_key)
+            .response_content_disposition(content_disposition)
             .presigned(presigning_config)
             .await
             .map_err(|e| {
@@ -146,6 +224,8 @@ impl S3Service {
     /// # Returns
     /// * `AppResult<bool>` - True if object exists, false otherwise
     pub async fn verify_object_exists(&self, s3_key: &str) -> AppResult<bool> {
+        Self::validate_key(s3_key, &self.allowed_key_prefixes)?;
+
         match self
             .client
             .head_object()
@@ -169,6 +249,44 @@ impl S3Service {
         }
     }
 
+    /// Fetch an S3 object as a byte stream
+    ///
+    /// # Arguments
+    /// * `s3_key` - S3 object key
+    ///
+    /// # Returns
+    /// * `AppResult<S3ObjectStream>` - Streamed object body or error
+    pub async fn get_object(&self, s3_key: &str) -> AppResult<S3ObjectStream> {
+        Self::validate_key(s3_key, &self.allowed_key_prefixes)?;
+
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(s3_key)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("NoSuchKey") || e.to_string().contains("404") {
+                    AppError::NotFound("File not found in S3".to_string())
+                } else {
+                    AppError::InternalError(format!("Failed to get S3 object: {}", e))
+                }
+            })?;
+
+        let content_length = output.content_length();
+        let body = stream::unfold(output.body, |mut body| async move {
+            body.next()
+                .await
+                .map(|chunk| (chunk.map_err(std::io::Error::other), body))
+        });
+
+        Ok(S3ObjectStream {
+            content_length,
+            body: Box::pin(body),
+        })
+    }
+
     /// Delete an S3 object
     ///
     /// # Arguments
@@ -177,6 +295,8 @@ impl S3Service {
     /// # Returns
     /// * `AppResult<()>` - Success or error
     pub async fn delete_object(&self, s3_key: &str) -> AppResult<()> {
+        Self::validate_key(s3_key, &self.allowed_key_prefixes)?;
+
         self.client
             .delete_object()
             .bucket(&self.bucket)
@@ -217,3 +337,62 @@ impl S3Service {
             .to_lowercase()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefixes() -> Vec<String> {
+        vec!["attachments/".to_string()]
+    }
+
+    #[test]
+    fn validate_key_accepts_built_keys() {
+        let key = S3Service::build_s3_key(Uuid::new_v4(), Uuid::new_v4(), "png");
+        assert!(S3Service::validate_key(&key, &prefixes()).is_ok());
+    }
+
+    #[test]
+    fn validate_key_rejects_other_prefixes() {
+        assert!(S3Service::validate_key("exports/board.zip", &prefixes()).is_err());
+        assert!(S3Service::validate_key("attachments/a.png", &[]).is_err());
+    }
+
+    #[test]
+    fn validate_key_rejects_dot_segments() {
+        assert!(S3Service::validate_key("attachments/../secrets.txt", &prefixes()).is_err());
+        assert!(S3Service::validate_key("attachments/./a.png", &prefixes()).is_err());
+        assert!(S3Service::validate_key("attachments/card/..", &prefixes()).is_err());
+    }
+
+    #[test]
+    fn validate_key_rejects_leading_and_empty_segments() {
+        let leading = ["/attachments/".to_string()];
+        assert!(S3Service::validate_key("/attachments/a.png", &leading).is_err());
+        assert!(S3Service::validate_key("attachments//a.png", &prefixes()).is_err());
+        assert!(S3Service::validate_key("attachments/card/", &prefixes()).is_err());
+    }
+
+    #[test]
+    fn validate_key_rejects_backslashes_and_control_characters() {
+        assert!(S3Service::validate_key("attachments\\..\\a.png", &prefixes()).is_err());
+        assert!(S3Service::validate_key("attachments/a\\b.png", &prefixes()).is_err());
+        assert!(S3Service::validate_key("attachments/a\n.png", &prefixes()).is_err());
+    }
+
+    #[test]
+    fn validate_key_rejects_empty_keys() {
+        assert!(S3Service::validate_key("", &prefixes()).is_err());
+        assert!(S3Service::validate_key("", &["".to_string()]).is_err());
+    }
+
+    #[test]
+    fn validate_key_rejects_over_long_keys() {
+        let longest = format!("attachments/{}", "a".repeat(MAX_S3_KEY_LENGTH - 12));
+        assert_eq!(longest.len(), MAX_S3_KEY_LENGTH);
+        assert!(S3Service::validate_key(&longest, &prefixes()).is_ok());
+
+        let too_long = format!("{}a", longest);
+        assert!(S3Service::validate_key(&too_long, &prefixes()).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::AppResult;
+use crate::models::CardWatcher;
+use crate::services::CardService;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Service for card watcher-related business logic
+pub struct WatcherService;
+
+impl WatcherService {
+    /// Start watching a card
+    ///
+    /// Watching a card that is already watched is a no-op.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    /// * `user_id` - Watching user's UUID
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    pub async fn watch_card(pool: &PgPool, card_id: Uuid, user_id: Uuid) -> AppResult<()> {
+        CardService::get_card_by_id(pool, card_id).await?;
+        CardWatcher::watch(pool, card_id, user_id).await?;
+        Ok(())
+    }
+
+    /// Stop watching a card
+    ///
+    /// Unwatching a card that is not watched is a no-op.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    /// * `user_id` - Watching user's UUID
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    pub async fn unwatch_card(pool: &PgPool, card_id: Uuid, user_id: Uuid) -> AppResult<()> {
+        CardWatcher::unwatch(pool, card_id, user_id).await?;
+        Ok(())
+    }
+
+    /// Get the users watching a card
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `card_id` - Card UUID
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Uuid>>` - Watching user IDs or error
+    pub async fn get_watcher_ids(pool: &PgPool, card_id: Uuid) -> AppResult<Vec<Uuid>> {
+        let user_ids = CardWatcher::find_user_ids_by_card_id(pool, card_id).await?;
+        Ok(user_ids)
+    }
+}
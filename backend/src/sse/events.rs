@@ -2,12 +2,15 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// SSE event types that mirror the WebSocket message types
+///
+/// Every subscriber of a board receives these payloads, so they must only
+/// carry public views of models (no passwords, tokens or storage details).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SseEvent {
     // Board events
     BoardUpdated {
-        board: crate::models::board::Board,
+        board: crate::models::board::BoardPublic,
     },
 
     // Column events
@@ -24,6 +27,14 @@ pub enum SseEvent {
         column_id: Uuid,
         new_position: i32,
     },
+    /// The column and its cards are hidden from the board
+    ColumnArchived {
+        column_id: Uuid,
+    },
+    /// An archived column is back, with its cards
+    ColumnRestored {
+        column: crate::models::board::ColumnWithCards,
+    },
 
     // Card events
     CardCreated {
@@ -46,6 +57,10 @@ pub enum SseEvent {
         column_id: Uuid,
         new_position: i32,
     },
+    ColumnCardsArchived {
+        column_id: Uuid,
+        card_ids: Vec<Uuid>,
+    },
 
     // Board label events
     BoardLabelCreated {
@@ -57,6 +72,10 @@ pub enum SseEvent {
     BoardLabelDeleted {
         label_id: Uuid,
     },
+    /// Full label order of a board after a reorder
+    LabelsReordered {
+        label_ids: Vec<Uuid>,
+    },
 
     // Card label assignment events
     CardLabelAssigned {
@@ -77,6 +96,35 @@ pub enum SseEvent {
         attachment_id: Uuid,
         card_id: Uuid,
     },
+    /// Every confirmed attachment of a card was deleted at once
+    AttachmentsDeleted {
+        card_id: Uuid,
+        attachment_ids: Vec<Uuid>,
+    },
+    /// Full attachment order of a card after a reorder
+    AttachmentsReordered {
+        card_id: Uuid,
+        attachment_ids: Vec<Uuid>,
+    },
+
+    // Notification events (sent only to the users concerned, never to a board)
+    /// A card the user watches was changed
+    CardChanged {
+        card_id: Uuid,
+        board_id: Uuid,
+        change: CardChange,
+    },
+
+    /// Keep-alive sent on idle streams; clients ignore it
+    Ping,
+}
+
+/// What happened to a watched card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardChange {
+    Updated,
+    Moved,
 }
 
 impl SseEvent {
@@ -88,21 +136,34 @@ impl SseEvent {
             SseEvent::ColumnUpdated { .. } => "column:updated",
             SseEvent::ColumnDeleted { .. } => "column:deleted",
             SseEvent::ColumnReordered { .. } => "column:reordered",
+            SseEvent::ColumnArchived { .. } => "column:archived",
+            SseEvent::ColumnRestored { .. } => "column:restored",
             SseEvent::CardCreated { .. } => "card:created",
             SseEvent::CardUpdated { .. } => "card:updated",
             SseEvent::CardDeleted { .. } => "card:deleted",
             SseEvent::CardMoved { .. } => "card:moved",
             SseEvent::CardReordered { .. } => "card:reordered",
+            SseEvent::ColumnCardsArchived { .. } => "column:cards_archived",
             SseEvent::BoardLabelCreated { .. } => "board_label:created",
             SseEvent::BoardLabelUpdated { .. } => "board_label:updated",
             SseEvent::BoardLabelDeleted { .. } => "board_label:deleted",
+            SseEvent::LabelsReordered { .. } => "board_label:reordered",
             SseEvent::CardLabelAssigned { .. } => "card_label:assigned",
             SseEvent::CardLabelUnassigned { .. } => "card_label:unassigned",
             SseEvent::AttachmentCreated { .. } => "attachment:created",
             SseEvent::AttachmentDeleted { .. } => "attachment:deleted",
+            SseEvent::AttachmentsDeleted { .. } => "attachment:bulk_deleted",
+            SseEvent::AttachmentsReordered { .. } => "attachment:reordered",
+            SseEvent::CardChanged { .. } => "card:changed",
+            SseEvent::Ping => "ping",
         }
     }
 
+    /// Whether the event is a keep-alive, which is never kept for replay
+    pub fn is_ping(&self) -> bool {
+        matches!(self, SseEvent::Ping)
+    }
+
     /// Serialize the event to JSON
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
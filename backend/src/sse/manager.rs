@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::convert::Infallible;
 use std::fmt;
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock, mpsc};
 use uuid::Uuid;
 
 use super::events::SseEvent;
@@ -10,15 +12,67 @@ use super::events::SseEvent;
 /// Maximum number of events to buffer per client
 const CHANNEL_BUFFER_SIZE: usize = 100;
 
+/// Number of recent events kept per board for clients that reconnect
+const HISTORY_SIZE: usize = 100;
+
+/// Default window for coalescing reorder events
+pub const DEFAULT_REORDER_COALESCE_WINDOW: Duration = Duration::from_millis(100);
+
+/// Entity whose position a reorder event describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReorderKey {
+    Card(Uuid),
+    Column(Uuid),
+}
+
+impl ReorderKey {
+    /// Key for reorder events; `None` for events that are never coalesced
+    fn for_event(event: &SseEvent) -> Option<Self> {
+        match event {
+            SseEvent::CardReordered { card_id, .. } => Some(ReorderKey::Card(*card_id)),
+            SseEvent::ColumnReordered { column_id, .. } => Some(ReorderKey::Column(*column_id)),
+            _ => None,
+        }
+    }
+}
+
+/// Reorder events held back for one board until its window closes
+struct PendingReorders {
+    /// Distinguishes this batch from later ones for the same board
+    generation: u64,
+    /// Latest event per entity, in the order entities were first reordered
+    events: Vec<(ReorderKey, SseEvent)>,
+}
+
+impl PendingReorders {
+    /// Add an event, replacing an earlier one for the same entity
+    fn push(&mut self, key: ReorderKey, event: SseEvent) {
+        match self.events.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, pending)) => *pending = event,
+            None => self.events.push((key, event)),
+        }
+    }
+}
+
 /// SSE Event wrapper that can be formatted for streaming
 #[derive(Clone)]
 pub struct SseEventWrapper {
+    /// Sent as the SSE `id` so clients can resume with `Last-Event-ID`
+    id: u64,
     event: SseEvent,
 }
 
 impl SseEventWrapper {
-    pub fn new(event: SseEvent) -> Self {
-        Self { event }
+    pub fn new(id: u64, event: SseEvent) -> Self {
+        Self { id, event }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn event(&self) -> &SseEvent {
+        &self.event
     }
 }
 
@@ -26,7 +80,13 @@ impl fmt::Display for SseEventWrapper {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.event.to_json() {
             Ok(json) => {
-                write!(f, "event: {}\ndata: {}\n\n", self.event.event_name(), json)
+                write!(
+                    f,
+                    "id: {}\nevent: {}\ndata: {}\n\n",
+                    self.id,
+                    self.event.event_name(),
+                    json
+                )
             }
             Err(e) => {
                 log::error!("Failed to serialize SSE event: {}", e);
@@ -36,42 +96,228 @@ impl fmt::Display for SseEventWrapper {
     }
 }
 
+/// Channel an SSE client's events are sent on
+type ClientSender = mpsc::Sender<Result<SseEventWrapper, Infallible>>;
+
+/// Map of board or user id -> channels of the clients listening for it
+type ClientChannels = HashMap<Uuid, Vec<ClientSender>>;
+
 /// Manager for SSE connections with per-board client tracking
 #[derive(Clone)]
 pub struct SseManager {
     /// Map of board_id -> list of client channels
     /// Each client has a channel sender to receive events
-    connections: Arc<RwLock<HashMap<Uuid, Vec<mpsc::Sender<Result<SseEventWrapper, Infallible>>>>>>,
+    connections: Arc<RwLock<ClientChannels>>,
+    /// Map of board_id -> reorder events waiting for the coalescing window
+    pending_reorders: Arc<Mutex<HashMap<Uuid, PendingReorders>>>,
+    /// How long reorder events are held back (zero disables coalescing)
+    reorder_window: Duration,
+    /// Source of `PendingReorders::generation`
+    next_generation: Arc<AtomicU64>,
+    /// Map of board_id -> most recent events, oldest first
+    ///
+    /// Kept after the last client disconnects so a reconnecting client can
+    /// catch up. Locked before `connections` whenever both are needed.
+    history: Arc<Mutex<HashMap<Uuid, VecDeque<SseEventWrapper>>>>,
+    /// Source of event ids, increasing across all boards
+    next_event_id: Arc<AtomicU64>,
+    /// Map of user_id -> notification channels of that user's clients
+    user_connections: Arc<RwLock<ClientChannels>>,
 }
 
 impl SseManager {
-    /// Create a new SSE manager
+    /// Create a new SSE manager with the default reorder coalescing window
     pub fn new() -> Self {
+        Self::with_reorder_window(DEFAULT_REORDER_COALESCE_WINDOW)
+    }
+
+    /// Create a new SSE manager that coalesces reorder events within `reorder_window`
+    ///
+    /// A zero window sends every reorder event immediately.
+    pub fn with_reorder_window(reorder_window: Duration) -> Self {
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            pending_reorders: Arc::new(Mutex::new(HashMap::new())),
+            reorder_window,
+            next_generation: Arc::new(AtomicU64::new(0)),
+            history: Arc::new(Mutex::new(HashMap::new())),
+            next_event_id: Arc::new(AtomicU64::new(1)),
+            user_connections: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
     /// Subscribe to updates for a specific board
-    /// Returns a receiver that will get events for this board
+    ///
+    /// Returns the buffered events newer than `last_event_id` (none when it is
+    /// `None`) together with a receiver for later events. Every event ends up
+    /// in exactly one of the two.
     pub async fn subscribe(
         &self,
         board_id: Uuid,
-    ) -> mpsc::Receiver<Result<SseEventWrapper, Infallible>> {
+        last_event_id: Option<u64>,
+    ) -> (
+        Vec<SseEventWrapper>,
+        mpsc::Receiver<Result<SseEventWrapper, Infallible>>,
+    ) {
         let (tx, rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
 
+        // Held until the client is registered so no event is missed in between
+        let history = self.history.lock().await;
+        let replay = match (last_event_id, history.get(&board_id)) {
+            (Some(last_event_id), Some(events)) => events
+                .iter()
+                .filter(|event| event.id > last_event_id)
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        };
+
         let mut connections = self.connections.write().await;
         connections
             .entry(board_id)
             .or_insert_with(Vec::new)
             .push(tx);
 
+        (replay, rx)
+    }
+
+    /// Subscribe to notifications for a specific user
+    pub async fn subscribe_user(
+        &self,
+        user_id: Uuid,
+    ) -> mpsc::Receiver<Result<SseEventWrapper, Infallible>> {
+        let (tx, rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+
+        let mut user_connections = self.user_connections.write().await;
+        user_connections
+            .entry(user_id)
+            .or_insert_with(Vec::new)
+            .push(tx);
+
         rx
     }
 
+    /// Send a notification to every connected client of the given users
+    ///
+    /// Notifications are not kept for replay; users who are offline miss them.
+    pub async fn notify_users(&self, user_ids: &[Uuid], event: SseEvent) {
+        if user_ids.is_empty() {
+            return;
+        }
+
+        let id = self.next_event_id.fetch_add(1, Ordering::Relaxed);
+        let wrapped_event = Ok(SseEventWrapper::new(id, event));
+
+        let mut user_connections = self.user_connections.write().await;
+        for user_id in user_ids {
+            let Some(clients) = user_connections.get_mut(user_id) else {
+                continue;
+            };
+
+            clients.retain(|client| match client.try_send(wrapped_event.clone()) {
+                Ok(_) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    log::warn!("SSE notification channel is full for user {}", user_id);
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            });
+
+            if clients.is_empty() {
+                user_connections.remove(user_id);
+            }
+        }
+    }
+
     /// Broadcast an event to all clients subscribed to a board
+    ///
+    /// `CardReordered`/`ColumnReordered` events are held for the reorder
+    /// window, and only the latest event per card/column is sent when it
+    /// closes. Any other event flushes the board's pending reorders first so
+    /// clients still see events in order.
     pub async fn broadcast(&self, board_id: Uuid, event: SseEvent) {
-        let wrapped_event = Ok(SseEventWrapper::new(event));
+        self.broadcast_with(board_id, event, true).await;
+    }
+
+    /// Broadcast an event, holding back reorder events only if `coalesce_reorders` is set
+    ///
+    /// For boards whose settings turn coalescing off; their reorder events
+    /// are sent right away like any other event.
+    pub async fn broadcast_with(&self, board_id: Uuid, event: SseEvent, coalesce_reorders: bool) {
+        if self.reorder_window.is_zero() {
+            self.send(board_id, event).await;
+            return;
+        }
+
+        // Held while sending so a flush cannot interleave with other events
+        let mut pending = self.pending_reorders.lock().await;
+
+        match ReorderKey::for_event(&event).filter(|_| coalesce_reorders) {
+            Some(key) => {
+                if let Some(batch) = pending.get_mut(&board_id) {
+                    batch.push(key, event);
+                    return;
+                }
+
+                let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+                pending.insert(
+                    board_id,
+                    PendingReorders {
+                        generation,
+                        events: vec![(key, event)],
+                    },
+                );
+
+                let manager = self.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(manager.reorder_window).await;
+                    manager.flush_reorders(board_id, generation).await;
+                });
+            }
+            None => {
+                if let Some(batch) = pending.remove(&board_id) {
+                    for (_, reorder) in batch.events {
+                        self.send(board_id, reorder).await;
+                    }
+                }
+                self.send(board_id, event).await;
+            }
+        }
+    }
+
+    /// Send a board's pending reorder events once their window closes
+    ///
+    /// Does nothing if the batch was already flushed by another event.
+    async fn flush_reorders(&self, board_id: Uuid, generation: u64) {
+        let mut pending = self.pending_reorders.lock().await;
+
+        if pending.get(&board_id).map(|batch| batch.generation) != Some(generation) {
+            return;
+        }
+
+        if let Some(batch) = pending.remove(&board_id) {
+            for (_, reorder) in batch.events {
+                self.send(board_id, reorder).await;
+            }
+        }
+    }
+
+    /// Send an event to all clients subscribed to a board right away
+    async fn send(&self, board_id: Uuid, event: SseEvent) {
+        let mut history = self.history.lock().await;
+        let id = self.next_event_id.fetch_add(1, Ordering::Relaxed);
+        let wrapped = SseEventWrapper::new(id, event);
+
+        // Pings only keep idle streams open; replaying them is pointless
+        if !wrapped.event.is_ping() {
+            let events = history.entry(board_id).or_default();
+            if events.len() == HISTORY_SIZE {
+                events.pop_front();
+            }
+            events.push_back(wrapped.clone());
+        }
+
+        let wrapped_event = Ok(wrapped);
 
         let mut connections = self.connections.write().await;
 
@@ -139,3 +385,195 @@ impl Default for SseManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(100);
+
+    fn card_reordered(card_id: Uuid, new_position: i32) -> SseEvent {
+        SseEvent::CardReordered {
+            card_id,
+            column_id: Uuid::nil(),
+            new_position,
+        }
+    }
+
+    /// Events delivered to `rx` so far
+    fn received(rx: &mut mpsc::Receiver<Result<SseEventWrapper, Infallible>>) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        while let Ok(Ok(wrapped)) = rx.try_recv() {
+            events.push(wrapped.event().clone());
+        }
+        events
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rapid_reorders_within_window_yield_one_event() {
+        let manager = SseManager::with_reorder_window(WINDOW);
+        let board_id = Uuid::new_v4();
+        let card_id = Uuid::new_v4();
+        let (_, mut rx) = manager.subscribe(board_id, None).await;
+
+        for position in 0..10 {
+            manager
+                .broadcast(board_id, card_reordered(card_id, position))
+                .await;
+        }
+        assert!(received(&mut rx).is_empty());
+
+        tokio::time::sleep(WINDOW * 2).await;
+
+        let events = received(&mut rx);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            SseEvent::CardReordered {
+                new_position: 9,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reorders_keep_latest_per_entity() {
+        let manager = SseManager::with_reorder_window(WINDOW);
+        let board_id = Uuid::new_v4();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let (_, mut rx) = manager.subscribe(board_id, None).await;
+
+        manager.broadcast(board_id, card_reordered(first, 1)).await;
+        manager.broadcast(board_id, card_reordered(second, 1)).await;
+        manager.broadcast(board_id, card_reordered(first, 2)).await;
+        tokio::time::sleep(WINDOW * 2).await;
+
+        let events = received(&mut rx);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0],
+            SseEvent::CardReordered { card_id, new_position: 2, .. } if card_id == first
+        ));
+        assert!(matches!(
+            events[1],
+            SseEvent::CardReordered { card_id, new_position: 1, .. } if card_id == second
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn other_events_flush_pending_reorders_first() {
+        let manager = SseManager::with_reorder_window(WINDOW);
+        let board_id = Uuid::new_v4();
+        let card_id = Uuid::new_v4();
+        let (_, mut rx) = manager.subscribe(board_id, None).await;
+
+        manager
+            .broadcast(board_id, card_reordered(card_id, 3))
+            .await;
+        manager
+            .broadcast(board_id, SseEvent::CardDeleted { card_id })
+            .await;
+
+        let events = received(&mut rx);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], SseEvent::CardReordered { .. }));
+        assert!(matches!(events[1], SseEvent::CardDeleted { .. }));
+
+        // The flushed batch is not sent again when its window closes
+        tokio::time::sleep(WINDOW * 2).await;
+        assert!(received(&mut rx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn reorders_are_sent_immediately_without_coalescing() {
+        let board_id = Uuid::new_v4();
+        let card_id = Uuid::new_v4();
+
+        let manager = SseManager::with_reorder_window(Duration::ZERO);
+        let (_, mut rx) = manager.subscribe(board_id, None).await;
+        manager
+            .broadcast(board_id, card_reordered(card_id, 1))
+            .await;
+        manager
+            .broadcast(board_id, card_reordered(card_id, 2))
+            .await;
+        assert_eq!(received(&mut rx).len(), 2);
+
+        let manager = SseManager::with_reorder_window(WINDOW);
+        let (_, mut rx) = manager.subscribe(board_id, None).await;
+        manager
+            .broadcast_with(board_id, card_reordered(card_id, 1), false)
+            .await;
+        assert_eq!(received(&mut rx).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn subscribe_replays_events_after_last_event_id() {
+        let manager = SseManager::with_reorder_window(Duration::ZERO);
+        let board_id = Uuid::new_v4();
+        let other_board_id = Uuid::new_v4();
+
+        for _ in 0..3 {
+            let card_id = Uuid::new_v4();
+            manager
+                .broadcast(board_id, SseEvent::CardDeleted { card_id })
+                .await;
+        }
+        manager
+            .broadcast(
+                other_board_id,
+                SseEvent::CardDeleted {
+                    card_id: Uuid::new_v4(),
+                },
+            )
+            .await;
+
+        let (replay, _rx) = manager.subscribe(board_id, Some(0)).await;
+        assert_eq!(replay.len(), 3);
+
+        let (replay, _rx) = manager.subscribe(board_id, Some(replay[0].id())).await;
+        assert_eq!(replay.len(), 2);
+
+        // A client that does not resume gets no replay
+        let (replay, _rx) = manager.subscribe(board_id, None).await;
+        assert!(replay.is_empty());
+    }
+
+    #[tokio::test]
+    async fn history_keeps_only_recent_events() {
+        let manager = SseManager::with_reorder_window(Duration::ZERO);
+        let board_id = Uuid::new_v4();
+
+        for _ in 0..HISTORY_SIZE + 5 {
+            let card_id = Uuid::new_v4();
+            manager
+                .broadcast(board_id, SseEvent::CardDeleted { card_id })
+                .await;
+        }
+
+        let (replay, _rx) = manager.subscribe(board_id, Some(0)).await;
+        assert_eq!(replay.len(), HISTORY_SIZE);
+        assert_eq!(replay[0].id(), 6);
+    }
+
+    #[tokio::test]
+    async fn pings_reach_clients_but_are_not_replayed() {
+        let manager = SseManager::with_reorder_window(Duration::ZERO);
+        let board_id = Uuid::new_v4();
+        let card_id = Uuid::new_v4();
+        let (_, mut rx) = manager.subscribe(board_id, None).await;
+
+        manager.broadcast(board_id, SseEvent::Ping).await;
+        manager
+            .broadcast(board_id, SseEvent::CardDeleted { card_id })
+            .await;
+
+        let events = received(&mut rx);
+        assert_eq!(events.len(), 2);
+        assert!(events[0].is_ping());
+
+        let (replay, _rx) = manager.subscribe(board_id, Some(0)).await;
+        assert_eq!(replay.len(), 1);
+        assert!(matches!(replay[0].event(), SseEvent::CardDeleted { .. }));
+    }
+}
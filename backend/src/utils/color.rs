@@ -0,0 +1,61 @@
+/// Color names accepted in place of a hex value
+pub const NAMED_COLORS: &[&str] = &[
+    "black", "white", "gray", "grey", "silver", "red", "maroon", "orange", "yellow", "olive",
+    "lime", "green", "teal", "cyan", "aqua", "blue", "navy", "purple", "violet", "fuchsia",
+    "magenta", "pink",
+];
+
+/// Check that a string is a color labels and cards may carry
+///
+/// Accepts `#rgb` or `#rrggbb` hex (either case) or one of `NAMED_COLORS`.
+///
+/// # Arguments
+/// * `color` - Color taken from the request body
+///
+/// # Returns
+/// * `bool` - True if the color is a short or long hex value or a known name
+pub fn is_valid_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6) && hex.bytes().all(|b| b.is_ascii_hexdigit()),
+        None => NAMED_COLORS
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(color)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_short_and_long_hex() {
+        assert!(is_valid_color("#f00"));
+        assert!(is_valid_color("#ff0000"));
+        assert!(is_valid_color("#09a1bc"));
+    }
+
+    #[test]
+    fn hex_and_names_ignore_case() {
+        assert!(is_valid_color("#FFF"));
+        assert!(is_valid_color("#Ab12eF"));
+        assert!(is_valid_color("Red"));
+        assert!(is_valid_color("NAVY"));
+    }
+
+    #[test]
+    fn rejects_hex_without_hash() {
+        assert!(!is_valid_color("fff"));
+        assert!(!is_valid_color("ff0000"));
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        assert!(!is_valid_color(""));
+        assert!(!is_valid_color("#"));
+        assert!(!is_valid_color("#ff00"));
+        assert!(!is_valid_color("#ff00000"));
+        assert!(!is_valid_color("#ggg"));
+        assert!(!is_valid_color("#red"));
+        assert!(!is_valid_color("rebeccapurple"));
+    }
+}
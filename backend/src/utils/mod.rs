@@ -5,4 +5,9 @@
 // - Date/time utilities
 // - Other shared utilities
 
+pub mod color;
 pub mod serde_helpers;
+pub mod share_token;
+
+pub use color::is_valid_color;
+pub use share_token::is_valid_share_token;
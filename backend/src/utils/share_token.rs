@@ -0,0 +1,50 @@
+/// Characters a share or view token may contain
+pub const SHARE_TOKEN_CHARSET: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Length of every generated share or view token
+pub const SHARE_TOKEN_LENGTH: usize = 12;
+
+/// Check that a string has the shape of a generated share or view token
+///
+/// Lets handlers reject malformed tokens before querying the database.
+///
+/// # Arguments
+/// * `token` - Token taken from the request path
+///
+/// # Returns
+/// * `bool` - True if the token has the right length and charset
+pub fn is_valid_share_token(token: &str) -> bool {
+    token.len() == SHARE_TOKEN_LENGTH && token.bytes().all(|b| SHARE_TOKEN_CHARSET.contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_generated_shape() {
+        assert!(is_valid_share_token("abcXYZ012789"));
+        assert!(is_valid_share_token("aaaaaaaaaaaa"));
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        assert!(!is_valid_share_token("abcXYZ01278"));
+        assert!(!is_valid_share_token("abcXYZ0127890"));
+    }
+
+    #[test]
+    fn rejects_characters_outside_charset() {
+        assert!(!is_valid_share_token("abcXYZ01278-"));
+        assert!(!is_valid_share_token("abcXYZ 12789"));
+        assert!(!is_valid_share_token("../etc/pass1"));
+        // Multi-byte characters must not pass as the right length
+        assert!(!is_valid_share_token("abcXYZ0127é"));
+    }
+
+    #[test]
+    fn rejects_empty() {
+        assert!(!is_valid_share_token(""));
+    }
+}
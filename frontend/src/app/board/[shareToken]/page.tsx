@@ -18,7 +18,7 @@ import { addRecentBoard } from "@/lib/recent-boards";
 import { Button } from "@/components/ui/button";
 import { Tag, Lock, Unlock } from "lucide-react";
 import { isBoardOwner, getBoardPassword } from "@/lib/board-passwords";
-import { setBoardLockState } from "@/lib/api";
+import { getBoard, setBoardLockState } from "@/lib/api";
 
 interface BoardPageProps {
   params: {
@@ -55,16 +55,32 @@ export default function BoardPage({ params }: BoardPageProps) {
       return;
     }
 
+    const toggle = (version: string) =>
+      setBoardLockState(board.share_token, password, !board.is_locked, version);
+
     setIsTogglingLock(true);
     try {
-      const updatedBoard = await setBoardLockState(
-        board.share_token,
-        password,
-        !board.is_locked
-      );
+      let updatedBoard;
+      try {
+        updatedBoard = await toggle(board.updated_at);
+      } catch (error: any) {
+        if (error.response?.status !== 409) throw error;
+        // Card and column edits also move the board's version; retry once
+        // against the current version unless the lock itself was changed
+        const current = await getBoard(board.share_token);
+        if (current.is_locked !== board.is_locked) {
+          loadBoard(shareToken);
+          return;
+        }
+        updatedBoard = await toggle(current.updated_at);
+      }
       updateBoard(updatedBoard);
-    } catch (error) {
+    } catch (error: any) {
       console.error("Failed to toggle lock state:", error);
+      // Someone else changed the board first; show its current state
+      if (error.response?.status === 409) {
+        loadBoard(shareToken);
+      }
     } finally {
       setIsTogglingLock(false);
     }
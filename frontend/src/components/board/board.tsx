@@ -67,7 +67,8 @@ export function Board() {
   // Board container ref for cursor positioning
   const boardRef = useRef<HTMLDivElement>(null);
 
-  // Convert board ID (string) to number for presence system
+  // Fallback presence ID for boards the backend has not assigned one to yet;
+  // matches the hash the backend assigns when it is free
   const getBoardIdNumber = (boardId: string): number => {
     // Simple hash function to convert string to number
     let hash = 0;
@@ -76,12 +77,12 @@ export function Board() {
       hash = (hash << 5) - hash + char;
       hash = hash & hash; // Convert to 32-bit integer
     }
-    return Math.abs(hash) % 65535; // Keep within u16 range
+    return hash >>> 0; // Reinterpret as u32
   };
 
   // Presence integration
   const { users, presenceCount, isConnected, updateCursor } = usePresence({
-    boardId: board?.id ? getBoardIdNumber(board.id) : 0,
+    boardId: board?.id ? board.presence_id ?? getBoardIdNumber(board.id) : 0,
     username: username || "Anonymous",
     enabled: !!board && hasUsername,
     throttleMs: 50,
@@ -104,6 +104,7 @@ export function EditCardDialog() {
         title: title.trim(),
         context: description.trim() || undefined,
         format,
+        board_id: board?.id,
       });
       setDescription(result.description);
     } catch (error) {
@@ -27,10 +27,12 @@ export const useSSE = (shareToken: string | null) => {
     addBoardLabel,
     updateBoardLabel,
     deleteBoardLabel,
+    reorderBoardLabels,
     assignLabelToCard,
     unassignLabelFromCard,
     addAttachment,
     removeAttachment,
+    reorderAttachments,
   } = useBoardStore();
 
   useEffect(() => {
@@ -46,7 +48,7 @@ export const useSSE = (shareToken: string | null) => {
 
       switch (event.type) {
         case "board_updated": {
-          const board = event.board as Board;
+          const board = event.board as Partial<Board>;
           updateBoard(board);
           break;
         }
@@ -75,6 +77,18 @@ export const useSSE = (shareToken: string | null) => {
           break;
         }
 
+        case "column_archived": {
+          // Archived columns and their cards are hidden from the board
+          deleteColumn(event.column_id);
+          break;
+        }
+
+        case "column_restored": {
+          const column = event.column as Column;
+          addColumn(column);
+          break;
+        }
+
         case "card_created": {
           const card = event.card as Card;
           addCard(card);
@@ -118,6 +132,12 @@ export const useSSE = (shareToken: string | null) => {
           break;
         }
 
+        case "column_cards_archived": {
+          // Archived cards are hidden from the board
+          event.card_ids.forEach((card_id) => deleteCard(card_id));
+          break;
+        }
+
         case "board_label_created": {
           const label = event.label as BoardLabel;
           addBoardLabel(label);
@@ -136,6 +156,12 @@ export const useSSE = (shareToken: string | null) => {
           break;
         }
 
+        case "labels_reordered": {
+          const { label_ids } = event;
+          reorderBoardLabels(label_ids);
+          break;
+        }
+
         case "card_label_assigned": {
           const { card_id, label } = event;
           const boardLabel = label as BoardLabel;
@@ -162,6 +188,20 @@ export const useSSE = (shareToken: string | null) => {
           break;
         }
 
+        case "attachments_deleted": {
+          const { card_id, attachment_ids } = event;
+          attachment_ids.forEach((attachmentId) =>
+            removeAttachment(card_id, attachmentId)
+          );
+          break;
+        }
+
+        case "attachments_reordered": {
+          const { card_id, attachment_ids } = event;
+          reorderAttachments(card_id, attachment_ids);
+          break;
+        }
+
         default:
           console.warn("[useSSE] Unknown event type:", event);
       }
@@ -201,10 +241,12 @@ export const useSSE = (shareToken: string | null) => {
     addBoardLabel,
     updateBoardLabel,
     deleteBoardLabel,
+    reorderBoardLabels,
     assignLabelToCard,
     unassignLabelFromCard,
     addAttachment,
     removeAttachment,
+    reorderAttachments,
   ]);
 
   return {
@@ -5,9 +5,16 @@ import axios, {
 } from "axios";
 import type {
   Board,
+  BoardSummary,
+  CreatedBoard,
   Column,
   Card,
   BoardLabel,
+  BoardLabelUsage,
+  CountMetrics,
+  CycleTimeMetrics,
+  Notification,
+  ScoredCard,
   SetLockStateRequest,
 } from "./types";
 import { getBoardPassword } from "./board-passwords";
@@ -99,13 +106,29 @@ function getHeadersWithPassword(shareToken?: string): Record<string, string> {
 }
 
 // Board API endpoints
-export const createBoard = async (name: string): Promise<Board> => {
-  const response = await api.post<Board>("/boards", { title: name });
+export const createBoard = async (name: string): Promise<CreatedBoard> => {
+  const response = await api.post<CreatedBoard>("/boards", { title: name });
   return response.data;
 };
 
-export const getBoard = async (shareToken: string): Promise<Board> => {
-  const response = await api.get<Board>(`/boards/share/${shareToken}`);
+// Archived columns and their cards are left out unless includeArchived is set
+export const getBoard = async (
+  shareToken: string,
+  includeArchived = false
+): Promise<Board> => {
+  const response = await api.get<Board>(`/boards/share/${shareToken}`, {
+    params: includeArchived ? { include_archived: true } : undefined,
+  });
+  return response.data;
+};
+
+// Summaries for up to 100 boards in one request; unknown IDs are left out
+export const getBoardSummaries = async (
+  ids: string[]
+): Promise<BoardSummary[]> => {
+  const response = await api.post<BoardSummary[]>("/boards/summaries", {
+    ids,
+  });
   return response.data;
 };
 
@@ -121,19 +144,43 @@ export const updateBoardName = async (
   return response.data;
 };
 
+// `version` is the board's updated_at as last seen; a stale version fails with 409
 export const setBoardLockState = async (
   shareToken: string,
   password: string,
-  isLocked: boolean
+  isLocked: boolean,
+  version: string
 ): Promise<Board> => {
-  const response = await api.post<Board>(`/boards/share/${shareToken}/lock`, {
-    password,
-    is_locked: isLocked,
-  } as SetLockStateRequest);
+  const response = await api.post<Board>(
+    `/boards/share/${shareToken}/lock`,
+    {
+      password,
+      is_locked: isLocked,
+    } as SetLockStateRequest,
+    { headers: { "If-Match": `"${version}"` } }
+  );
   return response.data;
 };
 
 // Column API endpoints
+export const getCycleTimeMetrics = async (
+  boardId: string
+): Promise<CycleTimeMetrics> => {
+  const response = await api.get<CycleTimeMetrics>(
+    `/boards/${boardId}/metrics/cycle-time`
+  );
+  return response.data;
+};
+
+export const getCountMetrics = async (
+  boardId: string
+): Promise<CountMetrics> => {
+  const response = await api.get<CountMetrics>(
+    `/boards/${boardId}/metrics/counts`
+  );
+  return response.data;
+};
+
 export const createColumn = async (
   boardId: string,
   title: string,
@@ -168,6 +215,32 @@ export const deleteColumn = async (
   });
 };
 
+// Hides the column and its cards; the cards themselves are kept
+export const archiveColumn = async (
+  columnId: string,
+  shareToken?: string
+): Promise<Column> => {
+  const response = await api.post<Column>(
+    `/columns/${columnId}/archive`,
+    undefined,
+    { headers: getHeadersWithPassword(shareToken) }
+  );
+  return response.data;
+};
+
+// Returns the column with its cards
+export const restoreColumn = async (
+  columnId: string,
+  shareToken?: string
+): Promise<Column> => {
+  const response = await api.post<Column>(
+    `/columns/${columnId}/restore`,
+    undefined,
+    { headers: getHeadersWithPassword(shareToken) }
+  );
+  return response.data;
+};
+
 export const reorderColumns = async (
   boardId: string,
   columnPositions: Array<[string, number]>,
@@ -195,6 +268,19 @@ export const createCard = async (
   return response.data;
 };
 
+export const createCardFromTemplate = async (
+  columnId: string,
+  templateId: string,
+  shareToken?: string
+): Promise<Card> => {
+  const response = await api.post<Card>(
+    `/columns/${columnId}/cards/from-template/${templateId}`,
+    undefined,
+    { headers: getHeadersWithPassword(shareToken) }
+  );
+  return response.data;
+};
+
 export const reorderCards = async (
   columnId: string,
   cardPositions: Array<[string, number]>,
@@ -207,6 +293,18 @@ export const reorderCards = async (
   );
 };
 
+export const archiveColumnCards = async (
+  columnId: string,
+  shareToken?: string
+): Promise<{ column_id: string; card_ids: string[] }> => {
+  const response = await api.post<{ column_id: string; card_ids: string[] }>(
+    `/columns/${columnId}/archive-cards`,
+    {},
+    { headers: getHeadersWithPassword(shareToken) }
+  );
+  return response.data;
+};
+
 export const updateCard = async (
   cardId: string,
   updates: Partial<
@@ -234,6 +332,19 @@ export const moveCard = async (
   return response.data;
 };
 
+export const mergeCards = async (
+  targetCardId: string,
+  sourceCardId: string,
+  shareToken?: string
+): Promise<Card> => {
+  const response = await api.post<Card>(
+    `/cards/${targetCardId}/merge/${sourceCardId}`,
+    undefined,
+    { headers: getHeadersWithPassword(shareToken) }
+  );
+  return response.data;
+};
+
 export const deleteCard = async (
   cardId: string,
   shareToken?: string
@@ -243,6 +354,52 @@ export const deleteCard = async (
   });
 };
 
+// Watching requires a signed-in user; changes arrive on GET /me/events
+export const watchCard = async (cardId: string): Promise<void> => {
+  await api.post(`/cards/${cardId}/watch`);
+};
+
+export const unwatchCard = async (cardId: string): Promise<void> => {
+  await api.delete(`/cards/${cardId}/watch`);
+};
+
+// Newest first, at most 100
+export const getNotifications = async (
+  unreadOnly = false
+): Promise<Notification[]> => {
+  const response = await api.get<Notification[]>("/me/notifications", {
+    params: unreadOnly ? { unread: true } : undefined,
+  });
+  return response.data;
+};
+
+export const markNotificationRead = async (
+  notificationId: string
+): Promise<Notification> => {
+  const response = await api.post<Notification>(
+    `/me/notifications/${notificationId}/read`
+  );
+  return response.data;
+};
+
+// Cards carrying any of `labelIds` rank higher
+export const searchCards = async (
+  boardId: string,
+  query: string,
+  labelIds: string[] = []
+): Promise<ScoredCard[]> => {
+  const response = await api.get<ScoredCard[]>(
+    `/boards/${boardId}/cards/search`,
+    {
+      params: {
+        q: query,
+        label_ids: labelIds.length > 0 ? labelIds.join(",") : undefined,
+      },
+    }
+  );
+  return response.data;
+};
+
 // Board Label API endpoints
 export const getBoardLabels = async (
   boardId: string,
@@ -254,6 +411,19 @@ export const getBoardLabels = async (
   return response.data;
 };
 
+export const getBoardLabelUsage = async (
+  boardId: string,
+  shareToken?: string
+): Promise<BoardLabelUsage[]> => {
+  const response = await api.get<BoardLabelUsage[]>(
+    `/boards/${boardId}/labels/usage`,
+    {
+      headers: getHeadersWithPassword(shareToken),
+    }
+  );
+  return response.data;
+};
+
 export const createBoardLabel = async (
   boardId: string,
   name: string,
@@ -297,6 +467,19 @@ export const deleteBoardLabel = async (
   });
 };
 
+export const reorderBoardLabels = async (
+  boardId: string,
+  labelPositions: Array<[string, number]>,
+  shareToken?: string
+): Promise<BoardLabel[]> => {
+  const response = await api.patch<BoardLabel[]>(
+    `/boards/${boardId}/labels/reorder`,
+    { label_positions: labelPositions },
+    { headers: getHeadersWithPassword(shareToken) }
+  );
+  return response.data;
+};
+
 // Card Label Assignment endpoints
 export const assignLabelToCard = async (
   cardId: string,
@@ -354,12 +537,20 @@ export const deleteLabel = async (
 };
 
 // AI generation endpoints
-export type DescriptionFormat = "bullets" | "long";
+/** Custom templates use `{title}` and `{context}` placeholders */
+export type DescriptionFormat =
+  | "bullets"
+  | "long"
+  | { custom: { template: string } };
 
 export interface GenerateDescriptionRequest {
   title: string;
   context?: string;
   format: DescriptionFormat;
+  /** Overrides the server's configured persona for this request */
+  persona?: string;
+  /** Board the card belongs to, so its `ai_enabled` setting is honored */
+  board_id?: string;
 }
 
 export interface GenerateDescriptionResponse {
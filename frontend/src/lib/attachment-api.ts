@@ -130,6 +130,30 @@ export async function getCardAttachments(
   return response.data;
 }
 
+/**
+ * Reorder a card's attachments
+ */
+export async function reorderAttachments(
+  cardId: string,
+  attachmentPositions: [string, number][],
+  shareToken?: string
+): Promise<CardAttachment[]> {
+  const headers: Record<string, string> = {};
+  if (shareToken) {
+    const password = getBoardPassword(shareToken);
+    if (password) {
+      headers["X-Board-Password"] = password;
+    }
+  }
+
+  const response = await api.patch<CardAttachment[]>(
+    `/cards/${cardId}/attachments/reorder`,
+    { attachment_positions: attachmentPositions },
+    { headers }
+  );
+  return response.data;
+}
+
 /**
  * Get download URL for an attachment
  */
@@ -168,3 +192,21 @@ export async function deleteAttachment(
 
   await api.delete(`/attachments/${attachmentId}`, { headers });
 }
+
+/**
+ * Delete all attachments of a card (every one must be the user's own)
+ */
+export async function deleteCardAttachments(
+  cardId: string,
+  shareToken?: string
+): Promise<void> {
+  const headers: Record<string, string> = {};
+  if (shareToken) {
+    const password = getBoardPassword(shareToken);
+    if (password) {
+      headers["X-Board-Password"] = password;
+    }
+  }
+
+  await api.delete(`/cards/${cardId}/attachments`, { headers });
+}
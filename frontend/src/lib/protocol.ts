@@ -1,9 +1,10 @@
 /**
  * Binary protocol for WebSocket presence communication.
  *
- * This module implements a highly efficient binary protocol that achieves 5-8 byte
- * message sizes for cursor updates (96% reduction vs JSON). All multi-byte integers
- * use big-endian byte order to match the Rust backend implementation.
+ * This module implements a highly efficient binary protocol that achieves 9 byte
+ * cursor updates, or 8 with 12-bit coordinates. All multi-byte integers use big-endian byte
+ * order to match the Rust backend implementation. Board ids are u32 (protocol v7);
+ * the server closes connections that still send the older u16 layout.
  */
 
 // Message type constants (must match Rust implementation)
@@ -15,9 +16,33 @@ export const MSG_USER_JOINED = 0x05;
 export const MSG_USER_LEFT = 0x06;
 export const MSG_PRESENCE_UPDATE = 0x07;
 export const MSG_HEARTBEAT = 0x08;
+export const MSG_FOCUS_CARD = 0x0B;
+export const MSG_FOCUS_BROADCAST = 0x0C;
+export const MSG_QUERY_PRESENCE = 0x11;
+export const MSG_CURSOR_UPDATE_COMPACT = 0x13;
+export const MSG_CURSOR_BATCH = 0x14;
+export const MSG_CURSOR_BROADCAST_BATCH = 0x15;
+export const MSG_ACTIVITY_UPDATE = 0x16;
+export const MSG_ACTIVITY_BROADCAST = 0x17;
+export const MSG_CLAIM_CARD = 0x18;
+export const MSG_RELEASE_CARD = 0x19;
+export const MSG_CARD_CLAIMED = 0x1a;
+export const MSG_CARD_RELEASED = 0x1b;
+export const MSG_ERROR = 0x1c;
 
 // Protocol constants
 export const MAX_USERNAME_LENGTH = 32;
+/** Length of the reconnect token a join may carry */
+export const RECONNECT_TOKEN_LENGTH = 16;
+/** Most points a cursor batch may carry */
+export const MAX_CURSOR_BATCH_POINTS = 255;
+/** Card slot value meaning no card is focused */
+export const NO_CARD_FOCUSED = 0;
+
+// Codes carried by an error message (protocol v11+)
+export const ERROR_UNKNOWN_MESSAGE_TYPE = 1;
+export const ERROR_INVALID_LENGTH = 2;
+export const ERROR_USERNAME_TOO_LONG = 3;
 
 /**
  * Binary message types
@@ -31,7 +56,12 @@ export type BinaryMessage =
       x: number;
       y: number;
     }
-  | { type: "join"; boardId: number; username: string }
+  | {
+      type: "join";
+      boardId: number;
+      username: string;
+      reconnectToken?: Uint8Array;
+    }
   | { type: "leave"; boardId: number }
   | {
       type: "user_joined";
@@ -42,7 +72,40 @@ export type BinaryMessage =
     }
   | { type: "user_left"; boardId: number; userId: number }
   | { type: "presence_update"; boardId: number; count: number }
-  | { type: "heartbeat" };
+  | { type: "heartbeat" }
+  | { type: "focus_card"; boardId: number; cardSlot: number }
+  | {
+      type: "focus_broadcast";
+      boardId: number;
+      userId: number;
+      cardSlot: number;
+    }
+  | {
+      type: "cursor_broadcast_batch";
+      boardId: number;
+      userId: number;
+      points: [number, number][];
+    }
+  | {
+      type: "activity_broadcast";
+      boardId: number;
+      userId: number;
+      cardRef: number;
+      active: boolean;
+    }
+  | {
+      type: "card_claimed";
+      boardId: number;
+      userId: number;
+      cardSlot: number;
+    }
+  | {
+      type: "card_released";
+      boardId: number;
+      userId: number;
+      cardSlot: number;
+    }
+  | { type: "error"; code: number };
 
 /**
  * Protocol errors
@@ -81,16 +144,41 @@ export function denormalizeCoord(coord: number): number {
   return coord / 65535;
 }
 
+/**
+ * Normalize a floating-point coordinate (0.0-1.0) to a 12-bit unsigned integer (0-4095).
+ *
+ * Used by compact cursor updates; one step is 1/4096 of the board instead of 1/65536.
+ *
+ * @param coord - A floating-point coordinate in the range [0.0, 1.0]
+ * @returns A 12-bit unsigned integer in the range [0, 4095]
+ */
+export function normalizeCoord12bit(coord: number): number {
+  const clamped = Math.max(0, Math.min(1, coord));
+  return Math.floor(clamped * 4095);
+}
+
+/**
+ * Denormalize a 12-bit unsigned integer (0-4095) to a floating-point coordinate (0.0-1.0).
+ *
+ * This is the inverse operation of normalizeCoord12bit.
+ *
+ * @param coord - A 12-bit unsigned integer in the range [0, 4095]
+ * @returns A floating-point coordinate in the range [0.0, 1.0]
+ */
+export function denormalizeCoord12bit(coord: number): number {
+  return Math.min(coord, 4095) / 4095;
+}
+
 /**
  * Encode a cursor update message.
  *
- * Layout (7 bytes):
+ * Layout (9 bytes):
  * - byte 0: message type (0x01)
- * - bytes 1-2: board_id (u16, big-endian)
- * - bytes 3-4: x coordinate (u16, big-endian, normalized 0-65535)
- * - bytes 5-6: y coordinate (u16, big-endian, normalized 0-65535)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - bytes 5-6: x coordinate (u16, big-endian, normalized 0-65535)
+ * - bytes 7-8: y coordinate (u16, big-endian, normalized 0-65535)
  *
- * @param boardId - The board ID (0-65535)
+ * @param boardId - The board ID (0-4294967295)
  * @param x - X coordinate (0.0-1.0)
  * @param y - Y coordinate (0.0-1.0)
  * @returns Encoded message as Uint8Array
@@ -100,13 +188,88 @@ export function encodeCursorUpdate(
   x: number,
   y: number
 ): Uint8Array {
-  const buffer = new ArrayBuffer(7);
+  const buffer = new ArrayBuffer(9);
   const view = new DataView(buffer);
 
   view.setUint8(0, MSG_CURSOR_UPDATE);
-  view.setUint16(1, boardId, false); // false = big-endian
-  view.setUint16(3, normalizeCoord(x), false);
-  view.setUint16(5, normalizeCoord(y), false);
+  view.setUint32(1, boardId, false); // false = big-endian
+  view.setUint16(5, normalizeCoord(x), false);
+  view.setUint16(7, normalizeCoord(y), false);
+
+  return new Uint8Array(buffer);
+}
+
+/**
+ * Encode a compact cursor update message (protocol v8+).
+ *
+ * For boards that opt into low-precision cursors. Only send it to a server
+ * that answered a Hello with version 8 or later.
+ *
+ * Layout (8 bytes):
+ * - byte 0: message type (0x13)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - bytes 5-7: x in the high 12 bits, y in the low 12 bits (normalized 0-4095)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @param x - X coordinate (0.0-1.0)
+ * @param y - Y coordinate (0.0-1.0)
+ * @returns Encoded message as Uint8Array
+ */
+export function encodeCursorUpdateCompact(
+  boardId: number,
+  x: number,
+  y: number
+): Uint8Array {
+  const buffer = new ArrayBuffer(8);
+  const view = new DataView(buffer);
+  const packed = (normalizeCoord12bit(x) << 12) | normalizeCoord12bit(y);
+
+  view.setUint8(0, MSG_CURSOR_UPDATE_COMPACT);
+  view.setUint32(1, boardId, false); // false = big-endian
+  view.setUint8(5, packed >>> 16);
+  view.setUint16(6, packed & 0xffff, false);
+
+  return new Uint8Array(buffer);
+}
+
+/**
+ * Encode a batch of cursor positions (protocol v9+).
+ *
+ * For fast drawing: send the points sampled since the last frame at once.
+ * The last point becomes the user's cursor. Only send it to a server that
+ * answered a Hello with version 9 or later.
+ *
+ * Layout (6 + 4 per point bytes):
+ * - byte 0: message type (0x14)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - byte 5: point count (1-255)
+ * - bytes 6+: per point, x then y (u16 each, big-endian, normalized 0-65535)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @param points - [x, y] coordinates (0.0-1.0), oldest first
+ * @returns Encoded message as Uint8Array
+ * @throws ProtocolError if there are no points or more than 255
+ */
+export function encodeCursorBatch(
+  boardId: number,
+  points: [number, number][]
+): Uint8Array {
+  if (points.length === 0 || points.length > MAX_CURSOR_BATCH_POINTS) {
+    throw new ProtocolError(
+      `Cursor batch must hold 1 to ${MAX_CURSOR_BATCH_POINTS} points, got ${points.length}`
+    );
+  }
+
+  const buffer = new ArrayBuffer(6 + 4 * points.length);
+  const view = new DataView(buffer);
+
+  view.setUint8(0, MSG_CURSOR_BATCH);
+  view.setUint32(1, boardId, false); // false = big-endian
+  view.setUint8(5, points.length);
+  points.forEach(([x, y], i) => {
+    view.setUint16(6 + 4 * i, normalizeCoord(x), false);
+    view.setUint16(8 + 4 * i, normalizeCoord(y), false);
+  });
 
   return new Uint8Array(buffer);
 }
@@ -114,17 +277,26 @@ export function encodeCursorUpdate(
 /**
  * Encode a join message.
  *
- * Layout (4-36 bytes):
+ * A client that sends the same reconnect token after a dropped connection
+ * gets back its user ID and color if it rejoins within a few seconds.
+ *
+ * Layout (6-54 bytes):
  * - byte 0: message type (0x03)
- * - bytes 1-2: board_id (u16, big-endian)
- * - byte 3: username length (u8)
- * - bytes 4+: username UTF-8 bytes (max 32 bytes)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - byte 5: username length (u8)
+ * - bytes 6+: username UTF-8 bytes (max 32 bytes)
+ * - then, optionally: reconnect token (16 bytes)
  *
- * @param boardId - The board ID (0-65535)
+ * @param boardId - The board ID (0-4294967295)
  * @param username - Username string (max 32 bytes UTF-8)
+ * @param reconnectToken - Optional token kept across reconnects (16 bytes)
  * @returns Encoded message as Uint8Array
  */
-export function encodeJoin(boardId: number, username: string): Uint8Array {
+export function encodeJoin(
+  boardId: number,
+  username: string,
+  reconnectToken?: Uint8Array
+): Uint8Array {
   // Encode username to UTF-8
   const encoder = new TextEncoder();
   const usernameBytes = encoder.encode(username);
@@ -136,16 +308,26 @@ export function encodeJoin(boardId: number, username: string): Uint8Array {
     );
   }
 
-  const buffer = new ArrayBuffer(4 + usernameBytes.length);
+  if (reconnectToken && reconnectToken.length !== RECONNECT_TOKEN_LENGTH) {
+    throw new ProtocolError(
+      `Reconnect token must be ${RECONNECT_TOKEN_LENGTH} bytes, got ${reconnectToken.length}`
+    );
+  }
+
+  const tokenLength = reconnectToken ? RECONNECT_TOKEN_LENGTH : 0;
+  const buffer = new ArrayBuffer(6 + usernameBytes.length + tokenLength);
   const view = new DataView(buffer);
 
   view.setUint8(0, MSG_JOIN);
-  view.setUint16(1, boardId, false);
-  view.setUint8(3, usernameBytes.length);
+  view.setUint32(1, boardId, false);
+  view.setUint8(5, usernameBytes.length);
 
   // Copy username bytes
   const uint8Array = new Uint8Array(buffer);
-  uint8Array.set(usernameBytes, 4);
+  uint8Array.set(usernameBytes, 6);
+  if (reconnectToken) {
+    uint8Array.set(reconnectToken, 6 + usernameBytes.length);
+  }
 
   return uint8Array;
 }
@@ -153,19 +335,19 @@ export function encodeJoin(boardId: number, username: string): Uint8Array {
 /**
  * Encode a leave message.
  *
- * Layout (3 bytes):
+ * Layout (5 bytes):
  * - byte 0: message type (0x04)
- * - bytes 1-2: board_id (u16, big-endian)
+ * - bytes 1-4: board_id (u32, big-endian)
  *
- * @param boardId - The board ID (0-65535)
+ * @param boardId - The board ID (0-4294967295)
  * @returns Encoded message as Uint8Array
  */
 export function encodeLeave(boardId: number): Uint8Array {
-  const buffer = new ArrayBuffer(3);
+  const buffer = new ArrayBuffer(5);
   const view = new DataView(buffer);
 
   view.setUint8(0, MSG_LEAVE);
-  view.setUint16(1, boardId, false);
+  view.setUint32(1, boardId, false);
 
   return new Uint8Array(buffer);
 }
@@ -182,6 +364,144 @@ export function encodeHeartbeat(): Uint8Array {
   return new Uint8Array([MSG_HEARTBEAT]);
 }
 
+/**
+ * Encode a focus card message.
+ *
+ * Layout (7 bytes):
+ * - byte 0: message type (0x0B)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - bytes 5-6: card_slot (u16, big-endian, 0 = no card focused)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @param cardSlot - The focused card's slot, or NO_CARD_FOCUSED
+ * @returns Encoded message as Uint8Array
+ */
+export function encodeFocusCard(
+  boardId: number,
+  cardSlot: number
+): Uint8Array {
+  const buffer = new ArrayBuffer(7);
+  const view = new DataView(buffer);
+
+  view.setUint8(0, MSG_FOCUS_CARD);
+  view.setUint32(1, boardId, false);
+  view.setUint16(5, cardSlot, false);
+
+  return new Uint8Array(buffer);
+}
+
+/**
+ * Encode an activity update message.
+ *
+ * Tells the room the user started or stopped editing a card. `cardRef` is
+ * any id every client derives the same way, e.g. a hash of the card UUID.
+ *
+ * Layout (10 bytes):
+ * - byte 0: message type (0x16)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - bytes 5-8: card_ref (u32, big-endian)
+ * - byte 9: active (1 = editing, 0 = stopped)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @param cardRef - The card's client-assigned id (0-4294967295)
+ * @param active - Whether the user is editing the card
+ * @returns Encoded message as Uint8Array
+ */
+export function encodeActivityUpdate(
+  boardId: number,
+  cardRef: number,
+  active: boolean
+): Uint8Array {
+  const buffer = new ArrayBuffer(10);
+  const view = new DataView(buffer);
+
+  view.setUint8(0, MSG_ACTIVITY_UPDATE);
+  view.setUint32(1, boardId, false);
+  view.setUint32(5, cardRef, false);
+  view.setUint8(9, active ? 1 : 0);
+
+  return new Uint8Array(buffer);
+}
+
+/**
+ * Encode a claim card message.
+ *
+ * Asks for the soft lock on a card before editing it. The server answers
+ * with a card claimed message naming the holder: this user if the claim was
+ * granted, otherwise whoever already holds it.
+ *
+ * Layout (7 bytes):
+ * - byte 0: message type (0x18)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - bytes 5-6: card_slot (u16, big-endian, never 0)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @param cardSlot - The card's slot (1-65535)
+ * @returns Encoded message as Uint8Array
+ */
+export function encodeClaimCard(
+  boardId: number,
+  cardSlot: number
+): Uint8Array {
+  return encodeCardSlotMessage(MSG_CLAIM_CARD, boardId, cardSlot);
+}
+
+/**
+ * Encode a release card message.
+ *
+ * Layout (7 bytes):
+ * - byte 0: message type (0x19)
+ * - bytes 1-4: board_id (u32, big-endian)
+ * - bytes 5-6: card_slot (u16, big-endian)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @param cardSlot - The slot of a card this user holds
+ * @returns Encoded message as Uint8Array
+ */
+export function encodeReleaseCard(
+  boardId: number,
+  cardSlot: number
+): Uint8Array {
+  return encodeCardSlotMessage(MSG_RELEASE_CARD, boardId, cardSlot);
+}
+
+function encodeCardSlotMessage(
+  msgType: number,
+  boardId: number,
+  cardSlot: number
+): Uint8Array {
+  const buffer = new ArrayBuffer(7);
+  const view = new DataView(buffer);
+
+  view.setUint8(0, msgType);
+  view.setUint32(1, boardId, false);
+  view.setUint16(5, cardSlot, false);
+
+  return new Uint8Array(buffer);
+}
+
+/**
+ * Encode a query presence message.
+ *
+ * The server answers with a presence update for the board without joining it.
+ *
+ * Layout (5 bytes):
+ * - byte 0: message type (0x11)
+ * - bytes 1-4: board_id (u32, big-endian)
+ *
+ * @param boardId - The board ID (0-4294967295)
+ * @returns Encoded message as Uint8Array
+ */
+export function encodeQueryPresence(boardId: number): Uint8Array {
+  const buffer = new ArrayBuffer(5);
+  const view = new DataView(buffer);
+
+  view.setUint8(0, MSG_QUERY_PRESENCE);
+  view.setUint32(1, boardId, false);
+
+  return new Uint8Array(buffer);
+}
+
 /**
  * Decode a binary message from a byte array.
  *
@@ -199,45 +519,45 @@ export function decodeMessage(data: Uint8Array): BinaryMessage {
 
   switch (msgType) {
     case MSG_CURSOR_UPDATE: {
-      if (data.length !== 7) {
+      if (data.length !== 9) {
         throw new ProtocolError(
-          `Invalid length for cursor_update: expected 7, got ${data.length}`
+          `Invalid length for cursor_update: expected 9, got ${data.length}`
         );
       }
 
       return {
         type: "cursor_update",
-        boardId: view.getUint16(1, false),
-        x: denormalizeCoord(view.getUint16(3, false)),
-        y: denormalizeCoord(view.getUint16(5, false)),
+        boardId: view.getUint32(1, false),
+        x: denormalizeCoord(view.getUint16(5, false)),
+        y: denormalizeCoord(view.getUint16(7, false)),
       };
     }
 
     case MSG_CURSOR_BROADCAST: {
-      if (data.length !== 8) {
+      if (data.length !== 10) {
         throw new ProtocolError(
-          `Invalid length for cursor_broadcast: expected 8, got ${data.length}`
+          `Invalid length for cursor_broadcast: expected 10, got ${data.length}`
         );
       }
 
       return {
         type: "cursor_broadcast",
-        boardId: view.getUint16(1, false),
-        userId: view.getUint8(3),
-        x: denormalizeCoord(view.getUint16(4, false)),
-        y: denormalizeCoord(view.getUint16(6, false)),
+        boardId: view.getUint32(1, false),
+        userId: view.getUint8(5),
+        x: denormalizeCoord(view.getUint16(6, false)),
+        y: denormalizeCoord(view.getUint16(8, false)),
       };
     }
 
     case MSG_JOIN: {
-      if (data.length < 4) {
+      if (data.length < 6) {
         throw new ProtocolError(
-          `Invalid length for join: expected at least 4, got ${data.length}`
+          `Invalid length for join: expected at least 6, got ${data.length}`
         );
       }
 
-      const boardId = view.getUint16(1, false);
-      const usernameLength = view.getUint8(3);
+      const boardId = view.getUint32(1, false);
+      const usernameLength = view.getUint8(5);
 
       if (usernameLength > MAX_USERNAME_LENGTH) {
         throw new ProtocolError(
@@ -245,15 +565,17 @@ export function decodeMessage(data: Uint8Array): BinaryMessage {
         );
       }
 
-      if (data.length !== 4 + usernameLength) {
+      const usernameEnd = 6 + usernameLength;
+      if (
+        data.length !== usernameEnd &&
+        data.length !== usernameEnd + RECONNECT_TOKEN_LENGTH
+      ) {
         throw new ProtocolError(
-          `Invalid length for join: expected ${4 + usernameLength}, got ${
-            data.length
-          }`
+          `Invalid length for join: expected ${usernameEnd}, got ${data.length}`
         );
       }
 
-      const usernameBytes = data.slice(4, 4 + usernameLength);
+      const usernameBytes = data.slice(6, usernameEnd);
       const decoder = new TextDecoder();
       const username = decoder.decode(usernameBytes);
 
@@ -261,32 +583,35 @@ export function decodeMessage(data: Uint8Array): BinaryMessage {
         type: "join",
         boardId,
         username,
+        ...(data.length > usernameEnd && {
+          reconnectToken: data.slice(usernameEnd),
+        }),
       };
     }
 
     case MSG_LEAVE: {
-      if (data.length !== 3) {
+      if (data.length !== 5) {
         throw new ProtocolError(
-          `Invalid length for leave: expected 3, got ${data.length}`
+          `Invalid length for leave: expected 5, got ${data.length}`
         );
       }
 
       return {
         type: "leave",
-        boardId: view.getUint16(1, false),
+        boardId: view.getUint32(1, false),
       };
     }
 
     case MSG_USER_JOINED: {
-      if (data.length < 8) {
+      if (data.length < 10) {
         throw new ProtocolError(
-          `Invalid length for user_joined: expected at least 8, got ${data.length}`
+          `Invalid length for user_joined: expected at least 10, got ${data.length}`
         );
       }
 
-      const boardId = view.getUint16(1, false);
-      const userId = view.getUint8(3);
-      const usernameLength = view.getUint8(4);
+      const boardId = view.getUint32(1, false);
+      const userId = view.getUint8(5);
+      const usernameLength = view.getUint8(6);
 
       if (usernameLength > MAX_USERNAME_LENGTH) {
         throw new ProtocolError(
@@ -294,19 +619,19 @@ export function decodeMessage(data: Uint8Array): BinaryMessage {
         );
       }
 
-      if (data.length !== 8 + usernameLength) {
+      if (data.length !== 10 + usernameLength) {
         throw new ProtocolError(
           `Invalid length for user_joined: expected ${
-            8 + usernameLength
+            10 + usernameLength
           }, got ${data.length}`
         );
       }
 
-      const usernameBytes = data.slice(5, 5 + usernameLength);
+      const usernameBytes = data.slice(7, 7 + usernameLength);
       const decoder = new TextDecoder();
       const username = decoder.decode(usernameBytes);
 
-      const colorOffset = 5 + usernameLength;
+      const colorOffset = 7 + usernameLength;
       const color: [number, number, number] = [
         view.getUint8(colorOffset),
         view.getUint8(colorOffset + 1),
@@ -323,30 +648,30 @@ export function decodeMessage(data: Uint8Array): BinaryMessage {
     }
 
     case MSG_USER_LEFT: {
-      if (data.length !== 4) {
+      if (data.length !== 6) {
         throw new ProtocolError(
-          `Invalid length for user_left: expected 4, got ${data.length}`
+          `Invalid length for user_left: expected 6, got ${data.length}`
         );
       }
 
       return {
         type: "user_left",
-        boardId: view.getUint16(1, false),
-        userId: view.getUint8(3),
+        boardId: view.getUint32(1, false),
+        userId: view.getUint8(5),
       };
     }
 
     case MSG_PRESENCE_UPDATE: {
-      if (data.length !== 4) {
+      if (data.length !== 7) {
         throw new ProtocolError(
-          `Invalid length for presence_update: expected 4, got ${data.length}`
+          `Invalid length for presence_update: expected 7, got ${data.length}`
         );
       }
 
       return {
         type: "presence_update",
-        boardId: view.getUint16(1, false),
-        count: view.getUint8(3),
+        boardId: view.getUint32(1, false),
+        count: view.getUint16(5, false),
       };
     }
 
@@ -362,6 +687,102 @@ export function decodeMessage(data: Uint8Array): BinaryMessage {
       };
     }
 
+    case MSG_FOCUS_BROADCAST: {
+      if (data.length !== 8) {
+        throw new ProtocolError(
+          `Invalid length for focus_broadcast: expected 8, got ${data.length}`
+        );
+      }
+
+      return {
+        type: "focus_broadcast",
+        boardId: view.getUint32(1, false),
+        userId: view.getUint8(5),
+        cardSlot: view.getUint16(6, false),
+      };
+    }
+
+    case MSG_CURSOR_BROADCAST_BATCH: {
+      if (data.length < 11) {
+        throw new ProtocolError(
+          `Invalid length for cursor_broadcast_batch: expected at least 11, got ${data.length}`
+        );
+      }
+
+      const count = view.getUint8(6);
+      if (count === 0 || data.length !== 7 + 4 * count) {
+        throw new ProtocolError(
+          `Invalid length for cursor_broadcast_batch: expected ${
+            7 + 4 * count
+          }, got ${data.length}`
+        );
+      }
+
+      const points: [number, number][] = [];
+      for (let i = 0; i < count; i++) {
+        points.push([
+          denormalizeCoord(view.getUint16(7 + 4 * i, false)),
+          denormalizeCoord(view.getUint16(9 + 4 * i, false)),
+        ]);
+      }
+
+      return {
+        type: "cursor_broadcast_batch",
+        boardId: view.getUint32(1, false),
+        userId: view.getUint8(5),
+        points,
+      };
+    }
+
+    case MSG_ACTIVITY_BROADCAST: {
+      if (data.length !== 11) {
+        throw new ProtocolError(
+          `Invalid length for activity_broadcast: expected 11, got ${data.length}`
+        );
+      }
+
+      const active = view.getUint8(10);
+      if (active > 1) {
+        throw new ProtocolError(`Invalid activity flag: ${active}`);
+      }
+
+      return {
+        type: "activity_broadcast",
+        boardId: view.getUint32(1, false),
+        userId: view.getUint8(5),
+        cardRef: view.getUint32(6, false),
+        active: active === 1,
+      };
+    }
+
+    case MSG_CARD_CLAIMED:
+    case MSG_CARD_RELEASED: {
+      const type =
+        msgType === MSG_CARD_CLAIMED ? "card_claimed" : "card_released";
+      if (data.length !== 8) {
+        throw new ProtocolError(
+          `Invalid length for ${type}: expected 8, got ${data.length}`
+        );
+      }
+
+      return {
+        type,
+        boardId: view.getUint32(1, false),
+        userId: view.getUint8(5),
+        cardSlot: view.getUint16(6, false),
+      };
+    }
+
+    case MSG_ERROR: {
+      if (data.length !== 2) {
+        throw new ProtocolError(
+          `Invalid length for error: expected 2, got ${data.length}`
+        );
+      }
+
+      return { type: "error", code: view.getUint8(1) };
+    }
+
     default:
       throw new ProtocolError(
         `Unknown message type: 0x${msgType.toString(16)}`
@@ -9,25 +9,32 @@ export type SSEEventType =
   | "column:updated"
   | "column:deleted"
   | "column:reordered"
+  | "column:archived"
+  | "column:restored"
   | "card:created"
   | "card:updated"
   | "card:deleted"
   | "card:moved"
   | "card:reordered"
+  | "column:cards_archived"
   | "board_label:created"
   | "board_label:updated"
   | "board_label:deleted"
+  | "board_label:reordered"
   | "card_label:assigned"
   | "card_label:unassigned"
   | "attachment:created"
-  | "attachment:deleted";
+  | "attachment:deleted"
+  | "attachment:bulk_deleted"
+  | "attachment:reordered";
 
 /**
  * SSE event data structures matching backend event payloads
  */
 export interface SSEBoardUpdatedEvent {
   type: "board_updated";
-  board: Board;
+  // Public view: never includes the password or share token
+  board: Omit<Board, "password" | "share_token" | "columns" | "labels">;
 }
 
 export interface SSEColumnCreatedEvent {
@@ -51,6 +58,17 @@ export interface SSEColumnReorderedEvent {
   new_position: number;
 }
 
+export interface SSEColumnArchivedEvent {
+  type: "column_archived";
+  column_id: string;
+}
+
+export interface SSEColumnRestoredEvent {
+  type: "column_restored";
+  // Includes the column's cards
+  column: Column;
+}
+
 export interface SSECardCreatedEvent {
   type: "card_created";
   card: Card;
@@ -81,6 +99,12 @@ export interface SSECardReorderedEvent {
   new_position: number;
 }
 
+export interface SSEColumnCardsArchivedEvent {
+  type: "column_cards_archived";
+  column_id: string;
+  card_ids: string[];
+}
+
 export interface SSEBoardLabelCreatedEvent {
   type: "board_label_created";
   label: Label;
@@ -96,6 +120,12 @@ export interface SSEBoardLabelDeletedEvent {
   label_id: string;
 }
 
+export interface SSELabelsReorderedEvent {
+  type: "labels_reordered";
+  /** The board's label IDs in their new order */
+  label_ids: string[];
+}
+
 export interface SSECardLabelAssignedEvent {
   type: "card_label_assigned";
   card_id: string;
@@ -120,6 +150,19 @@ export interface SSEAttachmentDeletedEvent {
   attachment_id: string;
 }
 
+export interface SSEAttachmentsDeletedEvent {
+  type: "attachments_deleted";
+  card_id: string;
+  attachment_ids: string[];
+}
+
+export interface SSEAttachmentsReorderedEvent {
+  type: "attachments_reordered";
+  card_id: string;
+  /** The card's attachment IDs in their new order */
+  attachment_ids: string[];
+}
+
 /**
  * Union type for all SSE events
  */
@@ -129,18 +172,24 @@ export type SSEEvent =
   | SSEColumnUpdatedEvent
   | SSEColumnDeletedEvent
   | SSEColumnReorderedEvent
+  | SSEColumnArchivedEvent
+  | SSEColumnRestoredEvent
   | SSECardCreatedEvent
   | SSECardUpdatedEvent
   | SSECardDeletedEvent
   | SSECardMovedEvent
   | SSECardReorderedEvent
+  | SSEColumnCardsArchivedEvent
   | SSEBoardLabelCreatedEvent
   | SSEBoardLabelUpdatedEvent
   | SSEBoardLabelDeletedEvent
+  | SSELabelsReorderedEvent
   | SSECardLabelAssignedEvent
   | SSECardLabelUnassignedEvent
   | SSEAttachmentCreatedEvent
-  | SSEAttachmentDeletedEvent;
+  | SSEAttachmentDeletedEvent
+  | SSEAttachmentsDeletedEvent
+  | SSEAttachmentsReorderedEvent;
 
 /**
  * Event handler type for SSE events
@@ -175,6 +224,7 @@ export class SSEClient {
   private reconnectDelay = 1000;
   private reconnectTimeout: NodeJS.Timeout | null = null;
   private status: SSEConnectionStatus = "disconnected";
+  private lastEventId: string | null = null;
 
   constructor(shareToken: string) {
     this.shareToken = shareToken;
@@ -191,7 +241,13 @@ export class SSEClient {
 
     const apiUrl =
       process.env.NEXT_PUBLIC_API_URL || "http://localhost:8080/api";
-    const url = `${apiUrl}/sse/${this.shareToken}`;
+    // Replayed events arrive as one batch frame; after a manual reconnect the
+    // last seen id is passed explicitly since EventSource only resends it itself
+    const params = new URLSearchParams({ batch: "true" });
+    if (this.lastEventId) {
+      params.set("last_event_id", this.lastEventId);
+    }
+    const url = `${apiUrl}/sse/${this.shareToken}?${params}`;
 
     console.log("[SSEClient] Connecting to:", url);
     this.updateStatus("connecting");
@@ -237,6 +293,11 @@ export class SSEClient {
   private setupEventListeners(): void {
     if (!this.eventSource) return;
 
+    // Events missed while disconnected, replayed as a JSON array
+    this.eventSource.addEventListener("batch", (e) => {
+      this.handleBatch(e);
+    });
+
     // Board events
     this.eventSource.addEventListener("board:updated", (e) => {
       this.handleEvent("board:updated", e);
@@ -255,6 +316,12 @@ export class SSEClient {
     this.eventSource.addEventListener("column:reordered", (e) => {
       this.handleEvent("column:reordered", e);
     });
+    this.eventSource.addEventListener("column:archived", (e) => {
+      this.handleEvent("column:archived", e);
+    });
+    this.eventSource.addEventListener("column:restored", (e) => {
+      this.handleEvent("column:restored", e);
+    });
 
     // Card events
     this.eventSource.addEventListener("card:created", (e) => {
@@ -272,6 +339,9 @@ export class SSEClient {
     this.eventSource.addEventListener("card:reordered", (e) => {
       this.handleEvent("card:reordered", e);
     });
+    this.eventSource.addEventListener("column:cards_archived", (e) => {
+      this.handleEvent("column:cards_archived", e);
+    });
 
     // Board label events
     this.eventSource.addEventListener("board_label:created", (e) => {
@@ -283,6 +353,9 @@ export class SSEClient {
     this.eventSource.addEventListener("board_label:deleted", (e) => {
       this.handleEvent("board_label:deleted", e);
     });
+    this.eventSource.addEventListener("board_label:reordered", (e) => {
+      this.handleEvent("board_label:reordered", e);
+    });
 
     // Card label assignment events
     this.eventSource.addEventListener("card_label:assigned", (e) => {
@@ -299,6 +372,12 @@ export class SSEClient {
     this.eventSource.addEventListener("attachment:deleted", (e) => {
       this.handleEvent("attachment:deleted", e);
     });
+    this.eventSource.addEventListener("attachment:bulk_deleted", (e) => {
+      this.handleEvent("attachment:bulk_deleted", e);
+    });
+    this.eventSource.addEventListener("attachment:reordered", (e) => {
+      this.handleEvent("attachment:reordered", e);
+    });
   }
 
   /**
@@ -308,20 +387,49 @@ export class SSEClient {
     try {
       console.log(`[SSEClient] Received ${eventType} event:`, event.data);
       const data = JSON.parse(event.data) as SSEEvent;
-
-      // Notify all event handlers
-      this.eventHandlers.forEach((handler) => {
-        try {
-          handler(data);
-        } catch (error) {
-          console.error("[SSEClient] Error in event handler:", error);
-        }
-      });
+      this.trackEventId(event);
+      this.dispatch(data);
     } catch (error) {
       console.error(`[SSEClient] Failed to parse ${eventType} event:`, error);
     }
   }
 
+  /**
+   * Handle a batch of replayed events, in order
+   */
+  private handleBatch(event: MessageEvent): void {
+    try {
+      const events = JSON.parse(event.data) as SSEEvent[];
+      console.log(`[SSEClient] Replaying ${events.length} missed events`);
+      this.trackEventId(event);
+      events.forEach((data) => this.dispatch(data));
+    } catch (error) {
+      console.error("[SSEClient] Failed to parse batch event:", error);
+    }
+  }
+
+  /**
+   * Remember the id of the latest event for resuming after a reconnect
+   */
+  private trackEventId(event: MessageEvent): void {
+    if (event.lastEventId) {
+      this.lastEventId = event.lastEventId;
+    }
+  }
+
+  /**
+   * Notify all event handlers
+   */
+  private dispatch(data: SSEEvent): void {
+    this.eventHandlers.forEach((handler) => {
+      try {
+        handler(data);
+      } catch (error) {
+        console.error("[SSEClient] Error in event handler:", error);
+      }
+    });
+  }
+
   /**
    * Subscribe to SSE events
    */
@@ -423,6 +531,7 @@ export class SSEClient {
 
     // Reset state
     this.reconnectAttempts = 0;
+    this.lastEventId = null;
     this.updateStatus("disconnected");
   }
 
@@ -6,19 +6,40 @@ export interface Board {
   id: string;
   title: string;
   share_token: string;
-  password: string;
+  // Only present in the board creation response
+  password?: string;
+  is_protected?: boolean;
   is_locked: boolean;
+  view_token: string;
+  // Stable ID used in the presence protocol, assigned by the backend
+  presence_id?: number;
   created_at: string;
   updated_at: string;
   columns?: Column[];
   labels?: BoardLabel[];
 }
 
+// Board creation response: the only payload that includes the password
+export type CreatedBoard = Board & { password: string };
+
+// Lightweight board overview from the batch summaries endpoint
+export interface BoardSummary {
+  id: string;
+  title: string;
+  is_locked: boolean;
+  column_count: number;
+  card_count: number;
+  updated_at: string;
+}
+
 export interface Column {
   id: string;
   board_id: string;
   title: string;
   position: number;
+  is_terminal: boolean;
+  // Only set on archived columns, which boards return with include_archived
+  archived_at?: string | null;
   created_at: string;
   updated_at: string;
   cards?: Card[];
@@ -29,13 +50,19 @@ export interface Card {
   column_id: string;
   title: string;
   description?: string | null;
+  color?: string | null;
   position: number;
+  is_template: boolean;
+  archived_at?: string | null;
   created_at: string;
   updated_at: string;
   labels?: BoardLabel[];
   attachments?: CardAttachment[];
 }
 
+// Card search hit; higher scores are more relevant
+export type ScoredCard = Card & { score: number };
+
 export interface CardAttachment {
   id: string;
   card_id: string;
@@ -46,6 +73,7 @@ export interface CardAttachment {
   file_size: number;
   s3_key: string;
   is_confirmed: boolean;
+  position: number;
   created_at: string;
   updated_at: string;
 }
@@ -55,13 +83,33 @@ export interface BoardLabel {
   board_id: string;
   name: string;
   color: string;
+  position: number;
   created_at: string;
   updated_at: string;
 }
 
+export interface BoardLabelUsage extends BoardLabel {
+  card_count: number;
+}
+
 // Keep Label as alias for backward compatibility during transition
 export type Label = BoardLabel;
 
+export interface CardChangedPayload {
+  card_id: string;
+  board_id: string;
+  change: "updated" | "moved";
+}
+
+export interface Notification {
+  id: string;
+  user_id: string;
+  kind: "card_changed";
+  payload: CardChangedPayload;
+  read_at: string | null;
+  created_at: string;
+}
+
 /**
  * API Request/Response types
  */
@@ -83,11 +131,27 @@ export interface CreateColumnRequest {
   board_id: string;
   title: string;
   position: number;
+  is_terminal?: boolean;
 }
 
 export interface UpdateColumnRequest {
   title?: string;
   position?: number;
+  is_terminal?: boolean;
+}
+
+export interface CycleTimeMetrics {
+  board_id: string;
+  completed_cards: number;
+  average_cycle_time_seconds: number | null;
+}
+
+// Archived columns and cards are not counted
+export interface CountMetrics {
+  board_id: string;
+  column_count: number;
+  card_count: number;
+  columns: { column_id: string; card_count: number }[];
 }
 
 export interface CreateCardRequest {
@@ -101,6 +165,7 @@ export interface UpdateCardRequest {
   description?: string | null;
   position?: number;
   column_id?: string;
+  is_template?: boolean;
 }
 
 export interface CreateLabelRequest {
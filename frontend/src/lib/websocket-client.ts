@@ -10,9 +10,12 @@ import {
   encodeJoin,
   encodeLeave,
   encodeHeartbeat,
+  encodeFocusCard,
+  encodeQueryPresence,
   decodeMessage,
   type BinaryMessage,
   ProtocolError,
+  RECONNECT_TOKEN_LENGTH,
 } from "./protocol";
 
 /**
@@ -30,6 +33,7 @@ export type PresenceEvent =
   | { type: "user_left"; userId: number }
   | { type: "cursor_move"; userId: number; x: number; y: number }
   | { type: "presence_count"; count: number }
+  | { type: "focus_change"; userId: number; cardSlot: number }
   | { type: "error"; error: Error };
 
 /**
@@ -49,11 +53,30 @@ export interface WebSocketClientOptions {
   heartbeatInterval?: number;
 }
 
+/**
+ * Read the retry hint from a close reason.
+ *
+ * The presence service closes rate-limited or overloaded connections with a
+ * reason like `connection rate limit exceeded; retry-after=3`.
+ *
+ * @param reason - Close reason from the CloseEvent
+ * @returns Seconds to wait before reconnecting, or null if there is no hint
+ */
+export function parseRetryAfter(reason: string): number | null {
+  for (const part of reason.split(";").slice(1)) {
+    const [key, value] = part.trim().split("=");
+    if (key === "retry-after" && /^\d+$/.test(value ?? "")) {
+      return Number(value);
+    }
+  }
+  return null;
+}
+
 /**
  * WebSocket client for real-time presence communication.
  *
  * Features:
- * - Automatic reconnection with exponential backoff
+ * - Automatic reconnection with exponential backoff, honoring server retry hints
  * - Periodic heartbeats to keep connection alive
  * - Event emitter pattern for presence updates
  * - Binary protocol message handling
@@ -70,6 +93,10 @@ export class WebSocketClient {
   private heartbeatIntervalMs: number;
   private isConnecting: boolean = false;
   private isDisconnecting: boolean = false;
+  /** Sent with every join so a reconnect keeps this user's ID and color */
+  private reconnectToken: Uint8Array = crypto.getRandomValues(
+    new Uint8Array(RECONNECT_TOKEN_LENGTH)
+  );
 
   constructor(url: string, options: WebSocketClientOptions = {}) {
     this.url = url;
@@ -127,7 +154,8 @@ export class WebSocketClient {
 
           if (!this.isDisconnecting) {
             this.emit({ type: "disconnected" });
-            this.reconnect();
+            const retryAfter = parseRetryAfter(event.reason);
+            this.reconnect(retryAfter === null ? 0 : retryAfter * 1000);
           }
         };
 
@@ -209,7 +237,7 @@ export class WebSocketClient {
    * @param username - Username string
    */
   sendJoin(boardId: number, username: string): void {
-    const message = encodeJoin(boardId, username);
+    const message = encodeJoin(boardId, username, this.reconnectToken);
     this.send(message);
   }
 
@@ -223,6 +251,29 @@ export class WebSocketClient {
     this.send(message);
   }
 
+  /**
+   * Send a focus card message.
+   *
+   * @param boardId - The board ID
+   * @param cardSlot - The focused card's slot (0 clears the focus)
+   */
+  sendFocusCard(boardId: number, cardSlot: number): void {
+    const message = encodeFocusCard(boardId, cardSlot);
+    this.send(message);
+  }
+
+  /**
+   * Ask for a board's presence count without joining it.
+   *
+   * The answer arrives as a regular presence update event.
+   *
+   * @param boardId - The board ID
+   */
+  sendQueryPresence(boardId: number): void {
+    const message = encodeQueryPresence(boardId);
+    this.send(message);
+  }
+
   /**
    * Add an event listener.
    *
@@ -324,6 +375,14 @@ export class WebSocketClient {
           });
           break;
 
+        case "focus_broadcast":
+          this.emit({
+            type: "focus_change",
+            userId: message.userId,
+            cardSlot: message.cardSlot,
+          });
+          break;
+
         case "heartbeat":
           // Heartbeat received, no action needed
           break;
@@ -382,8 +441,10 @@ export class WebSocketClient {
 
   /**
    * Attempt to reconnect with exponential backoff.
+   *
+   * @param minDelay - Shortest wait in ms, from the server's retry hint
    */
-  private reconnect(): void {
+  private reconnect(minDelay: number = 0): void {
     if (this.isDisconnecting) {
       return;
     }
@@ -396,7 +457,10 @@ export class WebSocketClient {
     }
 
     this.reconnectAttempts++;
-    const delay = this.reconnectDelay * Math.pow(2, this.reconnectAttempts - 1);
+    const delay = Math.max(
+      minDelay,
+      this.reconnectDelay * Math.pow(2, this.reconnectAttempts - 1)
+    );
 
     console.log(
       `[WebSocketClient] Reconnecting in ${delay}ms (attempt ${this.reconnectAttempts}/${this.maxReconnectAttempts})`
@@ -38,6 +38,7 @@ interface BoardStore {
   addBoardLabel: (label: BoardLabel) => void;
   updateBoardLabel: (labelId: string, updates: Partial<BoardLabel>) => void;
   deleteBoardLabel: (labelId: string) => void;
+  reorderBoardLabels: (labelIds: string[]) => void;
 
   // Card Label operations (legacy - kept for compatibility)
   addLabel: (cardId: string, label: BoardLabel) => void;
@@ -51,6 +52,7 @@ interface BoardStore {
   // Attachment operations
   addAttachment: (cardId: string, attachment: CardAttachment) => void;
   removeAttachment: (cardId: string, attachmentId: string) => void;
+  reorderAttachments: (cardId: string, attachmentIds: string[]) => void;
 
   // Filter operations
   setLabelFilter: (labelIds: string[]) => void;
@@ -387,6 +389,22 @@ export const useBoardStore = create<BoardStore>((set, get) => ({
       };
     }),
 
+  reorderBoardLabels: (labelIds) =>
+    set((state) => {
+      if (!state.board?.labels) return state;
+      // Labels missing from the order (e.g. not yet synced) go last
+      const rank = (id: string) => {
+        const index = labelIds.indexOf(id);
+        return index === -1 ? labelIds.length : index;
+      };
+      const labels = [...state.board.labels]
+        .sort((a, b) => rank(a.id) - rank(b.id))
+        .map((label, index) => ({ ...label, position: index }));
+      return {
+        board: { ...state.board, labels },
+      };
+    }),
+
   deleteBoardLabel: (labelId) =>
     set((state) => {
       if (!state.board) return state;
@@ -677,6 +695,37 @@ export const useBoardStore = create<BoardStore>((set, get) => ({
       };
     }),
 
+  reorderAttachments: (cardId, attachmentIds) =>
+    set((state) => {
+      if (!state.board?.columns) return state;
+
+      const columns = state.board.columns.map((col) => {
+        const hasCard = col.cards?.some((card) => card.id === cardId);
+        if (!hasCard) return col;
+
+        const cards = col.cards?.map((card) => {
+          if (card.id === cardId && card.attachments) {
+            // Attachments missing from the order (e.g. not yet synced) go last
+            const rank = (id: string) => {
+              const index = attachmentIds.indexOf(id);
+              return index === -1 ? attachmentIds.length : index;
+            };
+            const attachments = [...card.attachments]
+              .sort((a, b) => rank(a.id) - rank(b.id))
+              .map((a, index) => ({ ...a, position: index }));
+            return { ...card, attachments };
+          }
+          return card;
+        });
+
+        return { ...col, cards };
+      });
+
+      return {
+        board: { ...state.board, columns },
+      };
+    }),
+
   // Filter operations
   setLabelFilter: (labelIds: string[]) => {
     set({ selectedLabelFilter: labelIds });
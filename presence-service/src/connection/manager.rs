@@ -1,16 +1,102 @@
-use crate::connection::room::Room;
-use crate::connection::session::Session;
-use crate::protocol::messages::BinaryMessage;
+use crate::connection::room::{CardClaim, Room, UserInfo};
+use crate::connection::session::{OutgoingSequence, Session};
+use crate::protocol::messages::{BinaryMessage, ProtocolError};
+use crate::protocol::{
+    denormalize_coord_12bit, negotiate_version, normalize_coord, ReconnectToken,
+    MAX_ANNOUNCEMENT_LENGTH, MIN_PROTOCOL_VERSION, NO_CARD_FOCUSED, PROTOCOL_VERSION_V1,
+};
+use crate::redis::backoff::{ReconnectBackoff, BASE_DELAY, MAX_DELAY, STABLE_SUBSCRIPTION};
+use crate::redis::control::ControlMessage;
+use crate::redis::decode_cache::{DecodeCache, DecodeCacheStats};
 use crate::redis::pubsub::{RedisMessage, RedisPubSub};
-use std::collections::HashMap;
+use crate::utils::metrics::{BroadcastMetrics, BroadcastSnapshot, TrafficMetrics};
+use bytes::{Bytes, BytesMut};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{mpsc::UnboundedSender, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{error::SendError, UnboundedSender};
+use tokio::sync::{Mutex, RwLock, Semaphore};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 
+/// Maximum number of broadcast fan-outs running at once
+///
+/// Further broadcasts wait for a permit instead of piling up on the runtime.
+const MAX_IN_FLIGHT_BROADCASTS: usize = 64;
+
+/// Point-in-time view of a room, used by the admin endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomSnapshot {
+    pub board_id: u32,
+    pub user_count: usize,
+    /// Seconds since the last join, leave or cursor update in the room
+    pub idle_seconds: u64,
+    /// Mean round-trip time of members that have answered a Ping (v4+)
+    pub average_rtt_ms: Option<u64>,
+    /// `Room::digest` of the roster as 16 hex digits, to compare with clients
+    pub roster_digest: String,
+}
+
+/// Point-in-time view of this instance, served by the metrics endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    /// Client connections open on this instance
+    pub connections: usize,
+    /// Client connections accepted since the instance started
+    pub connections_opened: u64,
+    /// Rooms with local users
+    pub rooms: usize,
+    /// Local users per room, by board ID
+    pub room_users: BTreeMap<u32, usize>,
+    /// Binary frames received from clients
+    pub messages_received: u64,
+    /// Frames queued for clients, once per recipient
+    pub messages_sent: u64,
+    /// Messages fanned out to local clients, once per message
+    pub messages_broadcast: u64,
+    /// Broadcasts per message type and by number of clients reached
+    pub broadcasts: BroadcastSnapshot,
+}
+
+/// A join whose UserJoined broadcast is waiting for the room's coalescing window
+struct PendingJoin {
+    addr: SocketAddr,
+    user_joined: BinaryMessage,
+}
+
+/// A local client snapshotted for a broadcast
+struct Recipient {
+    addr: SocketAddr,
+    tx: UnboundedSender<Message>,
+    version: u8,
+    /// Present when the client's protocol version numbers frames
+    sequence: Option<OutgoingSequence>,
+}
+
+/// A cursor position in normalized units
+type CursorPosition = (u16, u16);
+
+/// Connections and rooms with an open cursor coalescing window, and the newest
+/// position received during it
+type PendingCursors = HashMap<(SocketAddr, u32), Option<CursorPosition>>;
+
+/// Rooms with a cursor tick scheduled, and each member's newest position during it
+type RoomCursors = HashMap<u32, HashMap<SocketAddr, CursorPosition>>;
+
+/// PresenceUpdate reporting `user_count` users, saturating at `u16::MAX`
+fn presence_update(board_id: u32, user_count: usize) -> BinaryMessage {
+    BinaryMessage::PresenceUpdate {
+        board_id,
+        count: u16::try_from(user_count).unwrap_or(u16::MAX),
+    }
+}
+
 /// Manages all WebSocket connections, sessions, and rooms
 #[derive(Clone)]
 pub struct ConnectionManager {
@@ -21,13 +107,61 @@ pub struct ConnectionManager {
     sessions: Arc<RwLock<HashMap<SocketAddr, Session>>>,
 
     /// Map of board IDs to rooms
-    rooms: Arc<RwLock<HashMap<u16, Room>>>,
+    rooms: Arc<RwLock<HashMap<u32, Room>>>,
 
     /// Redis pub/sub for multi-instance coordination
     redis_pubsub: Arc<RedisPubSub>,
 
     /// Unique identifier for this service instance
     instance_id: String,
+
+    /// Origin of the millisecond tick sent to v2+ clients
+    started_at: Instant,
+
+    /// Bytes and messages sent/received per message type
+    metrics: Arc<TrafficMetrics>,
+
+    /// Client connections accepted since the instance started
+    connections_opened: Arc<AtomicU64>,
+
+    /// Messages fanned out to local clients, by type and fan-out
+    broadcast_metrics: Arc<BroadcastMetrics>,
+
+    /// Hits and misses of the Redis payload decode cache
+    decode_cache_stats: Arc<DecodeCacheStats>,
+
+    /// Caps concurrent broadcast fan-outs
+    broadcast_permits: Arc<Semaphore>,
+
+    /// How long join and presence notifications are held to coalesce bursts (zero sends immediately)
+    presence_coalesce_window: Duration,
+
+    /// Rooms with a coalesced presence broadcast scheduled, and the joins it will announce
+    pending_presence: Arc<Mutex<HashMap<u32, Vec<PendingJoin>>>>,
+
+    /// Smallest cursor movement, in normalized units, that is broadcast (zero sends every update)
+    cursor_dead_zone: u16,
+
+    /// Shortest cursor coalescing window a client may ask for
+    cursor_interval_min: Duration,
+
+    /// Longest cursor coalescing window a client may ask for (zero disables coalescing)
+    cursor_interval_max: Duration,
+
+    /// Connections and rooms with an open cursor coalescing window
+    pending_cursors: Arc<Mutex<PendingCursors>>,
+
+    /// Shortest and longest Redis resubscribe delay, before the circuit breaker opens
+    redis_backoff: (Duration, Duration),
+
+    /// How long cursor updates without a client window are held per room (zero sends immediately)
+    cursor_tick: Duration,
+
+    /// Rooms with a cursor tick scheduled
+    room_cursors: Arc<Mutex<RoomCursors>>,
+
+    /// Most users seen at once per board since peaks were last taken
+    presence_peaks: Arc<Mutex<HashMap<u32, usize>>>,
 }
 
 impl ConnectionManager {
@@ -45,9 +179,85 @@ impl ConnectionManager {
             rooms: Arc::new(RwLock::new(HashMap::new())),
             redis_pubsub,
             instance_id,
+            started_at: Instant::now(),
+            metrics: Arc::new(TrafficMetrics::new()),
+            connections_opened: Arc::new(AtomicU64::new(0)),
+            broadcast_metrics: Arc::new(BroadcastMetrics::new()),
+            decode_cache_stats: Arc::new(DecodeCacheStats::default()),
+            broadcast_permits: Arc::new(Semaphore::new(MAX_IN_FLIGHT_BROADCASTS)),
+            presence_coalesce_window: Duration::ZERO,
+            pending_presence: Arc::new(Mutex::new(HashMap::new())),
+            cursor_dead_zone: 0,
+            cursor_interval_min: Duration::ZERO,
+            cursor_interval_max: Duration::ZERO,
+            pending_cursors: Arc::new(Mutex::new(HashMap::new())),
+            redis_backoff: (BASE_DELAY, MAX_DELAY),
+            cursor_tick: Duration::ZERO,
+            room_cursors: Arc::new(Mutex::new(HashMap::new())),
+            presence_peaks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Coalesce UserJoined and PresenceUpdate broadcasts per room over `window`
+    ///
+    /// When many clients join a room at once (e.g. reconnecting after a
+    /// deploy), each join would otherwise fan out a UserJoined and a
+    /// PresenceUpdate to every member. Within the window the joins are
+    /// announced together and the room gets a single PresenceUpdate with the
+    /// final count. A zero window (the default) broadcasts immediately.
+    pub fn with_presence_coalesce_window(mut self, window: Duration) -> Self {
+        self.presence_coalesce_window = window;
+        self
+    }
+
+    /// Drop cursor updates that move less than `dead_zone` normalized units
+    ///
+    /// Sub-pixel jitter from some pointing devices otherwise fans out a
+    /// broadcast per update. Movement is compared against the user's last
+    /// broadcast position. Zero (the default) broadcasts every update.
+    pub fn with_cursor_dead_zone(mut self, dead_zone: u16) -> Self {
+        self.cursor_dead_zone = dead_zone;
+        self
+    }
+
+    /// Let v6+ clients ask for their cursor updates to be coalesced
+    ///
+    /// A client's requested interval is clamped to `min..=max` (`max` is
+    /// raised to `min` if smaller). Within its window the first update is
+    /// broadcast right away and only the newest of the rest follows when the
+    /// window closes, so slow links are not flooded with positions they
+    /// would skip anyway. Clients that ask for nothing, and every client
+    /// while `max` is zero (the default), get each update broadcast.
+    pub fn with_cursor_interval_range(mut self, min: Duration, max: Duration) -> Self {
+        self.cursor_interval_min = min;
+        self.cursor_interval_max = max.max(min);
+        self
+    }
+
+    /// Broadcast cursor updates once per `tick` per room
+    ///
+    /// Each member's newest position during a tick is kept and broadcast
+    /// when the tick ends, so a room of N users moving continuously costs at
+    /// most one frame per user per tick for each recipient instead of one per
+    /// update. The first update waits up to a tick. Clients with their own
+    /// coalescing window (`with_cursor_interval_range`) keep using it. A zero
+    /// tick (the default) broadcasts every update immediately.
+    pub fn with_cursor_tick(mut self, tick: Duration) -> Self {
+        self.cursor_tick = tick;
+        self
+    }
+
+    /// Back off Redis resubscribes from `base` up to `max`
+    ///
+    /// Each retry waits a random delay in the upper half of a window that
+    /// doubles per consecutive failure, so instances that lose Redis together
+    /// spread out when it comes back. A subscription that stays up resets the
+    /// window to `base`.
+    pub fn with_redis_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.redis_backoff = (base, max);
+        self
+    }
+
     /// Start listening for Redis pub/sub messages
     pub async fn start_redis_listener(self: Arc<Self>) {
         info!(
@@ -59,17 +269,99 @@ impl ConnectionManager {
         // For now, subscribe to the global channel
         let channels = vec![RedisPubSub::global_channel()];
 
+        let control = Arc::clone(&self);
+        tokio::spawn(async move {
+            control.listen_for_control_with_retry().await;
+        });
+
         tokio::spawn(async move {
             self.subscribe_with_retry(channels).await;
         });
     }
 
+    /// Listen for board state changes on the control channel, with automatic retry
+    async fn listen_for_control_with_retry(&self) {
+        let (base, max) = self.redis_backoff;
+        let mut backoff = ReconnectBackoff::new().with_delays(base, max);
+
+        loop {
+            match self
+                .redis_pubsub
+                .subscribe_raw(vec![RedisPubSub::control_channel()])
+                .await
+            {
+                Ok(mut stream) => {
+                    let subscribed_at = Instant::now();
+
+                    while let Some((_, payload)) = stream.recv().await {
+                        match ControlMessage::decode(&payload) {
+                            Ok(message) => {
+                                self.handle_control_message(message).await;
+                            }
+                            Err(e) => {
+                                error!("Failed to decode control message: {}", e);
+                            }
+                        }
+                    }
+
+                    if subscribed_at.elapsed() >= STABLE_SUBSCRIPTION {
+                        backoff.on_success();
+                    }
+
+                    let delay = backoff.on_failure(&mut rand::thread_rng());
+                    warn!(
+                        "Control channel subscription ended, reconnecting in {:?}...",
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    let delay = backoff.on_failure(&mut rand::thread_rng());
+                    error!(
+                        "Failed to subscribe to control channel: {}, retrying in {:?}...",
+                        e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// Apply a board state change from the backend
+    ///
+    /// Every instance receives the change and evicts its own members of the
+    /// room: each is sent an Announcement saying why, then removed with the
+    /// usual UserLeft messages. Nothing is republished to Redis.
+    ///
+    /// Returns the number of local users removed, or `None` if the room has
+    /// no members on this instance.
+    pub async fn handle_control_message(&self, message: ControlMessage) -> Option<usize> {
+        let ControlMessage { board_id, event } = message;
+        info!("Board {} {:?}, evicting local members", board_id, event);
+
+        let announcement = BinaryMessage::Announcement {
+            board_id,
+            text: event.announcement().to_string(),
+        };
+        self.broadcast_to_room(board_id, announcement, None).await;
+
+        self.remove_room(board_id, false).await
+    }
+
     /// Subscribe to Redis channels with automatic retry
+    ///
+    /// Retries use jittered exponential backoff with a circuit breaker (see
+    /// `ReconnectBackoff`). Local broadcasting keeps working throughout.
     async fn subscribe_with_retry(&self, channels: Vec<String>) {
+        let (base, max) = self.redis_backoff;
+        let mut backoff = ReconnectBackoff::new().with_delays(base, max);
+        let mut decode_cache = DecodeCache::new(Arc::clone(&self.decode_cache_stats));
+
         loop {
             match self.redis_pubsub.subscribe(channels.clone()).await {
                 Ok(mut stream) => {
                     info!("Successfully subscribed to Redis channels");
+                    let subscribed_at = Instant::now();
 
                     // Process incoming messages
                     while let Some((channel, redis_msg)) = stream.recv().await {
@@ -79,8 +371,8 @@ impl ConnectionManager {
                             continue;
                         }
 
-                        // Decode the binary message
-                        match redis_msg.get_binary_message() {
+                        // Decode the binary message, reusing recent identical payloads
+                        match decode_cache.decode(&channel, &redis_msg.payload) {
                             Ok(binary_msg) => {
                                 self.handle_redis_message(&channel, binary_msg).await;
                             }
@@ -90,16 +382,30 @@ impl ConnectionManager {
                         }
                     }
 
-                    warn!("Redis subscription stream ended, reconnecting...");
+                    // Only a subscription that held for a while resets the backoff,
+                    // so a flapping connection cannot retry in a tight loop
+                    if subscribed_at.elapsed() >= STABLE_SUBSCRIPTION {
+                        backoff.on_success();
+                    }
+
+                    let delay = backoff.on_failure(&mut rand::thread_rng());
+                    warn!(
+                        "Redis subscription stream ended, reconnecting in {:?}...",
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
                 }
                 Err(e) => {
-                    error!("Failed to subscribe to Redis: {}, retrying in 5s...", e);
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    let delay = backoff.on_failure(&mut rand::thread_rng());
+                    error!(
+                        "Failed to subscribe to Redis: {}, retrying in {:?} (attempt {})...",
+                        e,
+                        delay,
+                        backoff.consecutive_failures()
+                    );
+                    tokio::time::sleep(delay).await;
                 }
             }
-
-            // Add a small delay before retry to avoid tight loop
-            tokio::time::sleep(Duration::from_millis(100)).await;
         }
     }
 
@@ -114,7 +420,13 @@ impl ConnectionManager {
             BinaryMessage::UserJoined { board_id, .. }
             | BinaryMessage::UserLeft { board_id, .. }
             | BinaryMessage::CursorBroadcast { board_id, .. }
-            | BinaryMessage::PresenceUpdate { board_id, .. } => {
+            | BinaryMessage::CursorBroadcastBatch { board_id, .. }
+            | BinaryMessage::PresenceUpdate { board_id, .. }
+            | BinaryMessage::FocusBroadcast { board_id, .. }
+            | BinaryMessage::ActivityBroadcast { board_id, .. }
+            | BinaryMessage::CardClaimed { board_id, .. }
+            | BinaryMessage::CardReleased { board_id, .. }
+            | BinaryMessage::Announcement { board_id, .. } => {
                 // Broadcast to local WebSocket clients in this room
                 self.broadcast_to_room(*board_id, message, None).await;
             }
@@ -125,7 +437,7 @@ impl ConnectionManager {
     }
 
     /// Publish a message to Redis
-    async fn publish_to_redis(&self, board_id: u16, message: &BinaryMessage) {
+    async fn publish_to_redis(&self, board_id: u32, message: &BinaryMessage) {
         let channel = RedisPubSub::board_channel(board_id);
         let redis_msg = RedisMessage::new(self.instance_id.clone(), message);
 
@@ -149,6 +461,7 @@ impl ConnectionManager {
 
         let mut sessions = self.sessions.write().await;
         sessions.insert(addr, Session::new(addr));
+        self.connections_opened.fetch_add(1, Ordering::Relaxed);
 
         info!("Client connected: {}", addr);
     }
@@ -165,7 +478,7 @@ impl ConnectionManager {
 
         if let Some(session) = session {
             // Remove client from all rooms they're in
-            let board_ids: Vec<u16> = session.board_ids().iter().copied().collect();
+            let board_ids: Vec<u32> = session.board_ids().to_vec();
             for board_id in board_ids {
                 self.handle_leave_internal(addr, board_id).await;
             }
@@ -183,9 +496,33 @@ impl ConnectionManager {
 
     /// Handle incoming messages from clients
     pub async fn handle_message(&self, addr: SocketAddr, msg: BinaryMessage) {
+        if let Some(session) = self.sessions.write().await.get_mut(&addr) {
+            session.touch(Instant::now());
+        }
+
+        // A Correlated request is handled as its inner frame; the id is only
+        // echoed to clients that negotiated v5
+        let (msg_id, msg) = match msg {
+            BinaryMessage::Correlated { msg_id, message } => {
+                let echoes = {
+                    let sessions = self.sessions.read().await;
+                    sessions
+                        .get(&addr)
+                        .is_some_and(|session| session.echoes_message_ids())
+                };
+                (echoes.then_some(msg_id), *message)
+            }
+            msg => (None, msg),
+        };
+
         match msg {
-            BinaryMessage::Join { board_id, username } => {
-                self.handle_join(addr, board_id, username).await;
+            BinaryMessage::Join {
+                board_id,
+                username,
+                reconnect_token,
+            } => {
+                self.handle_join(addr, board_id, username, reconnect_token, msg_id)
+                    .await;
             }
             BinaryMessage::Leave { board_id } => {
                 self.handle_leave(addr, board_id).await;
@@ -193,28 +530,94 @@ impl ConnectionManager {
             BinaryMessage::CursorUpdate { board_id, x, y } => {
                 self.handle_cursor_update(addr, board_id, x, y).await;
             }
+            BinaryMessage::CursorUpdateCompact { board_id, x, y } => {
+                // Widened so compact senders reach every client as ordinary broadcasts
+                let x = normalize_coord(denormalize_coord_12bit(x));
+                let y = normalize_coord(denormalize_coord_12bit(y));
+                self.handle_cursor_update(addr, board_id, x, y).await;
+            }
+            BinaryMessage::CursorBatch { board_id, points } => {
+                self.handle_cursor_batch(addr, board_id, points).await;
+            }
             BinaryMessage::Heartbeat => {
                 self.handle_heartbeat(addr).await;
             }
+            BinaryMessage::Hello {
+                version,
+                cursor_interval_ms,
+            } => {
+                self.handle_hello(addr, version, cursor_interval_ms).await;
+            }
+            BinaryMessage::Pong { nonce } => {
+                self.handle_pong(addr, nonce).await;
+            }
+            BinaryMessage::FocusCard {
+                board_id,
+                card_slot,
+            } => {
+                self.handle_focus_card(addr, board_id, card_slot).await;
+            }
+            BinaryMessage::ActivityUpdate {
+                board_id,
+                card_ref,
+                active,
+            } => {
+                self.handle_activity_update(addr, board_id, card_ref, active)
+                    .await;
+            }
+            BinaryMessage::ClaimCard {
+                board_id,
+                card_slot,
+            } => {
+                self.handle_claim_card(addr, board_id, card_slot).await;
+            }
+            BinaryMessage::ReleaseCard {
+                board_id,
+                card_slot,
+            } => {
+                self.handle_release_card(addr, board_id, card_slot).await;
+            }
+            BinaryMessage::QueryPresence { board_id } => {
+                self.handle_query_presence(addr, board_id, msg_id).await;
+            }
             _ => {
                 warn!("Received unexpected server message from client: {:?}", msg);
             }
         }
     }
 
-    /// Handle Join message
-    async fn handle_join(&self, addr: SocketAddr, board_id: u16, username: String) {
+    /// Handle Join message, acknowledging it if it carried a message id
+    ///
+    /// A user rejoining with the reconnect token of a recently departed user
+    /// gets back that user's ID and color.
+    async fn handle_join(
+        &self,
+        addr: SocketAddr,
+        board_id: u32,
+        username: String,
+        reconnect_token: Option<ReconnectToken>,
+        msg_id: Option<u16>,
+    ) {
         debug!("Client {} joining board {}", addr, board_id);
 
         // Check if client is already in the room
-        {
+        let current_username = {
             let sessions = self.sessions.read().await;
-            if let Some(session) = sessions.get(&addr) {
-                if session.board_ids().contains(&board_id) {
-                    warn!("Client {} already in room {}", addr, board_id);
-                    return;
-                }
+            sessions
+                .get(&addr)
+                .and_then(|session| session.get_board_info(board_id))
+                .map(|info| info.username.clone())
+        };
+
+        if let Some(current_username) = current_username {
+            // Re-joining under a new name renames the user in place
+            if current_username != username {
+                self.handle_rename(addr, board_id, &username).await;
+            } else {
+                warn!("Client {} already in room {}", addr, board_id);
             }
+            self.acknowledge_join(addr, board_id, msg_id).await;
+            return;
         }
 
         // Get or create room and assign user ID
@@ -222,26 +625,36 @@ impl ConnectionManager {
             let mut rooms = self.rooms.write().await;
             let room = rooms.entry(board_id).or_insert_with(|| Room::new(board_id));
 
-            // Assign user ID
-            let user_id = match room.assign_user_id() {
-                Some(id) => id,
-                None => {
-                    error!("Room {} is full (max 255 users)", board_id);
-                    return;
-                }
-            };
+            // Assign user ID, or give back a reconnecting user's seat
+            let (user_id, reclaimed_color) =
+                match room.reclaim_or_assign(reconnect_token.as_ref(), Instant::now()) {
+                    Some(seat) => seat,
+                    None => {
+                        error!("Room {} is full (max 255 users)", board_id);
+                        return;
+                    }
+                };
 
-            // Generate random color for cursor
-            let color = Self::generate_color();
+            // Generate random color for cursor unless the user had one
+            let color = reclaimed_color.unwrap_or_else(Self::generate_color);
 
             // Add user to room
             room.add_user(addr, user_id, username.clone(), color);
+            if let Some(token) = reconnect_token {
+                room.set_reconnect_token(addr, token);
+            }
 
             let user_count = room.user_count();
 
             (user_id, color, user_count)
         };
 
+        {
+            let mut peaks = self.presence_peaks.lock().await;
+            let peak = peaks.entry(board_id).or_insert(0);
+            *peak = (*peak).max(user_count);
+        }
+
         // Update session
         {
             let mut sessions = self.sessions.write().await;
@@ -255,6 +668,8 @@ impl ConnectionManager {
             addr, board_id, user_id, username
         );
 
+        self.acknowledge_join(addr, board_id, msg_id).await;
+
         // Send information about existing users to the newly joined user
         {
             let rooms = self.rooms.read().await;
@@ -276,11 +691,33 @@ impl ConnectionManager {
                     if let Err(e) = self.send_to_client(addr, existing_user_joined).await {
                         warn!("Failed to send existing user info to new user: {}", e);
                     }
+
+                    if existing_user.focused_card != NO_CARD_FOCUSED {
+                        let existing_focus = BinaryMessage::FocusBroadcast {
+                            board_id,
+                            user_id: existing_user.user_id,
+                            card_slot: existing_user.focused_card,
+                        };
+                        if let Err(e) = self.send_to_client(addr, existing_focus).await {
+                            warn!("Failed to send existing focus to new user: {}", e);
+                        }
+                    }
+                }
+
+                // Claims follow the roster so every holder is already known
+                for (card_slot, holder) in room.claims() {
+                    let existing_claim = BinaryMessage::CardClaimed {
+                        board_id,
+                        user_id: holder,
+                        card_slot,
+                    };
+                    if let Err(e) = self.send_to_client(addr, existing_claim).await {
+                        warn!("Failed to send existing claim to new user: {}", e);
+                    }
                 }
             }
         }
 
-        // Broadcast UserJoined to other room members (local and remote)
         let user_joined = BinaryMessage::UserJoined {
             board_id,
             user_id,
@@ -288,18 +725,63 @@ impl ConnectionManager {
             color,
         };
 
+        if self.presence_coalesce_window.is_zero() {
+            self.broadcast_user_joined(board_id, addr, user_joined)
+                .await;
+            self.broadcast_presence(board_id, user_count).await;
+        } else {
+            self.queue_presence_change(board_id, Some(PendingJoin { addr, user_joined }))
+                .await;
+        }
+    }
+
+    /// Answer a correlated Join with the joiner's own UserJoined
+    async fn acknowledge_join(&self, addr: SocketAddr, board_id: u32, msg_id: Option<u16>) {
+        let Some(msg_id) = msg_id else {
+            return;
+        };
+
+        let user_joined = {
+            let sessions = self.sessions.read().await;
+            sessions
+                .get(&addr)
+                .and_then(|session| session.get_board_info(board_id))
+                .map(|info| BinaryMessage::UserJoined {
+                    board_id,
+                    user_id: info.user_id,
+                    username: info.username.clone(),
+                    color: info.color,
+                })
+        };
+
+        if let Some(user_joined) = user_joined {
+            if let Err(e) = self
+                .send_to_client(addr, user_joined.correlate(Some(msg_id)))
+                .await
+            {
+                warn!("Failed to acknowledge join to {}: {}", addr, e);
+            }
+        }
+    }
+
+    /// Broadcast UserJoined to other room members (local and remote)
+    async fn broadcast_user_joined(
+        &self,
+        board_id: u32,
+        addr: SocketAddr,
+        user_joined: BinaryMessage,
+    ) {
         // Publish to Redis for other instances
         self.publish_to_redis(board_id, &user_joined).await;
 
         // Broadcast locally (excluding the new user who already knows they joined)
         self.broadcast_to_room(board_id, user_joined, Some(addr))
             .await;
+    }
 
-        // Send PresenceUpdate to all room members (including the new user)
-        let presence_update = BinaryMessage::PresenceUpdate {
-            board_id,
-            count: user_count as u8,
-        };
+    /// Send PresenceUpdate to all room members (local and remote)
+    async fn broadcast_presence(&self, board_id: u32, user_count: usize) {
+        let presence_update = presence_update(board_id, user_count);
 
         // Publish to Redis for other instances
         self.publish_to_redis(board_id, &presence_update).await;
@@ -309,13 +791,129 @@ impl ConnectionManager {
             .await;
     }
 
+    /// Record a presence change, scheduling the room's coalesced broadcast if needed
+    async fn queue_presence_change(&self, board_id: u32, join: Option<PendingJoin>) {
+        let scheduled = {
+            let mut pending = self.pending_presence.lock().await;
+            let scheduled = pending.contains_key(&board_id);
+            pending.entry(board_id).or_default().extend(join);
+            scheduled
+        };
+
+        if !scheduled {
+            let manager = self.clone();
+            let window = self.presence_coalesce_window;
+            tokio::spawn(async move {
+                tokio::time::sleep(window).await;
+                manager.flush_presence(board_id).await;
+            });
+        }
+    }
+
+    /// Drop a join that has not been announced yet
+    ///
+    /// Returns true if the join was still pending, in which case the room
+    /// never heard of the user and needs no UserLeft.
+    async fn cancel_pending_join(&self, board_id: u32, addr: SocketAddr) -> bool {
+        let mut pending = self.pending_presence.lock().await;
+        match pending.get_mut(&board_id) {
+            Some(joins) => {
+                let before = joins.len();
+                joins.retain(|join| join.addr != addr);
+                joins.len() != before
+            }
+            None => false,
+        }
+    }
+
+    /// Announce a room's pending joins and its current count in one pass
+    async fn flush_presence(&self, board_id: u32) {
+        let joins = match self.pending_presence.lock().await.remove(&board_id) {
+            Some(joins) => joins,
+            None => return,
+        };
+
+        let user_count = self.get_room_user_count(board_id).await;
+        if user_count == 0 {
+            return;
+        }
+
+        debug!(
+            "Flushing {} coalesced joins for room {} ({} users)",
+            joins.len(),
+            board_id,
+            user_count
+        );
+
+        for join in joins {
+            self.broadcast_user_joined(board_id, join.addr, join.user_joined)
+                .await;
+        }
+        self.broadcast_presence(board_id, user_count).await;
+    }
+
+    /// Rename a user and announce the new name to the room
+    async fn handle_rename(&self, addr: SocketAddr, board_id: u32, new_username: &str) {
+        let user = match self.rename_user(addr, board_id, new_username).await {
+            Some(user) => user,
+            None => {
+                warn!("Rejected rename of {} in room {}", addr, board_id);
+                return;
+            }
+        };
+
+        info!(
+            "Client {} renamed to {} in board {} (user {})",
+            addr, user.username, board_id, user.user_id
+        );
+
+        // Clients key users by ID, so a repeated UserJoined updates the name
+        let user_joined = BinaryMessage::UserJoined {
+            board_id,
+            user_id: user.user_id,
+            username: user.username,
+            color: user.color,
+        };
+
+        // Publish to Redis for other instances
+        self.publish_to_redis(board_id, &user_joined).await;
+
+        // Broadcast locally
+        self.broadcast_to_room(board_id, user_joined, Some(addr))
+            .await;
+    }
+
+    /// Rename a user in a room, updating the session's board info in lockstep
+    ///
+    /// The name is sanitized by the room. Returns the updated user info, or
+    /// `None` if the client is not in the room or the name is invalid.
+    pub async fn rename_user(
+        &self,
+        addr: SocketAddr,
+        board_id: u32,
+        new_username: &str,
+    ) -> Option<UserInfo> {
+        // Hold both locks so no reader sees the room and session disagree
+        let mut rooms = self.rooms.write().await;
+        let mut sessions = self.sessions.write().await;
+
+        let session = sessions.get_mut(&addr)?;
+        let user = rooms
+            .get_mut(&board_id)?
+            .rename_user(addr, new_username)?
+            .clone();
+        session.rename_board_user(board_id, user.username.clone());
+
+        Some(user)
+    }
+
     /// Handle Leave message
-    async fn handle_leave(&self, addr: SocketAddr, board_id: u16) {
+    async fn handle_leave(&self, addr: SocketAddr, board_id: u32) {
         self.handle_leave_internal(addr, board_id).await;
     }
 
     /// Internal leave handler (used by both explicit leave and disconnect)
-    async fn handle_leave_internal(&self, addr: SocketAddr, board_id: u16) {
+    async fn handle_leave_internal(&self, addr: SocketAddr, board_id: u32) {
         debug!("Client {} leaving board {}", addr, board_id);
 
         // Get user info before removing
@@ -330,13 +928,19 @@ impl ConnectionManager {
             }
         };
 
+        // A cursor position still held for coalescing is dropped with the user
+        self.pending_cursors.lock().await.remove(&(addr, board_id));
+        if let Some(positions) = self.room_cursors.lock().await.get_mut(&board_id) {
+            positions.remove(&addr);
+        }
+
         // Remove user from room and check if room should be deleted
-        let (should_delete_room, user_count) = {
+        let (should_delete_room, user_count, released_cards) = {
             let mut rooms = self.rooms.write().await;
             if let Some(room) = rooms.get_mut(&board_id) {
-                room.remove_user(addr);
+                let released_cards = room.remove_user(addr);
                 let count = room.user_count();
-                (count == 0, count)
+                (count == 0, count, released_cards)
             } else {
                 warn!("Room {} does not exist", board_id);
                 return;
@@ -353,31 +957,40 @@ impl ConnectionManager {
 
         info!("Client {} left board {} (user {})", addr, board_id, user_id);
 
-        // Broadcast UserLeft to remaining room members (local and remote)
-        let user_left = BinaryMessage::UserLeft { board_id, user_id };
-
-        // Publish to Redis for other instances
-        self.publish_to_redis(board_id, &user_left).await;
-
-        // Broadcast locally
-        self.broadcast_to_room(board_id, user_left, Some(addr))
-            .await;
-
-        // Send PresenceUpdate to remaining room members
-        if user_count > 0 {
-            let presence_update = BinaryMessage::PresenceUpdate {
+        // Claims end with the user; announced before UserLeft so clients still know the holder
+        for card_slot in released_cards {
+            let card_released = BinaryMessage::CardReleased {
                 board_id,
-                count: user_count as u8,
+                user_id,
+                card_slot,
             };
+            self.publish_to_redis(board_id, &card_released).await;
+            self.broadcast_to_room(board_id, card_released, Some(addr))
+                .await;
+        }
+
+        // A join still waiting to be announced is simply dropped
+        if !self.cancel_pending_join(board_id, addr).await {
+            // Broadcast UserLeft to remaining room members (local and remote)
+            let user_left = BinaryMessage::UserLeft { board_id, user_id };
 
             // Publish to Redis for other instances
-            self.publish_to_redis(board_id, &presence_update).await;
+            self.publish_to_redis(board_id, &user_left).await;
 
             // Broadcast locally
-            self.broadcast_to_room(board_id, presence_update, None)
+            self.broadcast_to_room(board_id, user_left, Some(addr))
                 .await;
         }
 
+        // Send PresenceUpdate to remaining room members
+        if user_count > 0 {
+            if self.presence_coalesce_window.is_zero() {
+                self.broadcast_presence(board_id, user_count).await;
+            } else {
+                self.queue_presence_change(board_id, None).await;
+            }
+        }
+
         // Clean up empty room
         if should_delete_room {
             let mut rooms = self.rooms.write().await;
@@ -387,7 +1000,105 @@ impl ConnectionManager {
     }
 
     /// Handle CursorUpdate message
-    async fn handle_cursor_update(&self, addr: SocketAddr, board_id: u16, x: u16, y: u16) {
+    async fn handle_cursor_update(&self, addr: SocketAddr, board_id: u32, x: u16, y: u16) {
+        trace!("Cursor update from {} in board {}", addr, board_id);
+
+        let interval = {
+            let sessions = self.sessions.read().await;
+            sessions
+                .get(&addr)
+                .map_or(Duration::ZERO, |session| session.cursor_interval())
+        };
+        if interval.is_zero() {
+            if self.cursor_tick.is_zero() {
+                self.broadcast_cursor_update(addr, board_id, x, y).await;
+            } else {
+                self.queue_room_cursor(addr, board_id, x, y).await;
+            }
+            return;
+        }
+
+        let key = (addr, board_id);
+        {
+            let mut pending = self.pending_cursors.lock().await;
+            if let Some(latest) = pending.get_mut(&key) {
+                // Inside the window only the newest position is kept
+                *latest = Some((x, y));
+                return;
+            }
+            pending.insert(key, None);
+        }
+
+        // The first update of a window goes out right away
+        self.broadcast_cursor_update(addr, board_id, x, y).await;
+        self.schedule_cursor_flush(key, interval);
+    }
+
+    /// Close a cursor coalescing window after `interval`
+    fn schedule_cursor_flush(&self, key: (SocketAddr, u32), interval: Duration) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(interval).await;
+            manager.flush_cursor(key, interval).await;
+        });
+    }
+
+    /// Broadcast the newest position held during a window
+    ///
+    /// Sending it opens the next window, so a client moving continuously is
+    /// broadcast once per interval. A window that received nothing is closed.
+    async fn flush_cursor(&self, key: (SocketAddr, u32), interval: Duration) {
+        let latest = {
+            let mut pending = self.pending_cursors.lock().await;
+            match pending.get_mut(&key).map(Option::take) {
+                Some(Some(position)) => Some(position),
+                Some(None) => {
+                    pending.remove(&key);
+                    None
+                }
+                None => None,
+            }
+        };
+
+        if let Some((x, y)) = latest {
+            let (addr, board_id) = key;
+            self.broadcast_cursor_update(addr, board_id, x, y).await;
+            self.schedule_cursor_flush(key, interval);
+        }
+    }
+
+    /// Hold a cursor position until the room's tick ends, replacing any held before
+    async fn queue_room_cursor(&self, addr: SocketAddr, board_id: u32, x: u16, y: u16) {
+        {
+            let mut room_cursors = self.room_cursors.lock().await;
+            if let Some(positions) = room_cursors.get_mut(&board_id) {
+                positions.insert(addr, (x, y));
+                return;
+            }
+            room_cursors.insert(board_id, HashMap::from([(addr, (x, y))]));
+        }
+
+        let manager = self.clone();
+        let tick = self.cursor_tick;
+        tokio::spawn(async move {
+            tokio::time::sleep(tick).await;
+            manager.flush_room_cursors(board_id).await;
+        });
+    }
+
+    /// Broadcast the newest position of each member that moved during the room's tick
+    async fn flush_room_cursors(&self, board_id: u32) {
+        let Some(positions) = self.room_cursors.lock().await.remove(&board_id) else {
+            return;
+        };
+
+        for (addr, (x, y)) in positions {
+            self.broadcast_cursor_update(addr, board_id, x, y).await;
+        }
+    }
+
+    /// Broadcast a cursor position to the rest of the room
+    async fn broadcast_cursor_update(&self, addr: SocketAddr, board_id: u32, x: u16, y: u16) {
         // Get user ID from session
         let user_id = {
             let sessions = self.sessions.read().await;
@@ -406,6 +1117,20 @@ impl ConnectionManager {
             }
         };
 
+        {
+            let mut rooms = self.rooms.write().await;
+            if let Some(room) = rooms.get_mut(&board_id) {
+                room.touch();
+                if room
+                    .move_cursor(addr, x, y, self.cursor_dead_zone)
+                    .is_none()
+                {
+                    trace!("Dropping cursor jitter from {} in board {}", addr, board_id);
+                    return;
+                }
+            }
+        }
+
         // Broadcast cursor position to other room members (local and remote)
         let cursor_broadcast = BinaryMessage::CursorBroadcast {
             board_id,
@@ -422,72 +1147,618 @@ impl ConnectionManager {
             .await;
     }
 
-    /// Handle Heartbeat message
-    async fn handle_heartbeat(&self, addr: SocketAddr) {
-        debug!("Heartbeat from {}", addr);
+    /// Handle CursorBatch message
+    ///
+    /// The last point becomes the user's cursor; the whole batch is forwarded
+    /// so others can interpolate the path. A batch already spans an interval,
+    /// so it bypasses cursor coalescing and the dead zone.
+    async fn handle_cursor_batch(&self, addr: SocketAddr, board_id: u32, points: Vec<(u16, u16)>) {
+        trace!(
+            "Cursor batch of {} points from {} in board {}",
+            points.len(),
+            addr,
+            board_id
+        );
 
-        // Send heartbeat response
+        let Some(&(x, y)) = points.last() else {
+            return;
+        };
+
+        let user_id = {
+            let mut rooms = self.rooms.write().await;
+            match rooms
+                .get_mut(&board_id)
+                .and_then(|room| room.move_cursor(addr, x, y, 0))
+            {
+                Some(user) => user.user_id,
+                None => {
+                    warn!("Client {} not in room {}", addr, board_id);
+                    return;
+                }
+            }
+        };
+
+        // A single update still waiting in a coalescing window or tick is now stale
+        if let Some(latest) = self.pending_cursors.lock().await.get_mut(&(addr, board_id)) {
+            *latest = None;
+        }
+        if let Some(positions) = self.room_cursors.lock().await.get_mut(&board_id) {
+            positions.remove(&addr);
+        }
+
+        let batch_broadcast = BinaryMessage::CursorBroadcastBatch {
+            board_id,
+            user_id,
+            points,
+        };
+
+        self.publish_to_redis(board_id, &batch_broadcast).await;
+        self.broadcast_to_room(board_id, batch_broadcast, Some(addr))
+            .await;
+    }
+
+    /// Handle FocusCard message
+    async fn handle_focus_card(&self, addr: SocketAddr, board_id: u32, card_slot: u16) {
+        // Only changes are broadcast; the room ignores repeats and non-members
+        let user_id = {
+            let mut rooms = self.rooms.write().await;
+            match rooms
+                .get_mut(&board_id)
+                .and_then(|room| room.set_focus(addr, card_slot))
+            {
+                Some(user) => user.user_id,
+                None => {
+                    debug!(
+                        "Ignoring focus of card {} by {} in room {}",
+                        card_slot, addr, board_id
+                    );
+                    return;
+                }
+            }
+        };
+
+        // Broadcast the new focus to other room members (local and remote)
+        let focus_broadcast = BinaryMessage::FocusBroadcast {
+            board_id,
+            user_id,
+            card_slot,
+        };
+
+        // Publish to Redis for other instances
+        self.publish_to_redis(board_id, &focus_broadcast).await;
+
+        // Broadcast locally
+        self.broadcast_to_room(board_id, focus_broadcast, Some(addr))
+            .await;
+    }
+
+    /// Handle ActivityUpdate message
+    ///
+    /// Relayed like a cursor update: nothing is remembered, so clients that
+    /// join later only learn of activity that changes after they arrive.
+    async fn handle_activity_update(
+        &self,
+        addr: SocketAddr,
+        board_id: u32,
+        card_ref: u32,
+        active: bool,
+    ) {
+        trace!(
+            "Activity on card {} from {} in board {}: {}",
+            card_ref,
+            addr,
+            board_id,
+            active
+        );
+
+        let user_id = {
+            let rooms = self.rooms.read().await;
+            match rooms.get(&board_id).and_then(|room| room.get_user(&addr)) {
+                Some(user) => user.user_id,
+                None => {
+                    warn!("Client {} not in room {}", addr, board_id);
+                    return;
+                }
+            }
+        };
+
+        let activity_broadcast = BinaryMessage::ActivityBroadcast {
+            board_id,
+            user_id,
+            card_ref,
+            active,
+        };
+
+        self.publish_to_redis(board_id, &activity_broadcast).await;
+        self.broadcast_to_room(board_id, activity_broadcast, Some(addr))
+            .await;
+    }
+
+    /// Handle ClaimCard message
+    ///
+    /// A granted claim is broadcast to the whole room, the requester
+    /// included, and to other instances. A claim on a card that is already
+    /// held is answered to the requester only, naming the holder.
+    ///
+    /// Claims are tracked per instance: users connected to different
+    /// instances can each be granted the same card.
+    async fn handle_claim_card(&self, addr: SocketAddr, board_id: u32, card_slot: u16) {
+        // Slot 0 names no card, as in FocusCard
+        if card_slot == NO_CARD_FOCUSED {
+            debug!("Ignoring claim of card slot 0 by {}", addr);
+            return;
+        }
+
+        let claim = {
+            let mut rooms = self.rooms.write().await;
+            rooms
+                .get_mut(&board_id)
+                .and_then(|room| room.claim_card(addr, card_slot))
+        };
+
+        match claim {
+            Some(CardClaim::Granted(user_id)) => {
+                debug!(
+                    "User {} claimed card {} in room {}",
+                    user_id, card_slot, board_id
+                );
+                let card_claimed = BinaryMessage::CardClaimed {
+                    board_id,
+                    user_id,
+                    card_slot,
+                };
+                self.publish_to_redis(board_id, &card_claimed).await;
+                self.broadcast_to_room(board_id, card_claimed, None).await;
+            }
+            Some(CardClaim::Held(holder)) => {
+                debug!(
+                    "Claim of card {} by {} in room {} refused: held by user {}",
+                    card_slot, addr, board_id, holder
+                );
+                let card_claimed = BinaryMessage::CardClaimed {
+                    board_id,
+                    user_id: holder,
+                    card_slot,
+                };
+                if let Err(e) = self.send_to_client(addr, card_claimed).await {
+                    warn!("Failed to answer claim of card {}: {}", card_slot, e);
+                }
+            }
+            None => {
+                warn!("Client {} not in room {}", addr, board_id);
+            }
+        }
+    }
+
+    /// Handle ReleaseCard message
+    ///
+    /// Broadcast to the whole room and to other instances; releases of cards
+    /// the sender does not hold are ignored.
+    async fn handle_release_card(&self, addr: SocketAddr, board_id: u32, card_slot: u16) {
+        let user_id = {
+            let mut rooms = self.rooms.write().await;
+            match rooms
+                .get_mut(&board_id)
+                .and_then(|room| room.release_card(addr, card_slot))
+            {
+                Some(user_id) => user_id,
+                None => {
+                    debug!(
+                        "Ignoring release of card {} by {} in room {}",
+                        card_slot, addr, board_id
+                    );
+                    return;
+                }
+            }
+        };
+
+        let card_released = BinaryMessage::CardReleased {
+            board_id,
+            user_id,
+            card_slot,
+        };
+        self.publish_to_redis(board_id, &card_released).await;
+        self.broadcast_to_room(board_id, card_released, None).await;
+    }
+
+    /// Handle QueryPresence message
+    ///
+    /// Answers with the board's current count, sent only to the asking
+    /// client. Room membership is left alone, so clients can ask about boards
+    /// they have not joined.
+    async fn handle_query_presence(&self, addr: SocketAddr, board_id: u32, msg_id: Option<u16>) {
+        let user_count = self.get_room_user_count(board_id).await;
+        let presence_update = presence_update(board_id, user_count);
+
+        if let Err(e) = self
+            .send_to_client(addr, presence_update.correlate(msg_id))
+            .await
+        {
+            warn!("Failed to send presence count to {}: {}", addr, e);
+        }
+    }
+
+    /// Handle Heartbeat message
+    async fn handle_heartbeat(&self, addr: SocketAddr) {
+        debug!("Heartbeat from {}", addr);
+
+        // Send heartbeat response
         let heartbeat = BinaryMessage::Heartbeat;
         if let Err(e) = self.send_to_client(addr, heartbeat).await {
             warn!("Failed to send heartbeat to {}: {}", addr, e);
+            return;
+        }
+
+        // v4+ clients also get a Ping so the round trip can be measured
+        let nonce = {
+            let mut sessions = self.sessions.write().await;
+            match sessions.get_mut(&addr) {
+                Some(session) if session.measures_rtt() => {
+                    let nonce = rand::random();
+                    session.start_ping(nonce, Instant::now());
+                    nonce
+                }
+                _ => return,
+            }
+        };
+
+        if let Err(e) = self
+            .send_to_client(addr, BinaryMessage::Ping { nonce })
+            .await
+        {
+            warn!("Failed to send ping to {}: {}", addr, e);
+        }
+    }
+
+    /// Handle Pong message by recording the connection's round-trip time
+    async fn handle_pong(&self, addr: SocketAddr, nonce: u32) {
+        let now = Instant::now();
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(&addr) else {
+            warn!("Session not found for {}", addr);
+            return;
+        };
+
+        match session.record_pong(nonce, now) {
+            Some(rtt) => trace!("RTT for {}: {:?}", addr, rtt),
+            None => debug!("Ignoring unexpected pong {} from {}", nonce, addr),
+        }
+    }
+
+    /// Most recently measured round-trip time for a client
+    pub async fn client_rtt(&self, addr: SocketAddr) -> Option<Duration> {
+        let sessions = self.sessions.read().await;
+        sessions.get(&addr).and_then(|session| session.rtt())
+    }
+
+    /// Tell a client that one of its frames failed to decode
+    ///
+    /// Only v11+ clients are told, and only of errors with a code in the
+    /// `types` table; the frame is dropped either way.
+    pub async fn report_protocol_error(&self, addr: SocketAddr, error: &ProtocolError) {
+        let Some(code) = error.error_code() else {
+            return;
+        };
+
+        let reports = {
+            let sessions = self.sessions.read().await;
+            sessions
+                .get(&addr)
+                .is_some_and(|session| session.reports_errors())
+        };
+        if !reports {
+            return;
+        }
+
+        let error = BinaryMessage::Error { code };
+        if let Err(e) = self.send_to_client(addr, error).await {
+            warn!("Failed to report protocol error to {}: {}", addr, e);
+        }
+    }
+
+    /// Handle Hello message by negotiating the protocol version
+    async fn handle_hello(&self, addr: SocketAddr, requested: u8, cursor_interval_ms: Option<u16>) {
+        let Some(version) = negotiate_version(requested) else {
+            self.reject_protocol_version(addr, requested).await;
+            return;
+        };
+
+        // The reply tells the client what coalescing window it got
+        let cursor_interval = cursor_interval_ms.map(|ms| {
+            Duration::from_millis(ms.into())
+                .clamp(self.cursor_interval_min, self.cursor_interval_max)
+        });
+
+        {
+            let mut sessions = self.sessions.write().await;
+            match sessions.get_mut(&addr) {
+                Some(session) => {
+                    session.set_protocol_version(version);
+                    if let Some(interval) = cursor_interval {
+                        session.set_cursor_interval(interval);
+                    }
+                }
+                None => {
+                    warn!("Session not found for {}", addr);
+                    return;
+                }
+            }
+        }
+
+        debug!(
+            "Client {} requested protocol v{}, using v{}",
+            addr, requested, version
+        );
+
+        let hello = BinaryMessage::Hello {
+            version,
+            cursor_interval_ms: cursor_interval
+                .map(|interval| u16::try_from(interval.as_millis()).unwrap_or(u16::MAX)),
+        };
+        if let Err(e) = self.send_to_client(addr, hello).await {
+            warn!("Failed to send hello to {}: {}", addr, e);
+        }
+    }
+
+    /// Disconnect clients that have sent nothing for longer than `timeout`
+    ///
+    /// Evicted clients leave their rooms the normal way, so the rest of the
+    /// room sees `UserLeft` and an updated `PresenceUpdate`. Their sockets are
+    /// sent a close frame in case they are still reading. Returns how many
+    /// clients were evicted.
+    pub async fn evict_idle(&self, timeout: Duration, now: Instant) -> usize {
+        let idle: Vec<SocketAddr> = {
+            let sessions = self.sessions.read().await;
+            sessions
+                .values()
+                .filter(|session| session.idle_for(now) > timeout)
+                .map(|session| session.addr())
+                .collect()
+        };
+
+        for &addr in &idle {
+            info!("Evicting {}: idle for more than {:?}", addr, timeout);
+            if let Some(tx) = self.connections.read().await.get(&addr) {
+                let _ = tx.send(Message::Close(Some(CloseFrame {
+                    code: CloseCode::Away,
+                    reason: "idle timeout".into(),
+                })));
+            }
+            self.disconnect(addr).await;
+        }
+
+        idle.len()
+    }
+
+    /// Remove every local user from their rooms and close all connections
+    ///
+    /// Users leave the normal way, so other instances learn of each
+    /// `UserLeft` through Redis and update their presence counts instead of
+    /// waiting for heartbeats to time out. The close frames are queued after
+    /// the leave notifications. Returns how many connections were closed.
+    pub async fn shutdown(&self) -> usize {
+        let connections: Vec<(SocketAddr, UnboundedSender<Message>)> = self
+            .connections
+            .read()
+            .await
+            .iter()
+            .map(|(addr, tx)| (*addr, tx.clone()))
+            .collect();
+
+        for (addr, _) in &connections {
+            self.disconnect(*addr).await;
+        }
+
+        for (_, tx) in &connections {
+            let _ = tx.send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Away,
+                reason: "server shutting down".into(),
+            })));
+        }
+
+        info!("Shut down {} connections", connections.len());
+        connections.len()
+    }
+
+    /// Close a client whose Hello asked for a version below `MIN_PROTOCOL_VERSION`
+    ///
+    /// Such a client encodes `board_id` as u16, so its frames would be
+    /// misread; closing with a reason lets it tell the user to reload.
+    async fn reject_protocol_version(&self, addr: SocketAddr, requested: u8) {
+        warn!(
+            "Closing {}: protocol v{} is below the minimum v{}",
+            addr, requested, MIN_PROTOCOL_VERSION
+        );
+
+        let connections = self.connections.read().await;
+        if let Some(tx) = connections.get(&addr) {
+            let _ = tx.send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Protocol,
+                reason: format!("protocol v{} or later required", MIN_PROTOCOL_VERSION).into(),
+            })));
+        }
+    }
+
+    /// Take the most users seen at once in each board since the last call
+    ///
+    /// Only joins on this instance are counted. Boards that are still
+    /// occupied carry their current user count into the next period, so a
+    /// period without joins still records the users who stayed.
+    pub async fn take_presence_peaks(&self) -> HashMap<u32, usize> {
+        let rooms = self.rooms.read().await;
+        let mut peaks = self.presence_peaks.lock().await;
+        let taken = std::mem::take(&mut *peaks);
+        for (board_id, room) in rooms.iter() {
+            if room.user_count() > 0 {
+                peaks.insert(*board_id, room.user_count());
+            }
         }
+        taken
+    }
+
+    /// Traffic metrics shared by all connections
+    pub fn metrics(&self) -> &TrafficMetrics {
+        &self.metrics
+    }
+
+    /// Hits and misses of the Redis payload decode cache
+    pub fn decode_cache_stats(&self) -> &DecodeCacheStats {
+        &self.decode_cache_stats
+    }
+
+    /// Current server tick in milliseconds (wraps after ~49 days)
+    fn cursor_tick(&self) -> u32 {
+        self.started_at.elapsed().as_millis() as u32
     }
 
     /// Broadcast a message to all users in a room
+    ///
+    /// Returns the number of local clients the message was queued for.
     async fn broadcast_to_room(
         &self,
-        board_id: u16,
+        board_id: u32,
         message: BinaryMessage,
         exclude: Option<SocketAddr>,
-    ) {
+    ) -> usize {
         // Get all user addresses in the room
         let user_addrs = {
             let rooms = self.rooms.read().await;
             match rooms.get(&board_id) {
-                Some(room) => room.user_addresses().iter().copied().collect::<Vec<_>>(),
+                Some(room) => room.user_addresses().to_vec(),
                 None => {
                     debug!("Room {} does not exist for broadcast", board_id);
-                    return;
+                    return 0;
                 }
             }
         };
 
-        // Encode message once
-        let encoded = message.encode();
-        let ws_message = Message::Binary(encoded.into());
+        self.send_to_addresses(user_addrs, &message, exclude).await
+    }
 
-        // Send to all users except the excluded one
-        let connections = self.connections.read().await;
-        for user_addr in user_addrs {
-            if let Some(excluded) = exclude {
-                if user_addr == excluded {
-                    continue;
-                }
+    /// Send a message to a set of local clients
+    ///
+    /// Senders are snapshotted under the connections/sessions locks, which are
+    /// released before fanning out so joins and leaves are not blocked by
+    /// large rooms.
+    ///
+    /// Returns the number of clients the message was queued for.
+    async fn send_to_addresses(
+        &self,
+        user_addrs: Vec<SocketAddr>,
+        message: &BinaryMessage,
+        exclude: Option<SocketAddr>,
+    ) -> usize {
+        let recipients = {
+            let locked_at = Instant::now();
+            let connections = self.connections.read().await;
+            let sessions = self.sessions.read().await;
+
+            let recipients: Vec<Recipient> = user_addrs
+                .into_iter()
+                .filter(|user_addr| Some(*user_addr) != exclude)
+                .filter_map(|user_addr| {
+                    let tx = connections.get(&user_addr)?.clone();
+                    let session = sessions.get(&user_addr);
+                    Some(Recipient {
+                        addr: user_addr,
+                        tx,
+                        version: session
+                            .map(|session| session.protocol_version())
+                            .unwrap_or(PROTOCOL_VERSION_V1),
+                        sequence: session.and_then(|session| session.outgoing_sequence().cloned()),
+                    })
+                })
+                .collect();
+
+            debug!(
+                "Broadcast snapshot of {} recipients held locks for {:?}",
+                recipients.len(),
+                locked_at.elapsed()
+            );
+            recipients
+        };
+
+        if recipients.is_empty() {
+            return 0;
+        }
+
+        let _permit = match self.broadcast_permits.acquire().await {
+            Ok(permit) => permit,
+            Err(e) => {
+                error!("Broadcast semaphore closed: {}", e);
+                return 0;
             }
+        };
 
-            if let Some(tx) = connections.get(&user_addr) {
-                if let Err(e) = tx.send(ws_message.clone()) {
-                    warn!("Failed to send message to {}: {}", user_addr, e);
-                }
+        // Encode message once per protocol version in use, all into one buffer
+        let tick = self.cursor_tick();
+        let mut scratch = BytesMut::new();
+        let mut encoded: HashMap<u8, Bytes> = HashMap::new();
+        let mut delivered = 0;
+
+        for recipient in recipients {
+            let frame = encoded.entry(recipient.version).or_insert_with(|| {
+                message
+                    .clone()
+                    .for_protocol_version(recipient.version, tick)
+                    .encode_into(&mut scratch);
+                scratch.split().freeze()
+            });
+
+            match self.deliver(&recipient.tx, frame, recipient.sequence.as_ref()) {
+                Ok(()) => delivered += 1,
+                Err(e) => warn!("Failed to send message to {}: {}", recipient.addr, e),
             }
         }
+
+        if delivered > 0 {
+            self.broadcast_metrics.record(message, delivered);
+        }
+        delivered
     }
 
     /// Send a message to a specific client
     async fn send_to_client(&self, addr: SocketAddr, message: BinaryMessage) -> Result<(), String> {
         let encoded = message.encode();
-        let ws_message = Message::Binary(encoded.into());
 
         let connections = self.connections.read().await;
+        let sessions = self.sessions.read().await;
         if let Some(tx) = connections.get(&addr) {
-            tx.send(ws_message)
-                .map_err(|e| format!("Send error: {}", e))?;
-            Ok(())
+            let sequence = sessions
+                .get(&addr)
+                .and_then(|session| session.outgoing_sequence());
+            self.deliver(tx, &encoded, sequence)
+                .map_err(|e| format!("Send error: {}", e))
         } else {
             Err(format!("Client {} not found", addr))
         }
     }
 
+    /// Queue an encoded frame on a client's channel and record it
+    ///
+    /// Frames for v3+ clients are wrapped with the connection's next sequence
+    /// number.
+    fn deliver(
+        &self,
+        tx: &UnboundedSender<Message>,
+        frame: &[u8],
+        sequence: Option<&OutgoingSequence>,
+    ) -> Result<(), SendError<Message>> {
+        let send = |frame: Vec<u8>| {
+            let frame = Bytes::from(frame);
+            let result = tx.send(Message::Binary(frame.clone()));
+            if result.is_ok() {
+                self.metrics.record_sent(&frame);
+            }
+            result
+        };
+
+        match sequence {
+            Some(sequence) => sequence.send_numbered(frame, send),
+            None => send(frame.to_vec()),
+        }
+    }
+
     /// Generate a random cursor color (RGB)
     fn generate_color() -> [u8; 3] {
         use rand::Rng;
@@ -525,19 +1796,160 @@ impl ConnectionManager {
         }
     }
 
+    /// Take a snapshot of all local rooms, sorted by board ID
+    pub async fn snapshot(&self) -> Vec<RoomSnapshot> {
+        let rooms = self.rooms.read().await;
+        let sessions = self.sessions.read().await;
+        let mut snapshot: Vec<RoomSnapshot> = rooms
+            .values()
+            .map(|room| {
+                let rtts: Vec<Duration> = room
+                    .user_addresses()
+                    .iter()
+                    .filter_map(|addr| sessions.get(addr).and_then(|session| session.rtt()))
+                    .collect();
+                let average_rtt_ms = (!rtts.is_empty()).then(|| {
+                    let total: Duration = rtts.iter().sum();
+                    (total / rtts.len() as u32).as_millis() as u64
+                });
+
+                RoomSnapshot {
+                    board_id: room.board_id(),
+                    user_count: room.user_count(),
+                    idle_seconds: room.idle_duration().as_secs(),
+                    average_rtt_ms,
+                    roster_digest: format!("{:016x}", room.digest()),
+                }
+            })
+            .collect();
+        snapshot.sort_by_key(|room| room.board_id);
+        snapshot
+    }
+
+    /// Forcibly remove every user from a room and drop it
+    ///
+    /// Each removed user is announced with a UserLeft message to the local
+    /// clients that were in the room and to other instances via Redis.
+    /// The connections themselves stay open so clients can rejoin.
+    ///
+    /// Returns the number of users removed, or `None` if the room does not exist.
+    pub async fn force_clear_room(&self, board_id: u32) -> Option<usize> {
+        self.remove_room(board_id, true).await
+    }
+
+    /// Remove every user from a local room and drop it
+    ///
+    /// UserLeft messages go to the room's local clients, and to other
+    /// instances too when `publish` is set.
+    async fn remove_room(&self, board_id: u32, publish: bool) -> Option<usize> {
+        let room = {
+            let mut rooms = self.rooms.write().await;
+            rooms.remove(&board_id)?
+        };
+
+        let users: Vec<UserInfo> = room.users().cloned().collect();
+
+        // Detach the board from every affected session
+        {
+            let mut sessions = self.sessions.write().await;
+            for user in &users {
+                if let Some(session) = sessions.get_mut(&user.addr) {
+                    session.remove_board(board_id);
+                }
+            }
+        }
+
+        let recipients: Vec<SocketAddr> = users.iter().map(|user| user.addr).collect();
+        for user in &users {
+            let user_left = BinaryMessage::UserLeft {
+                board_id,
+                user_id: user.user_id,
+            };
+
+            // Publish to Redis for other instances
+            if publish {
+                self.publish_to_redis(board_id, &user_left).await;
+            }
+
+            // Notify local clients that were in the room
+            self.send_to_addresses(recipients.clone(), &user_left, None)
+                .await;
+        }
+
+        info!("Cleared room {} ({} users removed)", board_id, users.len());
+
+        Some(users.len())
+    }
+
+    /// Send an Announcement to every client in a room, on all instances
+    ///
+    /// Used by the admin endpoint to check end-to-end delivery. The room does
+    /// not need to exist locally since other instances may have members.
+    ///
+    /// Returns the number of local clients the announcement was queued for.
+    pub async fn announce(&self, board_id: u32, text: String) -> Result<usize, ProtocolError> {
+        if text.len() > MAX_ANNOUNCEMENT_LENGTH {
+            return Err(ProtocolError::AnnouncementTooLong(text.len()));
+        }
+
+        let announcement = BinaryMessage::Announcement { board_id, text };
+
+        // Publish to Redis for other instances
+        self.publish_to_redis(board_id, &announcement).await;
+
+        // Broadcast locally
+        let delivered = self.broadcast_to_room(board_id, announcement, None).await;
+
+        info!(
+            "Announced to room {} ({} local clients)",
+            board_id, delivered
+        );
+
+        Ok(delivered)
+    }
+
     /// Get current user count for a board (for testing/debugging)
     #[allow(dead_code)]
-    pub async fn get_room_user_count(&self, board_id: u16) -> usize {
+    pub async fn get_room_user_count(&self, board_id: u32) -> usize {
         let rooms = self.rooms.read().await;
         rooms.get(&board_id).map(|r| r.user_count()).unwrap_or(0)
     }
 
+    /// Number of client connections registered on this instance
+    pub async fn connection_count(&self) -> usize {
+        self.connections.read().await.len()
+    }
+
     /// Get current room count (for testing/debugging)
     #[allow(dead_code)]
     pub async fn get_room_count(&self) -> usize {
         let rooms = self.rooms.read().await;
         rooms.len()
     }
+
+    /// Take a snapshot of connection, room and message counts
+    pub async fn metrics_snapshot(&self) -> MetricsSnapshot {
+        let room_users: BTreeMap<u32, usize> = {
+            let rooms = self.rooms.read().await;
+            rooms
+                .iter()
+                .map(|(board_id, room)| (*board_id, room.user_count()))
+                .collect()
+        };
+        let traffic = self.metrics.snapshot();
+        let broadcasts = self.broadcast_metrics.snapshot();
+
+        MetricsSnapshot {
+            connections: self.connection_count().await,
+            connections_opened: self.connections_opened.load(Ordering::Relaxed),
+            rooms: room_users.len(),
+            room_users,
+            messages_received: traffic.received.total_messages,
+            messages_sent: traffic.sent.total_messages,
+            messages_broadcast: broadcasts.total_messages,
+            broadcasts,
+        }
+    }
 }
 
 // Note: Default trait removed because ConnectionManager now requires Redis
@@ -560,4 +1972,1717 @@ mod tests {
     }
 
     // Note: test_manager_creation removed - requires Redis client for initialization
+
+    async fn create_manager() -> ConnectionManager {
+        ConnectionManager::new(Arc::new(RedisPubSub::in_memory()))
+    }
+
+    /// Messages queued for a client, taken out of their `Sequenced` envelopes
+    fn drain(rx: &mut tokio::sync::mpsc::UnboundedReceiver<Message>) -> Vec<BinaryMessage> {
+        let mut messages = Vec::new();
+        while let Ok(Message::Binary(data)) = rx.try_recv() {
+            match BinaryMessage::decode(&data).unwrap() {
+                BinaryMessage::Sequenced { message, .. } => messages.push(*message),
+                message => messages.push(message),
+            }
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_force_clear_room() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let addr1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9001);
+        let addr2 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9002);
+        let (tx1, mut rx1) = tokio::sync::mpsc::unbounded_channel();
+        let (tx2, mut rx2) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(addr1, tx1).await;
+        manager.connect(addr2, tx2).await;
+        for (addr, name) in [(addr1, "Alice"), (addr2, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 7,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx1);
+        drain(&mut rx2);
+
+        let snapshot = manager.snapshot().await;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].board_id, 7);
+        assert_eq!(snapshot[0].user_count, 2);
+
+        assert_eq!(manager.force_clear_room(7).await, Some(2));
+        assert_eq!(manager.get_room_user_count(7).await, 0);
+        assert_eq!(manager.get_room_count().await, 0);
+        assert_eq!(manager.force_clear_room(7).await, None);
+
+        for rx in [&mut rx1, &mut rx2] {
+            let mut left: Vec<u8> = drain(rx)
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    BinaryMessage::UserLeft {
+                        board_id: 7,
+                        user_id,
+                    } => Some(user_id),
+                    _ => None,
+                })
+                .collect();
+            left.sort();
+            assert_eq!(left, vec![0, 1]);
+        }
+
+        // Sessions no longer reference the cleared board, so clients can rejoin
+        manager
+            .handle_message(
+                addr1,
+                BinaryMessage::Join {
+                    board_id: 7,
+                    username: "Alice".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        assert_eq!(manager.get_room_user_count(7).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_broadcast_selected_per_client_version() {
+        use crate::protocol::PROTOCOL_VERSION;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let sender = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9011);
+        let v1_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9012);
+        let latest_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9013);
+        let (tx_sender, _rx_sender) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_v1, mut rx_v1) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_latest, mut rx_latest) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(sender, tx_sender).await;
+        manager.connect(v1_addr, tx_v1).await;
+        manager.connect(latest_addr, tx_latest).await;
+
+        manager
+            .handle_message(
+                latest_addr,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        assert_eq!(
+            drain(&mut rx_latest),
+            vec![BinaryMessage::Hello {
+                version: PROTOCOL_VERSION,
+                cursor_interval_ms: None,
+            }]
+        );
+
+        for (addr, name) in [(sender, "Alice"), (v1_addr, "Bob"), (latest_addr, "Carol")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 8,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_v1);
+        drain(&mut rx_latest);
+
+        manager
+            .handle_message(
+                sender,
+                BinaryMessage::CursorUpdate {
+                    board_id: 8,
+                    x: 10,
+                    y: 20,
+                },
+            )
+            .await;
+
+        // The client that never sent Hello keeps the 10-byte form
+        assert!(matches!(
+            drain(&mut rx_v1).as_slice(),
+            [BinaryMessage::CursorBroadcast { x: 10, y: 20, .. }]
+        ));
+        assert!(matches!(
+            drain(&mut rx_latest).as_slice(),
+            [BinaryMessage::CursorBroadcastTimed { x: 10, y: 20, .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_releases_connections_lock_before_sending() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let mut receivers = Vec::new();
+        for port in 0..200 {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 10000 + port);
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            manager.connect(addr, tx).await;
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 9,
+                        username: format!("User{}", port),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+            receivers.push(rx);
+        }
+        for rx in &mut receivers {
+            drain(rx);
+        }
+
+        // Hold every permit so the broadcast parks between snapshot and fan-out
+        let permits = manager
+            .broadcast_permits
+            .acquire_many(MAX_IN_FLIGHT_BROADCASTS as u32)
+            .await
+            .unwrap();
+
+        let broadcaster = manager.clone();
+        let broadcast = tokio::spawn(async move {
+            broadcaster
+                .broadcast_to_room(
+                    9,
+                    BinaryMessage::PresenceUpdate {
+                        board_id: 9,
+                        count: 200,
+                    },
+                    None,
+                )
+                .await;
+        });
+
+        // Let the broadcast snapshot its recipients and park on the semaphore
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Another task can take the write lock while the broadcast is pending
+        let write = tokio::time::timeout(Duration::from_secs(1), manager.connections.write()).await;
+        assert!(write.is_ok(), "connections lock held during fan-out");
+        drop(write);
+        assert!(!broadcast.is_finished());
+
+        drop(permits);
+        broadcast.await.unwrap();
+
+        for rx in &mut receivers {
+            assert_eq!(
+                drain(rx),
+                vec![BinaryMessage::PresenceUpdate {
+                    board_id: 9,
+                    count: 200
+                }]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_focus_broadcast_and_join_roster() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9021);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9022);
+        let carol = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9023);
+        let (tx_alice, mut rx_alice) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_bob, mut rx_bob) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_carol, mut rx_carol) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(alice, tx_alice).await;
+        manager.connect(bob, tx_bob).await;
+        manager.connect(carol, tx_carol).await;
+        for (addr, name) in [(alice, "Alice"), (bob, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 10,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_alice);
+        drain(&mut rx_bob);
+
+        let focus = BinaryMessage::FocusCard {
+            board_id: 10,
+            card_slot: 5,
+        };
+        manager.handle_message(alice, focus.clone()).await;
+        // Repeating the same focus is not re-broadcast
+        manager.handle_message(alice, focus).await;
+
+        assert_eq!(
+            drain(&mut rx_bob),
+            vec![BinaryMessage::FocusBroadcast {
+                board_id: 10,
+                user_id: 0,
+                card_slot: 5,
+            }]
+        );
+        assert!(drain(&mut rx_alice).is_empty());
+
+        // A late joiner learns the current focus along with the roster
+        manager
+            .handle_message(
+                carol,
+                BinaryMessage::Join {
+                    board_id: 10,
+                    username: "Carol".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        let roster = drain(&mut rx_carol);
+        assert!(roster.contains(&BinaryMessage::FocusBroadcast {
+            board_id: 10,
+            user_id: 0,
+            card_slot: 5,
+        }));
+        assert!(!roster
+            .iter()
+            .any(|msg| matches!(msg, BinaryMessage::FocusBroadcast { user_id: 1, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_sequence_numbers_for_v3_clients() {
+        use crate::protocol::PROTOCOL_VERSION;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let sender = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9031);
+        let v3_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9032);
+        let (tx_sender, mut rx_sender) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_v3, mut rx_v3) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(sender, tx_sender).await;
+        manager.connect(v3_addr, tx_v3).await;
+        manager
+            .handle_message(
+                v3_addr,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        for (addr, name) in [(sender, "Alice"), (v3_addr, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 11,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        for i in 0..50 {
+            manager
+                .handle_message(
+                    sender,
+                    BinaryMessage::CursorUpdate {
+                        board_id: 11,
+                        x: i,
+                        y: i,
+                    },
+                )
+                .await;
+        }
+
+        let frames = |rx: &mut tokio::sync::mpsc::UnboundedReceiver<Message>| {
+            let mut frames = Vec::new();
+            while let Ok(Message::Binary(data)) = rx.try_recv() {
+                frames.push(BinaryMessage::decode(&data).unwrap());
+            }
+            frames
+        };
+
+        // The v1 sender gets plain frames
+        assert!(frames(&mut rx_sender)
+            .iter()
+            .all(|msg| !matches!(msg, BinaryMessage::Sequenced { .. })));
+
+        // Every frame to the v3 client is numbered from 0 without gaps
+        let received = frames(&mut rx_v3);
+        assert!(received.len() > 50);
+        for (expected, msg) in received.iter().enumerate() {
+            match msg {
+                BinaryMessage::Sequenced { seq, .. } => assert_eq!(*seq, expected as u32),
+                other => panic!("expected sequenced frame, got {:?}", other),
+            }
+        }
+        assert!(matches!(
+            received.last(),
+            Some(BinaryMessage::Sequenced { message, .. })
+                if matches!(**message, BinaryMessage::CursorBroadcastTimed { x: 49, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_announce_delivered_to_room_members() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let member = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9041);
+        let outsider = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9042);
+        let (tx_member, mut rx_member) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_outsider, mut rx_outsider) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(member, tx_member).await;
+        manager.connect(outsider, tx_outsider).await;
+        manager
+            .handle_message(
+                member,
+                BinaryMessage::Join {
+                    board_id: 12,
+                    username: "Alice".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        drain(&mut rx_member);
+
+        let delivered = manager
+            .announce(12, "smoke test".to_string())
+            .await
+            .unwrap();
+        assert_eq!(delivered, 1);
+
+        assert_eq!(
+            drain(&mut rx_member),
+            vec![BinaryMessage::Announcement {
+                board_id: 12,
+                text: "smoke test".to_string(),
+            }]
+        );
+        assert!(drain(&mut rx_outsider).is_empty());
+
+        // Rooms without local members still publish, but reach nobody here
+        assert_eq!(manager.announce(13, "hi".to_string()).await.unwrap(), 0);
+        assert!(matches!(
+            manager.announce(12, "a".repeat(256)).await,
+            Err(ProtocolError::AnnouncementTooLong(256))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pong_records_rtt_for_v4_clients() {
+        use crate::protocol::PROTOCOL_VERSION;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let v1_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9051);
+        let v4_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9052);
+        let (tx_v1, mut rx_v1) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_v4, mut rx_v4) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(v1_addr, tx_v1).await;
+        manager.connect(v4_addr, tx_v4).await;
+        manager
+            .handle_message(
+                v4_addr,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        for addr in [v1_addr, v4_addr] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 14,
+                        username: "Alice".to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_v1);
+        drain(&mut rx_v4);
+
+        // v1 keeps the plain heartbeat echo
+        manager
+            .handle_message(v1_addr, BinaryMessage::Heartbeat)
+            .await;
+        assert_eq!(drain(&mut rx_v1), vec![BinaryMessage::Heartbeat]);
+
+        // v4 gets a Ping after the echo
+        manager
+            .handle_message(v4_addr, BinaryMessage::Heartbeat)
+            .await;
+        let nonce = match drain(&mut rx_v4).as_slice() {
+            [BinaryMessage::Heartbeat, BinaryMessage::Ping { nonce }] => *nonce,
+            other => panic!("expected heartbeat and ping, got {:?}", other),
+        };
+        assert_eq!(manager.client_rtt(v4_addr).await, None);
+
+        // A wrong nonce is ignored; the matching one records the RTT
+        manager
+            .handle_message(
+                v4_addr,
+                BinaryMessage::Pong {
+                    nonce: nonce.wrapping_add(1),
+                },
+            )
+            .await;
+        assert_eq!(manager.client_rtt(v4_addr).await, None);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        manager
+            .handle_message(v4_addr, BinaryMessage::Pong { nonce })
+            .await;
+        let rtt = manager.client_rtt(v4_addr).await.unwrap();
+        assert!(rtt >= Duration::from_millis(20));
+
+        let snapshot = manager.snapshot().await;
+        let room = snapshot.iter().find(|room| room.board_id == 14).unwrap();
+        assert_eq!(room.average_rtt_ms, Some(rtt.as_millis() as u64));
+        assert_eq!(manager.client_rtt(v1_addr).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_published_deletion_evicts_room() {
+        use crate::redis::control::BoardStateChange;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let pubsub = RedisPubSub::in_memory();
+        let manager = Arc::new(ConnectionManager::new(Arc::new(pubsub.clone())));
+        let member = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9061);
+        let (tx_member, mut rx_member) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(member, tx_member).await;
+        manager
+            .handle_message(
+                member,
+                BinaryMessage::Join {
+                    board_id: 15,
+                    username: "Alice".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        drain(&mut rx_member);
+
+        Arc::clone(&manager).start_redis_listener().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Publish the way the backend does, from outside the manager
+        let deleted = ControlMessage {
+            board_id: 15,
+            event: BoardStateChange::Deleted,
+        };
+        pubsub
+            .publish(&RedisPubSub::control_channel(), &deleted.encode())
+            .await
+            .unwrap();
+
+        for _ in 0..20 {
+            if manager.get_room_user_count(15).await == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(manager.get_room_user_count(15).await, 0);
+
+        let received = drain(&mut rx_member);
+        assert_eq!(
+            received.first(),
+            Some(&BinaryMessage::Announcement {
+                board_id: 15,
+                text: BoardStateChange::Deleted.announcement().to_string(),
+            })
+        );
+        assert!(matches!(
+            received.last(),
+            Some(BinaryMessage::UserLeft { board_id: 15, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_join_burst_coalesces_presence_broadcasts() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let window = Duration::from_millis(100);
+        let manager = create_manager().await.with_presence_coalesce_window(window);
+
+        let observer = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 11000);
+        let (tx_observer, mut rx_observer) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(observer, tx_observer).await;
+        manager
+            .handle_message(
+                observer,
+                BinaryMessage::Join {
+                    board_id: 16,
+                    username: "Observer".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        tokio::time::sleep(window * 2).await;
+        drain(&mut rx_observer);
+
+        let mut addrs = Vec::new();
+        let mut receivers = Vec::new();
+        for port in 1..=50 {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 11000 + port);
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            manager.connect(addr, tx).await;
+            addrs.push(addr);
+            receivers.push(rx);
+        }
+
+        let joins: Vec<_> = addrs
+            .iter()
+            .enumerate()
+            .map(|(i, &addr)| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    manager
+                        .handle_message(
+                            addr,
+                            BinaryMessage::Join {
+                                board_id: 16,
+                                username: format!("User{}", i),
+                                reconnect_token: None,
+                            },
+                        )
+                        .await;
+                })
+            })
+            .collect();
+        for join in joins {
+            join.await.unwrap();
+        }
+
+        // The last joiner leaves before its join is announced
+        manager.disconnect(addrs[49]).await;
+
+        tokio::time::sleep(window * 3).await;
+
+        let received = drain(&mut rx_observer);
+        let presence_counts: Vec<u16> = received
+            .iter()
+            .filter_map(|message| match message {
+                BinaryMessage::PresenceUpdate { count, .. } => Some(*count),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            presence_counts.len() < 5,
+            "{} presence broadcasts for 50 joins",
+            presence_counts.len()
+        );
+        assert_eq!(presence_counts.last(), Some(&50));
+
+        // Everyone still in the room is known, and nobody is told about the leaver
+        let mut joined: Vec<u8> = received
+            .iter()
+            .filter_map(|message| match message {
+                BinaryMessage::UserJoined { user_id, .. } => Some(*user_id),
+                _ => None,
+            })
+            .collect();
+        joined.sort_unstable();
+        joined.dedup();
+        assert_eq!(joined.len(), 49);
+        assert!(!received
+            .iter()
+            .any(|message| matches!(message, BinaryMessage::UserLeft { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_cursor_dead_zone_drops_micro_movements() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await.with_cursor_dead_zone(50);
+        let sender = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9021);
+        let viewer = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9022);
+        let (tx_sender, _rx_sender) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_viewer, mut rx_viewer) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(sender, tx_sender).await;
+        manager.connect(viewer, tx_viewer).await;
+        for (addr, name) in [(sender, "Alice"), (viewer, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 17,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_viewer);
+
+        // First position, jitter inside the dead zone, then a real move
+        for (x, y) in [(1000, 1000), (1020, 990), (1049, 1049), (1050, 1000)] {
+            manager
+                .handle_message(sender, BinaryMessage::CursorUpdate { board_id: 17, x, y })
+                .await;
+        }
+
+        let positions: Vec<(u16, u16)> = drain(&mut rx_viewer)
+            .into_iter()
+            .filter_map(|message| match message {
+                BinaryMessage::CursorBroadcast { x, y, .. } => Some((x, y)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(positions, vec![(1000, 1000), (1050, 1000)]);
+    }
+
+    #[tokio::test]
+    async fn test_query_presence_answers_without_joining() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let asker = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9031);
+        let (tx_asker, mut rx_asker) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(asker, tx_asker).await;
+
+        // Nobody is on the board yet
+        manager
+            .handle_message(asker, BinaryMessage::QueryPresence { board_id: 18 })
+            .await;
+        assert_eq!(
+            drain(&mut rx_asker),
+            vec![BinaryMessage::PresenceUpdate {
+                board_id: 18,
+                count: 0
+            }]
+        );
+
+        let mut members = Vec::new();
+        for (port, name) in [(9032, "Alice"), (9033, "Bob")] {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port);
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            manager.connect(addr, tx).await;
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 18,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+            members.push(rx);
+        }
+        for rx in &mut members {
+            drain(rx);
+        }
+
+        manager
+            .handle_message(asker, BinaryMessage::QueryPresence { board_id: 18 })
+            .await;
+        assert_eq!(
+            drain(&mut rx_asker),
+            vec![BinaryMessage::PresenceUpdate {
+                board_id: 18,
+                count: 2
+            }]
+        );
+
+        // Asking neither joins the board nor notifies its members
+        assert_eq!(manager.get_room_user_count(18).await, 2);
+        for rx in &mut members {
+            assert!(drain(rx).is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_correlated_requests_echo_message_id() {
+        use crate::protocol::PROTOCOL_VERSION;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let v5_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9061);
+        let v1_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9062);
+        let (tx_v5, mut rx_v5) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_v1, mut rx_v1) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(v5_addr, tx_v5).await;
+        manager.connect(v1_addr, tx_v1).await;
+        manager
+            .handle_message(
+                v5_addr,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        drain(&mut rx_v5);
+
+        let join = BinaryMessage::Join {
+            board_id: 19,
+            username: "Alice".to_string(),
+            reconnect_token: None,
+        };
+
+        // The joiner's own UserJoined comes back under the request's id
+        manager
+            .handle_message(v5_addr, join.clone().correlate(Some(0x1234)))
+            .await;
+        let received: Vec<_> = drain(&mut rx_v5);
+        let acks: Vec<_> = received
+            .iter()
+            .filter_map(|msg| match msg {
+                BinaryMessage::Correlated { msg_id, message } => Some((*msg_id, &**message)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(acks.len(), 1);
+        let (msg_id, ack) = acks[0];
+        assert_eq!(msg_id, 0x1234);
+        match ack {
+            BinaryMessage::UserJoined {
+                board_id, username, ..
+            } => {
+                assert_eq!(*board_id, 19);
+                assert_eq!(username, "Alice");
+            }
+            other => panic!("expected UserJoined, got {:?}", other),
+        }
+
+        // Re-joining is acknowledged too
+        manager
+            .handle_message(v5_addr, join.correlate(Some(0x1235)))
+            .await;
+        let received: Vec<_> = drain(&mut rx_v5);
+        assert!(matches!(
+            received.as_slice(),
+            [BinaryMessage::Correlated { msg_id: 0x1235, .. }]
+        ));
+
+        let query = BinaryMessage::QueryPresence { board_id: 19 };
+        manager
+            .handle_message(v5_addr, query.clone().correlate(Some(0xFFFF)))
+            .await;
+        let received: Vec<_> = drain(&mut rx_v5);
+        assert_eq!(
+            received,
+            vec![BinaryMessage::PresenceUpdate {
+                board_id: 19,
+                count: 1
+            }
+            .correlate(Some(0xFFFF))]
+        );
+
+        // Below v5 the request is still handled, but the reply is not correlated
+        manager
+            .handle_message(v1_addr, query.correlate(Some(7)))
+            .await;
+        assert_eq!(
+            drain(&mut rx_v1),
+            vec![BinaryMessage::PresenceUpdate {
+                board_id: 19,
+                count: 1
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cursor_updates_coalesced_per_client_interval() {
+        use crate::protocol::PROTOCOL_VERSION;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager()
+            .await
+            .with_cursor_interval_range(Duration::ZERO, Duration::from_millis(100));
+        let observer = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9071);
+        let coalesced = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9072);
+        let passthrough = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9073);
+        let (tx_observer, mut rx_observer) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_coalesced, mut rx_coalesced) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_passthrough, mut rx_passthrough) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(observer, tx_observer).await;
+        manager.connect(coalesced, tx_coalesced).await;
+        manager.connect(passthrough, tx_passthrough).await;
+
+        for (addr, rx, interval) in [
+            (coalesced, &mut rx_coalesced, 50),
+            (passthrough, &mut rx_passthrough, 0),
+        ] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Hello {
+                        version: PROTOCOL_VERSION,
+                        cursor_interval_ms: Some(interval),
+                    },
+                )
+                .await;
+            let received: Vec<_> = drain(rx);
+            assert_eq!(
+                received,
+                vec![BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: Some(interval),
+                }]
+            );
+        }
+
+        for (addr, name) in [
+            (observer, "Alice"),
+            (coalesced, "Bob"),
+            (passthrough, "Carol"),
+        ] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 20,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drain(&mut rx_observer);
+
+        let cursor_xs = |messages: Vec<BinaryMessage>| -> Vec<u16> {
+            messages
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    BinaryMessage::CursorBroadcast { x, .. } => Some(x),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // The 50ms client's burst: the first update at once, the newest at the window's end
+        for x in 0..10 {
+            manager
+                .handle_message(
+                    coalesced,
+                    BinaryMessage::CursorUpdate {
+                        board_id: 20,
+                        x,
+                        y: 0,
+                    },
+                )
+                .await;
+        }
+        assert_eq!(cursor_xs(drain(&mut rx_observer)), vec![0]);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(cursor_xs(drain(&mut rx_observer)), vec![9]);
+
+        // The 0ms client's updates all pass through
+        for x in 0..10 {
+            manager
+                .handle_message(
+                    passthrough,
+                    BinaryMessage::CursorUpdate {
+                        board_id: 20,
+                        x,
+                        y: 0,
+                    },
+                )
+                .await;
+        }
+        assert_eq!(
+            cursor_xs(drain(&mut rx_observer)),
+            (0..10).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_sends_existing_roster_to_joiner_only() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9081);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9082);
+        let carol = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9083);
+        let (tx_alice, mut rx_alice) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_bob, mut rx_bob) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_carol, mut rx_carol) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(alice, tx_alice).await;
+        manager.connect(bob, tx_bob).await;
+        manager.connect(carol, tx_carol).await;
+
+        for (addr, name) in [(alice, "Alice"), (bob, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 30,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_alice);
+        drain(&mut rx_bob);
+
+        manager
+            .handle_message(
+                carol,
+                BinaryMessage::Join {
+                    board_id: 30,
+                    username: "Carol".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+
+        // Carol hears about Alice and Bob, with their colors, but not herself
+        let expected: Vec<BinaryMessage> = {
+            let rooms = manager.rooms.read().await;
+            let mut existing: Vec<_> = rooms[&30]
+                .users()
+                .filter(|user| user.addr != carol)
+                .map(|user| BinaryMessage::UserJoined {
+                    board_id: 30,
+                    user_id: user.user_id,
+                    username: user.username.clone(),
+                    color: user.color,
+                })
+                .collect();
+            existing.sort_by_key(|msg| match msg {
+                BinaryMessage::UserJoined { user_id, .. } => *user_id,
+                _ => unreachable!(),
+            });
+            existing
+        };
+        let mut joined: Vec<_> = drain(&mut rx_carol)
+            .into_iter()
+            .filter(|msg| matches!(msg, BinaryMessage::UserJoined { .. }))
+            .collect();
+        joined.sort_by_key(|msg| match msg {
+            BinaryMessage::UserJoined { user_id, .. } => *user_id,
+            _ => unreachable!(),
+        });
+        assert_eq!(joined.len(), 2);
+        assert_eq!(joined, expected);
+        assert!(joined.iter().all(|msg| !matches!(
+            msg,
+            BinaryMessage::UserJoined { username, .. } if username == "Carol"
+        )));
+
+        // The roster goes only to Carol: the others just hear that she joined
+        for rx in [&mut rx_alice, &mut rx_bob] {
+            let joined: Vec<_> = drain(rx)
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    BinaryMessage::UserJoined { username, .. } => Some(username),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(joined, vec!["Carol".to_string()]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hello_below_minimum_version_closed() {
+        use crate::protocol::{PROTOCOL_VERSION, PROTOCOL_VERSION_V6};
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let old = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9091);
+        let current = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9092);
+        let (tx_old, mut rx_old) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_current, mut rx_current) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(old, tx_old).await;
+        manager.connect(current, tx_current).await;
+
+        // A client built for u16 board ids is told why instead of being misread
+        manager
+            .handle_message(
+                old,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION_V6,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        match rx_old.try_recv() {
+            Ok(Message::Close(Some(frame))) => assert_eq!(frame.code, CloseCode::Protocol),
+            other => panic!("expected close frame, got {:?}", other),
+        }
+
+        // Board ids past the old u16 range get their own rooms
+        manager
+            .handle_message(
+                current,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        for board_id in [0x0001_0007, 0x0002_0007] {
+            manager
+                .handle_message(
+                    current,
+                    BinaryMessage::Join {
+                        board_id,
+                        username: "Alice".to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        assert!(!drain(&mut rx_current).is_empty());
+        assert_eq!(manager.get_room_user_count(0x0001_0007).await, 1);
+        assert_eq!(manager.get_room_user_count(0x0002_0007).await, 1);
+        assert_eq!(manager.get_room_user_count(7).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_compact_cursor_update_broadcast_at_full_width() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let sender = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9101);
+        let viewer = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9102);
+        let (tx_sender, _rx_sender) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_viewer, mut rx_viewer) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(sender, tx_sender).await;
+        manager.connect(viewer, tx_viewer).await;
+        for (addr, name) in [(sender, "Alice"), (viewer, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 20,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_viewer);
+
+        manager
+            .handle_message(
+                sender,
+                BinaryMessage::CursorUpdateCompact {
+                    board_id: 20,
+                    x: 4095,
+                    y: 0,
+                },
+            )
+            .await;
+
+        let positions: Vec<(u16, u16)> = drain(&mut rx_viewer)
+            .into_iter()
+            .filter_map(|message| match message {
+                BinaryMessage::CursorBroadcast { x, y, .. } => Some((x, y)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(positions, vec![(u16::MAX, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_batch_forwarded_whole_to_v9_clients() {
+        use crate::protocol::PROTOCOL_VERSION;
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let sender = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9103);
+        let v1_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9104);
+        let latest_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9105);
+        let (tx_sender, _rx_sender) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_v1, mut rx_v1) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_latest, mut rx_latest) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(sender, tx_sender).await;
+        manager.connect(v1_addr, tx_v1).await;
+        manager.connect(latest_addr, tx_latest).await;
+        manager
+            .handle_message(
+                latest_addr,
+                BinaryMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    cursor_interval_ms: None,
+                },
+            )
+            .await;
+        for (addr, name) in [(sender, "Alice"), (v1_addr, "Bob"), (latest_addr, "Carol")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 21,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_v1);
+        drain(&mut rx_latest);
+
+        let points = vec![(10, 20), (30, 40), (50, 60)];
+        manager
+            .handle_message(
+                sender,
+                BinaryMessage::CursorBatch {
+                    board_id: 21,
+                    points: points.clone(),
+                },
+            )
+            .await;
+
+        // Older clients only see where the cursor ended up
+        assert!(matches!(
+            drain(&mut rx_v1).as_slice(),
+            [BinaryMessage::CursorBroadcast { x: 50, y: 60, .. }]
+        ));
+        assert!(matches!(
+            drain(&mut rx_latest).as_slice(),
+            [BinaryMessage::CursorBroadcastBatch { points: sent, .. }] if *sent == points
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_evict_idle_leaves_room() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let idle_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9106);
+        let active_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9107);
+        let (tx_idle, mut rx_idle) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_active, mut rx_active) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(idle_addr, tx_idle).await;
+        manager.connect(active_addr, tx_active).await;
+        for (addr, name) in [(idle_addr, "Alice"), (active_addr, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 22,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        assert_eq!(manager.get_room_user_count(22).await, 2);
+
+        // Only Bob keeps sending heartbeats
+        let timeout = Duration::from_secs(90);
+        let later = Instant::now() + Duration::from_secs(60);
+        manager
+            .sessions
+            .write()
+            .await
+            .get_mut(&active_addr)
+            .unwrap()
+            .touch(later);
+        drain(&mut rx_active);
+
+        let evicted = manager
+            .evict_idle(timeout, later + Duration::from_secs(45))
+            .await;
+        assert_eq!(evicted, 1);
+        assert_eq!(manager.get_room_user_count(22).await, 1);
+        assert_eq!(manager.connection_count().await, 1);
+
+        // The idle socket is told why it is being closed
+        let close = std::iter::from_fn(|| rx_idle.try_recv().ok())
+            .find_map(|message| match message {
+                Message::Close(frame) => frame,
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(close.code, CloseCode::Away);
+
+        // Bob sees Alice leave through the normal path
+        let received = drain(&mut rx_active);
+        assert!(received
+            .iter()
+            .any(|message| matches!(message, BinaryMessage::UserLeft { board_id: 22, .. })));
+        assert!(received.iter().any(|message| matches!(
+            message,
+            BinaryMessage::PresenceUpdate {
+                board_id: 22,
+                count: 1
+            }
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_presence_count_past_255_users() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+
+        // Fill the room with 255 users that have no connection on this instance
+        {
+            let mut room = Room::new(23);
+            for i in 0..255u16 {
+                let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 20000 + i);
+                let user_id = room.assign_user_id().unwrap();
+                room.add_user(addr, user_id, format!("User {}", i), [0, 0, 0]);
+            }
+            manager.rooms.write().await.insert(23, room);
+        }
+
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9108);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(addr, tx).await;
+        manager
+            .handle_message(
+                addr,
+                BinaryMessage::Join {
+                    board_id: 23,
+                    username: "Alice".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        assert_eq!(manager.get_room_user_count(23).await, 256);
+
+        // The 256th user is counted instead of wrapping to zero
+        assert!(drain(&mut rx).contains(&BinaryMessage::PresenceUpdate {
+            board_id: 23,
+            count: 256
+        }));
+
+        manager
+            .handle_message(addr, BinaryMessage::QueryPresence { board_id: 23 })
+            .await;
+        assert_eq!(
+            drain(&mut rx),
+            vec![BinaryMessage::PresenceUpdate {
+                board_id: 23,
+                count: 256
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_activity_update_broadcast_to_others() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9109);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9110);
+        let (tx_alice, mut rx_alice) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_bob, mut rx_bob) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(alice, tx_alice).await;
+        manager.connect(bob, tx_bob).await;
+        for (addr, name) in [(alice, "Alice"), (bob, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 24,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_alice);
+        drain(&mut rx_bob);
+
+        manager
+            .handle_message(
+                alice,
+                BinaryMessage::ActivityUpdate {
+                    board_id: 24,
+                    card_ref: 0xDEAD_BEEF,
+                    active: true,
+                },
+            )
+            .await;
+
+        assert_eq!(
+            drain(&mut rx_bob),
+            vec![BinaryMessage::ActivityBroadcast {
+                board_id: 24,
+                user_id: 0,
+                card_ref: 0xDEAD_BEEF,
+                active: true,
+            }]
+        );
+        // The sender already knows what it is editing
+        assert!(drain(&mut rx_alice).is_empty());
+
+        // Activity on a board the client has not joined is dropped
+        manager
+            .handle_message(
+                alice,
+                BinaryMessage::ActivityUpdate {
+                    board_id: 25,
+                    card_ref: 1,
+                    active: true,
+                },
+            )
+            .await;
+        assert!(drain(&mut rx_bob).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_card_claim_contention_and_release_on_leave() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9117);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9118);
+        let carol = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9119);
+        let (tx_alice, mut rx_alice) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_bob, mut rx_bob) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_carol, mut rx_carol) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(alice, tx_alice).await;
+        manager.connect(bob, tx_bob).await;
+        manager.connect(carol, tx_carol).await;
+        for (addr, name) in [(alice, "Alice"), (bob, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 26,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_alice);
+        drain(&mut rx_bob);
+
+        let claim = BinaryMessage::ClaimCard {
+            board_id: 26,
+            card_slot: 9,
+        };
+        let alice_holds = BinaryMessage::CardClaimed {
+            board_id: 26,
+            user_id: 0,
+            card_slot: 9,
+        };
+
+        // The grant reaches the whole room, Alice included
+        manager.handle_message(alice, claim.clone()).await;
+        assert_eq!(drain(&mut rx_alice), vec![alice_holds.clone()]);
+        assert_eq!(drain(&mut rx_bob), vec![alice_holds.clone()]);
+
+        // Bob's competing claim is answered to him alone
+        manager.handle_message(bob, claim.clone()).await;
+        assert_eq!(drain(&mut rx_bob), vec![alice_holds.clone()]);
+        assert!(drain(&mut rx_alice).is_empty());
+
+        // A late joiner learns the claim after the roster
+        manager
+            .handle_message(
+                carol,
+                BinaryMessage::Join {
+                    board_id: 26,
+                    username: "Carol".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        let roster = drain(&mut rx_carol);
+        let claim_at = roster.iter().position(|msg| *msg == alice_holds).unwrap();
+        let alice_at = roster
+            .iter()
+            .position(|msg| matches!(msg, BinaryMessage::UserJoined { user_id: 0, .. }))
+            .unwrap();
+        assert!(alice_at < claim_at);
+        drain(&mut rx_bob);
+
+        // Leaving releases the claim before Alice is reported gone
+        manager
+            .handle_message(alice, BinaryMessage::Leave { board_id: 26 })
+            .await;
+        let messages = drain(&mut rx_bob);
+        assert_eq!(
+            messages[..2],
+            [
+                BinaryMessage::CardReleased {
+                    board_id: 26,
+                    user_id: 0,
+                    card_slot: 9,
+                },
+                BinaryMessage::UserLeft {
+                    board_id: 26,
+                    user_id: 0,
+                },
+            ]
+        );
+
+        manager.handle_message(bob, claim).await;
+        assert_eq!(
+            drain(&mut rx_bob),
+            vec![BinaryMessage::CardClaimed {
+                board_id: 26,
+                user_id: 1,
+                card_slot: 9,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors_reported_only_from_v11() {
+        use crate::protocol::{ERROR_INVALID_LENGTH, PROTOCOL_VERSION_V10, PROTOCOL_VERSION_V11};
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let older = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9120);
+        let latest = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9121);
+        let (tx_older, mut rx_older) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_latest, mut rx_latest) = tokio::sync::mpsc::unbounded_channel();
+
+        manager.connect(older, tx_older).await;
+        manager.connect(latest, tx_latest).await;
+        for (addr, version) in [
+            (older, PROTOCOL_VERSION_V10),
+            (latest, PROTOCOL_VERSION_V11),
+        ] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Hello {
+                        version,
+                        cursor_interval_ms: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_older);
+        drain(&mut rx_latest);
+
+        let error = BinaryMessage::decode(&[0x04, 0, 0]).unwrap_err();
+        manager.report_protocol_error(older, &error).await;
+        manager.report_protocol_error(latest, &error).await;
+        assert!(drain(&mut rx_older).is_empty());
+        assert_eq!(
+            drain(&mut rx_latest),
+            vec![BinaryMessage::Error {
+                code: ERROR_INVALID_LENGTH
+            }]
+        );
+
+        // Errors without a code are not reported
+        manager
+            .report_protocol_error(latest, &ProtocolError::InvalidUtf8)
+            .await;
+        assert!(drain(&mut rx_latest).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_token_keeps_user_id_and_color() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9122);
+        let alice_again = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9123);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9124);
+        let (tx_alice, _rx_alice) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_alice_again, _rx_alice_again) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_bob, mut rx_bob) = tokio::sync::mpsc::unbounded_channel();
+        let join = |username: &str| BinaryMessage::Join {
+            board_id: 27,
+            username: username.to_string(),
+            reconnect_token: Some([9; 16]),
+        };
+
+        manager.connect(alice, tx_alice).await;
+        manager.connect(bob, tx_bob).await;
+        manager.handle_message(alice, join("Alice")).await;
+        manager
+            .handle_message(
+                bob,
+                BinaryMessage::Join {
+                    board_id: 27,
+                    username: "Bob".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+        let alice_joined = drain(&mut rx_bob)
+            .into_iter()
+            .find(|msg| matches!(msg, BinaryMessage::UserJoined { user_id: 0, .. }))
+            .unwrap();
+
+        // Alice drops and comes back on a new connection with the same token
+        manager.disconnect(alice).await;
+        manager.connect(alice_again, tx_alice_again).await;
+        manager.handle_message(alice_again, join("Alice")).await;
+
+        let messages = drain(&mut rx_bob);
+        assert!(messages.contains(&BinaryMessage::UserLeft {
+            board_id: 27,
+            user_id: 0,
+        }));
+        assert!(messages.contains(&alice_joined));
+    }
+
+    #[tokio::test]
+    async fn test_presence_peak_outlasts_users_leaving() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let addrs: Vec<SocketAddr> = (9125..9128)
+            .map(|port| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port))
+            .collect();
+        for (i, addr) in addrs.iter().enumerate() {
+            let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+            manager.connect(*addr, tx).await;
+            manager
+                .handle_message(
+                    *addr,
+                    BinaryMessage::Join {
+                        board_id: 28,
+                        username: format!("User{}", i),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+
+        // Two of the three leave before the peaks are taken
+        manager.disconnect(addrs[0]).await;
+        manager.disconnect(addrs[1]).await;
+
+        let peaks = manager.take_presence_peaks().await;
+        assert_eq!(peaks.get(&28), Some(&3));
+
+        // The next period starts from the user who stayed
+        let peaks = manager.take_presence_peaks().await;
+        assert_eq!(peaks.get(&28), Some(&1));
+
+        manager.disconnect(addrs[2]).await;
+        let peaks = manager.take_presence_peaks().await;
+        assert_eq!(peaks.get(&28), Some(&1));
+        assert!(manager.take_presence_peaks().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_empties_rooms() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager().await;
+        let mut receivers = Vec::new();
+        for (port, board_id) in [(9129, 30), (9130, 30), (9131, 31)] {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port);
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            manager.connect(addr, tx).await;
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id,
+                        username: format!("User{}", port),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+            receivers.push(rx);
+        }
+        assert_eq!(manager.get_room_count().await, 2);
+
+        assert_eq!(manager.shutdown().await, 3);
+
+        assert_eq!(manager.get_room_count().await, 0);
+        assert_eq!(manager.connection_count().await, 0);
+        for rx in &mut receivers {
+            let mut close = None;
+            while let Ok(message) = rx.try_recv() {
+                if let Message::Close(frame) = message {
+                    close = frame;
+                }
+            }
+            assert_eq!(close.unwrap().code, CloseCode::Away);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cursor_updates_coalesced_per_room_tick() {
+        use std::net::{IpAddr, Ipv4Addr};
+
+        let manager = create_manager()
+            .await
+            .with_cursor_tick(Duration::from_millis(50));
+        let observer = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9132);
+        let mover = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9133);
+        let (tx_observer, mut rx_observer) = tokio::sync::mpsc::unbounded_channel();
+        let (tx_mover, _rx_mover) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(observer, tx_observer).await;
+        manager.connect(mover, tx_mover).await;
+        for (addr, name) in [(observer, "Alice"), (mover, "Bob")] {
+            manager
+                .handle_message(
+                    addr,
+                    BinaryMessage::Join {
+                        board_id: 32,
+                        username: name.to_string(),
+                        reconnect_token: None,
+                    },
+                )
+                .await;
+        }
+        drain(&mut rx_observer);
+
+        let cursor_xs = |messages: Vec<BinaryMessage>| -> Vec<u16> {
+            messages
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    BinaryMessage::CursorBroadcast { x, .. } => Some(x),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // Ten moves within one tick go out as a single broadcast of the newest
+        for x in 0..10 {
+            manager
+                .handle_message(
+                    mover,
+                    BinaryMessage::CursorUpdate {
+                        board_id: 32,
+                        x,
+                        y: 0,
+                    },
+                )
+                .await;
+        }
+        assert!(cursor_xs(drain(&mut rx_observer)).is_empty());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(cursor_xs(drain(&mut rx_observer)), vec![9]);
+
+        // Nothing more is sent once the tick has been flushed
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(cursor_xs(drain(&mut rx_observer)).is_empty());
+    }
 }
@@ -1,3 +1,4 @@
 pub mod manager;
+pub mod rate_limit;
 pub mod room;
 pub mod session;
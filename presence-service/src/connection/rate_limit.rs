@@ -0,0 +1,181 @@
+//! Per-IP limit on new connections.
+//!
+//! The accept loop asks the limiter before upgrading a socket, so a single
+//! host cannot churn connections faster than the configured rate. Each IP
+//! keeps the times of its recent accepted connections in a sliding window;
+//! IPs with nothing left in the window are swept out periodically so the
+//! table does not grow with every address ever seen.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Sliding-window limit on new connections per IP
+#[derive(Debug)]
+pub struct ConnectionRateLimiter {
+    /// Connections allowed per IP within `window` (0 disables the limit)
+    max_per_window: usize,
+    window: Duration,
+    /// Accepted connection times per IP, oldest first
+    recent: HashMap<IpAddr, VecDeque<Instant>>,
+    last_sweep: Instant,
+}
+
+impl ConnectionRateLimiter {
+    /// Allow at most `max_per_window` new connections per IP within `window`
+    pub fn new(max_per_window: u32, window: Duration) -> Self {
+        Self {
+            max_per_window: max_per_window as usize,
+            window,
+            recent: HashMap::new(),
+            last_sweep: Instant::now(),
+        }
+    }
+
+    /// Record a connection attempt from `ip` at `now`
+    ///
+    /// Returns false if the IP is over its limit; rejected attempts are not
+    /// recorded, so a host that backs off is let in again after one window.
+    pub fn check(&mut self, ip: IpAddr, now: Instant) -> bool {
+        if self.max_per_window == 0 {
+            return true;
+        }
+
+        if now.saturating_duration_since(self.last_sweep) >= self.window {
+            self.sweep(now);
+        }
+
+        let times = self.recent.entry(ip).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) >= self.window)
+        {
+            times.pop_front();
+        }
+
+        if times.len() >= self.max_per_window {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+
+    /// How long until `ip` may open another connection
+    ///
+    /// Zero if it is under its limit at `now`. Sent to rejected clients as a
+    /// retry hint.
+    pub fn retry_after(&self, ip: IpAddr, now: Instant) -> Duration {
+        match self.recent.get(&ip) {
+            Some(times) if self.max_per_window > 0 && times.len() >= self.max_per_window => {
+                times.front().map_or(Duration::ZERO, |oldest| {
+                    self.window
+                        .saturating_sub(now.saturating_duration_since(*oldest))
+                })
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Number of IPs currently tracked
+    pub fn tracked_ips(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// Forget IPs whose connections have all left the window
+    fn sweep(&mut self, now: Instant) {
+        let window = self.window;
+        self.recent.retain(|_, times| {
+            times
+                .back()
+                .is_some_and(|t| now.saturating_duration_since(*t) < window)
+        });
+        self.last_sweep = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+    }
+
+    #[test]
+    fn test_rapid_connections_over_limit_rejected() {
+        let mut limiter = ConnectionRateLimiter::new(10, WINDOW);
+        let start = Instant::now();
+
+        // 25 connections from one host within 250ms: only the first 10 get in
+        let accepted = (0..25)
+            .filter(|i| limiter.check(ip(1), start + Duration::from_millis(i * 10)))
+            .count();
+        assert_eq!(accepted, 10);
+
+        // Other hosts are unaffected
+        assert!(limiter.check(ip(2), start + Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let mut limiter = ConnectionRateLimiter::new(2, WINDOW);
+        let start = Instant::now();
+
+        assert!(limiter.check(ip(1), start));
+        assert!(limiter.check(ip(1), start + Duration::from_millis(500)));
+        assert!(!limiter.check(ip(1), start + Duration::from_millis(900)));
+
+        // The first connection has left the window, the second has not
+        assert!(limiter.check(ip(1), start + Duration::from_millis(1000)));
+        assert!(!limiter.check(ip(1), start + Duration::from_millis(1400)));
+        assert!(limiter.check(ip(1), start + Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_retry_after_until_oldest_leaves_window() {
+        let mut limiter = ConnectionRateLimiter::new(2, WINDOW);
+        let start = Instant::now();
+
+        assert!(limiter.check(ip(1), start + Duration::from_millis(200)));
+        assert_eq!(
+            limiter.retry_after(ip(1), start + Duration::from_millis(300)),
+            Duration::ZERO
+        );
+        assert!(limiter.check(ip(1), start + Duration::from_millis(400)));
+
+        let now = start + Duration::from_millis(500);
+        assert!(!limiter.check(ip(1), now));
+        assert_eq!(limiter.retry_after(ip(1), now), Duration::from_millis(700));
+        assert!(limiter.check(ip(1), now + Duration::from_millis(700)));
+
+        // Unknown hosts may connect straight away
+        assert_eq!(limiter.retry_after(ip(2), now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_idle_ips_evicted() {
+        let mut limiter = ConnectionRateLimiter::new(5, WINDOW);
+        let start = Instant::now();
+
+        for last in 1..=50 {
+            assert!(limiter.check(ip(last), start));
+        }
+        assert_eq!(limiter.tracked_ips(), 50);
+
+        // The next check after a full window sweeps out the idle hosts
+        assert!(limiter.check(ip(99), start + WINDOW));
+        assert_eq!(limiter.tracked_ips(), 1);
+    }
+
+    #[test]
+    fn test_zero_disables_limit() {
+        let mut limiter = ConnectionRateLimiter::new(0, WINDOW);
+        let now = Instant::now();
+
+        assert!((0..1000).all(|_| limiter.check(ip(1), now)));
+        assert_eq!(limiter.tracked_ips(), 0);
+    }
+}
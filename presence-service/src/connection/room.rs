@@ -1,5 +1,41 @@
+use crate::protocol::{ReconnectToken, MAX_USERNAME_LENGTH, NO_CARD_FOCUSED};
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// FNV-1a 64-bit offset basis, the starting value of a roster digest
+const DIGEST_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a 64-bit prime
+const DIGEST_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// How long a departed user's ID and color stay reserved for their reconnect token
+///
+/// Reservations live in the room, so they end early if the room empties and
+/// is dropped.
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(15);
+
+/// Clean up a client-supplied username
+///
+/// Trims surrounding whitespace, drops control characters and truncates to
+/// `MAX_USERNAME_LENGTH` bytes on a character boundary. Returns `None` if
+/// nothing is left.
+pub fn sanitize_username(username: &str) -> Option<String> {
+    let mut sanitized = String::new();
+    for c in username.trim().chars().filter(|c| !c.is_control()) {
+        if sanitized.len() + c.len_utf8() > MAX_USERNAME_LENGTH {
+            break;
+        }
+        sanitized.push(c);
+    }
+
+    let sanitized = sanitized.trim_end();
+    if sanitized.is_empty() {
+        None
+    } else {
+        Some(sanitized.to_string())
+    }
+}
 
 /// Information about a user in a room
 #[derive(Debug, Clone)]
@@ -8,13 +44,36 @@ pub struct UserInfo {
     pub user_id: u8,
     pub username: String,
     pub color: [u8; 3],
+    /// Card slot the user currently has open (`NO_CARD_FOCUSED` if none)
+    pub focused_card: u16,
+    /// Last cursor position broadcast for the user, if any
+    pub cursor: Option<(u16, u16)>,
+    /// Token the user joined with, if any; reserves their seat when they leave
+    pub reconnect_token: Option<ReconnectToken>,
+}
+
+/// A user ID and color held for a departed user's reconnect token
+#[derive(Debug, Clone, Copy)]
+struct DepartedUser {
+    user_id: u8,
+    color: [u8; 3],
+    left_at: Instant,
+}
+
+/// Outcome of a claim on a card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardClaim {
+    /// The card was free and now belongs to the requester, whose user ID this is
+    Granted(u8),
+    /// The card was already claimed, possibly by the requester; holds the holder's user ID
+    Held(u8),
 }
 
 /// Represents a board room where users collaborate
 #[derive(Debug)]
 pub struct Room {
     /// Board ID
-    board_id: u16,
+    board_id: u32,
 
     /// Map of socket addresses to user info
     users: HashMap<SocketAddr, UserInfo>,
@@ -24,11 +83,20 @@ pub struct Room {
 
     /// Set of currently assigned user IDs
     assigned_ids: HashSet<u8>,
+
+    /// Claimed card slots and the address of the user holding each
+    claims: HashMap<u16, SocketAddr>,
+
+    /// Seats of users who left with a reconnect token; their IDs stay assigned
+    departed: HashMap<ReconnectToken, DepartedUser>,
+
+    /// Last time a user joined, left or moved their cursor in this room
+    last_activity: Instant,
 }
 
 impl Room {
     /// Create a new room
-    pub fn new(board_id: u16) -> Self {
+    pub fn new(board_id: u32) -> Self {
         // Initialize all 256 IDs as available (0-255)
         let available_ids: HashSet<u8> = (0..=255).collect();
 
@@ -37,11 +105,14 @@ impl Room {
             users: HashMap::new(),
             available_ids,
             assigned_ids: HashSet::new(),
+            claims: HashMap::new(),
+            departed: HashMap::new(),
+            last_activity: Instant::now(),
         }
     }
 
     /// Get board ID
-    pub fn board_id(&self) -> u16 {
+    pub fn board_id(&self) -> u32 {
         self.board_id
     }
 
@@ -62,22 +133,215 @@ impl Room {
         self.available_ids.insert(id);
     }
 
+    /// Give a joining user back the seat held for `token`, or a fresh user ID
+    ///
+    /// Returns the user ID and, when the seat was reclaimed, the color the
+    /// user had before. Seats older than `RECONNECT_GRACE` at `now` are pruned
+    /// first. If every ID is taken, the oldest held seat is given up to make
+    /// room. Returns `None` if the room is full.
+    pub fn reclaim_or_assign(
+        &mut self,
+        token: Option<&ReconnectToken>,
+        now: Instant,
+    ) -> Option<(u8, Option<[u8; 3]>)> {
+        self.prune_departed(now);
+
+        if let Some(departed) = token.and_then(|token| self.departed.remove(token)) {
+            return Some((departed.user_id, Some(departed.color)));
+        }
+
+        if self.available_ids.is_empty() {
+            let oldest = self
+                .departed
+                .iter()
+                .min_by_key(|(_, departed)| departed.left_at)
+                .map(|(token, _)| *token);
+            if let Some(departed) = oldest.and_then(|token| self.departed.remove(&token)) {
+                self.release_user_id(departed.user_id);
+            }
+        }
+
+        self.assign_user_id().map(|user_id| (user_id, None))
+    }
+
+    /// Release the seats of departed users whose grace window ended by `now`
+    pub fn prune_departed(&mut self, now: Instant) {
+        let expired: Vec<ReconnectToken> = self
+            .departed
+            .iter()
+            .filter(|(_, departed)| {
+                now.saturating_duration_since(departed.left_at) >= RECONNECT_GRACE
+            })
+            .map(|(token, _)| *token)
+            .collect();
+        for token in expired {
+            if let Some(departed) = self.departed.remove(&token) {
+                self.release_user_id(departed.user_id);
+            }
+        }
+    }
+
+    /// Set the reconnect token of a user in the room
+    pub fn set_reconnect_token(&mut self, addr: SocketAddr, token: ReconnectToken) {
+        if let Some(user) = self.users.get_mut(&addr) {
+            user.reconnect_token = Some(token);
+        }
+    }
+
     /// Add a user to the room
+    ///
+    /// `user_id` is marked as taken, whether or not it came from
+    /// `assign_user_id` or `reclaim_or_assign`.
     pub fn add_user(&mut self, addr: SocketAddr, user_id: u8, username: String, color: [u8; 3]) {
+        self.available_ids.remove(&user_id);
+        self.assigned_ids.insert(user_id);
+
         let user_info = UserInfo {
             addr,
             user_id,
             username,
             color,
+            focused_card: NO_CARD_FOCUSED,
+            cursor: None,
+            reconnect_token: None,
         };
         self.users.insert(addr, user_info);
+        self.touch();
     }
 
-    /// Remove a user from the room
-    pub fn remove_user(&mut self, addr: SocketAddr) {
+    /// Remove a user from the room, releasing every card they claimed
+    ///
+    /// A user who joined with a reconnect token keeps their user ID and
+    /// color reserved for `RECONNECT_GRACE`. Returns the card slots the user
+    /// held.
+    pub fn remove_user(&mut self, addr: SocketAddr) -> Vec<u16> {
         if let Some(user_info) = self.users.remove(&addr) {
-            self.release_user_id(user_info.user_id);
+            match user_info.reconnect_token {
+                Some(token) => {
+                    let departed = DepartedUser {
+                        user_id: user_info.user_id,
+                        color: user_info.color,
+                        left_at: Instant::now(),
+                    };
+                    // A token still holding an older seat gives that one up
+                    if let Some(replaced) = self.departed.insert(token, departed) {
+                        self.release_user_id(replaced.user_id);
+                    }
+                }
+                None => self.release_user_id(user_info.user_id),
+            }
+        }
+        self.touch();
+
+        let released: Vec<u16> = self
+            .claims
+            .iter()
+            .filter(|(_, holder)| **holder == addr)
+            .map(|(card_slot, _)| *card_slot)
+            .collect();
+        for card_slot in &released {
+            self.claims.remove(card_slot);
+        }
+        released
+    }
+
+    /// Rename a user in the room
+    ///
+    /// The new name is sanitized first. Returns the updated user info, or
+    /// `None` if the user is not in the room or the name is empty after
+    /// sanitizing.
+    pub fn rename_user(&mut self, addr: SocketAddr, new_username: &str) -> Option<&UserInfo> {
+        let username = sanitize_username(new_username)?;
+        let user = self.users.get_mut(&addr)?;
+        user.username = username;
+        Some(user)
+    }
+
+    /// Set the card a user has focused
+    ///
+    /// Returns the updated user info if the focus changed, or `None` if the
+    /// user is not in the room or already focuses that card.
+    pub fn set_focus(&mut self, addr: SocketAddr, card_slot: u16) -> Option<&UserInfo> {
+        let user = self.users.get_mut(&addr)?;
+        if user.focused_card == card_slot {
+            return None;
         }
+        user.focused_card = card_slot;
+        self.touch();
+        self.users.get(&addr)
+    }
+
+    /// Claim a card for a user
+    ///
+    /// Returns `None` if the user is not in the room.
+    pub fn claim_card(&mut self, addr: SocketAddr, card_slot: u16) -> Option<CardClaim> {
+        let user_id = self.users.get(&addr)?.user_id;
+        if let Some(holder) = self.claims.get(&card_slot) {
+            return self
+                .users
+                .get(holder)
+                .map(|holder| CardClaim::Held(holder.user_id));
+        }
+
+        self.claims.insert(card_slot, addr);
+        self.touch();
+        Some(CardClaim::Granted(user_id))
+    }
+
+    /// Release a user's claim on a card
+    ///
+    /// Returns the user's ID, or `None` if they do not hold the card.
+    pub fn release_card(&mut self, addr: SocketAddr, card_slot: u16) -> Option<u8> {
+        if self.claims.get(&card_slot) != Some(&addr) {
+            return None;
+        }
+        self.claims.remove(&card_slot);
+        self.touch();
+        self.users.get(&addr).map(|user| user.user_id)
+    }
+
+    /// Claimed cards, as (card slot, holder's user ID) pairs
+    pub fn claims(&self) -> impl Iterator<Item = (u16, u8)> + '_ {
+        self.claims.iter().filter_map(|(card_slot, holder)| {
+            self.users
+                .get(holder)
+                .map(|user| (*card_slot, user.user_id))
+        })
+    }
+
+    /// Move a user's cursor unless the movement is inside the dead zone
+    ///
+    /// Movement is measured in normalized units along the larger axis since
+    /// the last accepted position; anything below `dead_zone` is ignored and
+    /// leaves the stored position unchanged, so slow drift still adds up.
+    /// Returns the user info if the cursor moved, or `None` if the movement
+    /// was ignored or the user is not in the room.
+    pub fn move_cursor(
+        &mut self,
+        addr: SocketAddr,
+        x: u16,
+        y: u16,
+        dead_zone: u16,
+    ) -> Option<&UserInfo> {
+        let user = self.users.get_mut(&addr)?;
+        if let Some((last_x, last_y)) = user.cursor {
+            if x.abs_diff(last_x).max(y.abs_diff(last_y)) < dead_zone {
+                return None;
+            }
+        }
+        user.cursor = Some((x, y));
+        self.touch();
+        self.users.get(&addr)
+    }
+
+    /// Record activity in the room
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Time elapsed since the last recorded activity
+    pub fn idle_duration(&self) -> Duration {
+        self.last_activity.elapsed()
     }
 
     /// Get user info by address
@@ -105,6 +369,34 @@ impl Room {
         self.users.values()
     }
 
+    /// Digest of the roster, for clients to detect drift without the full list
+    ///
+    /// FNV-1a (64-bit) over the users sorted by user ID, each contributing
+    /// `user_id` (1 byte), `color` (3 bytes), the username's length (1 byte)
+    /// and its UTF-8 bytes. Clients compute the same over the roster they
+    /// hold and only need the full roster when the two differ. Cursors and
+    /// focus are not part of it.
+    pub fn digest(&self) -> u64 {
+        let mut users: Vec<&UserInfo> = self.users.values().collect();
+        users.sort_unstable_by_key(|user| user.user_id);
+
+        let mut hash = DIGEST_OFFSET_BASIS;
+        for user in users {
+            let header = [
+                user.user_id,
+                user.color[0],
+                user.color[1],
+                user.color[2],
+                user.username.len() as u8,
+            ];
+            for byte in header.iter().chain(user.username.as_bytes()) {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(DIGEST_PRIME);
+            }
+        }
+        hash
+    }
+
     /// Check if a user is in the room
     pub fn contains_user(&self, addr: &SocketAddr) -> bool {
         self.users.contains_key(addr)
@@ -226,4 +518,270 @@ mod tests {
         assert!(addresses.contains(&addr2));
         assert!(addresses.contains(&addr3));
     }
+
+    #[test]
+    fn test_activity_tracking() {
+        let mut room = Room::new(1);
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(room.idle_duration() >= Duration::from_millis(20));
+
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let user_id = room.assign_user_id().unwrap();
+        room.add_user(addr, user_id, "Alice".to_string(), [255, 0, 0]);
+        assert!(room.idle_duration() < Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_rename_user() {
+        let mut room = Room::new(1);
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let other = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+        let user_id = room.assign_user_id().unwrap();
+        room.add_user(addr, user_id, "Alice".to_string(), [255, 0, 0]);
+
+        let renamed = room.rename_user(addr, "  Alicia\n ").unwrap();
+        assert_eq!(renamed.username, "Alicia");
+        assert_eq!(renamed.user_id, user_id);
+        assert_eq!(room.get_user(&addr).unwrap().username, "Alicia");
+
+        // Blank names and unknown users are rejected
+        assert!(room.rename_user(addr, " \t ").is_none());
+        assert!(room.rename_user(other, "Bob").is_none());
+        assert_eq!(room.get_user(&addr).unwrap().username, "Alicia");
+    }
+
+    #[test]
+    fn test_set_focus() {
+        let mut room = Room::new(1);
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let other = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+        let user_id = room.assign_user_id().unwrap();
+        room.add_user(addr, user_id, "Alice".to_string(), [255, 0, 0]);
+        assert_eq!(room.get_user(&addr).unwrap().focused_card, NO_CARD_FOCUSED);
+
+        let focused = room.set_focus(addr, 42).unwrap();
+        assert_eq!(focused.user_id, user_id);
+        assert_eq!(focused.focused_card, 42);
+
+        // Repeating the same focus is not a change
+        assert!(room.set_focus(addr, 42).is_none());
+        assert!(room.set_focus(other, 42).is_none());
+
+        let cleared = room.set_focus(addr, NO_CARD_FOCUSED).unwrap();
+        assert_eq!(cleared.focused_card, NO_CARD_FOCUSED);
+    }
+
+    #[test]
+    fn test_move_cursor_dead_zone() {
+        let mut room = Room::new(1);
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let user_id = room.assign_user_id().unwrap();
+        room.add_user(addr, user_id, "Alice".to_string(), [255, 0, 0]);
+
+        // The first position is always accepted
+        let moved = room.move_cursor(addr, 1000, 1000, 10).unwrap();
+        assert_eq!(moved.user_id, user_id);
+        assert_eq!(moved.cursor, Some((1000, 1000)));
+
+        // Jitter below the threshold on either axis is dropped
+        assert!(room.move_cursor(addr, 1009, 991, 10).is_none());
+        assert_eq!(room.get_user(&addr).unwrap().cursor, Some((1000, 1000)));
+
+        // Movement measured from the last accepted position, not the last update
+        assert_eq!(
+            room.move_cursor(addr, 1010, 1000, 10).unwrap().cursor,
+            Some((1010, 1000))
+        );
+        assert!(room.move_cursor(addr, 1010, 1001, 10).is_none());
+
+        // A zero dead zone accepts every update, even repeats
+        assert!(room.move_cursor(addr, 1010, 1000, 0).is_some());
+        assert!(room.move_cursor(addr, 1010, 1000, 0).is_some());
+    }
+
+    #[test]
+    fn test_card_claim_contention() {
+        let mut room = Room::new(1);
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+        let stranger = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8082);
+        room.add_user(alice, 0, "Alice".to_string(), [255, 0, 0]);
+        room.add_user(bob, 1, "Bob".to_string(), [0, 255, 0]);
+
+        assert_eq!(room.claim_card(alice, 42), Some(CardClaim::Granted(0)));
+        // Bob is told Alice holds it; Alice claiming again changes nothing
+        assert_eq!(room.claim_card(bob, 42), Some(CardClaim::Held(0)));
+        assert_eq!(room.claim_card(alice, 42), Some(CardClaim::Held(0)));
+        assert_eq!(room.claim_card(stranger, 42), None);
+        assert_eq!(room.claims().collect::<Vec<_>>(), vec![(42, 0)]);
+
+        // Only the holder can release
+        assert_eq!(room.release_card(bob, 42), None);
+        assert_eq!(room.release_card(alice, 42), Some(0));
+        assert_eq!(room.release_card(alice, 42), None);
+
+        assert_eq!(room.claim_card(bob, 42), Some(CardClaim::Granted(1)));
+    }
+
+    #[test]
+    fn test_card_claims_released_on_leave() {
+        let mut room = Room::new(1);
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+        room.add_user(alice, 0, "Alice".to_string(), [255, 0, 0]);
+        room.add_user(bob, 1, "Bob".to_string(), [0, 255, 0]);
+        room.claim_card(alice, 1);
+        room.claim_card(alice, 2);
+        room.claim_card(bob, 3);
+
+        let mut released = room.remove_user(alice);
+        released.sort_unstable();
+        assert_eq!(released, vec![1, 2]);
+        assert_eq!(room.claims().collect::<Vec<_>>(), vec![(3, 1)]);
+
+        // Alice's cards are free again
+        assert_eq!(room.claim_card(bob, 1), Some(CardClaim::Granted(1)));
+        assert!(room.remove_user(alice).is_empty());
+    }
+
+    #[test]
+    fn test_reclaim_within_grace_window() {
+        let mut room = Room::new(1);
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let alice_again = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8082);
+        let token = [7; 16];
+
+        let (user_id, color) = room
+            .reclaim_or_assign(Some(&token), Instant::now())
+            .unwrap();
+        assert_eq!((user_id, color), (0, None));
+        room.add_user(alice, user_id, "Alice".to_string(), [255, 0, 0]);
+        room.set_reconnect_token(alice, token);
+        room.remove_user(alice);
+
+        // Alice's ID stays reserved while she is gone
+        let now = Instant::now();
+        assert_eq!(room.reclaim_or_assign(None, now), Some((1, None)));
+        room.add_user(bob, 1, "Bob".to_string(), [0, 255, 0]);
+
+        let reconnect_at = now + RECONNECT_GRACE - Duration::from_secs(1);
+        assert_eq!(
+            room.reclaim_or_assign(Some(&token), reconnect_at),
+            Some((0, Some([255, 0, 0])))
+        );
+        room.add_user(alice_again, 0, "Alice".to_string(), [255, 0, 0]);
+
+        // A seat is reclaimed only once
+        assert_eq!(
+            room.reclaim_or_assign(Some(&token), reconnect_at),
+            Some((2, None))
+        );
+    }
+
+    #[test]
+    fn test_fresh_assignment_after_grace_window() {
+        let mut room = Room::new(1);
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let token = [7; 16];
+
+        room.add_user(alice, 0, "Alice".to_string(), [255, 0, 0]);
+        room.set_reconnect_token(alice, token);
+        room.remove_user(alice);
+        let available = room.available_id_count();
+
+        let later = Instant::now() + RECONNECT_GRACE + Duration::from_secs(1);
+        room.prune_departed(later);
+        assert_eq!(room.available_id_count(), available + 1);
+
+        // The expired token gets the lowest free ID and no color
+        assert_eq!(room.reclaim_or_assign(Some(&token), later), Some((0, None)));
+    }
+
+    #[test]
+    fn test_full_room_gives_up_oldest_reserved_seat() {
+        let mut room = Room::new(1);
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let token = [7; 16];
+
+        let now = Instant::now();
+        for _ in 0..256 {
+            assert!(room.reclaim_or_assign(None, now).is_some());
+        }
+        room.add_user(alice, 42, "Alice".to_string(), [255, 0, 0]);
+        room.set_reconnect_token(alice, token);
+        room.remove_user(alice);
+
+        assert_eq!(room.reclaim_or_assign(None, now), Some((42, None)));
+        assert_eq!(room.reclaim_or_assign(Some(&token), now), None);
+    }
+
+    #[test]
+    fn test_sanitize_username() {
+        assert_eq!(sanitize_username(" Bob "), Some("Bob".to_string()));
+        assert_eq!(sanitize_username("B\u{7}o\tb"), Some("Bob".to_string()));
+        assert_eq!(sanitize_username(""), None);
+
+        // Truncated to the byte limit without splitting a character
+        let long = "é".repeat(MAX_USERNAME_LENGTH);
+        let sanitized = sanitize_username(&long).unwrap();
+        assert_eq!(sanitized.len(), MAX_USERNAME_LENGTH / 2 * 2);
+        assert_eq!(sanitized, "é".repeat(MAX_USERNAME_LENGTH / 2));
+    }
+
+    #[test]
+    fn test_digest_tracks_roster_changes() {
+        let mut room = Room::new(1);
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+        let empty = room.digest();
+        assert_eq!(empty, DIGEST_OFFSET_BASIS);
+
+        let alice_id = room.assign_user_id().unwrap();
+        room.add_user(alice, alice_id, "Alice".to_string(), [255, 0, 0]);
+        let one = room.digest();
+        assert_ne!(one, empty);
+
+        let bob_id = room.assign_user_id().unwrap();
+        room.add_user(bob, bob_id, "Bob".to_string(), [0, 255, 0]);
+        let two = room.digest();
+        assert_ne!(two, one);
+
+        // Cursor and focus changes leave the roster, and so the digest, alone
+        room.move_cursor(alice, 100, 200, 0);
+        room.set_focus(bob, 7);
+        assert_eq!(room.digest(), two);
+        assert_eq!(room.digest(), two);
+
+        room.rename_user(bob, "Robert");
+        let renamed = room.digest();
+        assert_ne!(renamed, two);
+
+        room.remove_user(bob);
+        assert_eq!(room.digest(), one);
+        room.remove_user(alice);
+        assert_eq!(room.digest(), empty);
+    }
+
+    #[test]
+    fn test_digest_independent_of_join_order() {
+        let alice = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let bob = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081);
+
+        let mut first = Room::new(1);
+        first.add_user(alice, 0, "Alice".to_string(), [255, 0, 0]);
+        first.add_user(bob, 1, "Bob".to_string(), [0, 255, 0]);
+
+        let mut second = Room::new(1);
+        second.add_user(bob, 1, "Bob".to_string(), [0, 255, 0]);
+        second.add_user(alice, 0, "Alice".to_string(), [255, 0, 0]);
+
+        assert_eq!(first.digest(), second.digest());
+
+        // Same names, different colors
+        let mut recolored = Room::new(1);
+        recolored.add_user(alice, 0, "Alice".to_string(), [255, 0, 0]);
+        recolored.add_user(bob, 1, "Bob".to_string(), [0, 0, 255]);
+        assert_ne!(first.digest(), recolored.digest());
+    }
 }
@@ -1,5 +1,12 @@
+use crate::protocol::messages::encode_sequenced;
+use crate::protocol::{
+    PROTOCOL_VERSION_V1, PROTOCOL_VERSION_V11, PROTOCOL_VERSION_V3, PROTOCOL_VERSION_V4,
+    PROTOCOL_VERSION_V5,
+};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Information about a user's participation in a specific board
 #[derive(Debug, Clone)]
@@ -9,6 +16,32 @@ pub struct BoardInfo {
     pub color: [u8; 3],
 }
 
+/// Numbers the frames sent to one v3+ connection
+///
+/// Clones share the same counter.
+#[derive(Debug, Clone, Default)]
+pub struct OutgoingSequence {
+    next: Arc<Mutex<u32>>,
+}
+
+impl OutgoingSequence {
+    /// Wrap `frame` with the next sequence number and hand it to `send`
+    ///
+    /// The counter stays locked while `send` runs, so frames queued from
+    /// concurrent broadcasts enter the connection in sequence order. The
+    /// number is only consumed if `send` succeeds.
+    pub fn send_numbered<E>(
+        &self,
+        frame: &[u8],
+        send: impl FnOnce(Vec<u8>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        send(encode_sequenced(*next, frame))?;
+        *next = next.wrapping_add(1);
+        Ok(())
+    }
+}
+
 /// Represents a client session
 #[derive(Debug, Clone)]
 pub struct Session {
@@ -16,7 +49,25 @@ pub struct Session {
     addr: SocketAddr,
 
     /// Map of board IDs to board-specific info
-    boards: HashMap<u16, BoardInfo>,
+    boards: HashMap<u32, BoardInfo>,
+
+    /// Negotiated protocol version (v1 until the client sends Hello)
+    protocol_version: u8,
+
+    /// Sequence numbers for outgoing frames (used from v3)
+    outgoing_sequence: OutgoingSequence,
+
+    /// Nonce and send time of the Ping awaiting a Pong (v4+)
+    pending_ping: Option<(u32, Instant)>,
+
+    /// Most recently measured round-trip time (v4+)
+    rtt: Option<Duration>,
+
+    /// Window this client's cursor updates are coalesced over (zero: none)
+    cursor_interval: Duration,
+
+    /// When the client last sent a message
+    last_seen: Instant,
 }
 
 impl Session {
@@ -25,6 +76,12 @@ impl Session {
         Self {
             addr,
             boards: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION_V1,
+            outgoing_sequence: OutgoingSequence::default(),
+            pending_ping: None,
+            rtt: None,
+            cursor_interval: Duration::ZERO,
+            last_seen: Instant::now(),
         }
     }
 
@@ -33,8 +90,84 @@ impl Session {
         self.addr
     }
 
+    /// Get the negotiated protocol version
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol_version
+    }
+
+    /// Set the negotiated protocol version
+    pub fn set_protocol_version(&mut self, version: u8) {
+        self.protocol_version = version;
+    }
+
+    /// Sequence for outgoing frames, if the negotiated version numbers them
+    pub fn outgoing_sequence(&self) -> Option<&OutgoingSequence> {
+        (self.protocol_version >= PROTOCOL_VERSION_V3).then_some(&self.outgoing_sequence)
+    }
+
+    /// Whether the negotiated version exchanges Ping/Pong for RTT measurement
+    pub fn measures_rtt(&self) -> bool {
+        self.protocol_version >= PROTOCOL_VERSION_V4
+    }
+
+    /// Whether the negotiated version echoes message ids of Correlated requests
+    pub fn echoes_message_ids(&self) -> bool {
+        self.protocol_version >= PROTOCOL_VERSION_V5
+    }
+
+    /// Whether the negotiated version answers undecodable frames with an Error
+    pub fn reports_errors(&self) -> bool {
+        self.protocol_version >= PROTOCOL_VERSION_V11
+    }
+
+    /// Remember a Ping sent at `now`, replacing any unanswered one
+    pub fn start_ping(&mut self, nonce: u32, now: Instant) {
+        self.pending_ping = Some((nonce, now));
+    }
+
+    /// Record the Pong for the pending Ping and return the measured RTT
+    ///
+    /// Returns `None` (and keeps waiting) if the nonce does not match the
+    /// outstanding Ping, e.g. a late answer to one that was replaced.
+    pub fn record_pong(&mut self, nonce: u32, now: Instant) -> Option<Duration> {
+        match self.pending_ping {
+            Some((pending, sent_at)) if pending == nonce => {
+                let rtt = now.saturating_duration_since(sent_at);
+                self.pending_ping = None;
+                self.rtt = Some(rtt);
+                Some(rtt)
+            }
+            _ => None,
+        }
+    }
+
+    /// Most recently measured round-trip time, if any
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Window this client's cursor updates are coalesced over
+    pub fn cursor_interval(&self) -> Duration {
+        self.cursor_interval
+    }
+
+    /// Set the window this client's cursor updates are coalesced over
+    pub fn set_cursor_interval(&mut self, interval: Duration) {
+        self.cursor_interval = interval;
+    }
+
+    /// Record that the client sent a message at `now`
+    pub fn touch(&mut self, now: Instant) {
+        self.last_seen = now;
+    }
+
+    /// How long the client has been silent as of `now`
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_seen)
+    }
+
     /// Add a board to the session
-    pub fn add_board(&mut self, board_id: u16, user_id: u8, username: String, color: [u8; 3]) {
+    pub fn add_board(&mut self, board_id: u32, user_id: u8, username: String, color: [u8; 3]) {
         self.boards.insert(
             board_id,
             BoardInfo {
@@ -45,23 +178,36 @@ impl Session {
         );
     }
 
+    /// Update the username stored for a board
+    ///
+    /// Returns `false` if the session is not in the board.
+    pub fn rename_board_user(&mut self, board_id: u32, username: String) -> bool {
+        match self.boards.get_mut(&board_id) {
+            Some(info) => {
+                info.username = username;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Remove a board from the session
-    pub fn remove_board(&mut self, board_id: u16) {
+    pub fn remove_board(&mut self, board_id: u32) {
         self.boards.remove(&board_id);
     }
 
     /// Get board info for a specific board
-    pub fn get_board_info(&self, board_id: u16) -> Option<&BoardInfo> {
+    pub fn get_board_info(&self, board_id: u32) -> Option<&BoardInfo> {
         self.boards.get(&board_id)
     }
 
     /// Get all board IDs this session is part of
-    pub fn board_ids(&self) -> Vec<u16> {
+    pub fn board_ids(&self) -> Vec<u32> {
         self.boards.keys().copied().collect()
     }
 
     /// Check if session is in a specific board
-    pub fn is_in_board(&self, board_id: u16) -> bool {
+    pub fn is_in_board(&self, board_id: u32) -> bool {
         self.boards.contains_key(&board_id)
     }
 
@@ -83,6 +229,8 @@ mod tests {
 
         assert_eq!(session.addr(), addr);
         assert_eq!(session.board_count(), 0);
+        assert_eq!(session.protocol_version(), PROTOCOL_VERSION_V1);
+        assert!(session.outgoing_sequence().is_none());
     }
 
     #[test]
@@ -128,4 +276,179 @@ mod tests {
         assert!(board_ids.contains(&2));
         assert!(board_ids.contains(&3));
     }
+
+    #[test]
+    fn test_rename_board_user() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut session = Session::new(addr);
+        session.add_board(1, 5, "Alice".to_string(), [255, 0, 0]);
+        session.add_board(2, 3, "Alice".to_string(), [0, 255, 0]);
+
+        assert!(session.rename_board_user(1, "Alicia".to_string()));
+        assert_eq!(session.get_board_info(1).unwrap().username, "Alicia");
+        assert_eq!(session.get_board_info(2).unwrap().username, "Alice");
+
+        assert!(!session.rename_board_user(9, "Nobody".to_string()));
+    }
+
+    #[test]
+    fn test_room_and_session_rename_in_lockstep() {
+        use crate::connection::room::Room;
+
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut room = Room::new(1);
+        let mut session = Session::new(addr);
+        let user_id = room.assign_user_id().unwrap();
+        room.add_user(addr, user_id, "Alice".to_string(), [255, 0, 0]);
+        session.add_board(1, user_id, "Alice".to_string(), [255, 0, 0]);
+
+        // Same sequence the manager uses: the room's sanitized name feeds the session
+        let renamed = room.rename_user(addr, " Alicia ").unwrap().username.clone();
+        assert!(session.rename_board_user(1, renamed));
+
+        assert_eq!(room.get_user(&addr).unwrap().username, "Alicia");
+        assert_eq!(session.get_board_info(1).unwrap().username, "Alicia");
+    }
+
+    #[test]
+    fn test_outgoing_sequence_only_from_v3() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut session = Session::new(addr);
+
+        session.set_protocol_version(crate::protocol::PROTOCOL_VERSION_V2);
+        assert!(session.outgoing_sequence().is_none());
+
+        session.set_protocol_version(PROTOCOL_VERSION_V3);
+        assert!(session.outgoing_sequence().is_some());
+    }
+
+    #[test]
+    fn test_message_ids_echoed_only_from_v5() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut session = Session::new(addr);
+        assert!(!session.echoes_message_ids());
+
+        session.set_protocol_version(PROTOCOL_VERSION_V4);
+        assert!(!session.echoes_message_ids());
+
+        session.set_protocol_version(PROTOCOL_VERSION_V5);
+        assert!(session.echoes_message_ids());
+    }
+
+    #[test]
+    fn test_errors_reported_only_from_v11() {
+        use crate::protocol::PROTOCOL_VERSION_V10;
+
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut session = Session::new(addr);
+        assert!(!session.reports_errors());
+
+        session.set_protocol_version(PROTOCOL_VERSION_V10);
+        assert!(!session.reports_errors());
+
+        session.set_protocol_version(PROTOCOL_VERSION_V11);
+        assert!(session.reports_errors());
+    }
+
+    #[test]
+    fn test_record_pong_measures_rtt() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut session = Session::new(addr);
+        assert!(!session.measures_rtt());
+        session.set_protocol_version(PROTOCOL_VERSION_V4);
+        assert!(session.measures_rtt());
+
+        let sent_at = Instant::now();
+        session.start_ping(7, sent_at);
+
+        // A stale nonce is ignored and the ping stays pending
+        assert_eq!(
+            session.record_pong(6, sent_at + Duration::from_millis(5)),
+            None
+        );
+        assert_eq!(session.rtt(), None);
+
+        let rtt = session.record_pong(7, sent_at + Duration::from_millis(42));
+        assert_eq!(rtt, Some(Duration::from_millis(42)));
+        assert_eq!(session.rtt(), Some(Duration::from_millis(42)));
+
+        // Duplicate pongs do not overwrite the measurement
+        assert_eq!(
+            session.record_pong(7, sent_at + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(session.rtt(), Some(Duration::from_millis(42)));
+    }
+
+    #[test]
+    fn test_touch_resets_idle_time() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let mut session = Session::new(addr);
+
+        let seen_at = Instant::now();
+        session.touch(seen_at);
+        assert_eq!(
+            session.idle_for(seen_at + Duration::from_secs(90)),
+            Duration::from_secs(90)
+        );
+
+        session.touch(seen_at + Duration::from_secs(60));
+        assert_eq!(
+            session.idle_for(seen_at + Duration::from_secs(90)),
+            Duration::from_secs(30)
+        );
+
+        // A clock reading from before the last message is not negative idle time
+        assert_eq!(session.idle_for(seen_at), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_outgoing_sequence_monotonic_across_burst() {
+        use crate::protocol::BinaryMessage;
+
+        let sequence = OutgoingSequence::default();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
+        let frame = BinaryMessage::PresenceUpdate {
+            board_id: 1,
+            count: 3,
+        }
+        .encode();
+
+        // Several broadcasters racing to the same connection
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                let sequence = sequence.clone();
+                let tx = tx.clone();
+                let frame = &frame;
+                scope.spawn(move || {
+                    for _ in 0..250 {
+                        sequence.send_numbered(frame, |f| tx.send(f)).unwrap();
+                    }
+                });
+            }
+        });
+
+        let mut expected = 0u32;
+        while let Ok(data) = rx.try_recv() {
+            match BinaryMessage::decode(&data).unwrap() {
+                BinaryMessage::Sequenced { seq, message } => {
+                    assert_eq!(seq, expected);
+                    assert_eq!(message.encode(), frame);
+                }
+                other => panic!("expected sequenced frame, got {:?}", other),
+            }
+            expected += 1;
+        }
+        assert_eq!(expected, 2000);
+
+        // A failed send does not consume a number
+        drop(rx);
+        assert!(sequence.send_numbered(&frame, |f| tx.send(f)).is_err());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
+        sequence.send_numbered(&frame, |f| tx.send(f)).unwrap();
+        assert!(matches!(
+            BinaryMessage::decode(&rx.try_recv().unwrap()).unwrap(),
+            BinaryMessage::Sequenced { seq: 2000, .. }
+        ));
+    }
 }
@@ -0,0 +1,335 @@
+//! Minimal HTTP admin endpoint for inspecting and cleaning up rooms.
+//!
+//! `GET /protocol` is public and describes the binary protocol as JSON (see
+//! `protocol::schema`). Every other route requires
+//! `Authorization: Bearer <ADMIN_TOKEN>`:
+//!
+//! * `GET /admin/rooms` - list rooms with user counts, idle time and average RTT
+//! * `GET /admin/metrics` - bytes and messages per message type
+//! * `POST /admin/rooms/{board_id}/reap` - force-clear a room
+//! * `POST /admin/rooms/{board_id}/announce` - send the request body as an
+//!   Announcement to everyone in the room (end-to-end smoke test)
+
+use crate::connection::manager::ConnectionManager;
+use crate::protocol::schema::protocol_schema;
+use crate::protocol::MAX_ANNOUNCEMENT_LENGTH;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Maximum size of an admin request head (request line + headers)
+pub(crate) const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Parsed admin request
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdminRequest {
+    pub method: String,
+    pub path: String,
+    pub bearer_token: Option<String>,
+    pub content_length: usize,
+}
+
+/// Admin route resolved from method and path
+#[derive(Debug, PartialEq, Eq)]
+pub enum AdminRoute {
+    ListRooms,
+    ReapRoom(u32),
+    Announce(u32),
+    Metrics,
+    Protocol,
+    NotFound,
+}
+
+/// Parse an HTTP/1.1 request head
+///
+/// Returns `None` if the head is incomplete or malformed.
+pub fn parse_request(head: &[u8]) -> Option<AdminRequest> {
+    let head = std::str::from_utf8(head).ok()?;
+    let head = head.split("\r\n\r\n").next()?;
+    let mut lines = head.split("\r\n");
+
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+
+    let mut bearer_token = None;
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            bearer_token = value.trim().strip_prefix("Bearer ").map(|t| t.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().ok()?;
+        }
+    }
+
+    Some(AdminRequest {
+        method,
+        path,
+        bearer_token,
+        content_length,
+    })
+}
+
+/// Resolve a request to an admin route
+pub fn route(method: &str, path: &str) -> AdminRoute {
+    let path = path.split('?').next().unwrap_or(path);
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["admin", "rooms"]) => AdminRoute::ListRooms,
+        ("GET", ["admin", "metrics"]) => AdminRoute::Metrics,
+        ("GET", ["protocol"]) => AdminRoute::Protocol,
+        ("POST", ["admin", "rooms", board_id, "reap"]) => match board_id.parse() {
+            Ok(board_id) => AdminRoute::ReapRoom(board_id),
+            Err(_) => AdminRoute::NotFound,
+        },
+        ("POST", ["admin", "rooms", board_id, "announce"]) => match board_id.parse() {
+            Ok(board_id) => AdminRoute::Announce(board_id),
+            Err(_) => AdminRoute::NotFound,
+        },
+        _ => AdminRoute::NotFound,
+    }
+}
+
+/// Accept admin connections until the listener fails
+pub async fn serve_admin(listener: TcpListener, manager: Arc<ConnectionManager>, token: String) {
+    let token = Arc::new(token);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer_addr)) => {
+                let manager = Arc::clone(&manager);
+                let token = Arc::clone(&token);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_admin_request(stream, peer_addr, manager, &token).await {
+                        tracing::warn!("Admin request from {} failed: {}", peer_addr, e);
+                    }
+                });
+            }
+            Err(e) => {
+                tracing::error!("Failed to accept admin connection: {}", e);
+            }
+        }
+    }
+}
+
+/// Handle a single admin HTTP request and close the connection
+async fn handle_admin_request(
+    mut stream: TcpStream,
+    peer_addr: SocketAddr,
+    manager: Arc<ConnectionManager>,
+    token: &str,
+) -> std::io::Result<()> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    // Read until the end of the request head
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 || buf.len() + n > MAX_REQUEST_SIZE {
+            return write_response(&mut stream, 400, r#"{"error":"bad request"}"#).await;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let request = match parse_request(&buf) {
+        Some(request) => request,
+        None => return write_response(&mut stream, 400, r#"{"error":"bad request"}"#).await,
+    };
+
+    // Read the rest of the body, if any
+    if head_end + request.content_length > MAX_REQUEST_SIZE {
+        return write_response(&mut stream, 400, r#"{"error":"bad request"}"#).await;
+    }
+    while buf.len() < head_end + request.content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return write_response(&mut stream, 400, r#"{"error":"bad request"}"#).await;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = &buf[head_end..head_end + request.content_length];
+
+    let route = route(&request.method, &request.path);
+
+    // The protocol description is public; everything else needs the token
+    if route != AdminRoute::Protocol && request.bearer_token.as_deref() != Some(token) {
+        tracing::warn!("Rejected unauthorized admin request from {}", peer_addr);
+        return write_response(&mut stream, 401, r#"{"error":"unauthorized"}"#).await;
+    }
+
+    match route {
+        AdminRoute::ListRooms => {
+            let rooms = manager.snapshot().await;
+            let body = serde_json::json!({ "rooms": rooms }).to_string();
+            write_response(&mut stream, 200, &body).await
+        }
+        AdminRoute::ReapRoom(board_id) => match manager.force_clear_room(board_id).await {
+            Some(users_removed) => {
+                tracing::info!("Admin {} reaped room {}", peer_addr, board_id);
+                let body = serde_json::json!({
+                    "board_id": board_id,
+                    "users_removed": users_removed,
+                })
+                .to_string();
+                write_response(&mut stream, 200, &body).await
+            }
+            None => write_response(&mut stream, 404, r#"{"error":"room not found"}"#).await,
+        },
+        AdminRoute::Announce(board_id) => {
+            let text = match std::str::from_utf8(body) {
+                Ok(text) if !text.is_empty() && text.len() <= MAX_ANNOUNCEMENT_LENGTH => text,
+                _ => {
+                    return write_response(
+                        &mut stream,
+                        400,
+                        r#"{"error":"announcement must be 1-255 bytes of UTF-8"}"#,
+                    )
+                    .await
+                }
+            };
+
+            match manager.announce(board_id, text.to_string()).await {
+                Ok(delivered) => {
+                    tracing::info!("Admin {} announced to room {}", peer_addr, board_id);
+                    let body = serde_json::json!({
+                        "board_id": board_id,
+                        "delivered": delivered,
+                    })
+                    .to_string();
+                    write_response(&mut stream, 200, &body).await
+                }
+                Err(e) => {
+                    let body = serde_json::json!({ "error": e.to_string() }).to_string();
+                    write_response(&mut stream, 400, &body).await
+                }
+            }
+        }
+        AdminRoute::Metrics => {
+            let body = serde_json::json!({
+                "traffic": manager.metrics().snapshot(),
+                "redis_decode_cache": manager.decode_cache_stats().snapshot(),
+            })
+            .to_string();
+            write_response(&mut stream, 200, &body).await
+        }
+        AdminRoute::Protocol => {
+            let body = serde_json::to_string(&protocol_schema())
+                .expect("protocol schema always serializes");
+            write_response(&mut stream, 200, &body).await
+        }
+        AdminRoute::NotFound => write_response(&mut stream, 404, r#"{"error":"not found"}"#).await,
+    }
+}
+
+/// Write a JSON response and shut down the stream
+pub(crate) async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    body: &str,
+) -> std::io::Result<()> {
+    write_response_as(stream, status, "application/json", body).await
+}
+
+/// Write a response with the given content type and shut down the stream
+pub(crate) async fn write_response_as(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let head = b"POST /admin/rooms/5/reap HTTP/1.1\r\nHost: localhost\r\nauthorization: Bearer secret\r\n\r\n";
+        let request = parse_request(head).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/admin/rooms/5/reap");
+        assert_eq!(request.bearer_token.as_deref(), Some("secret"));
+        assert_eq!(request.content_length, 0);
+    }
+
+    #[test]
+    fn test_parse_request_content_length() {
+        let head = b"POST /admin/rooms/5/announce HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        let request = parse_request(head).unwrap();
+
+        assert_eq!(request.content_length, 5);
+        assert!(parse_request(b"POST / HTTP/1.1\r\nContent-Length: x\r\n\r\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_request_without_token() {
+        let head = b"GET /admin/rooms HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let request = parse_request(head).unwrap();
+
+        assert_eq!(request.bearer_token, None);
+    }
+
+    #[test]
+    fn test_parse_request_malformed() {
+        assert!(parse_request(b"\r\n\r\n").is_none());
+        assert!(parse_request(&[0xff, 0xfe]).is_none());
+    }
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(route("GET", "/admin/rooms"), AdminRoute::ListRooms);
+        assert_eq!(route("GET", "/admin/rooms/"), AdminRoute::ListRooms);
+        assert_eq!(
+            route("POST", "/admin/rooms/42/reap"),
+            AdminRoute::ReapRoom(42)
+        );
+        assert_eq!(
+            route("POST", "/admin/rooms/42/announce"),
+            AdminRoute::Announce(42)
+        );
+        assert_eq!(
+            route("GET", "/admin/rooms/42/announce"),
+            AdminRoute::NotFound
+        );
+        assert_eq!(route("GET", "/admin/metrics"), AdminRoute::Metrics);
+        assert_eq!(route("GET", "/protocol"), AdminRoute::Protocol);
+        assert_eq!(route("POST", "/protocol"), AdminRoute::NotFound);
+        assert_eq!(route("GET", "/admin/rooms/42/reap"), AdminRoute::NotFound);
+        assert_eq!(route("POST", "/admin/rooms/abc/reap"), AdminRoute::NotFound);
+        assert_eq!(
+            route("POST", "/admin/rooms/4294967296/reap"),
+            AdminRoute::NotFound
+        );
+        assert_eq!(route("GET", "/"), AdminRoute::NotFound);
+    }
+}
@@ -0,0 +1,463 @@
+//! Minimal HTTP endpoint exposing instance metrics to operators.
+//!
+//! Enabled by `METRICS_PORT`. Unlike the admin endpoint it needs no token and
+//! only reads, so it can be scraped by monitoring; keep the port internal.
+//!
+//! * `GET /metrics` - the same figures in the Prometheus text exposition
+//!   format (see `render_prometheus`)
+//! * `GET /metrics.json` - connections, rooms, users per room and message
+//!   counters (see `MetricsSnapshot`)
+//! * `GET /protocol` - the binary protocol description, as on the admin
+//!   endpoint, so it is reachable without `ADMIN_TOKEN`
+
+use super::admin::{parse_request, write_response, write_response_as, MAX_REQUEST_SIZE};
+use crate::connection::manager::{ConnectionManager, MetricsSnapshot};
+use crate::protocol::schema::protocol_schema;
+use crate::utils::metrics::FANOUT_BUCKETS;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Accept metrics connections until the listener fails
+pub async fn serve_metrics(listener: TcpListener, manager: Arc<ConnectionManager>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer_addr)) => {
+                let manager = Arc::clone(&manager);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_metrics_request(stream, manager).await {
+                        tracing::warn!("Metrics request from {} failed: {}", peer_addr, e);
+                    }
+                });
+            }
+            Err(e) => {
+                tracing::error!("Failed to accept metrics connection: {}", e);
+            }
+        }
+    }
+}
+
+/// Handle a single metrics HTTP request and close the connection
+async fn handle_metrics_request(
+    mut stream: TcpStream,
+    manager: Arc<ConnectionManager>,
+) -> std::io::Result<()> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    // Requests have no body, so the head is all there is to read
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 || buf.len() + n > MAX_REQUEST_SIZE {
+            return write_response(&mut stream, 400, r#"{"error":"bad request"}"#).await;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let request = match parse_request(&buf) {
+        Some(request) => request,
+        None => return write_response(&mut stream, 400, r#"{"error":"bad request"}"#).await,
+    };
+    let path = request.path.split('?').next().unwrap_or(&request.path);
+
+    match (request.method.as_str(), path) {
+        ("GET", "/metrics") => {
+            let body = render_prometheus(&manager.metrics_snapshot().await);
+            write_response_as(&mut stream, 200, PROMETHEUS_CONTENT_TYPE, &body).await
+        }
+        ("GET", "/metrics.json") => {
+            let body = serde_json::to_string(&manager.metrics_snapshot().await)
+                .expect("metrics snapshot always serializes");
+            write_response(&mut stream, 200, &body).await
+        }
+        ("GET", "/protocol") => {
+            let body = serde_json::to_string(&protocol_schema())
+                .expect("protocol schema always serializes");
+            write_response(&mut stream, 200, &body).await
+        }
+        _ => write_response(&mut stream, 404, r#"{"error":"not found"}"#).await,
+    }
+}
+
+/// Render a metrics snapshot in the Prometheus text exposition format
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    write_family(
+        &mut out,
+        "presence_connections",
+        "gauge",
+        "Client connections open on this instance",
+    );
+    write_sample(&mut out, "presence_connections", "", snapshot.connections);
+    write_family(
+        &mut out,
+        "presence_connections_total",
+        "counter",
+        "Client connections accepted since the instance started",
+    );
+    write_sample(
+        &mut out,
+        "presence_connections_total",
+        "",
+        snapshot.connections_opened,
+    );
+
+    write_family(
+        &mut out,
+        "presence_rooms",
+        "gauge",
+        "Rooms with local users",
+    );
+    write_sample(&mut out, "presence_rooms", "", snapshot.rooms);
+    write_family(
+        &mut out,
+        "presence_users",
+        "gauge",
+        "Local users across all rooms",
+    );
+    let users: usize = snapshot.room_users.values().sum();
+    write_sample(&mut out, "presence_users", "", users);
+
+    // Per-board counts would add a series for every board ever opened, so
+    // only their distribution is exported; /admin/rooms has the detail.
+    // Rooms are bounded by local users the same way broadcasts are.
+    write_family(
+        &mut out,
+        "presence_room_size",
+        "histogram",
+        "Local users per room",
+    );
+    for bound in FANOUT_BUCKETS {
+        let rooms = snapshot
+            .room_users
+            .values()
+            .filter(|users| **users <= *bound)
+            .count();
+        let labels = format!("le=\"{}\"", bound);
+        write_sample(&mut out, "presence_room_size_bucket", &labels, rooms);
+    }
+    write_sample(
+        &mut out,
+        "presence_room_size_bucket",
+        "le=\"+Inf\"",
+        snapshot.room_users.len(),
+    );
+    write_sample(&mut out, "presence_room_size_sum", "", users);
+    write_sample(
+        &mut out,
+        "presence_room_size_count",
+        "",
+        snapshot.room_users.len(),
+    );
+
+    write_family(
+        &mut out,
+        "presence_messages_received_total",
+        "counter",
+        "Binary frames received from clients",
+    );
+    write_sample(
+        &mut out,
+        "presence_messages_received_total",
+        "",
+        snapshot.messages_received,
+    );
+    write_family(
+        &mut out,
+        "presence_messages_sent_total",
+        "counter",
+        "Frames queued for clients, once per recipient",
+    );
+    write_sample(
+        &mut out,
+        "presence_messages_sent_total",
+        "",
+        snapshot.messages_sent,
+    );
+    write_family(
+        &mut out,
+        "presence_messages_broadcast_total",
+        "counter",
+        "Messages fanned out to local clients, once per message",
+    );
+    for (msg_type, count) in &snapshot.broadcasts.by_type {
+        let labels = format!("type=\"{}\"", msg_type);
+        write_sample(
+            &mut out,
+            "presence_messages_broadcast_total",
+            &labels,
+            count,
+        );
+    }
+
+    // Prometheus buckets are cumulative, the snapshot's are not
+    write_family(
+        &mut out,
+        "presence_broadcast_fanout",
+        "histogram",
+        "Local clients reached per broadcast",
+    );
+    let mut cumulative = 0;
+    for bucket in &snapshot.broadcasts.fanout_histogram {
+        cumulative += bucket.count;
+        let labels = format!("le=\"{}\"", bucket.le);
+        write_sample(
+            &mut out,
+            "presence_broadcast_fanout_bucket",
+            &labels,
+            cumulative,
+        );
+    }
+    write_sample(
+        &mut out,
+        "presence_broadcast_fanout_sum",
+        "",
+        snapshot.broadcasts.fanout_sum,
+    );
+    write_sample(&mut out, "presence_broadcast_fanout_count", "", cumulative);
+
+    out
+}
+
+/// Write the HELP and TYPE lines of a metric family
+fn write_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    out.push_str(&format!(
+        "# HELP {} {}\n# TYPE {} {}\n",
+        name, help, name, kind
+    ));
+}
+
+/// Write one sample, with `labels` already formatted as `name="value",...`
+fn write_sample(out: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    if labels.is_empty() {
+        out.push_str(&format!("{} {}\n", name, value));
+    } else {
+        out.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_harness::test_manager;
+    use crate::protocol::BinaryMessage;
+    use crate::utils::metrics::BroadcastMetrics;
+    use std::collections::{BTreeMap, HashSet};
+    use std::net::SocketAddr;
+    use tokio::io::AsyncWriteExt;
+
+    /// Send a GET request to the endpoint and return the status line and body
+    async fn get(addr: SocketAddr, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    /// A parsed exposition sample: metric name, labels and value
+    type Sample = (String, BTreeMap<String, String>, f64);
+
+    /// Parse the text exposition format, failing on any malformed line
+    fn parse_exposition(text: &str) -> Vec<Sample> {
+        let mut families = HashSet::new();
+        let mut samples = Vec::new();
+
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some("HELP"), Some(_), Some(_)) => {}
+                    (Some("TYPE"), Some(name), Some(kind)) => {
+                        assert!(["counter", "gauge", "histogram"].contains(&kind));
+                        assert!(families.insert(name.to_string()), "{} declared twice", name);
+                    }
+                    _ => panic!("malformed comment: {}", line),
+                }
+                continue;
+            }
+
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let value: f64 = value.parse().unwrap();
+            let (name, labels) = match series.split_once('{') {
+                Some((name, labels)) => (name, labels.strip_suffix('}').unwrap()),
+                None => (series, ""),
+            };
+            assert!(name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'));
+
+            let labels: BTreeMap<String, String> = labels
+                .split(',')
+                .filter(|label| !label.is_empty())
+                .map(|label| {
+                    let (key, value) = label.split_once('=').unwrap();
+                    let value = value.strip_prefix('"').unwrap().strip_suffix('"').unwrap();
+                    (key.to_string(), value.to_string())
+                })
+                .collect();
+
+            // Histogram samples belong to the family without their suffix
+            let family = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix))
+                .filter(|family| families.contains(*family))
+                .unwrap_or(name);
+            assert!(families.contains(family), "{} has no TYPE line", name);
+
+            samples.push((name.to_string(), labels, value));
+        }
+
+        samples
+    }
+
+    #[test]
+    fn test_prometheus_exposition_parses() {
+        let broadcasts = BroadcastMetrics::new();
+        let cursor = BinaryMessage::CursorBroadcast {
+            board_id: 1234,
+            user_id: 0,
+            x: 1,
+            y: 2,
+        };
+        broadcasts.record(&cursor, 1);
+        broadcasts.record(&cursor, 3);
+        broadcasts.record(
+            &BinaryMessage::PresenceUpdate {
+                board_id: 1234,
+                count: 4,
+            },
+            4,
+        );
+        let broadcasts = broadcasts.snapshot();
+
+        let snapshot = MetricsSnapshot {
+            connections: 4,
+            connections_opened: 9,
+            rooms: 2,
+            room_users: BTreeMap::from([(1234, 4), (5678, 1)]),
+            messages_received: 20,
+            messages_sent: 30,
+            messages_broadcast: broadcasts.total_messages,
+            broadcasts,
+        };
+        let samples = parse_exposition(&render_prometheus(&snapshot));
+        let value = |name: &str, label: Option<(&str, &str)>| {
+            samples
+                .iter()
+                .find(|(sample, labels, _)| {
+                    sample == name
+                        && label.map_or(labels.is_empty(), |(key, value)| {
+                            labels.get(key).map(String::as_str) == Some(value)
+                        })
+                })
+                .map(|(_, _, value)| *value)
+                .unwrap_or_else(|| panic!("missing {} {:?}", name, label))
+        };
+
+        assert_eq!(value("presence_connections", None), 4.0);
+        assert_eq!(value("presence_connections_total", None), 9.0);
+        assert_eq!(value("presence_rooms", None), 2.0);
+        assert_eq!(value("presence_users", None), 5.0);
+        assert_eq!(value("presence_room_size_bucket", Some(("le", "1"))), 1.0);
+        assert_eq!(value("presence_room_size_bucket", Some(("le", "2"))), 1.0);
+        assert_eq!(value("presence_room_size_bucket", Some(("le", "5"))), 2.0);
+        assert_eq!(
+            value("presence_room_size_bucket", Some(("le", "+Inf"))),
+            2.0
+        );
+        assert_eq!(value("presence_room_size_sum", None), 5.0);
+        assert_eq!(value("presence_room_size_count", None), 2.0);
+        assert!(samples
+            .iter()
+            .all(|(_, labels, _)| !labels.contains_key("board_id")));
+        assert_eq!(value("presence_messages_received_total", None), 20.0);
+        assert_eq!(value("presence_messages_sent_total", None), 30.0);
+        assert_eq!(
+            value(
+                "presence_messages_broadcast_total",
+                Some(("type", "cursor_broadcast"))
+            ),
+            2.0
+        );
+        assert_eq!(
+            value(
+                "presence_messages_broadcast_total",
+                Some(("type", "presence_update"))
+            ),
+            1.0
+        );
+
+        // Buckets are cumulative and end at +Inf with the total count
+        assert_eq!(
+            value("presence_broadcast_fanout_bucket", Some(("le", "1"))),
+            1.0
+        );
+        assert_eq!(
+            value("presence_broadcast_fanout_bucket", Some(("le", "5"))),
+            3.0
+        );
+        assert_eq!(
+            value("presence_broadcast_fanout_bucket", Some(("le", "+Inf"))),
+            3.0
+        );
+        assert_eq!(value("presence_broadcast_fanout_count", None), 3.0);
+        assert_eq!(value("presence_broadcast_fanout_sum", None), 8.0);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_serves_json() {
+        let manager = test_manager().await;
+        let client = SocketAddr::from(([127, 0, 0, 1], 9128));
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        manager.connect(client, tx).await;
+        manager
+            .handle_message(
+                client,
+                BinaryMessage::Join {
+                    board_id: 29,
+                    username: "Alice".to_string(),
+                    reconnect_token: None,
+                },
+            )
+            .await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_metrics(listener, Arc::clone(&manager)));
+
+        let (status, body) = get(addr, "/metrics.json").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let metrics: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(metrics["connections"], 1);
+        assert_eq!(metrics["rooms"], 1);
+        assert_eq!(metrics["room_users"]["29"], 1);
+        assert!(metrics["messages_received"].is_u64());
+        assert!(metrics["messages_sent"].as_u64().unwrap() > 0);
+        assert!(metrics["messages_broadcast"].is_u64());
+
+        let (status, body) = get(addr, "/metrics").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let samples = parse_exposition(&body);
+        assert!(samples
+            .iter()
+            .any(|(name, labels, value)| name == "presence_room_size_bucket"
+                && labels["le"] == "1"
+                && *value == 1.0));
+
+        let (status, body) = get(addr, "/protocol").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let schema: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(schema["min_version"], crate::protocol::MIN_PROTOCOL_VERSION);
+
+        let (status, _) = get(addr, "/admin/rooms").await;
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+    }
+}
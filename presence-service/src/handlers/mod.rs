@@ -1 +1,5 @@
+pub mod admin;
+pub mod metrics;
+#[cfg(test)]
+pub(crate) mod test_harness;
 pub mod websocket;
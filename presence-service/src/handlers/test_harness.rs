@@ -0,0 +1,169 @@
+//! In-memory WebSocket clients for driving `handle_connection` in tests.
+//!
+//! Each `TestClient` holds one end of a `tokio::io::duplex` pair while a
+//! spawned task runs `handle_connection` on the other, so whole connection
+//! flows can be scripted frame by frame without binding a port.
+
+use super::websocket::handle_connection;
+use crate::config::Config;
+use crate::connection::manager::ConnectionManager;
+use crate::protocol::BinaryMessage;
+use crate::redis::pubsub::RedisPubSub;
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::DuplexStream;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+/// How long `recv` waits for the next frame before treating the server as quiet
+const RECV_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Bytes buffered in each direction of the in-memory stream
+const DUPLEX_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Config with every optional limit and coalescing window disabled
+pub fn test_config(allowed_origins: &[&str], allow_any_origin: bool) -> Config {
+    Config {
+        redis_url: "redis://localhost:6379".to_string(),
+        ws_port: 0,
+        log_level: "info".to_string(),
+        admin_token: None,
+        admin_port: 0,
+        max_protocol_strikes: 3,
+        allowed_origins: allowed_origins.iter().map(|o| o.to_string()).collect(),
+        allow_any_origin,
+        ws_read_buffer_size: 4096,
+        ws_write_buffer_size: 4096,
+        ws_max_message_size: 2048,
+        ws_max_frame_size: 2048,
+        presence_coalesce_ms: 0,
+        cursor_dead_zone: 0,
+        connection_rate_limit: 0,
+        max_connections: 0,
+        cursor_interval_min_ms: 0,
+        cursor_interval_max_ms: 0,
+        idle_timeout_secs: 0,
+        cursor_tick_ms: 0,
+        redis_backoff_base_ms: 100,
+        redis_backoff_max_ms: 30000,
+        metrics_port: None,
+    }
+}
+
+/// Connection manager publishing to an in-memory pub/sub instead of Redis
+pub async fn test_manager() -> Arc<ConnectionManager> {
+    Arc::new(ConnectionManager::new(Arc::new(RedisPubSub::in_memory())))
+}
+
+/// A WebSocket client connected to `handle_connection` over an in-memory stream
+pub struct TestClient {
+    ws: WebSocketStream<DuplexStream>,
+    server: JoinHandle<Result<(), String>>,
+}
+
+impl TestClient {
+    /// Open a connection served as if it came from `addr`
+    pub async fn connect(
+        addr: SocketAddr,
+        manager: Arc<ConnectionManager>,
+        config: Arc<Config>,
+    ) -> Self {
+        let (client_io, server_io) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+        let server = tokio::spawn(async move {
+            handle_connection(server_io, addr, manager, config)
+                .await
+                .map_err(|e| e.to_string())
+        });
+
+        let request = "ws://localhost/".into_client_request().unwrap();
+        let (ws, _) = tokio_tungstenite::client_async(request, client_io)
+            .await
+            .expect("handshake failed");
+
+        Self { ws, server }
+    }
+
+    /// Send a message as a binary frame
+    pub async fn send(&mut self, message: BinaryMessage) {
+        self.send_frame(Message::Binary(message.encode().into()))
+            .await;
+    }
+
+    /// Send a raw WebSocket frame, e.g. text or an undecodable payload
+    pub async fn send_frame(&mut self, frame: Message) {
+        self.ws.send(frame).await.expect("send failed");
+    }
+
+    /// Next message from the server, taken out of its `Sequenced` envelope
+    ///
+    /// Returns `None` once the server closes the connection or stays quiet
+    /// for `RECV_TIMEOUT`.
+    pub async fn recv(&mut self) -> Option<BinaryMessage> {
+        let data = self.recv_frame().await?;
+        let message = BinaryMessage::decode(&data).expect("undecodable server frame");
+        Some(match message {
+            BinaryMessage::Sequenced { message, .. } => *message,
+            message => message,
+        })
+    }
+
+    /// Next binary frame from the server, exactly as sent
+    ///
+    /// Returns `None` once the server closes the connection or stays quiet
+    /// for `RECV_TIMEOUT`.
+    pub async fn recv_frame(&mut self) -> Option<Vec<u8>> {
+        loop {
+            let frame = tokio::time::timeout(RECV_TIMEOUT, self.ws.next())
+                .await
+                .ok()??;
+            match frame.ok()? {
+                Message::Binary(data) => return Some(data.to_vec()),
+                Message::Close(_) => return None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip messages until one matches `predicate`
+    ///
+    /// Panics if the server goes quiet first.
+    pub async fn recv_until(
+        &mut self,
+        predicate: impl Fn(&BinaryMessage) -> bool,
+    ) -> BinaryMessage {
+        while let Some(message) = self.recv().await {
+            if predicate(&message) {
+                return message;
+            }
+        }
+        panic!("server went quiet before the expected message");
+    }
+
+    /// Skip messages until the server's close frame
+    pub async fn recv_close(&mut self) -> Option<CloseFrame> {
+        loop {
+            let frame = tokio::time::timeout(RECV_TIMEOUT, self.ws.next())
+                .await
+                .ok()??;
+            if let Message::Close(close) = frame.ok()? {
+                return close;
+            }
+        }
+    }
+
+    /// Close the connection and wait for the server to finish with it
+    ///
+    /// The manager has processed the disconnect once this returns.
+    pub async fn close(mut self) {
+        let _ = self.ws.close(None).await;
+        while let Some(Ok(_)) = self.ws.next().await {}
+        self.server
+            .await
+            .expect("server task panicked")
+            .expect("server returned an error");
+    }
+}
@@ -1,25 +1,310 @@
+use crate::config::Config;
 use crate::connection::manager::ConnectionManager;
-use crate::protocol::BinaryMessage;
+use crate::protocol::{is_client_message_type, BinaryMessage};
 use futures_util::{SinkExt, StreamExt};
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::TcpStream;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::mpsc;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::{header, StatusCode};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{accept_hdr_async_with_config, tungstenite::Message, WebSocketStream};
+use tracing::Instrument;
+
+/// How long to wait for queued messages (e.g. a close frame) to flush on disconnect
+const SEND_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Close reason sent to clients that send Text frames
+pub const TEXT_FRAME_CLOSE_REASON: &str = "binary frames required";
+
+/// Close reason sent to clients whose first frame does not decode
+pub const UNSUPPORTED_PROTOCOL_CLOSE_REASON: &str = "unsupported protocol version";
+
+/// Close reason sent to clients whose frame exceeds `WS_MAX_FRAME_SIZE` or `WS_MAX_MESSAGE_SIZE`
+pub const FRAME_TOO_LARGE_CLOSE_REASON: &str = "frame too large";
+
+/// Close reason sent to clients over the per-IP connection rate limit
+pub const RATE_LIMIT_CLOSE_REASON: &str = "connection rate limit exceeded";
+
+/// Close reason sent to clients turned away at `MAX_CONNECTIONS`
+pub const OVERLOADED_CLOSE_REASON: &str = "server at connection limit";
+
+/// How long clients turned away at `MAX_CONNECTIONS` are asked to wait
+pub const OVERLOADED_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Key of the retry hint appended to a close reason
+pub const RETRY_AFTER_KEY: &str = "retry-after";
+
+/// Longest a connection that is only being turned away may take to close
+const REJECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of checking a client message against the allow-list
+#[derive(Debug, PartialEq, Eq)]
+pub enum GuardVerdict {
+    /// Message type is allowed from clients
+    Allow,
+    /// Message type is server-only; drop it and count a strike
+    Reject,
+    /// Too many disallowed messages; close the connection
+    Close,
+}
+
+/// Tracks disallowed message types sent by a single client
+#[derive(Debug)]
+pub struct ProtocolGuard {
+    strikes: u32,
+    max_strikes: u32,
+}
+
+impl ProtocolGuard {
+    /// Create a guard that closes after `max_strikes` disallowed messages
+    pub fn new(max_strikes: u32) -> Self {
+        Self {
+            strikes: 0,
+            max_strikes,
+        }
+    }
+
+    /// Check a decoded message against the client allow-list
+    ///
+    /// A Correlated request is judged by the frame it wraps.
+    pub fn check(&mut self, msg: &BinaryMessage) -> GuardVerdict {
+        let request = match msg {
+            BinaryMessage::Correlated { message, .. } => message,
+            _ => msg,
+        };
+        if is_client_message_type(request.message_type()) {
+            return GuardVerdict::Allow;
+        }
+
+        self.strikes += 1;
+        if self.strikes >= self.max_strikes {
+            GuardVerdict::Close
+        } else {
+            GuardVerdict::Reject
+        }
+    }
+
+    /// Number of disallowed messages seen so far
+    pub fn strikes(&self) -> u32 {
+        self.strikes
+    }
+}
+
+/// Close message for a client that sent a Text frame
+///
+/// The presence protocol is binary-only, so a Text frame means the client is
+/// speaking the wrong protocol; decoding it would only yield unknown-type
+/// errors.
+pub fn text_frame_close() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: CloseCode::Unsupported,
+        reason: TEXT_FRAME_CLOSE_REASON.into(),
+    }))
+}
+
+/// Close message for a client whose first frame does not decode
+///
+/// A client built for an older layout (e.g. u16 board ids) that never sends
+/// a Hello would otherwise have every frame dropped as malformed while it
+/// believes it is connected.
+pub fn unsupported_protocol_close() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: CloseCode::Protocol,
+        reason: UNSUPPORTED_PROTOCOL_CLOSE_REASON.into(),
+    }))
+}
+
+/// Close message for a client that sent an oversized frame
+///
+/// The WebSocket layer refuses the frame from its header, before the payload
+/// is buffered or decoded. No client built for this protocol sends one, so
+/// the connection is closed as a protocol violation.
+pub fn frame_too_large_close() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: CloseCode::Protocol,
+        reason: FRAME_TOO_LARGE_CLOSE_REASON.into(),
+    }))
+}
+
+/// Close message asking the client to wait `retry_after` before reconnecting
+///
+/// The reason reads `<reason>; retry-after=<seconds>`, with the wait rounded
+/// up to whole seconds and at least 1, so a client that ignores the hint
+/// still sees a readable reason.
+pub fn retry_after_close(code: CloseCode, reason: &str, retry_after: Duration) -> Message {
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    Message::Close(Some(CloseFrame {
+        code,
+        reason: format!("{}; {}={}", reason, RETRY_AFTER_KEY, seconds.max(1)).into(),
+    }))
+}
+
+/// Read the retry hint from a close reason written by `retry_after_close`
+pub fn parse_retry_after(reason: &str) -> Option<Duration> {
+    reason
+        .split(';')
+        .skip(1)
+        .filter_map(|part| part.trim().split_once('='))
+        .find(|(key, _)| *key == RETRY_AFTER_KEY)
+        .and_then(|(_, seconds)| seconds.parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// Complete the handshake only to close the connection with `close`
+///
+/// Used for connections turned away before they are registered, so clients
+/// learn why and when to retry instead of seeing a failed handshake. The
+/// whole exchange is bounded by `REJECT_TIMEOUT`.
+pub async fn reject_connection<S>(
+    stream: S,
+    addr: SocketAddr,
+    config: &Config,
+    close: Message,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let exchange = async {
+        let mut ws = accept_with_origin_check(stream, addr, config).await?;
+        ws.send(close).await?;
+        // Wait for the client's close reply so the frame is not cut off
+        while let Some(Ok(_)) = ws.next().await {}
+        Ok::<_, tokio_tungstenite::tungstenite::Error>(())
+    };
+    tokio::time::timeout(REJECT_TIMEOUT, exchange).await??;
+    Ok(())
+}
+
+/// Check a handshake `Origin` header against the allow-list
+///
+/// Requests without an `Origin` header come from non-browser clients, which
+/// are not exposed to cross-site WebSocket hijacking, so they are allowed.
+pub fn is_origin_allowed(
+    origin: Option<&str>,
+    allowed_origins: &[String],
+    allow_any_origin: bool,
+) -> bool {
+    match origin {
+        None => true,
+        Some(_) if allow_any_origin => true,
+        Some(origin) => {
+            let origin = origin.trim().trim_end_matches('/');
+            allowed_origins
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+        }
+    }
+}
+
+/// Perform the WebSocket handshake, rejecting disallowed origins with 403
+///
+/// The stream is configured with the buffer and frame limits from `config`,
+/// so oversized frames fail in the WebSocket layer before reaching the decoder.
+pub async fn accept_with_origin_check<S>(
+    stream: S,
+    addr: SocketAddr,
+    config: &Config,
+) -> Result<WebSocketStream<S>, tokio_tungstenite::tungstenite::Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // The error type is fixed by tungstenite's handshake `Callback` trait
+    #[allow(clippy::result_large_err)]
+    let callback = |req: &Request, resp: Response| check_origin(req, resp, addr, config);
+    accept_hdr_async_with_config(stream, callback, Some(config.websocket_config())).await
+}
+
+/// Handshake callback passing `resp` through if the request's origin is allowed
+///
+/// Otherwise answers 403 Forbidden.
+#[allow(clippy::result_large_err)] // Signature set by tungstenite's handshake `Callback`
+fn check_origin(
+    req: &Request,
+    resp: Response,
+    addr: SocketAddr,
+    config: &Config,
+) -> Result<Response, ErrorResponse> {
+    let origin = req
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok());
+
+    if is_origin_allowed(origin, &config.allowed_origins, config.allow_any_origin) {
+        return Ok(resp);
+    }
+
+    tracing::warn!("Rejected WebSocket from {} with origin {:?}", addr, origin);
+    let mut rejection = ErrorResponse::new(Some("Origin not allowed".to_string()));
+    *rejection.status_mut() = StatusCode::FORBIDDEN;
+    Err(rejection)
+}
+
+/// Tracing span covering everything logged on behalf of one connection
+///
+/// `user_id` is the authenticated backend user, so operators can correlate
+/// presence activity with backend requests. Anonymous connections are
+/// recorded as `user_id=none`.
+pub fn connection_span(addr: SocketAddr, user_id: Option<&str>) -> tracing::Span {
+    tracing::info_span!(
+        "connection",
+        peer = %addr,
+        user_id = %user_id.unwrap_or("none")
+    )
+}
 
 /// Handle a WebSocket connection from a client
 ///
-/// This function accepts a TCP stream, upgrades it to WebSocket,
-/// and manages the bidirectional communication with the client.
-pub async fn handle_connection(
-    stream: TcpStream,
+/// This function accepts a stream (a TCP socket in production, an in-memory
+/// duplex in tests), upgrades it to WebSocket, and manages the bidirectional
+/// communication with the client.
+/// Joins, leaves and cursor updates are logged inside the connection's span.
+pub async fn handle_connection<S>(
+    stream: S,
     addr: SocketAddr,
     manager: Arc<ConnectionManager>,
-) -> Result<(), Box<dyn std::error::Error>> {
+    config: Arc<Config>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // Connections carry no backend identity yet, so every span is anonymous
+    let span = connection_span(addr, None);
+    serve_connection(stream, addr, manager, config)
+        .instrument(span)
+        .await
+}
+
+async fn serve_connection<S>(
+    stream: S,
+    addr: SocketAddr,
+    manager: Arc<ConnectionManager>,
+    config: Arc<Config>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     tracing::info!("New WebSocket connection from: {}", addr);
 
-    // Upgrade TCP stream to WebSocket
-    let ws_stream = accept_async(stream).await?;
+    if config.max_connections > 0 && manager.connection_count().await >= config.max_connections {
+        tracing::warn!(
+            "Turning away {}: at the limit of {} connections",
+            addr,
+            config.max_connections
+        );
+        let close = retry_after_close(
+            CloseCode::Again,
+            OVERLOADED_CLOSE_REASON,
+            OVERLOADED_RETRY_AFTER,
+        );
+        return reject_connection(stream, addr, &config, close).await;
+    }
+
+    // Upgrade TCP stream to WebSocket (rejects disallowed origins)
+    let ws_stream = accept_with_origin_check(stream, addr, &config).await?;
     tracing::debug!("WebSocket handshake completed for: {}", addr);
 
     // Split the WebSocket into sender and receiver
@@ -31,30 +316,69 @@ pub async fn handle_connection(
     // Register connection with manager
     manager.connect(addr, tx.clone()).await;
 
+    let mut guard = ProtocolGuard::new(config.max_protocol_strikes);
+    let mut decoded_any = false;
+
     // Spawn task to handle outbound messages
-    let send_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            // Send message via WebSocket
-            if let Err(e) = write.send(msg).await {
-                tracing::error!("Failed to send message: {}", e);
-                break;
+    let mut send_task = tokio::spawn(
+        async move {
+            while let Some(msg) = rx.recv().await {
+                // Send message via WebSocket
+                if let Err(e) = write.send(msg).await {
+                    tracing::error!("Failed to send message: {}", e);
+                    break;
+                }
             }
+            tracing::debug!("Outbound message task completed");
         }
-        tracing::debug!("Outbound message task completed");
-    });
+        .in_current_span(),
+    );
 
     // Process inbound messages
     while let Some(message) = read.next().await {
         match message {
             Ok(Message::Binary(data)) => {
+                manager.metrics().record_received(&data);
+
                 // Decode binary message
-                match BinaryMessage::decode(&data) {
-                    Ok(decoded_msg) => {
-                        // Route to ConnectionManager
-                        manager.handle_message(addr, decoded_msg).await;
+                let decoded = BinaryMessage::decode(&data);
+                decoded_any |= decoded.is_ok();
+                match decoded {
+                    Ok(decoded_msg) => match guard.check(&decoded_msg) {
+                        GuardVerdict::Allow => {
+                            // Route to ConnectionManager
+                            manager.handle_message(addr, decoded_msg).await;
+                        }
+                        GuardVerdict::Reject => {
+                            tracing::warn!(
+                                "Dropped server-only message from {} (strike {}): {:?}",
+                                addr,
+                                guard.strikes(),
+                                decoded_msg
+                            );
+                        }
+                        GuardVerdict::Close => {
+                            tracing::warn!(
+                                "Closing {} after {} disallowed messages",
+                                addr,
+                                guard.strikes()
+                            );
+                            let _ = tx.send(Message::Close(Some(CloseFrame {
+                                code: CloseCode::Policy,
+                                reason: "too many disallowed messages".into(),
+                            })));
+                            break;
+                        }
+                    },
+                    Err(e) if !decoded_any => {
+                        // Nothing has decoded yet: the client speaks another version
+                        tracing::warn!("Closing {} after undecodable first message: {}", addr, e);
+                        let _ = tx.send(unsupported_protocol_close());
+                        break;
                     }
                     Err(e) => {
                         tracing::warn!("Failed to decode message from {}: {}", addr, e);
+                        manager.report_protocol_error(addr, &e).await;
                         // Continue processing other messages
                     }
                 }
@@ -75,13 +399,24 @@ pub async fn handle_connection(
                 tracing::trace!("Pong received from {}", addr);
             }
             Ok(Message::Text(text)) => {
-                // We only support binary protocol, log and ignore text messages
-                tracing::warn!("Received unexpected text message from {}: {}", addr, text);
+                // The protocol is binary-only; close instead of guessing at text
+                tracing::warn!(
+                    "Closing {} after unexpected text message ({} bytes)",
+                    addr,
+                    text.len()
+                );
+                let _ = tx.send(text_frame_close());
+                break;
             }
             Ok(Message::Frame(_)) => {
                 // Raw frame, shouldn't normally receive this
                 tracing::trace!("Received raw frame from {}", addr);
             }
+            Err(tokio_tungstenite::tungstenite::Error::Capacity(e)) => {
+                tracing::warn!("Closing {} after oversized frame: {}", addr, e);
+                let _ = tx.send(frame_too_large_close());
+                break;
+            }
             Err(e) => {
                 tracing::error!("WebSocket error for {}: {}", addr, e);
                 break;
@@ -92,13 +427,629 @@ pub async fn handle_connection(
     // Cleanup on disconnect
     tracing::info!("WebSocket disconnecting: {}", addr);
 
-    // Cancel the send task
-    send_task.abort();
-
-    // Notify manager of disconnect
+    // Notify manager of disconnect (drops the manager's sender)
     manager.disconnect(addr).await;
 
+    // Let the send task flush anything still queued, then stop it
+    drop(tx);
+    if tokio::time::timeout(SEND_FLUSH_TIMEOUT, &mut send_task)
+        .await
+        .is_err()
+    {
+        send_task.abort();
+    }
+
     tracing::info!("WebSocket disconnected: {}", addr);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_harness::{test_config, test_manager, TestClient};
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+    /// Run a client handshake with the given origin against the server-side check
+    async fn handshake(
+        config: Config,
+        origin: Option<&str>,
+    ) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+
+        let server = tokio::spawn(async move {
+            accept_with_origin_check(server_io, addr, &config)
+                .await
+                .map(|_| ())
+        });
+
+        let mut request = "ws://localhost/".into_client_request().unwrap();
+        if let Some(origin) = origin {
+            request
+                .headers_mut()
+                .insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+        }
+        let client = tokio_tungstenite::client_async(request, client_io)
+            .await
+            .map(|_| ());
+
+        let _ = server.await.unwrap();
+        client
+    }
+
+    #[test]
+    fn test_origin_allow_list() {
+        let allowed = vec!["http://localhost:3000".to_string()];
+        let check = |origin| is_origin_allowed(Some(origin), &allowed, false);
+
+        assert!(check("http://localhost:3000"));
+        assert!(check("http://localhost:3000/"));
+        assert!(check("HTTP://LOCALHOST:3000"));
+        assert!(!check("https://evil.example"));
+        assert!(!check("http://localhost:3001"));
+        assert!(!check("null"));
+    }
+
+    #[test]
+    fn test_origin_missing_or_permissive() {
+        assert!(is_origin_allowed(None, &[], false));
+        assert!(is_origin_allowed(Some("https://evil.example"), &[], true));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_allowed_origin() {
+        let config = test_config(&["http://localhost:3000"], false);
+
+        let result = handshake(config, Some("http://localhost:3000")).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_disallowed_origin() {
+        let config = test_config(&["http://localhost:3000"], false);
+
+        match handshake(config, Some("https://evil.example")).await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            }
+            other => panic!("expected 403 rejection, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_guard_allows_client_messages() {
+        let mut guard = ProtocolGuard::new(3);
+
+        assert_eq!(guard.check(&BinaryMessage::Heartbeat), GuardVerdict::Allow);
+        assert_eq!(
+            guard.check(&BinaryMessage::Leave { board_id: 1 }),
+            GuardVerdict::Allow
+        );
+        assert_eq!(guard.strikes(), 0);
+    }
+
+    #[test]
+    fn test_guard_strikes_then_closes() {
+        let mut guard = ProtocolGuard::new(3);
+        let server_only = BinaryMessage::CursorBroadcast {
+            board_id: 1,
+            user_id: 0,
+            x: 0,
+            y: 0,
+        };
+
+        assert_eq!(guard.check(&server_only), GuardVerdict::Reject);
+        assert_eq!(guard.strikes(), 1);
+
+        // Allowed messages do not reset strikes
+        assert_eq!(guard.check(&BinaryMessage::Heartbeat), GuardVerdict::Allow);
+
+        assert_eq!(guard.check(&server_only), GuardVerdict::Reject);
+        assert_eq!(guard.check(&server_only), GuardVerdict::Close);
+        assert_eq!(guard.strikes(), 3);
+    }
+
+    #[test]
+    fn test_guard_single_strike() {
+        let mut guard = ProtocolGuard::new(1);
+        let msg = BinaryMessage::PresenceUpdate {
+            board_id: 1,
+            count: 1,
+        };
+
+        assert_eq!(guard.check(&msg), GuardVerdict::Close);
+    }
+
+    #[test]
+    fn test_guard_checks_correlated_request() {
+        let mut guard = ProtocolGuard::new(3);
+
+        let query = BinaryMessage::QueryPresence { board_id: 1 }.correlate(Some(1));
+        assert_eq!(guard.check(&query), GuardVerdict::Allow);
+
+        let reply = BinaryMessage::PresenceUpdate {
+            board_id: 1,
+            count: 1,
+        }
+        .correlate(Some(1));
+        assert_eq!(guard.check(&reply), GuardVerdict::Reject);
+        assert_eq!(guard.strikes(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_text_frame_closes_with_reason() {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let config = test_config(&[], true);
+
+        let server = tokio::spawn(async move {
+            let mut ws = accept_with_origin_check(server_io, addr, &config)
+                .await
+                .unwrap();
+            let received = ws.next().await.unwrap().unwrap();
+            assert!(matches!(received, Message::Text(_)));
+            ws.send(text_frame_close()).await.unwrap();
+        });
+
+        let request = "ws://localhost/".into_client_request().unwrap();
+        let (mut client, _) = tokio_tungstenite::client_async(request, client_io)
+            .await
+            .unwrap();
+        client
+            .send(Message::Text(r#"{"type":"join"}"#.into()))
+            .await
+            .unwrap();
+
+        match client.next().await {
+            Some(Ok(Message::Close(Some(frame)))) => {
+                assert_eq!(frame.code, CloseCode::Unsupported);
+                assert_eq!(frame.reason.as_str(), TEXT_FRAME_CLOSE_REASON);
+            }
+            other => panic!("expected close frame, got {:?}", other),
+        }
+
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_unsupported_protocol_close() {
+        match unsupported_protocol_close() {
+            Message::Close(Some(frame)) => {
+                assert_eq!(frame.code, CloseCode::Protocol);
+                assert_eq!(frame.reason.as_str(), UNSUPPORTED_PROTOCOL_CLOSE_REASON);
+            }
+            other => panic!("expected close frame, got {:?}", other),
+        }
+
+        // A u16-layout Join, as sent by clients from before protocol v7
+        assert!(
+            BinaryMessage::decode(&[0x03, 0x04, 0xD2, 5, b'A', b'l', b'i', b'c', b'e']).is_err()
+        );
+    }
+
+    /// Log sink shared between a test subscriber and the assertions
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for CapturedLogs {
+        type Writer = CapturedLogs;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Log a join inside a connection span and return the formatted output
+    fn log_join_in_span(user_id: Option<&str>) -> String {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _entered = connection_span(addr, user_id).entered();
+            tracing::info!("Client {} joined board 1 as user 0 (Alice)", addr);
+        });
+
+        let output = logs.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_authenticated_join_logs_user_id() {
+        let output = log_join_in_span(Some("42"));
+
+        assert!(output.contains("user_id=42"), "{}", output);
+        assert!(output.contains("peer=127.0.0.1:9000"), "{}", output);
+        assert!(output.contains("joined board 1"), "{}", output);
+    }
+
+    #[test]
+    fn test_anonymous_join_logs_user_id_none() {
+        let output = log_join_in_span(None);
+
+        assert!(output.contains("user_id=none"), "{}", output);
+    }
+
+    #[test]
+    fn test_retry_after_close_reason() {
+        let close = retry_after_close(
+            CloseCode::Policy,
+            RATE_LIMIT_CLOSE_REASON,
+            Duration::from_millis(2300),
+        );
+        match close {
+            Message::Close(Some(frame)) => {
+                assert_eq!(frame.code, CloseCode::Policy);
+                assert_eq!(
+                    frame.reason.as_str(),
+                    "connection rate limit exceeded; retry-after=3"
+                );
+                assert_eq!(
+                    parse_retry_after(frame.reason.as_str()),
+                    Some(Duration::from_secs(3))
+                );
+            }
+            other => panic!("expected close frame, got {:?}", other),
+        }
+
+        assert_eq!(parse_retry_after(TEXT_FRAME_CLOSE_REASON), None);
+        assert_eq!(parse_retry_after("busy; retry-after=soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_connection_receives_retry_after() {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let config = test_config(&[], true);
+
+        let server = tokio::spawn(async move {
+            let close = retry_after_close(
+                CloseCode::Again,
+                OVERLOADED_CLOSE_REASON,
+                OVERLOADED_RETRY_AFTER,
+            );
+            reject_connection(server_io, addr, &config, close)
+                .await
+                .map_err(|e| e.to_string())
+        });
+
+        let request = "ws://localhost/".into_client_request().unwrap();
+        let (mut client, _) = tokio_tungstenite::client_async(request, client_io)
+            .await
+            .unwrap();
+
+        match client.next().await {
+            Some(Ok(Message::Close(Some(frame)))) => {
+                assert_eq!(frame.code, CloseCode::Again);
+                assert_eq!(
+                    parse_retry_after(frame.reason.as_str()),
+                    Some(OVERLOADED_RETRY_AFTER)
+                );
+            }
+            other => panic!("expected close frame, got {:?}", other),
+        }
+
+        // The client's reply completes the close handshake
+        assert!(client.next().await.is_none());
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_rejected() {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let mut config = test_config(&[], true);
+        config.ws_max_message_size = 64;
+        config.ws_max_frame_size = 64;
+
+        let server = tokio::spawn(async move {
+            let mut ws = accept_with_origin_check(server_io, addr, &config)
+                .await
+                .unwrap();
+
+            // A frame at the limit is accepted
+            let received = ws.next().await.unwrap().unwrap();
+            assert_eq!(received, Message::Binary(vec![0u8; 64].into()));
+
+            ws.next().await.unwrap()
+        });
+
+        let request = "ws://localhost/".into_client_request().unwrap();
+        let (mut client, _) = tokio_tungstenite::client_async(request, client_io)
+            .await
+            .unwrap();
+        client
+            .send(Message::Binary(vec![0u8; 64].into()))
+            .await
+            .unwrap();
+        client
+            .send(Message::Binary(vec![0u8; 65].into()))
+            .await
+            .unwrap();
+
+        match server.await.unwrap() {
+            Err(tokio_tungstenite::tungstenite::Error::Capacity(_)) => {}
+            other => panic!("expected capacity error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_closes_with_protocol_error() {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let mut config = test_config(&[], true);
+        config.ws_max_message_size = 1024;
+        config.ws_max_frame_size = 1024;
+
+        let server = tokio::spawn(async move {
+            let mut ws = accept_with_origin_check(server_io, addr, &config)
+                .await
+                .unwrap();
+            match ws.next().await {
+                Some(Err(tokio_tungstenite::tungstenite::Error::Capacity(_))) => {}
+                other => panic!("expected capacity error, got {:?}", other),
+            }
+            ws.send(frame_too_large_close()).await.unwrap();
+        });
+
+        let request = "ws://localhost/".into_client_request().unwrap();
+        let (mut client, _) = tokio_tungstenite::client_async(request, client_io)
+            .await
+            .unwrap();
+        client
+            .send(Message::Binary(vec![0u8; 2048].into()))
+            .await
+            .unwrap();
+
+        match client.next().await {
+            Some(Ok(Message::Close(Some(frame)))) => {
+                assert_eq!(frame.code, CloseCode::Protocol);
+                assert_eq!(frame.reason.as_str(), FRAME_TOO_LARGE_CLOSE_REASON);
+            }
+            other => panic!("expected close frame, got {:?}", other),
+        }
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_join_roster_cursor_leave_flow() {
+        let manager = test_manager().await;
+        let config = Arc::new(test_config(&[], true));
+        let alice_addr: SocketAddr = "127.0.0.1:9111".parse().unwrap();
+        let bob_addr: SocketAddr = "127.0.0.1:9112".parse().unwrap();
+
+        let mut alice = TestClient::connect(alice_addr, manager.clone(), config.clone()).await;
+        alice
+            .send(BinaryMessage::Join {
+                board_id: 40,
+                username: "Alice".to_string(),
+                reconnect_token: None,
+            })
+            .await;
+        alice
+            .recv_until(|msg| {
+                matches!(
+                    msg,
+                    BinaryMessage::PresenceUpdate {
+                        board_id: 40,
+                        count: 1
+                    }
+                )
+            })
+            .await;
+
+        // Bob is sent the roster; Alice hears that he joined
+        let mut bob = TestClient::connect(bob_addr, manager.clone(), config.clone()).await;
+        bob.send(BinaryMessage::Join {
+            board_id: 40,
+            username: "Bob".to_string(),
+            reconnect_token: None,
+        })
+        .await;
+        let roster = bob
+            .recv_until(|msg| matches!(msg, BinaryMessage::UserJoined { .. }))
+            .await;
+        assert!(matches!(
+            roster,
+            BinaryMessage::UserJoined { ref username, .. } if username == "Alice"
+        ));
+        let bob_id = match alice
+            .recv_until(|msg| matches!(msg, BinaryMessage::UserJoined { .. }))
+            .await
+        {
+            BinaryMessage::UserJoined {
+                user_id, username, ..
+            } => {
+                assert_eq!(username, "Bob");
+                user_id
+            }
+            _ => unreachable!(),
+        };
+
+        bob.send(BinaryMessage::CursorUpdate {
+            board_id: 40,
+            x: 100,
+            y: 200,
+        })
+        .await;
+        let cursor = alice
+            .recv_until(|msg| matches!(msg, BinaryMessage::CursorBroadcast { .. }))
+            .await;
+        assert_eq!(
+            cursor,
+            BinaryMessage::CursorBroadcast {
+                board_id: 40,
+                user_id: bob_id,
+                x: 100,
+                y: 200,
+            }
+        );
+
+        bob.send(BinaryMessage::Leave { board_id: 40 }).await;
+        let left = alice
+            .recv_until(|msg| matches!(msg, BinaryMessage::UserLeft { .. }))
+            .await;
+        assert_eq!(
+            left,
+            BinaryMessage::UserLeft {
+                board_id: 40,
+                user_id: bob_id,
+            }
+        );
+
+        bob.close().await;
+        alice.close().await;
+        assert_eq!(manager.connection_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_frames_without_hello_decodable_by_frontend() {
+        use crate::protocol::{
+            MSG_CURSOR_BROADCAST, MSG_HEARTBEAT, MSG_PRESENCE_UPDATE, MSG_USER_JOINED,
+            MSG_USER_LEFT,
+        };
+
+        let manager = test_manager().await;
+        let config = Arc::new(test_config(&[], true));
+        let alice_addr: SocketAddr = "127.0.0.1:9116".parse().unwrap();
+        let bob_addr: SocketAddr = "127.0.0.1:9117".parse().unwrap();
+
+        // Neither client sends Hello, like frontend/src/lib/websocket-client.ts
+        let mut alice = TestClient::connect(alice_addr, manager.clone(), config.clone()).await;
+        let mut bob = TestClient::connect(bob_addr, manager.clone(), config.clone()).await;
+        for (client, name) in [(&mut alice, "Alice"), (&mut bob, "Bob")] {
+            client
+                .send(BinaryMessage::Join {
+                    board_id: 42,
+                    username: name.to_string(),
+                    reconnect_token: None,
+                })
+                .await;
+        }
+        bob.send(BinaryMessage::CursorUpdate {
+            board_id: 42,
+            x: 100,
+            y: 200,
+        })
+        .await;
+        alice.send(BinaryMessage::Heartbeat).await;
+        bob.send(BinaryMessage::Leave { board_id: 42 }).await;
+
+        let mut frames = Vec::new();
+        while let Some(frame) = alice.recv_frame().await {
+            frames.push(frame);
+        }
+
+        // Plain frames of the types and sizes `decodeMessage` in
+        // frontend/src/lib/protocol.ts accepts: no Sequenced envelope, no
+        // timed cursor broadcast and no Ping
+        for frame in &frames {
+            match frame[0] {
+                MSG_CURSOR_BROADCAST => assert_eq!(frame.len(), 10),
+                MSG_HEARTBEAT => assert_eq!(frame.len(), 1),
+                MSG_PRESENCE_UPDATE => assert_eq!(frame.len(), 7),
+                MSG_USER_LEFT => assert_eq!(frame.len(), 6),
+                MSG_USER_JOINED => {}
+                other => panic!("frontend cannot decode message type 0x{:02X}", other),
+            }
+        }
+        for expected in [
+            MSG_USER_JOINED,
+            MSG_PRESENCE_UPDATE,
+            MSG_CURSOR_BROADCAST,
+            MSG_HEARTBEAT,
+            MSG_USER_LEFT,
+        ] {
+            assert!(
+                frames.iter().any(|frame| frame[0] == expected),
+                "no frame of type 0x{:02X}",
+                expected
+            );
+        }
+
+        bob.close().await;
+        alice.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_leaves_room() {
+        let manager = test_manager().await;
+        let config = Arc::new(test_config(&[], true));
+        let alice_addr: SocketAddr = "127.0.0.1:9113".parse().unwrap();
+        let bob_addr: SocketAddr = "127.0.0.1:9114".parse().unwrap();
+
+        let mut alice = TestClient::connect(alice_addr, manager.clone(), config.clone()).await;
+        let mut bob = TestClient::connect(bob_addr, manager.clone(), config.clone()).await;
+        for (client, name) in [(&mut alice, "Alice"), (&mut bob, "Bob")] {
+            client
+                .send(BinaryMessage::Join {
+                    board_id: 41,
+                    username: name.to_string(),
+                    reconnect_token: None,
+                })
+                .await;
+        }
+        alice
+            .recv_until(|msg| matches!(msg, BinaryMessage::PresenceUpdate { count: 2, .. }))
+            .await;
+
+        // Closing without a Leave still removes Bob from the room
+        bob.close().await;
+        alice
+            .recv_until(|msg| matches!(msg, BinaryMessage::UserLeft { board_id: 41, .. }))
+            .await;
+        assert_eq!(manager.get_room_user_count(41).await, 1);
+
+        alice.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_text_frame_closes_served_connection() {
+        let manager = test_manager().await;
+        let config = Arc::new(test_config(&[], true));
+        let addr: SocketAddr = "127.0.0.1:9115".parse().unwrap();
+
+        let mut client = TestClient::connect(addr, manager.clone(), config).await;
+        client.send_frame(Message::Text("hello".into())).await;
+
+        let close = client.recv_close().await.expect("expected a close frame");
+        assert_eq!(close.code, CloseCode::Unsupported);
+        assert_eq!(close.reason.as_str(), TEXT_FRAME_CLOSE_REASON);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_closes_served_connection() {
+        let manager = test_manager().await;
+        let mut config = test_config(&[], true);
+        config.ws_max_message_size = 1024;
+        config.ws_max_frame_size = 1024;
+        let addr: SocketAddr = "127.0.0.1:9116".parse().unwrap();
+
+        let mut client = TestClient::connect(addr, manager, Arc::new(config)).await;
+        client
+            .send_frame(Message::Binary(vec![0u8; 2048].into()))
+            .await;
+
+        let close = client.recv_close().await.expect("expected a close frame");
+        assert_eq!(close.code, CloseCode::Protocol);
+        assert_eq!(close.reason.as_str(), FRAME_TOO_LARGE_CLOSE_REASON);
+    }
+}
@@ -1,50 +1,73 @@
 use anyhow::Result;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::net::TcpListener;
-use tracing::info;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tracing::{debug, info, warn};
 
-mod config;
-mod connection;
-mod handlers;
-mod presence;
-mod protocol;
-mod redis;
-mod utils;
+use presence_service::config::Config;
+use presence_service::connection::manager::ConnectionManager;
+use presence_service::connection::rate_limit::ConnectionRateLimiter;
+use presence_service::handlers::admin::serve_admin;
+use presence_service::handlers::metrics::serve_metrics;
+use presence_service::handlers::websocket::{
+    handle_connection, reject_connection, retry_after_close, RATE_LIMIT_CLOSE_REASON,
+};
+use presence_service::redis::client::RedisClient;
+use presence_service::redis::pubsub::RedisPubSub;
+use presence_service::redis::store::{epoch_day, PeakStore};
 
-use connection::manager::ConnectionManager;
-use handlers::websocket::handle_connection;
-use redis::client::RedisClient;
-use redis::pubsub::RedisPubSub;
+/// How often connections are checked against `IDLE_TIMEOUT_SECS`
+const IDLE_SCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often per-board peak presence is written to Redis
+const PEAK_RECORD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Time given to connection tasks to write their close frames before exiting
+const SHUTDOWN_CLOSE_GRACE: Duration = Duration::from_secs(1);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing subscriber
+    // Load config from .env file
+    dotenvy::dotenv().ok();
+
+    let config = Arc::new(Config::from_env()?);
+
+    // Initialize tracing subscriber (RUST_LOG takes precedence over LOG_LEVEL)
     tracing_subscriber::fmt()
         .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| config.log_level.clone().into()),
         )
         .init();
 
     info!("Starting presence-service...");
+    config.log_effective();
 
-    // Load config from .env file
-    dotenvy::dotenv().ok();
-
-    // Get WebSocket port from environment or use default
-    let port = std::env::var("WS_PORT").unwrap_or_else(|_| "3001".to_string());
-    let addr = format!("0.0.0.0:{}", port);
+    let addr = format!("0.0.0.0:{}", config.ws_port);
 
     // Initialize Redis
-    let redis_url =
-        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string());
-
-    info!("Connecting to Redis at {}", redis_url);
-    let redis_client = RedisClient::new(&redis_url).await?;
+    info!("Connecting to Redis at {}", config.redis_url);
+    let redis_client = RedisClient::new(&config.redis_url).await?;
+    let peak_store = PeakStore::new(redis_client.clone());
     let redis_pubsub = Arc::new(RedisPubSub::new(redis_client).await?);
     info!("Redis connection established");
 
     // Create connection manager with Redis support
-    let manager = Arc::new(ConnectionManager::new(Arc::clone(&redis_pubsub)));
+    let manager = Arc::new(
+        ConnectionManager::new(Arc::clone(&redis_pubsub))
+            .with_presence_coalesce_window(Duration::from_millis(config.presence_coalesce_ms))
+            .with_cursor_dead_zone(config.cursor_dead_zone)
+            .with_cursor_tick(Duration::from_millis(config.cursor_tick_ms))
+            .with_redis_backoff(
+                Duration::from_millis(config.redis_backoff_base_ms),
+                Duration::from_millis(config.redis_backoff_max_ms),
+            )
+            .with_cursor_interval_range(
+                Duration::from_millis(config.cursor_interval_min_ms.into()),
+                Duration::from_millis(config.cursor_interval_max_ms.into()),
+            ),
+    );
 
     // Start Redis listener for cross-instance coordination
     let manager_clone = Arc::clone(&manager);
@@ -52,17 +75,118 @@ async fn main() -> Result<()> {
         manager_clone.start_redis_listener().await;
     });
 
+    // Evict connections that stopped sending heartbeats
+    if config.idle_timeout_secs > 0 {
+        let idle_timeout = Duration::from_secs(config.idle_timeout_secs);
+        let manager_clone = Arc::clone(&manager);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(IDLE_SCAN_INTERVAL);
+            loop {
+                interval.tick().await;
+                let evicted = manager_clone.evict_idle(idle_timeout, Instant::now()).await;
+                if evicted > 0 {
+                    info!("Evicted {} idle connections", evicted);
+                }
+            }
+        });
+    } else {
+        info!("IDLE_TIMEOUT_SECS is 0, idle connections are never evicted");
+    }
+
+    // Record each board's peak presence for the day
+    let manager_clone = Arc::clone(&manager);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PEAK_RECORD_INTERVAL);
+        loop {
+            interval.tick().await;
+            let peaks = manager_clone.take_presence_peaks().await;
+            let day = epoch_day(SystemTime::now());
+            if let Err(e) = peak_store.record_peaks(day, &peaks).await {
+                warn!("Failed to record peak presence: {}", e);
+            }
+        }
+    });
+
+    // Start admin endpoint if an admin token is configured
+    match config.admin_token.clone() {
+        Some(token) => {
+            let admin_addr = format!("0.0.0.0:{}", config.admin_port);
+            let admin_listener = TcpListener::bind(&admin_addr).await?;
+            info!("Admin endpoint listening on {}", admin_addr);
+
+            let manager_clone = Arc::clone(&manager);
+            tokio::spawn(async move {
+                serve_admin(admin_listener, manager_clone, token).await;
+            });
+        }
+        None => {
+            info!("ADMIN_TOKEN not set, admin endpoint disabled");
+        }
+    }
+
+    // Start metrics endpoint if a metrics port is configured
+    match config.metrics_port {
+        Some(port) => {
+            let metrics_addr = format!("0.0.0.0:{}", port);
+            let metrics_listener = TcpListener::bind(&metrics_addr).await?;
+            info!("Metrics endpoint listening on {}", metrics_addr);
+
+            let manager_clone = Arc::clone(&manager);
+            tokio::spawn(async move {
+                serve_metrics(metrics_listener, manager_clone).await;
+            });
+        }
+        None => {
+            info!("METRICS_PORT not set, metrics endpoint disabled");
+        }
+    }
+
     // Bind TCP listener
     let listener = TcpListener::bind(&addr).await?;
     info!("WebSocket server listening on {}", addr);
 
-    // Accept connections
+    let mut rate_limiter =
+        ConnectionRateLimiter::new(config.connection_rate_limit, Duration::from_secs(1));
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    // Accept connections until asked to shut down
     loop {
-        match listener.accept().await {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = &mut shutdown => break,
+        };
+
+        match accepted {
             Ok((stream, peer_addr)) => {
+                let now = Instant::now();
+                if !rate_limiter.check(peer_addr.ip(), now) {
+                    // Tell the client when it may retry rather than just dropping it
+                    let retry_after = rate_limiter.retry_after(peer_addr.ip(), now);
+                    warn!(
+                        "Connection rate limit exceeded for {} (retry after {:?})",
+                        peer_addr.ip(),
+                        retry_after
+                    );
+                    let config = Arc::clone(&config);
+                    tokio::spawn(async move {
+                        let close = retry_after_close(
+                            CloseCode::Policy,
+                            RATE_LIMIT_CLOSE_REASON,
+                            retry_after,
+                        );
+                        if let Err(e) = reject_connection(stream, peer_addr, &config, close).await {
+                            debug!("Failed to turn away {}: {}", peer_addr, e);
+                        }
+                    });
+                    continue;
+                }
+
                 let manager = Arc::clone(&manager);
+                let config = Arc::clone(&config);
                 tokio::spawn(async move {
-                    if let Err(e) = handle_connection(stream, peer_addr, manager).await {
+                    if let Err(e) = handle_connection(stream, peer_addr, manager, config).await {
                         tracing::error!("Connection error for {}: {}", peer_addr, e);
                     }
                 });
@@ -72,4 +196,40 @@ async fn main() -> Result<()> {
             }
         }
     }
+
+    // Stop accepting, then let every room's members and peers know the users are gone
+    drop(listener);
+    info!("Shutting down, draining rooms...");
+    manager.shutdown().await;
+    tokio::time::sleep(SHUTDOWN_CLOSE_GRACE).await;
+    info!("presence-service stopped");
+
+    Ok(())
+}
+
+/// Resolve when the process is asked to stop (SIGTERM, or Ctrl+C)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(e) => {
+                warn!("Failed to install SIGTERM handler: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                return;
+            }
+        };
+        tokio::select! {
+            _ = terminate.recv() => info!("Received SIGTERM"),
+            _ = tokio::signal::ctrl_c() => info!("Received Ctrl+C"),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Received Ctrl+C");
+    }
 }
@@ -4,7 +4,7 @@
 //! to integrate with WebSocket frame handling.
 
 use super::messages::{BinaryMessage, ProtocolError};
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 
 /// A codec for encoding and decoding binary protocol messages in WebSocket frames.
 ///
@@ -29,7 +29,9 @@ impl BinaryCodec {
     ///
     /// A `Bytes` buffer containing the encoded message
     pub fn encode(&self, message: &BinaryMessage) -> Bytes {
-        Bytes::from(message.encode())
+        let mut buf = BytesMut::new();
+        message.encode_into(&mut buf);
+        buf.freeze()
     }
 
     /// Decode bytes from a WebSocket frame into a binary message.
@@ -87,6 +89,7 @@ mod tests {
         let original = BinaryMessage::Join {
             board_id: 100,
             username: "Alice".to_string(),
+            reconnect_token: None,
         };
 
         let encoded = codec.encode(&original);
@@ -1,8 +1,8 @@
 //! Binary message encoding and decoding.
 //!
-//! This module implements a highly efficient binary protocol that achieves 5-6 byte
-//! message sizes for cursor updates (96% reduction vs JSON). All multi-byte integers
-//! use big-endian byte order for network transmission.
+//! This module implements a highly efficient binary protocol that achieves 9 byte
+//! cursor updates, or 8 with 12-bit coordinates (93-94% reduction vs JSON). All
+//! multi-byte integers use big-endian byte order for network transmission.
 
 use bytes::BytesMut;
 use std::io::Cursor;
@@ -23,109 +23,594 @@ pub enum ProtocolError {
     #[error("Invalid UTF-8 in username")]
     InvalidUtf8,
 
-    #[error("Username too long: {0} bytes (max 32)")]
+    #[error("Username too long: {0} bytes (max {max})", max = MAX_USERNAME_LENGTH)]
     UsernameTooLong(usize),
 
     #[error("Buffer underflow")]
     BufferUnderflow,
+
+    #[error("Sequenced frames cannot be nested")]
+    NestedSequenced,
+
+    #[error("Correlated frames cannot wrap another envelope")]
+    NestedCorrelated,
+
+    #[error("Announcement too long: {0} bytes (max {max})", max = MAX_ANNOUNCEMENT_LENGTH)]
+    AnnouncementTooLong(usize),
+
+    #[error("Compact coordinate out of range: {0} (max {max})", max = MAX_COMPACT_COORD)]
+    CompactCoordOutOfRange(u16),
+
+    #[error("Cursor batch must hold 1 to {max} points, got {0}", max = MAX_CURSOR_BATCH_POINTS)]
+    InvalidCursorBatch(usize),
+
+    #[error("Invalid activity flag: {0} (expected 0 or 1)")]
+    InvalidActivityFlag(u8),
+}
+
+impl ProtocolError {
+    /// Code reported to the client in an `Error` frame, if this error has one
+    ///
+    /// See the code table in `types`.
+    pub fn error_code(&self) -> Option<u8> {
+        match self {
+            ProtocolError::UnknownMessageType(_) => Some(ERROR_UNKNOWN_MESSAGE_TYPE),
+            ProtocolError::InvalidLength { .. } => Some(ERROR_INVALID_LENGTH),
+            ProtocolError::UsernameTooLong(_) => Some(ERROR_USERNAME_TOO_LONG),
+            _ => None,
+        }
+    }
 }
 
 /// Binary protocol messages.
 ///
-/// Each variant represents one of the 8 message types in the protocol.
+/// Each variant represents one message type in the protocol.
 /// All messages are designed for minimal size while maintaining type safety.
 #[derive(Debug, Clone, PartialEq)]
 pub enum BinaryMessage {
-    /// Client → Server: Update cursor position (5 bytes)
+    /// Client → Server: Update cursor position (9 bytes)
     ///
     /// Layout:
     /// - byte 0: message type (0x01)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    /// - bytes 3-4: x coordinate (u16, big-endian, normalized 0-65535)
-    /// - bytes 5-6: y coordinate (u16, big-endian, normalized 0-65535)
-    CursorUpdate { board_id: u16, x: u16, y: u16 },
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-6: x coordinate (u16, big-endian, normalized 0-65535)
+    /// - bytes 7-8: y coordinate (u16, big-endian, normalized 0-65535)
+    CursorUpdate { board_id: u32, x: u16, y: u16 },
 
-    /// Server → Client: Broadcast cursor position (7 bytes)
+    /// Server → Client: Broadcast cursor position (10 bytes)
     ///
     /// Layout:
     /// - byte 0: message type (0x02)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    /// - byte 3: user_id (u8)
-    /// - bytes 4-5: x coordinate (u16, big-endian, normalized 0-65535)
-    /// - bytes 6-7: y coordinate (u16, big-endian, normalized 0-65535)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    /// - bytes 6-7: x coordinate (u16, big-endian, normalized 0-65535)
+    /// - bytes 8-9: y coordinate (u16, big-endian, normalized 0-65535)
     CursorBroadcast {
-        board_id: u16,
+        board_id: u32,
         user_id: u8,
         x: u16,
         y: u16,
     },
 
-    /// Client → Server: Join a board (4-36 bytes)
+    /// Client → Server: Join a board (6 to `MAX_JOIN_SIZE` bytes)
+    ///
+    /// A client that keeps `reconnect_token` across reconnects gets back its
+    /// user ID and color if it rejoins within `RECONNECT_GRACE` of dropping.
     ///
     /// Layout:
     /// - byte 0: message type (0x03)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    /// - byte 3: username length (u8)
-    /// - bytes 4+: username UTF-8 bytes (max 32 bytes)
-    Join { board_id: u16, username: String },
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: username length (u8)
+    /// - bytes 6+: username UTF-8 bytes (max `MAX_USERNAME_LENGTH` bytes)
+    /// - then, optionally: reconnect_token (`RECONNECT_TOKEN_LENGTH` bytes)
+    Join {
+        board_id: u32,
+        username: String,
+        reconnect_token: Option<ReconnectToken>,
+    },
 
-    /// Client → Server: Leave a board (3 bytes)
+    /// Client → Server: Leave a board (5 bytes)
     ///
     /// Layout:
     /// - byte 0: message type (0x04)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    Leave { board_id: u16 },
+    /// - bytes 1-4: board_id (u32, big-endian)
+    Leave { board_id: u32 },
 
-    /// Server → Client: User joined notification (7-40 bytes)
+    /// Server → Client: User joined notification (10 to `MAX_USER_JOINED_SIZE` bytes)
     ///
     /// Layout:
     /// - byte 0: message type (0x05)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    /// - byte 3: user_id (u8)
-    /// - byte 4: username length (u8)
-    /// - bytes 5+: username UTF-8 bytes (max 32 bytes)
-    /// - bytes (5+len) to (8+len): RGB color (3 bytes)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    /// - byte 6: username length (u8)
+    /// - bytes 7+: username UTF-8 bytes (max `MAX_USERNAME_LENGTH` bytes)
+    /// - bytes (7+len) to (9+len): RGB color (3 bytes)
     UserJoined {
-        board_id: u16,
+        board_id: u32,
         user_id: u8,
         username: String,
         color: [u8; 3],
     },
 
-    /// Server → Client: User left notification (4 bytes)
+    /// Server → Client: User left notification (6 bytes)
     ///
     /// Layout:
     /// - byte 0: message type (0x06)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    /// - byte 3: user_id (u8)
-    UserLeft { board_id: u16, user_id: u8 },
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    UserLeft { board_id: u32, user_id: u8 },
 
-    /// Server → Client: Presence count update (4 bytes)
+    /// Server → Client: Presence count update (7 bytes)
     ///
     /// Layout:
     /// - byte 0: message type (0x07)
-    /// - bytes 1-2: board_id (u16, big-endian)
-    /// - byte 3: count (u8)
-    PresenceUpdate { board_id: u16, count: u8 },
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-6: count (u16, big-endian)
+    PresenceUpdate { board_id: u32, count: u16 },
 
     /// Bidirectional: Heartbeat (1 byte)
     ///
     /// Layout:
     /// - byte 0: message type (0x08)
     Heartbeat,
+
+    /// Bidirectional: Protocol version negotiation (2 or 4 bytes)
+    ///
+    /// The client sends the highest version it supports; the server replies
+    /// with the version it will use. Clients that never send Hello get v1
+    /// behavior; a Hello below `MIN_PROTOCOL_VERSION` is refused.
+    ///
+    /// From v6 a client may also ask for its cursor updates to be coalesced
+    /// over `cursor_interval_ms`. The server clamps the interval to its
+    /// configured range and replies with the one it applies; clients that
+    /// send none get every update broadcast.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x09)
+    /// - byte 1: protocol version (u8)
+    /// - bytes 2-3: cursor_interval_ms (u16, big-endian), optional
+    Hello {
+        version: u8,
+        cursor_interval_ms: Option<u16>,
+    },
+
+    /// Server → Client: Cursor broadcast with server tick, v2+ only (14 bytes)
+    ///
+    /// The tick is milliseconds on the server's monotonic clock (wrapping),
+    /// so clients can fade cursors whose last tick falls behind the newest one.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x0A)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    /// - bytes 6-7: x coordinate (u16, big-endian, normalized 0-65535)
+    /// - bytes 8-9: y coordinate (u16, big-endian, normalized 0-65535)
+    /// - bytes 10-13: server tick in milliseconds (u32, big-endian)
+    CursorBroadcastTimed {
+        board_id: u32,
+        user_id: u8,
+        x: u16,
+        y: u16,
+        tick: u32,
+    },
+
+    /// Client → Server: Focus a card (7 bytes)
+    ///
+    /// `card_slot` identifies the card within the board; 0 clears the focus.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x0B)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-6: card_slot (u16, big-endian)
+    FocusCard { board_id: u32, card_slot: u16 },
+
+    /// Server → Client: A user's focused card changed (8 bytes)
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x0C)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    /// - bytes 6-7: card_slot (u16, big-endian, 0 = none)
+    FocusBroadcast {
+        board_id: u32,
+        user_id: u8,
+        card_slot: u16,
+    },
+
+    /// Server → Client: Sequence-numbered frame, v3+ only (5 bytes + inner frame)
+    ///
+    /// Every frame sent to a v3+ client is wrapped so the client can detect
+    /// gaps. `seq` counts up from 0 per connection (wrapping).
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x0D)
+    /// - bytes 1-4: seq (u32, big-endian)
+    /// - bytes 5+: inner frame (any other message, not itself sequenced)
+    Sequenced {
+        seq: u32,
+        message: Box<BinaryMessage>,
+    },
+
+    /// Server → Client: Text announcement for a board (6-261 bytes)
+    ///
+    /// Injected by operators through the admin endpoint, e.g. to check
+    /// end-to-end delivery.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x0E)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: text length (u8)
+    /// - bytes 6+: text UTF-8 bytes (max 255 bytes)
+    Announcement { board_id: u32, text: String },
+
+    /// Server → Client: Round-trip probe, v4+ only (5 bytes)
+    ///
+    /// Sent after each heartbeat reply. The client answers with a `Pong`
+    /// carrying the same nonce.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x0F)
+    /// - bytes 1-4: nonce (u32, big-endian)
+    Ping { nonce: u32 },
+
+    /// Client → Server: Echo of a `Ping`, v4+ only (5 bytes)
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x10)
+    /// - bytes 1-4: nonce (u32, big-endian)
+    Pong { nonce: u32 },
+
+    /// Client → Server: Ask for a board's presence count (5 bytes)
+    ///
+    /// Answered with a `PresenceUpdate` sent only to the asking client. The
+    /// client does not need to have joined the board, and asking does not
+    /// join it.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x11)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    QueryPresence { board_id: u32 },
+
+    /// Bidirectional: Client message id around a request or its reply, v5+ only (3 bytes + inner frame)
+    ///
+    /// A client wraps a request to match it with the server's answer. A
+    /// correlated `Join` is answered with the joiner's own `UserJoined` and a
+    /// correlated `QueryPresence` with its `PresenceUpdate`, each wrapped with
+    /// the same `msg_id`. Other requests are handled as if sent bare and get
+    /// no correlated reply; neither does a `Join` the server refuses.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x12)
+    /// - bytes 1-2: msg_id (u16, big-endian)
+    /// - bytes 3+: inner frame (any message except `Sequenced` or `Correlated`)
+    Correlated {
+        msg_id: u16,
+        message: Box<BinaryMessage>,
+    },
+
+    /// Client → Server: Update cursor position at 12-bit precision, v8+ (8 bytes)
+    ///
+    /// For boards that opt into low-precision cursors. Each axis has 4096
+    /// steps instead of 65536, so a step is under a pixel on boards up to
+    /// 4096px across and about 2px at 8192px. The server widens the
+    /// coordinates to 16 bits; other clients receive an ordinary
+    /// `CursorBroadcast`.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x13)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-7: x in the high 12 bits, y in the low 12 bits (normalized 0-4095)
+    CursorUpdateCompact { board_id: u32, x: u16, y: u16 },
+
+    /// Client → Server: Several cursor positions in order, v9+ (6 + 4 per point bytes)
+    ///
+    /// Lets a client drawing quickly send the points it sampled since its
+    /// last frame at once. The last point becomes the user's cursor; the
+    /// whole batch is forwarded so other clients can interpolate the path.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x14)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: point count (u8, 1 to `MAX_CURSOR_BATCH_POINTS`)
+    /// - bytes 6+: per point, x then y (u16 each, big-endian, normalized 0-65535)
+    CursorBatch {
+        board_id: u32,
+        points: Vec<(u16, u16)>,
+    },
+
+    /// Server → Client: Broadcast a cursor batch, v9+ only (7 + 4 per point bytes)
+    ///
+    /// Clients on earlier versions receive only the last point, as a
+    /// `CursorBroadcast`.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x15)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    /// - byte 6: point count (u8, 1 to `MAX_CURSOR_BATCH_POINTS`)
+    /// - bytes 7+: per point, x then y (u16 each, big-endian, normalized 0-65535)
+    CursorBroadcastBatch {
+        board_id: u32,
+        user_id: u8,
+        points: Vec<(u16, u16)>,
+    },
+
+    /// Client → Server: Start or stop editing a card (10 bytes)
+    ///
+    /// `card_ref` is chosen by the client (e.g. a hash of the card UUID) and
+    /// only has to be stable across clients; the server relays it unchanged.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x16)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-8: card_ref (u32, big-endian)
+    /// - byte 9: active (u8, 1 = editing, 0 = stopped)
+    ActivityUpdate {
+        board_id: u32,
+        card_ref: u32,
+        active: bool,
+    },
+
+    /// Server → Client: A user started or stopped editing a card (11 bytes)
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x17)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id (u8)
+    /// - bytes 6-9: card_ref (u32, big-endian)
+    /// - byte 10: active (u8, 1 = editing, 0 = stopped)
+    ActivityBroadcast {
+        board_id: u32,
+        user_id: u8,
+        card_ref: u32,
+        active: bool,
+    },
+
+    /// Client → Server: Claim a card for editing (7 bytes)
+    ///
+    /// A soft lock: at most one user holds the claim on a card, and clients
+    /// keep others from editing it. Answered with a `CardClaimed` naming the
+    /// holder, which is the requester if the claim was granted. Claims end
+    /// with a `ReleaseCard` or when the holder leaves the board.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x18)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-6: card_slot (u16, big-endian, never 0)
+    ClaimCard { board_id: u32, card_slot: u16 },
+
+    /// Client → Server: Release a claimed card (7 bytes)
+    ///
+    /// Ignored unless the sender holds the claim.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x19)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - bytes 5-6: card_slot (u16, big-endian)
+    ReleaseCard { board_id: u32, card_slot: u16 },
+
+    /// Server → Client: A user holds the claim on a card (8 bytes)
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x1A)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id of the holder (u8)
+    /// - bytes 6-7: card_slot (u16, big-endian)
+    CardClaimed {
+        board_id: u32,
+        user_id: u8,
+        card_slot: u16,
+    },
+
+    /// Server → Client: A user's claim on a card ended (8 bytes)
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x1B)
+    /// - bytes 1-4: board_id (u32, big-endian)
+    /// - byte 5: user_id of the former holder (u8)
+    /// - bytes 6-7: card_slot (u16, big-endian)
+    CardReleased {
+        board_id: u32,
+        user_id: u8,
+        card_slot: u16,
+    },
+
+    /// Server → Client: A frame from the client was rejected, v11+ only (2 bytes)
+    ///
+    /// Sent in place of silently dropping a frame that failed to decode.
+    /// `code` is one of the `ERROR_*` codes in `types`.
+    ///
+    /// Layout:
+    /// - byte 0: message type (0x1C)
+    /// - byte 1: code (u8)
+    Error { code: u8 },
 }
 
 impl BinaryMessage {
+    /// Get the message type byte for this message.
+    pub fn message_type(&self) -> u8 {
+        match self {
+            BinaryMessage::CursorUpdate { .. } => MSG_CURSOR_UPDATE,
+            BinaryMessage::CursorBroadcast { .. } => MSG_CURSOR_BROADCAST,
+            BinaryMessage::Join { .. } => MSG_JOIN,
+            BinaryMessage::Leave { .. } => MSG_LEAVE,
+            BinaryMessage::UserJoined { .. } => MSG_USER_JOINED,
+            BinaryMessage::UserLeft { .. } => MSG_USER_LEFT,
+            BinaryMessage::PresenceUpdate { .. } => MSG_PRESENCE_UPDATE,
+            BinaryMessage::Heartbeat => MSG_HEARTBEAT,
+            BinaryMessage::Hello { .. } => MSG_HELLO,
+            BinaryMessage::CursorBroadcastTimed { .. } => MSG_CURSOR_BROADCAST_TIMED,
+            BinaryMessage::FocusCard { .. } => MSG_FOCUS_CARD,
+            BinaryMessage::FocusBroadcast { .. } => MSG_FOCUS_BROADCAST,
+            BinaryMessage::Sequenced { .. } => MSG_SEQUENCED,
+            BinaryMessage::Announcement { .. } => MSG_ANNOUNCEMENT,
+            BinaryMessage::Ping { .. } => MSG_PING,
+            BinaryMessage::Pong { .. } => MSG_PONG,
+            BinaryMessage::QueryPresence { .. } => MSG_QUERY_PRESENCE,
+            BinaryMessage::Correlated { .. } => MSG_CORRELATED,
+            BinaryMessage::CursorUpdateCompact { .. } => MSG_CURSOR_UPDATE_COMPACT,
+            BinaryMessage::CursorBatch { .. } => MSG_CURSOR_BATCH,
+            BinaryMessage::CursorBroadcastBatch { .. } => MSG_CURSOR_BROADCAST_BATCH,
+            BinaryMessage::ActivityUpdate { .. } => MSG_ACTIVITY_UPDATE,
+            BinaryMessage::ActivityBroadcast { .. } => MSG_ACTIVITY_BROADCAST,
+            BinaryMessage::ClaimCard { .. } => MSG_CLAIM_CARD,
+            BinaryMessage::ReleaseCard { .. } => MSG_RELEASE_CARD,
+            BinaryMessage::CardClaimed { .. } => MSG_CARD_CLAIMED,
+            BinaryMessage::CardReleased { .. } => MSG_CARD_RELEASED,
+            BinaryMessage::Error { .. } => MSG_ERROR,
+        }
+    }
+
+    /// Select the wire form of this message for a client's protocol version.
+    ///
+    /// `CursorBroadcast` carries the server `tick` for v2+ clients and keeps
+    /// the 10-byte form for v1. Clients before v9 get only the last point of a
+    /// `CursorBroadcastBatch`, as a `CursorBroadcast`. Every other message is
+    /// the same in all versions.
+    pub fn for_protocol_version(self, version: u8, tick: u32) -> Self {
+        match self {
+            BinaryMessage::CursorBroadcastBatch {
+                board_id,
+                user_id,
+                points,
+            } if version < PROTOCOL_VERSION_V9 => {
+                let (x, y) = points.last().copied().unwrap_or_default();
+                BinaryMessage::CursorBroadcast {
+                    board_id,
+                    user_id,
+                    x,
+                    y,
+                }
+                .for_protocol_version(version, tick)
+            }
+            BinaryMessage::CursorBroadcast {
+                board_id,
+                user_id,
+                x,
+                y,
+            } if version >= PROTOCOL_VERSION_V2 => BinaryMessage::CursorBroadcastTimed {
+                board_id,
+                user_id,
+                x,
+                y,
+                tick,
+            },
+            other => other,
+        }
+    }
+
+    /// Wrap a reply in a `Correlated` envelope echoing `msg_id`, if there is one.
+    pub fn correlate(self, msg_id: Option<u16>) -> Self {
+        match msg_id {
+            Some(msg_id) => BinaryMessage::Correlated {
+                msg_id,
+                message: Box::new(self),
+            },
+            None => self,
+        }
+    }
+
+    /// Check that every string, compact coordinate and cursor batch fits its limit.
+    ///
+    /// `encode` relies on this: usernames, announcement text and cursor
+    /// batches are written with a 1-byte length prefix, so longer ones would
+    /// corrupt the frame, and compact coordinates share bytes with each other.
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        match self {
+            BinaryMessage::Join { username, .. } | BinaryMessage::UserJoined { username, .. }
+                if username.len() > MAX_USERNAME_LENGTH =>
+            {
+                Err(ProtocolError::UsernameTooLong(username.len()))
+            }
+            BinaryMessage::Announcement { text, .. } if text.len() > MAX_ANNOUNCEMENT_LENGTH => {
+                Err(ProtocolError::AnnouncementTooLong(text.len()))
+            }
+            BinaryMessage::CursorUpdateCompact { x, y, .. } if (*x).max(*y) > MAX_COMPACT_COORD => {
+                Err(ProtocolError::CompactCoordOutOfRange((*x).max(*y)))
+            }
+            BinaryMessage::CursorBatch { points, .. }
+            | BinaryMessage::CursorBroadcastBatch { points, .. }
+                if points.is_empty() || points.len() > MAX_CURSOR_BATCH_POINTS =>
+            {
+                Err(ProtocolError::InvalidCursorBatch(points.len()))
+            }
+            BinaryMessage::Sequenced { message, .. }
+            | BinaryMessage::Correlated { message, .. } => message.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of bytes `encode` would produce for this message.
+    ///
+    /// Computed from the layouts above without encoding, for pre-sizing
+    /// buffers and for metrics. Like `encode`, it assumes the message
+    /// passes `validate`.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            BinaryMessage::CursorUpdate { .. } => 9,
+            BinaryMessage::CursorBroadcast { .. } => 10,
+            BinaryMessage::Join {
+                username,
+                reconnect_token,
+                ..
+            } => 6 + username.len() + reconnect_token.map_or(0, |token| token.len()),
+            BinaryMessage::Leave { .. } => 5,
+            BinaryMessage::UserJoined { username, .. } => 10 + username.len(),
+            BinaryMessage::UserLeft { .. } => 6,
+            BinaryMessage::PresenceUpdate { .. } => 7,
+            BinaryMessage::Heartbeat => 1,
+            BinaryMessage::Hello {
+                cursor_interval_ms, ..
+            } => {
+                if cursor_interval_ms.is_some() {
+                    4
+                } else {
+                    2
+                }
+            }
+            BinaryMessage::CursorBroadcastTimed { .. } => 14,
+            BinaryMessage::FocusCard { .. } => 7,
+            BinaryMessage::FocusBroadcast { .. } => 8,
+            BinaryMessage::Sequenced { message, .. } => 5 + message.encoded_len(),
+            BinaryMessage::Announcement { text, .. } => 6 + text.len(),
+            BinaryMessage::Ping { .. } | BinaryMessage::Pong { .. } => 5,
+            BinaryMessage::QueryPresence { .. } => 5,
+            BinaryMessage::Correlated { message, .. } => 3 + message.encoded_len(),
+            BinaryMessage::CursorUpdateCompact { .. } => 8,
+            BinaryMessage::CursorBatch { points, .. } => 6 + 4 * points.len(),
+            BinaryMessage::CursorBroadcastBatch { points, .. } => 7 + 4 * points.len(),
+            BinaryMessage::ActivityUpdate { .. } => 10,
+            BinaryMessage::ActivityBroadcast { .. } => 11,
+            BinaryMessage::ClaimCard { .. } | BinaryMessage::ReleaseCard { .. } => 7,
+            BinaryMessage::CardClaimed { .. } | BinaryMessage::CardReleased { .. } => 8,
+            BinaryMessage::Error { .. } => 2,
+        }
+    }
+
     /// Encode this message into a byte vector.
     ///
     /// All multi-byte integers are encoded in big-endian byte order.
     /// Strings are encoded with a 1-byte length prefix followed by UTF-8 bytes.
+    /// Callers must pass messages that `validate`; this is checked in debug builds.
     ///
     /// # Returns
     ///
     /// A `Vec<u8>` containing the complete encoded message, ready to send.
     pub fn encode(&self) -> Vec<u8> {
         let mut buf = BytesMut::new();
+        self.encode_into(&mut buf);
+        buf.to_vec()
+    }
+
+    /// Encode this message, appending the bytes to `buf`.
+    ///
+    /// Produces exactly the bytes of `encode`, but lets hot loops reuse one
+    /// scratch buffer instead of allocating per message. Bytes already in
+    /// `buf` are kept, so clear or `split` it between frames. Room for
+    /// `encoded_len` bytes is reserved up front, so the buffer grows at most once.
+    pub fn encode_into(&self, buf: &mut BytesMut) {
+        debug_assert!(self.validate().is_ok(), "encoding invalid message");
+        buf.reserve(self.encoded_len());
 
         match self {
             BinaryMessage::CursorUpdate { board_id, x, y } => {
@@ -148,12 +633,17 @@ impl BinaryMessage {
                 buf.extend_from_slice(&y.to_be_bytes());
             }
 
-            BinaryMessage::Join { board_id, username } => {
+            BinaryMessage::Join {
+                board_id,
+                username,
+                reconnect_token,
+            } => {
                 buf.extend_from_slice(&[MSG_JOIN]);
                 buf.extend_from_slice(&board_id.to_be_bytes());
-                let username_bytes = username.as_bytes();
-                buf.extend_from_slice(&[username_bytes.len() as u8]);
-                buf.extend_from_slice(username_bytes);
+                write_str(buf, username);
+                if let Some(token) = reconnect_token {
+                    buf.extend_from_slice(token);
+                }
             }
 
             BinaryMessage::Leave { board_id } => {
@@ -170,9 +660,7 @@ impl BinaryMessage {
                 buf.extend_from_slice(&[MSG_USER_JOINED]);
                 buf.extend_from_slice(&board_id.to_be_bytes());
                 buf.extend_from_slice(&[*user_id]);
-                let username_bytes = username.as_bytes();
-                buf.extend_from_slice(&[username_bytes.len() as u8]);
-                buf.extend_from_slice(username_bytes);
+                write_str(buf, username);
                 buf.extend_from_slice(color);
             }
 
@@ -185,127 +673,280 @@ impl BinaryMessage {
             BinaryMessage::PresenceUpdate { board_id, count } => {
                 buf.extend_from_slice(&[MSG_PRESENCE_UPDATE]);
                 buf.extend_from_slice(&board_id.to_be_bytes());
-                buf.extend_from_slice(&[*count]);
+                buf.extend_from_slice(&count.to_be_bytes());
             }
 
             BinaryMessage::Heartbeat => {
                 buf.extend_from_slice(&[MSG_HEARTBEAT]);
             }
-        }
-
-        buf.to_vec()
-    }
-
-    /// Decode a message from a byte slice.
-    ///
-    /// All multi-byte integers are expected to be in big-endian byte order.
-    /// Strings are expected to have a 1-byte length prefix followed by UTF-8 bytes.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - The byte slice to decode
-    ///
-    /// # Returns
-    ///
-    /// A `Result` containing the decoded message or a `ProtocolError` if decoding fails.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - The message type is unknown
-    /// - The buffer is too short for the message type
-    /// - UTF-8 validation fails for username strings
-    /// - Username length exceeds maximum
-    pub fn decode(data: &[u8]) -> Result<Self, ProtocolError> {
-        if data.is_empty() {
-            return Err(ProtocolError::BufferUnderflow);
-        }
-
-        let mut cursor = Cursor::new(data);
-        let mut msg_type_buf = [0u8; 1];
-        cursor
-            .read_exact(&mut msg_type_buf)
-            .map_err(|_| ProtocolError::BufferUnderflow)?;
-        let msg_type = msg_type_buf[0];
 
-        match msg_type {
-            MSG_CURSOR_UPDATE => {
-                if data.len() != 7 {
-                    return Err(ProtocolError::InvalidLength {
-                        expected: 7,
-                        actual: data.len(),
-                    });
+            BinaryMessage::Hello {
+                version,
+                cursor_interval_ms,
+            } => {
+                buf.extend_from_slice(&[MSG_HELLO, *version]);
+                if let Some(interval) = cursor_interval_ms {
+                    buf.extend_from_slice(&interval.to_be_bytes());
                 }
+            }
 
-                let board_id = read_u16(&mut cursor)?;
-                let x = read_u16(&mut cursor)?;
-                let y = read_u16(&mut cursor)?;
+            BinaryMessage::CursorBroadcastTimed {
+                board_id,
+                user_id,
+                x,
+                y,
+                tick,
+            } => {
+                buf.extend_from_slice(&[MSG_CURSOR_BROADCAST_TIMED]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                buf.extend_from_slice(&x.to_be_bytes());
+                buf.extend_from_slice(&y.to_be_bytes());
+                buf.extend_from_slice(&tick.to_be_bytes());
+            }
 
-                Ok(BinaryMessage::CursorUpdate { board_id, x, y })
+            BinaryMessage::FocusCard {
+                board_id,
+                card_slot,
+            } => {
+                buf.extend_from_slice(&[MSG_FOCUS_CARD]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&card_slot.to_be_bytes());
             }
 
-            MSG_CURSOR_BROADCAST => {
-                if data.len() != 8 {
-                    return Err(ProtocolError::InvalidLength {
-                        expected: 8,
-                        actual: data.len(),
-                    });
-                }
+            BinaryMessage::FocusBroadcast {
+                board_id,
+                user_id,
+                card_slot,
+            } => {
+                buf.extend_from_slice(&[MSG_FOCUS_BROADCAST]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                buf.extend_from_slice(&card_slot.to_be_bytes());
+            }
 
-                let board_id = read_u16(&mut cursor)?;
-                let user_id = read_u8(&mut cursor)?;
-                let x = read_u16(&mut cursor)?;
-                let y = read_u16(&mut cursor)?;
+            BinaryMessage::Sequenced { seq, message } => {
+                buf.extend_from_slice(&[MSG_SEQUENCED]);
+                buf.extend_from_slice(&seq.to_be_bytes());
+                message.encode_into(buf);
+            }
 
-                Ok(BinaryMessage::CursorBroadcast {
-                    board_id,
-                    user_id,
-                    x,
-                    y,
-                })
+            BinaryMessage::Announcement { board_id, text } => {
+                buf.extend_from_slice(&[MSG_ANNOUNCEMENT]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                write_str(buf, text);
             }
 
-            MSG_JOIN => {
-                if data.len() < 4 {
-                    return Err(ProtocolError::InvalidLength {
-                        expected: 4,
-                        actual: data.len(),
-                    });
-                }
+            BinaryMessage::Ping { nonce } => {
+                buf.extend_from_slice(&[MSG_PING]);
+                buf.extend_from_slice(&nonce.to_be_bytes());
+            }
 
-                let board_id = read_u16(&mut cursor)?;
-                let username = read_string(&mut cursor, MAX_USERNAME_LENGTH)?;
+            BinaryMessage::Pong { nonce } => {
+                buf.extend_from_slice(&[MSG_PONG]);
+                buf.extend_from_slice(&nonce.to_be_bytes());
+            }
 
-                Ok(BinaryMessage::Join { board_id, username })
+            BinaryMessage::QueryPresence { board_id } => {
+                buf.extend_from_slice(&[MSG_QUERY_PRESENCE]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
             }
 
-            MSG_LEAVE => {
-                if data.len() != 3 {
+            BinaryMessage::Correlated { msg_id, message } => {
+                buf.extend_from_slice(&[MSG_CORRELATED]);
+                buf.extend_from_slice(&msg_id.to_be_bytes());
+                message.encode_into(buf);
+            }
+
+            BinaryMessage::CursorUpdateCompact { board_id, x, y } => {
+                buf.extend_from_slice(&[MSG_CURSOR_UPDATE_COMPACT]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                let packed = (u32::from(*x) << 12) | u32::from(*y);
+                buf.extend_from_slice(&packed.to_be_bytes()[1..]);
+            }
+
+            BinaryMessage::CursorBatch { board_id, points } => {
+                buf.extend_from_slice(&[MSG_CURSOR_BATCH]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                write_points(buf, points);
+            }
+
+            BinaryMessage::CursorBroadcastBatch {
+                board_id,
+                user_id,
+                points,
+            } => {
+                buf.extend_from_slice(&[MSG_CURSOR_BROADCAST_BATCH]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                write_points(buf, points);
+            }
+
+            BinaryMessage::ActivityUpdate {
+                board_id,
+                card_ref,
+                active,
+            } => {
+                buf.extend_from_slice(&[MSG_ACTIVITY_UPDATE]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&card_ref.to_be_bytes());
+                buf.extend_from_slice(&[u8::from(*active)]);
+            }
+
+            BinaryMessage::ActivityBroadcast {
+                board_id,
+                user_id,
+                card_ref,
+                active,
+            } => {
+                buf.extend_from_slice(&[MSG_ACTIVITY_BROADCAST]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                buf.extend_from_slice(&card_ref.to_be_bytes());
+                buf.extend_from_slice(&[u8::from(*active)]);
+            }
+
+            BinaryMessage::ClaimCard {
+                board_id,
+                card_slot,
+            } => {
+                buf.extend_from_slice(&[MSG_CLAIM_CARD]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&card_slot.to_be_bytes());
+            }
+
+            BinaryMessage::ReleaseCard {
+                board_id,
+                card_slot,
+            } => {
+                buf.extend_from_slice(&[MSG_RELEASE_CARD]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&card_slot.to_be_bytes());
+            }
+
+            BinaryMessage::CardClaimed {
+                board_id,
+                user_id,
+                card_slot,
+            } => {
+                buf.extend_from_slice(&[MSG_CARD_CLAIMED]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                buf.extend_from_slice(&card_slot.to_be_bytes());
+            }
+
+            BinaryMessage::CardReleased {
+                board_id,
+                user_id,
+                card_slot,
+            } => {
+                buf.extend_from_slice(&[MSG_CARD_RELEASED]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                buf.extend_from_slice(&card_slot.to_be_bytes());
+            }
+
+            BinaryMessage::Error { code } => {
+                buf.extend_from_slice(&[MSG_ERROR, *code]);
+            }
+        }
+    }
+
+    /// Decode a message from a byte slice.
+    ///
+    /// All multi-byte integers are expected to be in big-endian byte order.
+    /// Strings are expected to have a 1-byte length prefix followed by UTF-8 bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The byte slice to decode
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded message or a `ProtocolError` if decoding fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The message type is unknown
+    /// - The buffer is too short for the message type
+    /// - UTF-8 validation fails for username strings
+    /// - Username length exceeds maximum
+    pub fn decode(data: &[u8]) -> Result<Self, ProtocolError> {
+        Self::decode_borrowed(data).map(BinaryMessageRef::into_owned)
+    }
+
+    /// Decode a message without copying its strings.
+    ///
+    /// Accepts and rejects exactly the frames `decode` does. Usernames and
+    /// announcement texts are validated as UTF-8 but returned as slices of
+    /// `data`, so a message that is only re-encoded never allocates a `String`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The byte slice to decode
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a message borrowing from `data` or a `ProtocolError`.
+    pub fn decode_borrowed(data: &[u8]) -> Result<BinaryMessageRef<'_>, ProtocolError> {
+        if data.is_empty() {
+            return Err(ProtocolError::BufferUnderflow);
+        }
+
+        let mut cursor = Cursor::new(data);
+        let msg_type = read_u8(&mut cursor)?;
+
+        match msg_type {
+            MSG_JOIN => {
+                if data.len() < 6 {
                     return Err(ProtocolError::InvalidLength {
-                        expected: 3,
+                        expected: 6,
                         actual: data.len(),
                     });
                 }
 
-                let board_id = read_u16(&mut cursor)?;
-
-                Ok(BinaryMessage::Leave { board_id })
+                let board_id = read_u32(&mut cursor)?;
+                let username = read_str(&mut cursor, MAX_USERNAME_LENGTH)?;
+                let username_end = cursor.position() as usize;
+                let reconnect_token = match data.len() - username_end {
+                    0 => None,
+                    RECONNECT_TOKEN_LENGTH => {
+                        let mut token = [0u8; RECONNECT_TOKEN_LENGTH];
+                        cursor
+                            .read_exact(&mut token)
+                            .map_err(|_| ProtocolError::BufferUnderflow)?;
+                        Some(token)
+                    }
+                    _ => {
+                        return Err(ProtocolError::InvalidLength {
+                            expected: username_end + RECONNECT_TOKEN_LENGTH,
+                            actual: data.len(),
+                        })
+                    }
+                };
+
+                Ok(BinaryMessageRef::Join {
+                    board_id,
+                    username,
+                    reconnect_token,
+                })
             }
 
             MSG_USER_JOINED => {
-                if data.len() < 8 {
+                if data.len() < 10 {
                     return Err(ProtocolError::InvalidLength {
-                        expected: 8,
+                        expected: 10,
                         actual: data.len(),
                     });
                 }
 
-                let board_id = read_u16(&mut cursor)?;
+                let board_id = read_u32(&mut cursor)?;
                 let user_id = read_u8(&mut cursor)?;
-                let username = read_string(&mut cursor, MAX_USERNAME_LENGTH)?;
+                let username = read_str(&mut cursor, MAX_USERNAME_LENGTH)?;
                 let color = read_color(&mut cursor)?;
 
-                Ok(BinaryMessage::UserJoined {
+                Ok(BinaryMessageRef::UserJoined {
                     board_id,
                     user_id,
                     username,
@@ -313,30 +954,142 @@ impl BinaryMessage {
                 })
             }
 
-            MSG_USER_LEFT => {
-                if data.len() != 4 {
+            MSG_SEQUENCED => {
+                if data.len() < 6 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 6,
+                        actual: data.len(),
+                    });
+                }
+
+                let seq = read_u32(&mut cursor)?;
+                let inner = &data[5..];
+                if inner[0] == MSG_SEQUENCED {
+                    return Err(ProtocolError::NestedSequenced);
+                }
+                let message = Box::new(BinaryMessage::decode_borrowed(inner)?);
+
+                Ok(BinaryMessageRef::Sequenced { seq, message })
+            }
+
+            MSG_ANNOUNCEMENT => {
+                if data.len() < 6 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 6,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let text = read_str(&mut cursor, MAX_ANNOUNCEMENT_LENGTH)?;
+
+                Ok(BinaryMessageRef::Announcement { board_id, text })
+            }
+
+            MSG_CORRELATED => {
+                if data.len() < 4 {
                     return Err(ProtocolError::InvalidLength {
                         expected: 4,
                         actual: data.len(),
                     });
                 }
 
-                let board_id = read_u16(&mut cursor)?;
+                let msg_id = read_u16(&mut cursor)?;
+                let inner = &data[3..];
+                if matches!(inner[0], MSG_SEQUENCED | MSG_CORRELATED) {
+                    return Err(ProtocolError::NestedCorrelated);
+                }
+                let message = Box::new(BinaryMessage::decode_borrowed(inner)?);
+
+                Ok(BinaryMessageRef::Correlated { msg_id, message })
+            }
+
+            _ => Self::decode_fixed(msg_type, data, cursor).map(BinaryMessageRef::Other),
+        }
+    }
+
+    /// Decode the message types that carry no strings.
+    ///
+    /// `cursor` is positioned just past the type byte.
+    fn decode_fixed(
+        msg_type: u8,
+        data: &[u8],
+        mut cursor: Cursor<&[u8]>,
+    ) -> Result<Self, ProtocolError> {
+        match msg_type {
+            MSG_CURSOR_UPDATE => {
+                if data.len() != 9 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 9,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let x = read_u16(&mut cursor)?;
+                let y = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::CursorUpdate { board_id, x, y })
+            }
+
+            MSG_CURSOR_BROADCAST => {
+                if data.len() != 10 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 10,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let x = read_u16(&mut cursor)?;
+                let y = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::CursorBroadcast {
+                    board_id,
+                    user_id,
+                    x,
+                    y,
+                })
+            }
+
+            MSG_LEAVE => {
+                if data.len() != 5 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 5,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+
+                Ok(BinaryMessage::Leave { board_id })
+            }
+
+            MSG_USER_LEFT => {
+                if data.len() != 6 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 6,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
                 let user_id = read_u8(&mut cursor)?;
 
                 Ok(BinaryMessage::UserLeft { board_id, user_id })
             }
 
             MSG_PRESENCE_UPDATE => {
-                if data.len() != 4 {
+                if data.len() != 7 {
                     return Err(ProtocolError::InvalidLength {
-                        expected: 4,
+                        expected: 7,
                         actual: data.len(),
                     });
                 }
 
-                let board_id = read_u16(&mut cursor)?;
-                let count = read_u8(&mut cursor)?;
+                let board_id = read_u32(&mut cursor)?;
+                let count = read_u16(&mut cursor)?;
 
                 Ok(BinaryMessage::PresenceUpdate { board_id, count })
             }
@@ -352,66 +1105,567 @@ impl BinaryMessage {
                 Ok(BinaryMessage::Heartbeat)
             }
 
-            unknown => Err(ProtocolError::UnknownMessageType(unknown)),
-        }
-    }
-}
+            MSG_HELLO => {
+                if data.len() != 2 && data.len() != 4 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 2,
+                        actual: data.len(),
+                    });
+                }
 
-// Helper functions for reading primitive types
+                let version = read_u8(&mut cursor)?;
+                let cursor_interval_ms = if data.len() == 4 {
+                    Some(read_u16(&mut cursor)?)
+                } else {
+                    None
+                };
 
-/// Read a big-endian u16 from the cursor.
-fn read_u16(cursor: &mut Cursor<&[u8]>) -> Result<u16, ProtocolError> {
-    let mut buf = [0u8; 2];
-    cursor
-        .read_exact(&mut buf)
-        .map_err(|_| ProtocolError::BufferUnderflow)?;
-    Ok(u16::from_be_bytes(buf))
-}
+                Ok(BinaryMessage::Hello {
+                    version,
+                    cursor_interval_ms,
+                })
+            }
 
-/// Read a u8 from the cursor.
-fn read_u8(cursor: &mut Cursor<&[u8]>) -> Result<u8, ProtocolError> {
-    let mut buf = [0u8; 1];
-    cursor
-        .read_exact(&mut buf)
-        .map_err(|_| ProtocolError::BufferUnderflow)?;
-    Ok(buf[0])
-}
+            MSG_CURSOR_BROADCAST_TIMED => {
+                if data.len() != 14 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 14,
+                        actual: data.len(),
+                    });
+                }
 
-/// Read a length-prefixed string from the cursor.
-///
-/// The string is encoded as a 1-byte length followed by UTF-8 bytes.
-fn read_string(cursor: &mut Cursor<&[u8]>, max_length: usize) -> Result<String, ProtocolError> {
-    let length = read_u8(cursor)? as usize;
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let x = read_u16(&mut cursor)?;
+                let y = read_u16(&mut cursor)?;
+                let tick = read_u32(&mut cursor)?;
 
-    if length > max_length {
-        return Err(ProtocolError::UsernameTooLong(length));
-    }
+                Ok(BinaryMessage::CursorBroadcastTimed {
+                    board_id,
+                    user_id,
+                    x,
+                    y,
+                    tick,
+                })
+            }
 
-    let mut buf = vec![0u8; length];
-    cursor
-        .read_exact(&mut buf)
-        .map_err(|_| ProtocolError::BufferUnderflow)?;
+            MSG_FOCUS_CARD => {
+                if data.len() != 7 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 7,
+                        actual: data.len(),
+                    });
+                }
 
-    String::from_utf8(buf).map_err(|_| ProtocolError::InvalidUtf8)
-}
+                let board_id = read_u32(&mut cursor)?;
+                let card_slot = read_u16(&mut cursor)?;
 
-/// Read a 3-byte RGB color from the cursor.
-fn read_color(cursor: &mut Cursor<&[u8]>) -> Result<[u8; 3], ProtocolError> {
-    let mut color = [0u8; 3];
-    cursor
-        .read_exact(&mut color)
-        .map_err(|_| ProtocolError::BufferUnderflow)?;
-    Ok(color)
-}
+                Ok(BinaryMessage::FocusCard {
+                    board_id,
+                    card_slot,
+                })
+            }
 
-// Coordinate normalization helpers
+            MSG_FOCUS_BROADCAST => {
+                if data.len() != 8 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 8,
+                        actual: data.len(),
+                    });
+                }
 
-/// Normalize a floating-point coordinate (0.0-1.0) to a 16-bit unsigned integer (0-65535).
-///
-/// This allows us to represent fractional coordinates with high precision
-/// while using only 2 bytes per coordinate.
-///
-/// # Arguments
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let card_slot = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::FocusBroadcast {
+                    board_id,
+                    user_id,
+                    card_slot,
+                })
+            }
+
+            MSG_PING => {
+                if data.len() != 5 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 5,
+                        actual: data.len(),
+                    });
+                }
+
+                let nonce = read_u32(&mut cursor)?;
+
+                Ok(BinaryMessage::Ping { nonce })
+            }
+
+            MSG_PONG => {
+                if data.len() != 5 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 5,
+                        actual: data.len(),
+                    });
+                }
+
+                let nonce = read_u32(&mut cursor)?;
+
+                Ok(BinaryMessage::Pong { nonce })
+            }
+
+            MSG_QUERY_PRESENCE => {
+                if data.len() != 5 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 5,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+
+                Ok(BinaryMessage::QueryPresence { board_id })
+            }
+
+            MSG_CURSOR_UPDATE_COMPACT => {
+                if data.len() != 8 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 8,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let packed = u32::from_be_bytes([0, data[5], data[6], data[7]]);
+                let x = (packed >> 12) as u16;
+                let y = (packed & u32::from(MAX_COMPACT_COORD)) as u16;
+
+                Ok(BinaryMessage::CursorUpdateCompact { board_id, x, y })
+            }
+
+            MSG_CURSOR_BATCH => {
+                if data.len() < 10 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 10,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let points = read_points(&mut cursor, data.len())?;
+
+                Ok(BinaryMessage::CursorBatch { board_id, points })
+            }
+
+            MSG_CURSOR_BROADCAST_BATCH => {
+                if data.len() < 11 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 11,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let points = read_points(&mut cursor, data.len())?;
+
+                Ok(BinaryMessage::CursorBroadcastBatch {
+                    board_id,
+                    user_id,
+                    points,
+                })
+            }
+
+            MSG_ACTIVITY_UPDATE => {
+                if data.len() != 10 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 10,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let card_ref = read_u32(&mut cursor)?;
+                let active = read_flag(&mut cursor)?;
+
+                Ok(BinaryMessage::ActivityUpdate {
+                    board_id,
+                    card_ref,
+                    active,
+                })
+            }
+
+            MSG_ACTIVITY_BROADCAST => {
+                if data.len() != 11 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 11,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let card_ref = read_u32(&mut cursor)?;
+                let active = read_flag(&mut cursor)?;
+
+                Ok(BinaryMessage::ActivityBroadcast {
+                    board_id,
+                    user_id,
+                    card_ref,
+                    active,
+                })
+            }
+
+            MSG_CLAIM_CARD => {
+                if data.len() != 7 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 7,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let card_slot = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::ClaimCard {
+                    board_id,
+                    card_slot,
+                })
+            }
+
+            MSG_RELEASE_CARD => {
+                if data.len() != 7 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 7,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let card_slot = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::ReleaseCard {
+                    board_id,
+                    card_slot,
+                })
+            }
+
+            MSG_CARD_CLAIMED => {
+                if data.len() != 8 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 8,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let card_slot = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::CardClaimed {
+                    board_id,
+                    user_id,
+                    card_slot,
+                })
+            }
+
+            MSG_CARD_RELEASED => {
+                if data.len() != 8 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 8,
+                        actual: data.len(),
+                    });
+                }
+
+                let board_id = read_u32(&mut cursor)?;
+                let user_id = read_u8(&mut cursor)?;
+                let card_slot = read_u16(&mut cursor)?;
+
+                Ok(BinaryMessage::CardReleased {
+                    board_id,
+                    user_id,
+                    card_slot,
+                })
+            }
+
+            MSG_ERROR => {
+                if data.len() != 2 {
+                    return Err(ProtocolError::InvalidLength {
+                        expected: 2,
+                        actual: data.len(),
+                    });
+                }
+
+                let code = read_u8(&mut cursor)?;
+
+                Ok(BinaryMessage::Error { code })
+            }
+
+            unknown => Err(ProtocolError::UnknownMessageType(unknown)),
+        }
+    }
+}
+
+/// A decoded message that borrows its strings from the frame.
+///
+/// Returned by `BinaryMessage::decode_borrowed`. Only the variants carrying a
+/// username or text (and the envelopes that can wrap them) differ from
+/// `BinaryMessage`; every other message is held as `Other`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryMessageRef<'a> {
+    /// `BinaryMessage::Join` with a borrowed username
+    Join {
+        board_id: u32,
+        username: &'a str,
+        reconnect_token: Option<ReconnectToken>,
+    },
+
+    /// `BinaryMessage::UserJoined` with a borrowed username
+    UserJoined {
+        board_id: u32,
+        user_id: u8,
+        username: &'a str,
+        color: [u8; 3],
+    },
+
+    /// `BinaryMessage::Announcement` with borrowed text
+    Announcement { board_id: u32, text: &'a str },
+
+    /// `BinaryMessage::Sequenced` around a borrowed message
+    Sequenced {
+        seq: u32,
+        message: Box<BinaryMessageRef<'a>>,
+    },
+
+    /// `BinaryMessage::Correlated` around a borrowed message
+    Correlated {
+        msg_id: u16,
+        message: Box<BinaryMessageRef<'a>>,
+    },
+
+    /// Any message without strings
+    Other(BinaryMessage),
+}
+
+impl BinaryMessageRef<'_> {
+    /// Copy the borrowed strings into an owned `BinaryMessage`.
+    pub fn into_owned(self) -> BinaryMessage {
+        match self {
+            BinaryMessageRef::Join {
+                board_id,
+                username,
+                reconnect_token,
+            } => BinaryMessage::Join {
+                board_id,
+                username: username.to_string(),
+                reconnect_token,
+            },
+            BinaryMessageRef::UserJoined {
+                board_id,
+                user_id,
+                username,
+                color,
+            } => BinaryMessage::UserJoined {
+                board_id,
+                user_id,
+                username: username.to_string(),
+                color,
+            },
+            BinaryMessageRef::Announcement { board_id, text } => BinaryMessage::Announcement {
+                board_id,
+                text: text.to_string(),
+            },
+            BinaryMessageRef::Sequenced { seq, message } => BinaryMessage::Sequenced {
+                seq,
+                message: Box::new(message.into_owned()),
+            },
+            BinaryMessageRef::Correlated { msg_id, message } => BinaryMessage::Correlated {
+                msg_id,
+                message: Box::new(message.into_owned()),
+            },
+            BinaryMessageRef::Other(message) => message,
+        }
+    }
+
+    /// Encode this message, appending the bytes to `buf`.
+    ///
+    /// Produces the same bytes as encoding the owned message, so a decoded
+    /// frame can be re-encoded without allocating its strings.
+    #[allow(dead_code)]
+    pub fn encode_into(&self, buf: &mut BytesMut) {
+        match self {
+            BinaryMessageRef::Join {
+                board_id,
+                username,
+                reconnect_token,
+            } => {
+                buf.extend_from_slice(&[MSG_JOIN]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                write_str(buf, username);
+                if let Some(token) = reconnect_token {
+                    buf.extend_from_slice(token);
+                }
+            }
+            BinaryMessageRef::UserJoined {
+                board_id,
+                user_id,
+                username,
+                color,
+            } => {
+                buf.extend_from_slice(&[MSG_USER_JOINED]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                buf.extend_from_slice(&[*user_id]);
+                write_str(buf, username);
+                buf.extend_from_slice(color);
+            }
+            BinaryMessageRef::Announcement { board_id, text } => {
+                buf.extend_from_slice(&[MSG_ANNOUNCEMENT]);
+                buf.extend_from_slice(&board_id.to_be_bytes());
+                write_str(buf, text);
+            }
+            BinaryMessageRef::Sequenced { seq, message } => {
+                buf.extend_from_slice(&[MSG_SEQUENCED]);
+                buf.extend_from_slice(&seq.to_be_bytes());
+                message.encode_into(buf);
+            }
+            BinaryMessageRef::Correlated { msg_id, message } => {
+                buf.extend_from_slice(&[MSG_CORRELATED]);
+                buf.extend_from_slice(&msg_id.to_be_bytes());
+                message.encode_into(buf);
+            }
+            BinaryMessageRef::Other(message) => message.encode_into(buf),
+        }
+    }
+}
+
+/// Wrap an encoded frame in a `Sequenced` envelope.
+///
+/// Lets the server number a frame that was already encoded once for a
+/// broadcast without re-encoding the inner message per recipient.
+pub fn encode_sequenced(seq: u32, frame: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(5 + frame.len());
+    buf.push(MSG_SEQUENCED);
+    buf.extend_from_slice(&seq.to_be_bytes());
+    buf.extend_from_slice(frame);
+    buf
+}
+
+// Helper functions for reading primitive types
+
+/// Read a big-endian u16 from the cursor.
+fn read_u16(cursor: &mut Cursor<&[u8]>) -> Result<u16, ProtocolError> {
+    let mut buf = [0u8; 2];
+    cursor
+        .read_exact(&mut buf)
+        .map_err(|_| ProtocolError::BufferUnderflow)?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+/// Read a big-endian u32 from the cursor.
+fn read_u32(cursor: &mut Cursor<&[u8]>) -> Result<u32, ProtocolError> {
+    let mut buf = [0u8; 4];
+    cursor
+        .read_exact(&mut buf)
+        .map_err(|_| ProtocolError::BufferUnderflow)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Read a u8 from the cursor.
+fn read_u8(cursor: &mut Cursor<&[u8]>) -> Result<u8, ProtocolError> {
+    let mut buf = [0u8; 1];
+    cursor
+        .read_exact(&mut buf)
+        .map_err(|_| ProtocolError::BufferUnderflow)?;
+    Ok(buf[0])
+}
+
+/// Read a boolean flag byte (0 or 1) from the cursor.
+fn read_flag(cursor: &mut Cursor<&[u8]>) -> Result<bool, ProtocolError> {
+    match read_u8(cursor)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        other => Err(ProtocolError::InvalidActivityFlag(other)),
+    }
+}
+
+/// Read a length-prefixed string from the cursor without copying it.
+///
+/// The string is encoded as a 1-byte length followed by UTF-8 bytes; the
+/// returned slice borrows from the cursor's buffer.
+fn read_str<'a>(
+    cursor: &mut Cursor<&'a [u8]>,
+    max_length: usize,
+) -> Result<&'a str, ProtocolError> {
+    let length = read_u8(cursor)? as usize;
+
+    if length > max_length {
+        return Err(ProtocolError::UsernameTooLong(length));
+    }
+
+    let data: &'a [u8] = cursor.get_ref();
+    let start = cursor.position() as usize;
+    let bytes = data
+        .get(start..start + length)
+        .ok_or(ProtocolError::BufferUnderflow)?;
+    cursor.set_position((start + length) as u64);
+
+    std::str::from_utf8(bytes).map_err(|_| ProtocolError::InvalidUtf8)
+}
+
+/// Write a string as a 1-byte length followed by its UTF-8 bytes.
+fn write_str(buf: &mut BytesMut, s: &str) {
+    buf.extend_from_slice(&[s.len() as u8]);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Write a cursor batch: a 1-byte count followed by x/y pairs.
+fn write_points(buf: &mut BytesMut, points: &[(u16, u16)]) {
+    buf.extend_from_slice(&[points.len() as u8]);
+    for (x, y) in points {
+        buf.extend_from_slice(&x.to_be_bytes());
+        buf.extend_from_slice(&y.to_be_bytes());
+    }
+}
+
+/// Read a count-prefixed cursor batch from the cursor.
+///
+/// The batch must run to the end of the frame, `frame_len` bytes long.
+fn read_points(
+    cursor: &mut Cursor<&[u8]>,
+    frame_len: usize,
+) -> Result<Vec<(u16, u16)>, ProtocolError> {
+    let count = read_u8(cursor)? as usize;
+    if count == 0 {
+        return Err(ProtocolError::InvalidCursorBatch(count));
+    }
+
+    let expected = cursor.position() as usize + 4 * count;
+    if frame_len != expected {
+        return Err(ProtocolError::InvalidLength {
+            expected,
+            actual: frame_len,
+        });
+    }
+
+    (0..count)
+        .map(|_| Ok((read_u16(cursor)?, read_u16(cursor)?)))
+        .collect()
+}
+
+/// Read a 3-byte RGB color from the cursor.
+fn read_color(cursor: &mut Cursor<&[u8]>) -> Result<[u8; 3], ProtocolError> {
+    let mut color = [0u8; 3];
+    cursor
+        .read_exact(&mut color)
+        .map_err(|_| ProtocolError::BufferUnderflow)?;
+    Ok(color)
+}
+
+// Coordinate normalization helpers
+
+/// Normalize a floating-point coordinate (0.0-1.0) to a 16-bit unsigned integer (0-65535).
+///
+/// This allows us to represent fractional coordinates with high precision
+/// while using only 2 bytes per coordinate.
+///
+/// # Arguments
 ///
 /// * `coord` - A floating-point coordinate in the range [0.0, 1.0]
 ///
@@ -457,65 +1711,1358 @@ pub fn denormalize_coord(coord: u16) -> f32 {
     coord as f32 / 65535.0
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Normalize a floating-point coordinate (0.0-1.0) to a 12-bit value (0-4095).
+///
+/// Used by `CursorUpdateCompact`. Precision drops to 1/4096 of the board per
+/// step, against 1/65536 for `normalize_coord`.
+///
+/// # Arguments
+///
+/// * `coord` - A floating-point coordinate, clamped to [0.0, 1.0]
+///
+/// # Returns
+///
+/// A 16-bit unsigned integer in the range [0, 4095]
+///
+/// # Examples
+///
+/// ```
+/// # use presence_service::protocol::messages::normalize_coord_12bit;
+/// assert_eq!(normalize_coord_12bit(0.0), 0);
+/// assert_eq!(normalize_coord_12bit(1.0), 4095);
+/// assert_eq!(normalize_coord_12bit(0.5), 2047);
+/// ```
+pub fn normalize_coord_12bit(coord: f32) -> u16 {
+    let clamped = coord.clamp(0.0, 1.0);
+    (clamped * MAX_COMPACT_COORD as f32) as u16
+}
+
+/// Denormalize a 12-bit value (0-4095) to a floating-point coordinate (0.0-1.0).
+///
+/// This is the inverse operation of `normalize_coord_12bit`.
+///
+/// # Arguments
+///
+/// * `coord` - A 12-bit value in the range [0, 4095]
+///
+/// # Returns
+///
+/// A floating-point coordinate in the range [0.0, 1.0]
+///
+/// # Examples
+///
+/// ```
+/// # use presence_service::protocol::messages::denormalize_coord_12bit;
+/// assert_eq!(denormalize_coord_12bit(0), 0.0);
+/// assert_eq!(denormalize_coord_12bit(4095), 1.0);
+/// assert!((denormalize_coord_12bit(2047) - 0.5).abs() < 0.001);
+/// ```
+pub fn denormalize_coord_12bit(coord: u16) -> f32 {
+    coord.min(MAX_COMPACT_COORD) as f32 / MAX_COMPACT_COORD as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_normalize_denormalize_roundtrip() {
+        let coords = vec![0.0, 0.25, 0.5, 0.75, 1.0];
+        for coord in coords {
+            let normalized = normalize_coord(coord);
+            let denormalized = denormalize_coord(normalized);
+            assert!(
+                (coord - denormalized).abs() < 0.001,
+                "Roundtrip failed for {}: got {}",
+                coord,
+                denormalized
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_clamps() {
+        assert_eq!(normalize_coord(-0.5), 0);
+        assert_eq!(normalize_coord(1.5), 65535);
+    }
+
+    #[test]
+    fn test_cursor_update_encoding() {
+        let msg = BinaryMessage::CursorUpdate {
+            board_id: 1234,
+            x: normalize_coord(0.5),
+            y: normalize_coord(0.75),
+        };
+        let encoded = msg.encode();
+        assert_eq!(encoded.len(), 9);
+        assert_eq!(encoded[0], MSG_CURSOR_UPDATE);
+    }
+
+    #[test]
+    fn test_board_id_beyond_u16() {
+        let msg = BinaryMessage::CursorUpdate {
+            board_id: 0x0102_0304,
+            x: 5,
+            y: 6,
+        };
+        let encoded = msg.encode();
+        assert_eq!(encoded, vec![MSG_CURSOR_UPDATE, 1, 2, 3, 4, 0, 5, 0, 6]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        // A frame in the old u16 layout no longer decodes
+        assert!(matches!(
+            BinaryMessage::decode(&[MSG_CURSOR_UPDATE, 3, 4, 0, 5, 0, 6]),
+            Err(ProtocolError::InvalidLength {
+                expected: 9,
+                actual: 7
+            })
+        ));
+    }
+
+    /// Every message that names a board, with its encoded size for a
+    /// four-letter username
+    fn board_scoped_messages(board_id: u32) -> Vec<(BinaryMessage, usize)> {
+        vec![
+            (
+                BinaryMessage::CursorUpdate {
+                    board_id,
+                    x: 1,
+                    y: 2,
+                },
+                9,
+            ),
+            (
+                BinaryMessage::CursorBroadcast {
+                    board_id,
+                    user_id: 3,
+                    x: 1,
+                    y: 2,
+                },
+                10,
+            ),
+            (
+                BinaryMessage::Join {
+                    board_id,
+                    username: "Anna".to_string(),
+                    reconnect_token: None,
+                },
+                10,
+            ),
+            (BinaryMessage::Leave { board_id }, 5),
+            (
+                BinaryMessage::UserJoined {
+                    board_id,
+                    user_id: 3,
+                    username: "Anna".to_string(),
+                    color: [1, 2, 3],
+                },
+                14,
+            ),
+            (
+                BinaryMessage::UserLeft {
+                    board_id,
+                    user_id: 3,
+                },
+                6,
+            ),
+            (BinaryMessage::PresenceUpdate { board_id, count: 4 }, 7),
+        ]
+    }
+
+    #[test]
+    fn test_board_id_is_u32_in_every_board_message() {
+        for board_id in [0x0102_0304, 65_536, u32::MAX] {
+            for (msg, len) in board_scoped_messages(board_id) {
+                let encoded = msg.encode();
+                assert_eq!(encoded.len(), len, "{:?}", msg);
+                assert_eq!(encoded[1..5], board_id.to_be_bytes(), "{:?}", msg);
+                assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+            }
+        }
+    }
+
+    #[test]
+    fn test_board_id_width_same_for_every_version() {
+        // There is no u16 layout to fall back to: clients without a Hello
+        // (v1) get the same u32 board_id as negotiated ones
+        for version in [PROTOCOL_VERSION_V1, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION] {
+            for (msg, len) in board_scoped_messages(0x0102_0304) {
+                let encoded = msg.clone().for_protocol_version(version, 500).encode();
+                assert_eq!(encoded[1..5], [1, 2, 3, 4], "v{} {:?}", version, msg);
+                if version == PROTOCOL_VERSION_V1 {
+                    assert_eq!(encoded.len(), len, "v1 {:?}", msg);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_heartbeat_encoding() {
+        let msg = BinaryMessage::Heartbeat;
+        let encoded = msg.encode();
+        assert_eq!(encoded.len(), 1);
+        assert_eq!(encoded[0], MSG_HEARTBEAT);
+    }
+
+    #[test]
+    fn test_decode_unknown_type() {
+        let data = vec![0xFF];
+        let result = BinaryMessage::decode(&data);
+        assert!(matches!(
+            result,
+            Err(ProtocolError::UnknownMessageType(0xFF))
+        ));
+    }
+
+    #[test]
+    fn test_decode_buffer_underflow() {
+        let data = vec![MSG_CURSOR_UPDATE, 0x00]; // Too short
+        let result = BinaryMessage::decode(&data);
+        assert!(matches!(result, Err(ProtocolError::InvalidLength { .. })));
+    }
+
+    #[test]
+    fn test_message_type_matches_encoding() {
+        let messages = vec![
+            BinaryMessage::CursorUpdate {
+                board_id: 1,
+                x: 0,
+                y: 0,
+            },
+            BinaryMessage::Join {
+                board_id: 1,
+                username: "Alice".to_string(),
+                reconnect_token: None,
+            },
+            BinaryMessage::UserLeft {
+                board_id: 1,
+                user_id: 2,
+            },
+            BinaryMessage::Heartbeat,
+        ];
+
+        for msg in messages {
+            assert_eq!(msg.message_type(), msg.encode()[0]);
+        }
+    }
+
+    #[test]
+    fn test_client_message_types() {
+        assert!(is_client_message_type(MSG_CURSOR_UPDATE));
+        assert!(is_client_message_type(MSG_JOIN));
+        assert!(is_client_message_type(MSG_LEAVE));
+        assert!(is_client_message_type(MSG_HEARTBEAT));
+        assert!(is_client_message_type(MSG_HELLO));
+        assert!(is_client_message_type(MSG_FOCUS_CARD));
+        assert!(is_client_message_type(MSG_PONG));
+        assert!(is_client_message_type(MSG_QUERY_PRESENCE));
+        assert!(is_client_message_type(MSG_CORRELATED));
+        assert!(is_client_message_type(MSG_CURSOR_UPDATE_COMPACT));
+        assert!(is_client_message_type(MSG_CURSOR_BATCH));
+        assert!(is_client_message_type(MSG_ACTIVITY_UPDATE));
+        assert!(is_client_message_type(MSG_CLAIM_CARD));
+        assert!(is_client_message_type(MSG_RELEASE_CARD));
+        assert!(!is_client_message_type(MSG_PING));
+        assert!(!is_client_message_type(MSG_FOCUS_BROADCAST));
+        assert!(!is_client_message_type(MSG_CURSOR_BROADCAST));
+        assert!(!is_client_message_type(MSG_CURSOR_BROADCAST_TIMED));
+        assert!(!is_client_message_type(MSG_USER_JOINED));
+        assert!(!is_client_message_type(MSG_USER_LEFT));
+        assert!(!is_client_message_type(MSG_PRESENCE_UPDATE));
+        assert!(!is_client_message_type(MSG_SEQUENCED));
+        assert!(!is_client_message_type(MSG_CURSOR_BROADCAST_BATCH));
+        assert!(!is_client_message_type(MSG_ACTIVITY_BROADCAST));
+        assert!(!is_client_message_type(MSG_CARD_CLAIMED));
+        assert!(!is_client_message_type(MSG_CARD_RELEASED));
+        assert!(!is_client_message_type(MSG_ERROR));
+    }
+
+    #[test]
+    fn test_cursor_broadcast_v1_roundtrip() {
+        let msg = BinaryMessage::CursorBroadcast {
+            board_id: 1234,
+            user_id: 9,
+            x: 100,
+            y: 200,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(encoded.len(), 10);
+        assert_eq!(encoded[0], MSG_CURSOR_BROADCAST);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_cursor_broadcast_timed_roundtrip() {
+        let msg = BinaryMessage::CursorBroadcastTimed {
+            board_id: 1234,
+            user_id: 9,
+            x: 100,
+            y: 200,
+            tick: 0xDEAD_BEEF,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(encoded.len(), 14);
+        assert_eq!(encoded[0], MSG_CURSOR_BROADCAST_TIMED);
+        assert_eq!(&encoded[10..], &[0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..10]),
+            Err(ProtocolError::InvalidLength { expected: 14, .. })
+        ));
+    }
+
+    #[test]
+    fn test_hello_roundtrip() {
+        let msg = BinaryMessage::Hello {
+            version: 2,
+            cursor_interval_ms: None,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(encoded, vec![MSG_HELLO, 2]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        let msg = BinaryMessage::Hello {
+            version: 6,
+            cursor_interval_ms: Some(300),
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(encoded, vec![MSG_HELLO, 6, 0x01, 0x2C]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        // A half-written interval is rejected
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..3]),
+            Err(ProtocolError::InvalidLength { actual: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cursor_form_selected_by_version() {
+        let broadcast = BinaryMessage::CursorBroadcast {
+            board_id: 1,
+            user_id: 2,
+            x: 3,
+            y: 4,
+        };
+
+        let v1 = broadcast
+            .clone()
+            .for_protocol_version(PROTOCOL_VERSION_V1, 500);
+        assert_eq!(v1, broadcast);
+        assert_eq!(v1.encode().len(), 10);
+
+        let v2 = broadcast.for_protocol_version(PROTOCOL_VERSION_V2, 500);
+        assert_eq!(
+            v2,
+            BinaryMessage::CursorBroadcastTimed {
+                board_id: 1,
+                user_id: 2,
+                x: 3,
+                y: 4,
+                tick: 500,
+            }
+        );
+
+        // Other messages are unaffected by the version
+        let left = BinaryMessage::UserLeft {
+            board_id: 1,
+            user_id: 2,
+        };
+        assert_eq!(
+            left.clone().for_protocol_version(PROTOCOL_VERSION, 500),
+            left
+        );
+    }
+
+    #[test]
+    fn test_focus_card_roundtrip() {
+        let msg = BinaryMessage::FocusCard {
+            board_id: 1234,
+            card_slot: 0x0102,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(encoded, vec![MSG_FOCUS_CARD, 0, 0, 0x04, 0xD2, 0x01, 0x02]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..6]),
+            Err(ProtocolError::InvalidLength { expected: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn test_focus_broadcast_roundtrip() {
+        let msg = BinaryMessage::FocusBroadcast {
+            board_id: 1234,
+            user_id: 7,
+            card_slot: NO_CARD_FOCUSED,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(
+            encoded,
+            vec![MSG_FOCUS_BROADCAST, 0, 0, 0x04, 0xD2, 7, 0, 0]
+        );
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        assert!(matches!(
+            BinaryMessage::decode(&[MSG_FOCUS_BROADCAST, 0, 0, 0, 1, 2, 0, 0, 0]),
+            Err(ProtocolError::InvalidLength { expected: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_activity_update_roundtrip() {
+        let msg = BinaryMessage::ActivityUpdate {
+            board_id: 1234,
+            card_ref: 0xDEAD_BEEF,
+            active: true,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(encoded[..5], [MSG_ACTIVITY_UPDATE, 0, 0, 0x04, 0xD2]);
+        assert_eq!(encoded[5..], [0xDE, 0xAD, 0xBE, 0xEF, 1]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..9]),
+            Err(ProtocolError::InvalidLength { expected: 10, .. })
+        ));
+
+        // Only 0 and 1 are valid flags
+        let mut bad_flag = encoded.clone();
+        bad_flag[9] = 2;
+        assert!(matches!(
+            BinaryMessage::decode(&bad_flag),
+            Err(ProtocolError::InvalidActivityFlag(2))
+        ));
+    }
+
+    #[test]
+    fn test_activity_broadcast_roundtrip() {
+        let msg = BinaryMessage::ActivityBroadcast {
+            board_id: 1234,
+            user_id: 7,
+            card_ref: 42,
+            active: false,
+        };
+        let encoded = msg.encode();
+
+        assert_eq!(
+            encoded,
+            vec![MSG_ACTIVITY_BROADCAST, 0, 0, 0x04, 0xD2, 7, 0, 0, 0, 42, 0]
+        );
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..10]),
+            Err(ProtocolError::InvalidLength { expected: 11, .. })
+        ));
+    }
+
+    #[test]
+    fn test_card_claim_roundtrip() {
+        let claim = BinaryMessage::ClaimCard {
+            board_id: 1234,
+            card_slot: 42,
+        };
+        let encoded = claim.encode();
+        assert_eq!(encoded, vec![MSG_CLAIM_CARD, 0, 0, 0x04, 0xD2, 0, 42]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), claim);
+
+        let release = BinaryMessage::ReleaseCard {
+            board_id: 1234,
+            card_slot: 42,
+        };
+        let encoded = release.encode();
+        assert_eq!(encoded, vec![MSG_RELEASE_CARD, 0, 0, 0x04, 0xD2, 0, 42]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), release);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..6]),
+            Err(ProtocolError::InvalidLength { expected: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn test_card_claimed_released_roundtrip() {
+        let claimed = BinaryMessage::CardClaimed {
+            board_id: 1234,
+            user_id: 7,
+            card_slot: 42,
+        };
+        let encoded = claimed.encode();
+        assert_eq!(encoded, vec![MSG_CARD_CLAIMED, 0, 0, 0x04, 0xD2, 7, 0, 42]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), claimed);
+
+        let released = BinaryMessage::CardReleased {
+            board_id: 1234,
+            user_id: 7,
+            card_slot: 42,
+        };
+        let encoded = released.encode();
+        assert_eq!(encoded, vec![MSG_CARD_RELEASED, 0, 0, 0x04, 0xD2, 7, 0, 42]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), released);
+
+        let mut too_long = encoded.clone();
+        too_long.push(0);
+        assert!(matches!(
+            BinaryMessage::decode(&too_long),
+            Err(ProtocolError::InvalidLength { expected: 8, .. })
+        ));
+    }
 
     #[test]
-    fn test_normalize_denormalize_roundtrip() {
-        let coords = vec![0.0, 0.25, 0.5, 0.75, 1.0];
-        for coord in coords {
-            let normalized = normalize_coord(coord);
-            let denormalized = denormalize_coord(normalized);
-            assert!(
-                (coord - denormalized).abs() < 0.001,
-                "Roundtrip failed for {}: got {}",
-                coord,
-                denormalized
+    fn test_error_roundtrip() {
+        let msg = BinaryMessage::Error {
+            code: ERROR_INVALID_LENGTH,
+        };
+        let encoded = msg.encode();
+        assert_eq!(encoded, vec![MSG_ERROR, 2]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..1]),
+            Err(ProtocolError::InvalidLength { expected: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_codes_decode() {
+        let cases = [
+            (
+                BinaryMessage::decode(&[0xFF]).unwrap_err(),
+                ERROR_UNKNOWN_MESSAGE_TYPE,
+            ),
+            (
+                BinaryMessage::decode(&[MSG_LEAVE, 0, 0]).unwrap_err(),
+                ERROR_INVALID_LENGTH,
+            ),
+            (
+                BinaryMessage::decode(&[MSG_JOIN, 0, 0, 0, 1, MAX_USERNAME_LENGTH as u8 + 1])
+                    .unwrap_err(),
+                ERROR_USERNAME_TOO_LONG,
+            ),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.error_code(), Some(code), "{:?}", error);
+
+            let encoded = BinaryMessage::Error { code }.encode();
+            assert_eq!(encoded, vec![MSG_ERROR, code]);
+            assert_eq!(
+                BinaryMessage::decode(&encoded).unwrap(),
+                BinaryMessage::Error { code }
             );
         }
+
+        assert_eq!(ProtocolError::InvalidUtf8.error_code(), None);
+        assert_eq!(ProtocolError::NestedSequenced.error_code(), None);
     }
 
     #[test]
-    fn test_normalize_clamps() {
-        assert_eq!(normalize_coord(-0.5), 0);
-        assert_eq!(normalize_coord(1.5), 65535);
+    fn test_sequenced_roundtrip() {
+        let inner = BinaryMessage::UserLeft {
+            board_id: 1234,
+            user_id: 7,
+        };
+        let msg = BinaryMessage::Sequenced {
+            seq: 0x0102_0304,
+            message: Box::new(inner.clone()),
+        };
+        let encoded = msg.encode();
+
+        let expected = [
+            MSG_SEQUENCED,
+            1,
+            2,
+            3,
+            4,
+            MSG_USER_LEFT,
+            0,
+            0,
+            0x04,
+            0xD2,
+            7,
+        ];
+        assert_eq!(encoded, expected);
+        assert_eq!(encoded, encode_sequenced(0x0102_0304, &inner.encode()));
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        // The envelope needs a complete inner frame
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..5]),
+            Err(ProtocolError::InvalidLength { expected: 6, .. })
+        ));
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..8]),
+            Err(ProtocolError::InvalidLength { expected: 6, .. })
+        ));
+
+        let nested = encode_sequenced(2, &encoded);
+        assert!(matches!(
+            BinaryMessage::decode(&nested),
+            Err(ProtocolError::NestedSequenced)
+        ));
     }
 
     #[test]
-    fn test_cursor_update_encoding() {
-        let msg = BinaryMessage::CursorUpdate {
+    fn test_announcement_roundtrip() {
+        let msg = BinaryMessage::Announcement {
             board_id: 1234,
-            x: normalize_coord(0.5),
-            y: normalize_coord(0.75),
+            text: "héllo".to_string(),
         };
         let encoded = msg.encode();
-        assert_eq!(encoded.len(), 7);
-        assert_eq!(encoded[0], MSG_CURSOR_UPDATE);
+
+        assert_eq!(&encoded[..6], &[MSG_ANNOUNCEMENT, 0, 0, 0x04, 0xD2, 6]);
+        assert_eq!(encoded.len(), 12);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+
+        let longest = BinaryMessage::Announcement {
+            board_id: 1,
+            text: "a".repeat(MAX_ANNOUNCEMENT_LENGTH),
+        };
+        assert_eq!(BinaryMessage::decode(&longest.encode()).unwrap(), longest);
+
+        assert!(matches!(
+            BinaryMessage::decode(&[MSG_ANNOUNCEMENT, 0, 0, 0, 1]),
+            Err(ProtocolError::InvalidLength { expected: 6, .. })
+        ));
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..10]),
+            Err(ProtocolError::BufferUnderflow)
+        ));
     }
 
     #[test]
-    fn test_heartbeat_encoding() {
-        let msg = BinaryMessage::Heartbeat;
+    fn test_join_reconnect_token_roundtrip() {
+        let without = BinaryMessage::Join {
+            board_id: 1234,
+            username: "Bo".to_string(),
+            reconnect_token: None,
+        };
+        let encoded = without.encode();
+        assert_eq!(encoded, vec![MSG_JOIN, 0, 0, 0x04, 0xD2, 2, b'B', b'o']);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), without);
+
+        let token: ReconnectToken = std::array::from_fn(|i| i as u8);
+        let with = BinaryMessage::Join {
+            board_id: 1234,
+            username: "Bo".to_string(),
+            reconnect_token: Some(token),
+        };
+        let encoded = with.encode();
+        assert_eq!(encoded.len(), 8 + RECONNECT_TOKEN_LENGTH);
+        assert_eq!(&encoded[8..], &token);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), with);
+
+        // Anything after the username other than a whole token is rejected
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..12]),
+            Err(ProtocolError::InvalidLength {
+                expected: 24,
+                actual: 12
+            })
+        ));
+        let mut too_long = encoded.clone();
+        too_long.push(0);
+        assert!(matches!(
+            BinaryMessage::decode(&too_long),
+            Err(ProtocolError::InvalidLength { expected: 24, .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_borrowed_user_joined() {
+        let msg = BinaryMessage::UserJoined {
+            board_id: 1234,
+            user_id: 7,
+            username: "Zoë".to_string(),
+            color: [0xFF, 0x57, 0x33],
+        };
         let encoded = msg.encode();
-        assert_eq!(encoded.len(), 1);
-        assert_eq!(encoded[0], MSG_HEARTBEAT);
+
+        let borrowed = BinaryMessage::decode_borrowed(&encoded).unwrap();
+        match &borrowed {
+            BinaryMessageRef::UserJoined { username, .. } => {
+                // Points into the frame rather than at a copy
+                assert_eq!(*username, "Zoë");
+                assert!(encoded.as_ptr_range().contains(&username.as_ptr()));
+            }
+            other => panic!("expected UserJoined, got {:?}", other),
+        }
+
+        let mut buf = BytesMut::new();
+        borrowed.encode_into(&mut buf);
+        assert_eq!(&buf[..], &encoded[..]);
+        assert_eq!(borrowed.into_owned(), msg);
+
+        // Same validation as the owned decode
+        let mut invalid = encoded.clone();
+        invalid[7] = 0xFF;
+        assert!(matches!(
+            BinaryMessage::decode_borrowed(&invalid),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+        assert!(matches!(
+            BinaryMessage::decode_borrowed(&encoded[..10]),
+            Err(ProtocolError::BufferUnderflow)
+        ));
     }
 
     #[test]
-    fn test_decode_unknown_type() {
-        let data = vec![0xFF];
-        let result = BinaryMessage::decode(&data);
+    fn test_username_limit_enforced_consistently() {
+        use crate::connection::room::sanitize_username;
+
+        // Unit tests run with an overridden limit, so this covers the wiring
+        // rather than the production value
+        assert_ne!(MAX_USERNAME_LENGTH, 32);
+
+        let longest = "a".repeat(MAX_USERNAME_LENGTH);
+        let too_long = "a".repeat(MAX_USERNAME_LENGTH + 1);
+
+        // Encoder validation
+        let join = BinaryMessage::Join {
+            board_id: 1,
+            username: longest.clone(),
+            reconnect_token: Some([7; RECONNECT_TOKEN_LENGTH]),
+        };
+        assert!(join.validate().is_ok());
+        assert_eq!(join.encode().len(), MAX_JOIN_SIZE);
+        let user_joined = BinaryMessage::UserJoined {
+            board_id: 1,
+            user_id: 2,
+            username: longest.clone(),
+            color: [1, 2, 3],
+        };
+        assert_eq!(user_joined.encode().len(), MAX_USER_JOINED_SIZE);
         assert!(matches!(
-            result,
-            Err(ProtocolError::UnknownMessageType(0xFF))
+            BinaryMessage::Join {
+                board_id: 1,
+                username: too_long.clone(),
+                reconnect_token: None,
+            }
+            .validate(),
+            Err(ProtocolError::UsernameTooLong(len)) if len == MAX_USERNAME_LENGTH + 1
         ));
+
+        // Decoder bound
+        assert_eq!(BinaryMessage::decode(&join.encode()).unwrap(), join);
+        let mut oversized = vec![MSG_JOIN, 0, 0, 0, 1, too_long.len() as u8];
+        oversized.extend_from_slice(too_long.as_bytes());
+        assert!(matches!(
+            BinaryMessage::decode(&oversized),
+            Err(ProtocolError::UsernameTooLong(len)) if len == MAX_USERNAME_LENGTH + 1
+        ));
+
+        // Sanitizer
+        assert_eq!(sanitize_username(&too_long), Some(longest));
     }
 
     #[test]
-    fn test_decode_buffer_underflow() {
-        let data = vec![MSG_CURSOR_UPDATE, 0x00]; // Too short
-        let result = BinaryMessage::decode(&data);
-        assert!(matches!(result, Err(ProtocolError::InvalidLength { .. })));
+    fn test_ping_pong_roundtrip() {
+        let ping = BinaryMessage::Ping { nonce: 0xDEAD_BEEF };
+        let encoded = ping.encode();
+        assert_eq!(encoded, vec![MSG_PING, 0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), ping);
+
+        let pong = BinaryMessage::Pong { nonce: 0xDEAD_BEEF };
+        let encoded = pong.encode();
+        assert_eq!(encoded, vec![MSG_PONG, 0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), pong);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..4]),
+            Err(ProtocolError::InvalidLength { expected: 5, .. })
+        ));
+        assert!(matches!(
+            BinaryMessage::decode(&[MSG_PING, 0, 0, 0, 1, 0]),
+            Err(ProtocolError::InvalidLength { expected: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_query_presence_roundtrip() {
+        let query = BinaryMessage::QueryPresence { board_id: 1234 };
+        let encoded = query.encode();
+        assert_eq!(encoded, vec![MSG_QUERY_PRESENCE, 0, 0, 0x04, 0xD2]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), query);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..4]),
+            Err(ProtocolError::InvalidLength { expected: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_correlated_roundtrip() {
+        let join = BinaryMessage::Join {
+            board_id: 1234,
+            username: "A".to_string(),
+            reconnect_token: None,
+        };
+        let msg = join.clone().correlate(Some(0xBEEF));
+        let encoded = msg.encode();
+
+        let expected = [
+            MSG_CORRELATED,
+            0xBE,
+            0xEF,
+            MSG_JOIN,
+            0,
+            0,
+            0x04,
+            0xD2,
+            1,
+            b'A',
+        ];
+        assert_eq!(encoded, expected);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), msg);
+        assert_eq!(join.clone().correlate(None), join);
+
+        // The envelope needs a complete inner frame
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..3]),
+            Err(ProtocolError::InvalidLength { expected: 4, .. })
+        ));
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..5]),
+            Err(ProtocolError::InvalidLength { expected: 6, .. })
+        ));
+
+        // Neither envelope may be wrapped
+        let nested = BinaryMessage::Correlated {
+            msg_id: 1,
+            message: Box::new(msg),
+        };
+        assert!(matches!(
+            BinaryMessage::decode(&nested.encode()),
+            Err(ProtocolError::NestedCorrelated)
+        ));
+        let mut around_sequenced = vec![MSG_CORRELATED, 0, 1];
+        around_sequenced.extend(encode_sequenced(1, &BinaryMessage::Heartbeat.encode()));
+        assert!(matches!(
+            BinaryMessage::decode(&around_sequenced),
+            Err(ProtocolError::NestedCorrelated)
+        ));
+
+        // Server replies to v3+ clients are sequenced around the correlation
+        let presence = BinaryMessage::PresenceUpdate {
+            board_id: 1234,
+            count: 3,
+        };
+        let reply = BinaryMessage::Sequenced {
+            seq: 9,
+            message: Box::new(presence.correlate(Some(7))),
+        };
+        assert_eq!(BinaryMessage::decode(&reply.encode()).unwrap(), reply);
+    }
+
+    #[test]
+    fn test_negotiate_version() {
+        // Clients built for the u16 board_id layouts or the u8 presence count are refused
+        assert_eq!(negotiate_version(0), None);
+        assert_eq!(negotiate_version(1), None);
+        assert_eq!(negotiate_version(6), None);
+        assert_eq!(negotiate_version(9), None);
+        assert_eq!(negotiate_version(10), Some(PROTOCOL_VERSION_V10));
+        assert_eq!(negotiate_version(200), Some(PROTOCOL_VERSION));
+    }
+
+    #[test]
+    fn test_normalize_12bit() {
+        assert_eq!(normalize_coord_12bit(-0.5), 0);
+        assert_eq!(normalize_coord_12bit(1.5), MAX_COMPACT_COORD);
+        for coord in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            let denormalized = denormalize_coord_12bit(normalize_coord_12bit(coord));
+            // One 12-bit step is 1/4095 of the range
+            assert!((coord - denormalized).abs() <= 1.0 / 4095.0);
+        }
+    }
+
+    #[test]
+    fn test_cursor_update_compact_roundtrip() {
+        let update = BinaryMessage::CursorUpdateCompact {
+            board_id: 0x01020304,
+            x: 0xABC,
+            y: 0x123,
+        };
+        let encoded = update.encode();
+        assert_eq!(
+            encoded,
+            vec![MSG_CURSOR_UPDATE_COMPACT, 1, 2, 3, 4, 0xAB, 0xC1, 0x23]
+        );
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), update);
+
+        let corners = BinaryMessage::CursorUpdateCompact {
+            board_id: 1,
+            x: MAX_COMPACT_COORD,
+            y: 0,
+        };
+        assert_eq!(BinaryMessage::decode(&corners.encode()).unwrap(), corners);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..7]),
+            Err(ProtocolError::InvalidLength { expected: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cursor_update_compact_rejects_wide_coords() {
+        let update = BinaryMessage::CursorUpdateCompact {
+            board_id: 1,
+            x: 0,
+            y: MAX_COMPACT_COORD + 1,
+        };
+        assert!(matches!(
+            update.validate(),
+            Err(ProtocolError::CompactCoordOutOfRange(4096))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_batch_roundtrip() {
+        let batch = BinaryMessage::CursorBatch {
+            board_id: 0x01020304,
+            points: vec![(1, 2), (0xABCD, 0xFFFF)],
+        };
+        let encoded = batch.encode();
+        assert_eq!(encoded[..6], [MSG_CURSOR_BATCH, 1, 2, 3, 4, 2]);
+        assert_eq!(encoded[6..], [0, 1, 0, 2, 0xAB, 0xCD, 0xFF, 0xFF]);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), batch);
+
+        let full = BinaryMessage::CursorBatch {
+            board_id: 1,
+            points: (0..MAX_CURSOR_BATCH_POINTS as u16)
+                .map(|i| (i, !i))
+                .collect(),
+        };
+        let encoded = full.encode();
+        assert_eq!(encoded.len(), MAX_CURSOR_BATCH_SIZE);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), full);
+
+        // The count must match the points that follow
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..encoded.len() - 1]),
+            Err(ProtocolError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            BinaryMessage::decode(&[MSG_CURSOR_BATCH, 0, 0, 0, 1, 0, 0, 0, 0, 0]),
+            Err(ProtocolError::InvalidCursorBatch(0))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_broadcast_batch_roundtrip() {
+        let batch = BinaryMessage::CursorBroadcastBatch {
+            board_id: 1234,
+            user_id: 9,
+            points: vec![(10, 20), (30, 40), (50, 60)],
+        };
+        let encoded = batch.encode();
+        assert_eq!(encoded.len(), 7 + 4 * 3);
+        assert_eq!(encoded[0], MSG_CURSOR_BROADCAST_BATCH);
+        assert_eq!(encoded[5], 9);
+        assert_eq!(encoded[6], 3);
+        assert_eq!(BinaryMessage::decode(&encoded).unwrap(), batch);
+
+        assert!(matches!(
+            BinaryMessage::decode(&encoded[..10]),
+            Err(ProtocolError::InvalidLength { expected: 11, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cursor_batch_limits() {
+        let empty = BinaryMessage::CursorBatch {
+            board_id: 1,
+            points: Vec::new(),
+        };
+        assert!(matches!(
+            empty.validate(),
+            Err(ProtocolError::InvalidCursorBatch(0))
+        ));
+
+        let oversized = BinaryMessage::CursorBroadcastBatch {
+            board_id: 1,
+            user_id: 1,
+            points: vec![(0, 0); MAX_CURSOR_BATCH_POINTS + 1],
+        };
+        assert!(matches!(
+            oversized.validate(),
+            Err(ProtocolError::InvalidCursorBatch(256))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_broadcast_batch_for_older_versions() {
+        let batch = BinaryMessage::CursorBroadcastBatch {
+            board_id: 1234,
+            user_id: 9,
+            points: vec![(10, 20), (30, 40)],
+        };
+
+        // Only the last point reaches clients that cannot decode batches
+        assert_eq!(
+            batch.clone().for_protocol_version(PROTOCOL_VERSION_V1, 500),
+            BinaryMessage::CursorBroadcast {
+                board_id: 1234,
+                user_id: 9,
+                x: 30,
+                y: 40,
+            }
+        );
+        assert_eq!(
+            batch.clone().for_protocol_version(PROTOCOL_VERSION_V8, 500),
+            BinaryMessage::CursorBroadcastTimed {
+                board_id: 1234,
+                user_id: 9,
+                x: 30,
+                y: 40,
+                tick: 500,
+            }
+        );
+        assert_eq!(
+            batch.clone().for_protocol_version(PROTOCOL_VERSION_V9, 500),
+            batch
+        );
+    }
+
+    /// Any message that passes `validate`, wrapped at most once
+    fn any_message() -> impl Strategy<Value = BinaryMessage> {
+        let username = || {
+            proptest::string::string_regex(&format!(".{{0,{}}}", MAX_USERNAME_LENGTH / 4)).unwrap()
+        };
+        let text =
+            proptest::string::string_regex(&format!(".{{0,{}}}", MAX_ANNOUNCEMENT_LENGTH / 4))
+                .unwrap();
+        let points = || proptest::collection::vec(any::<(u16, u16)>(), 1..=MAX_CURSOR_BATCH_POINTS);
+
+        let leaf = prop_oneof![
+            any::<(u32, u16, u16)>().prop_map(|(board_id, x, y)| BinaryMessage::CursorUpdate {
+                board_id,
+                x,
+                y
+            }),
+            any::<(u32, u8, u16, u16)>().prop_map(|(board_id, user_id, x, y)| {
+                BinaryMessage::CursorBroadcast {
+                    board_id,
+                    user_id,
+                    x,
+                    y,
+                }
+            }),
+            (any::<u32>(), username(), any::<Option<ReconnectToken>>()).prop_map(
+                |(board_id, username, reconnect_token)| {
+                    BinaryMessage::Join {
+                        board_id,
+                        username,
+                        reconnect_token,
+                    }
+                }
+            ),
+            any::<u32>().prop_map(|board_id| BinaryMessage::Leave { board_id }),
+            (any::<(u32, u8, [u8; 3])>(), username()).prop_map(
+                |((board_id, user_id, color), username)| BinaryMessage::UserJoined {
+                    board_id,
+                    user_id,
+                    username,
+                    color,
+                }
+            ),
+            any::<(u32, u8)>()
+                .prop_map(|(board_id, user_id)| BinaryMessage::UserLeft { board_id, user_id }),
+            any::<(u32, u16)>()
+                .prop_map(|(board_id, count)| BinaryMessage::PresenceUpdate { board_id, count }),
+            Just(BinaryMessage::Heartbeat),
+            any::<(u8, Option<u16>)>().prop_map(|(version, cursor_interval_ms)| {
+                BinaryMessage::Hello {
+                    version,
+                    cursor_interval_ms,
+                }
+            }),
+            any::<(u32, u8, u16, u16, u32)>().prop_map(|(board_id, user_id, x, y, tick)| {
+                BinaryMessage::CursorBroadcastTimed {
+                    board_id,
+                    user_id,
+                    x,
+                    y,
+                    tick,
+                }
+            }),
+            any::<(u32, u16)>().prop_map(|(board_id, card_slot)| BinaryMessage::FocusCard {
+                board_id,
+                card_slot
+            }),
+            any::<(u32, u8, u16)>().prop_map(|(board_id, user_id, card_slot)| {
+                BinaryMessage::FocusBroadcast {
+                    board_id,
+                    user_id,
+                    card_slot,
+                }
+            }),
+            (any::<u32>(), text)
+                .prop_map(|(board_id, text)| BinaryMessage::Announcement { board_id, text }),
+            any::<u32>().prop_map(|nonce| BinaryMessage::Ping { nonce }),
+            any::<u32>().prop_map(|nonce| BinaryMessage::Pong { nonce }),
+            any::<u32>().prop_map(|board_id| BinaryMessage::QueryPresence { board_id }),
+            (any::<u32>(), 0..=MAX_COMPACT_COORD, 0..=MAX_COMPACT_COORD)
+                .prop_map(|(board_id, x, y)| BinaryMessage::CursorUpdateCompact { board_id, x, y }),
+            (any::<u32>(), points())
+                .prop_map(|(board_id, points)| BinaryMessage::CursorBatch { board_id, points }),
+            (any::<(u32, u8)>(), points()).prop_map(|((board_id, user_id), points)| {
+                BinaryMessage::CursorBroadcastBatch {
+                    board_id,
+                    user_id,
+                    points,
+                }
+            }),
+            any::<(u32, u32, bool)>().prop_map(|(board_id, card_ref, active)| {
+                BinaryMessage::ActivityUpdate {
+                    board_id,
+                    card_ref,
+                    active,
+                }
+            }),
+            any::<(u32, u8, u32, bool)>().prop_map(|(board_id, user_id, card_ref, active)| {
+                BinaryMessage::ActivityBroadcast {
+                    board_id,
+                    user_id,
+                    card_ref,
+                    active,
+                }
+            }),
+            any::<(u32, u16)>().prop_map(|(board_id, card_slot)| BinaryMessage::ClaimCard {
+                board_id,
+                card_slot
+            }),
+            any::<(u32, u16)>().prop_map(|(board_id, card_slot)| BinaryMessage::ReleaseCard {
+                board_id,
+                card_slot
+            }),
+            any::<(u32, u8, u16)>().prop_map(|(board_id, user_id, card_slot)| {
+                BinaryMessage::CardClaimed {
+                    board_id,
+                    user_id,
+                    card_slot,
+                }
+            }),
+            any::<(u32, u8, u16)>().prop_map(|(board_id, user_id, card_slot)| {
+                BinaryMessage::CardReleased {
+                    board_id,
+                    user_id,
+                    card_slot,
+                }
+            }),
+            any::<u8>().prop_map(|code| BinaryMessage::Error { code }),
+        ];
+
+        leaf.prop_flat_map(|message| {
+            prop_oneof![
+                Just(message.clone()),
+                any::<u32>().prop_map({
+                    let message = message.clone();
+                    move |seq| BinaryMessage::Sequenced {
+                        seq,
+                        message: Box::new(message.clone()),
+                    }
+                }),
+                any::<u16>().prop_map(move |msg_id| BinaryMessage::Correlated {
+                    msg_id,
+                    message: Box::new(message.clone()),
+                }),
+            ]
+        })
+    }
+
+    #[test]
+    fn test_encoded_len_matches_encode() {
+        let wrap = |message: BinaryMessage| {
+            [
+                BinaryMessage::Sequenced {
+                    seq: u32::MAX,
+                    message: Box::new(message.clone()),
+                },
+                BinaryMessage::Correlated {
+                    msg_id: u16::MAX,
+                    message: Box::new(message.clone()),
+                },
+                message,
+            ]
+        };
+
+        let mut messages = Vec::new();
+        for board_id in [0, 1234, u32::MAX] {
+            for (username, text, points) in [
+                (String::new(), String::new(), 1),
+                ("Alice".to_string(), "Deploy at 5pm".to_string(), 8),
+                (
+                    "a".repeat(MAX_USERNAME_LENGTH),
+                    "a".repeat(MAX_ANNOUNCEMENT_LENGTH),
+                    MAX_CURSOR_BATCH_POINTS,
+                ),
+            ] {
+                let points = vec![(u16::MAX, 0); points];
+                messages.extend([
+                    BinaryMessage::CursorUpdate {
+                        board_id,
+                        x: 0,
+                        y: u16::MAX,
+                    },
+                    BinaryMessage::CursorBroadcast {
+                        board_id,
+                        user_id: 255,
+                        x: 0,
+                        y: u16::MAX,
+                    },
+                    BinaryMessage::Join {
+                        board_id,
+                        username: username.clone(),
+                        reconnect_token: None,
+                    },
+                    BinaryMessage::Join {
+                        board_id,
+                        username: username.clone(),
+                        reconnect_token: Some([u8::MAX; RECONNECT_TOKEN_LENGTH]),
+                    },
+                    BinaryMessage::Leave { board_id },
+                    BinaryMessage::UserJoined {
+                        board_id,
+                        user_id: 255,
+                        username,
+                        color: [255, 0, 0],
+                    },
+                    BinaryMessage::UserLeft {
+                        board_id,
+                        user_id: 0,
+                    },
+                    BinaryMessage::PresenceUpdate {
+                        board_id,
+                        count: u16::MAX,
+                    },
+                    BinaryMessage::Heartbeat,
+                    BinaryMessage::Hello {
+                        version: PROTOCOL_VERSION,
+                        cursor_interval_ms: None,
+                    },
+                    BinaryMessage::Hello {
+                        version: PROTOCOL_VERSION,
+                        cursor_interval_ms: Some(u16::MAX),
+                    },
+                    BinaryMessage::CursorBroadcastTimed {
+                        board_id,
+                        user_id: 1,
+                        x: 0,
+                        y: u16::MAX,
+                        tick: u32::MAX,
+                    },
+                    BinaryMessage::FocusCard {
+                        board_id,
+                        card_slot: u16::MAX,
+                    },
+                    BinaryMessage::FocusBroadcast {
+                        board_id,
+                        user_id: 1,
+                        card_slot: NO_CARD_FOCUSED,
+                    },
+                    BinaryMessage::Announcement { board_id, text },
+                    BinaryMessage::Ping { nonce: board_id },
+                    BinaryMessage::Pong { nonce: board_id },
+                    BinaryMessage::QueryPresence { board_id },
+                    BinaryMessage::CursorUpdateCompact {
+                        board_id,
+                        x: MAX_COMPACT_COORD,
+                        y: 0,
+                    },
+                    BinaryMessage::CursorBatch {
+                        board_id,
+                        points: points.clone(),
+                    },
+                    BinaryMessage::CursorBroadcastBatch {
+                        board_id,
+                        user_id: 1,
+                        points,
+                    },
+                    BinaryMessage::ActivityUpdate {
+                        board_id,
+                        card_ref: u32::MAX,
+                        active: true,
+                    },
+                    BinaryMessage::ActivityBroadcast {
+                        board_id,
+                        user_id: 1,
+                        card_ref: 0,
+                        active: false,
+                    },
+                    BinaryMessage::ClaimCard {
+                        board_id,
+                        card_slot: u16::MAX,
+                    },
+                    BinaryMessage::ReleaseCard {
+                        board_id,
+                        card_slot: 1,
+                    },
+                    BinaryMessage::CardClaimed {
+                        board_id,
+                        user_id: 255,
+                        card_slot: u16::MAX,
+                    },
+                    BinaryMessage::CardReleased {
+                        board_id,
+                        user_id: 0,
+                        card_slot: 1,
+                    },
+                    BinaryMessage::Error { code: 0 },
+                    BinaryMessage::Error { code: u8::MAX },
+                ]);
+            }
+        }
+
+        for message in messages.into_iter().flat_map(wrap) {
+            assert_eq!(
+                message.encoded_len(),
+                message.encode().len(),
+                "{:?}",
+                message
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_encoded_len_matches_encode(message in any_message()) {
+            prop_assert_eq!(message.encoded_len(), message.encode().len());
+        }
+
+        #[test]
+        fn prop_encode_into_matches_encode(
+            message in any_message(),
+            prefix in proptest::collection::vec(any::<u8>(), 0..8),
+        ) {
+            let mut buf = BytesMut::from(&prefix[..]);
+            message.encode_into(&mut buf);
+
+            // Appends after what the buffer already held
+            prop_assert_eq!(&buf[..prefix.len()], &prefix[..]);
+            prop_assert_eq!(&buf[prefix.len()..], &message.encode()[..]);
+        }
+
+        #[test]
+        fn prop_decode_borrowed_matches_decode(message in any_message()) {
+            let encoded = message.encode();
+            let borrowed = BinaryMessage::decode_borrowed(&encoded).unwrap();
+
+            let mut buf = BytesMut::new();
+            borrowed.encode_into(&mut buf);
+            prop_assert_eq!(&buf[..], &encoded[..]);
+            prop_assert_eq!(borrowed.into_owned(), message);
+        }
     }
 }
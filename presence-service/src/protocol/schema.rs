@@ -0,0 +1,520 @@
+//! Machine-readable description of the binary protocol.
+//!
+//! Served as JSON by `GET /protocol` so clients can discover the versions and
+//! message types this server speaks. Type IDs, names, directions and string
+//! limits come from `types.rs`; the field lists mirror the layouts documented
+//! on `BinaryMessage` and are checked against real encodings in the tests.
+
+use super::types::*;
+use serde::Serialize;
+
+/// Message types both sides send (the server echoes heartbeats, answers Hello
+/// and wraps replies to correlated requests)
+const BIDIRECTIONAL_MESSAGE_TYPES: &[u8] = &[MSG_HEARTBEAT, MSG_HELLO, MSG_CORRELATED];
+
+/// The whole protocol
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolSchema {
+    /// Highest version this server speaks
+    pub version: u8,
+    /// Lowest version a Hello may request (clients that never send Hello
+    /// get v1 behavior on the current layouts)
+    pub min_version: u8,
+    /// Byte order of every multi-byte integer
+    pub byte_order: &'static str,
+    pub messages: Vec<MessageSchema>,
+}
+
+/// Who sends a message type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+    Bidirectional,
+}
+
+/// One message type
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageSchema {
+    pub name: &'static str,
+    /// First byte of every frame of this type
+    #[serde(rename = "type")]
+    pub msg_type: u8,
+    pub direction: Direction,
+    /// First protocol version that uses the message
+    pub since_version: u8,
+    /// Smallest encoded frame, including the type byte
+    pub min_size: usize,
+    /// Largest encoded frame, including the type byte
+    pub max_size: usize,
+    /// Fields following the type byte, in wire order
+    pub fields: Vec<FieldSchema>,
+}
+
+/// Wire encoding of a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    U8,
+    U16,
+    U32,
+    /// Three bytes: red, green, blue
+    Rgb,
+    /// Three bytes: a 12-bit x in the high bits, a 12-bit y in the low bits
+    PackedXy,
+    /// `RECONNECT_TOKEN_LENGTH` opaque bytes chosen by the client
+    Token,
+    /// `u8` byte length followed by that many UTF-8 bytes
+    String,
+    /// `u8` point count (at least 1) followed by that many `u16` x/y pairs
+    Points,
+    /// Another complete frame (never itself `sequenced`; only `sequenced`
+    /// may wrap a `correlated` frame)
+    Frame,
+}
+
+/// One field of a message
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    /// Longest value: bytes for strings, points for point lists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Trailing field a frame may end before
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+impl FieldSchema {
+    const fn new(name: &'static str, field_type: FieldType) -> Self {
+        Self {
+            name,
+            field_type,
+            max_length: None,
+            optional: false,
+        }
+    }
+
+    const fn string(name: &'static str, max_length: usize) -> Self {
+        Self {
+            name,
+            field_type: FieldType::String,
+            max_length: Some(max_length),
+            optional: false,
+        }
+    }
+
+    const fn points(name: &'static str, max_length: usize) -> Self {
+        Self {
+            name,
+            field_type: FieldType::Points,
+            max_length: Some(max_length),
+            optional: false,
+        }
+    }
+
+    const fn optional(name: &'static str, field_type: FieldType) -> Self {
+        Self {
+            name,
+            field_type,
+            max_length: None,
+            optional: true,
+        }
+    }
+
+    /// Smallest and largest encoded size; `frame_max` bounds `Frame` fields
+    fn size_range(&self, frame_max: usize) -> (usize, usize) {
+        let (min, max) = match self.field_type {
+            FieldType::U8 => (1, 1),
+            FieldType::U16 => (2, 2),
+            FieldType::U32 => (4, 4),
+            FieldType::Rgb | FieldType::PackedXy => (3, 3),
+            FieldType::Token => (RECONNECT_TOKEN_LENGTH, RECONNECT_TOKEN_LENGTH),
+            FieldType::String => (1, 1 + self.max_length.unwrap_or(0)),
+            FieldType::Points => (5, 1 + 4 * self.max_length.unwrap_or(0)),
+            // The smallest frame is a lone Heartbeat
+            FieldType::Frame => (1, frame_max),
+        };
+        if self.optional {
+            (0, max)
+        } else {
+            (min, max)
+        }
+    }
+}
+
+/// Fields of a message type, after the type byte
+fn fields(msg_type: u8) -> Vec<FieldSchema> {
+    use FieldType::*;
+
+    let board_id = FieldSchema::new("board_id", U32);
+    let user_id = FieldSchema::new("user_id", U8);
+    let x = FieldSchema::new("x", U16);
+    let y = FieldSchema::new("y", U16);
+    let card_ref = FieldSchema::new("card_ref", U32);
+    // 1 while the user is editing the card, 0 once they stop
+    let active = FieldSchema::new("active", U8);
+
+    match msg_type {
+        MSG_CURSOR_UPDATE => vec![board_id, x, y],
+        MSG_CURSOR_BROADCAST => vec![board_id, user_id, x, y],
+        MSG_JOIN => vec![
+            board_id,
+            FieldSchema::string("username", MAX_USERNAME_LENGTH),
+            FieldSchema::optional("reconnect_token", Token),
+        ],
+        MSG_LEAVE | MSG_QUERY_PRESENCE => vec![board_id],
+        MSG_USER_JOINED => vec![
+            board_id,
+            user_id,
+            FieldSchema::string("username", MAX_USERNAME_LENGTH),
+            FieldSchema::new("color", Rgb),
+        ],
+        MSG_USER_LEFT => vec![board_id, user_id],
+        MSG_PRESENCE_UPDATE => vec![board_id, FieldSchema::new("count", U16)],
+        MSG_HELLO => vec![
+            FieldSchema::new("version", U8),
+            FieldSchema::optional("cursor_interval_ms", U16),
+        ],
+        MSG_CURSOR_BROADCAST_TIMED => vec![board_id, user_id, x, y, FieldSchema::new("tick", U32)],
+        MSG_FOCUS_CARD => vec![board_id, FieldSchema::new("card_slot", U16)],
+        MSG_FOCUS_BROADCAST => vec![board_id, user_id, FieldSchema::new("card_slot", U16)],
+        MSG_SEQUENCED => vec![
+            FieldSchema::new("seq", U32),
+            FieldSchema::new("frame", Frame),
+        ],
+        MSG_ANNOUNCEMENT => vec![
+            board_id,
+            FieldSchema::string("text", MAX_ANNOUNCEMENT_LENGTH),
+        ],
+        MSG_PING | MSG_PONG => vec![FieldSchema::new("nonce", U32)],
+        MSG_CORRELATED => vec![
+            FieldSchema::new("msg_id", U16),
+            FieldSchema::new("frame", Frame),
+        ],
+        MSG_CURSOR_UPDATE_COMPACT => vec![board_id, FieldSchema::new("xy", PackedXy)],
+        MSG_CURSOR_BATCH => vec![
+            board_id,
+            FieldSchema::points("points", MAX_CURSOR_BATCH_POINTS),
+        ],
+        MSG_CURSOR_BROADCAST_BATCH => vec![
+            board_id,
+            user_id,
+            FieldSchema::points("points", MAX_CURSOR_BATCH_POINTS),
+        ],
+        MSG_ACTIVITY_UPDATE => vec![board_id, card_ref, active],
+        MSG_ACTIVITY_BROADCAST => vec![board_id, user_id, card_ref, active],
+        MSG_CLAIM_CARD | MSG_RELEASE_CARD => vec![board_id, FieldSchema::new("card_slot", U16)],
+        MSG_CARD_CLAIMED | MSG_CARD_RELEASED => {
+            vec![board_id, user_id, FieldSchema::new("card_slot", U16)]
+        }
+        MSG_ERROR => vec![FieldSchema::new("code", U8)],
+        _ => Vec::new(),
+    }
+}
+
+/// First protocol version that uses a message type
+fn since_version(msg_type: u8) -> u8 {
+    match msg_type {
+        MSG_CURSOR_BROADCAST_TIMED => PROTOCOL_VERSION_V2,
+        MSG_SEQUENCED => PROTOCOL_VERSION_V3,
+        MSG_PING | MSG_PONG => PROTOCOL_VERSION_V4,
+        MSG_CORRELATED => PROTOCOL_VERSION_V5,
+        MSG_CURSOR_UPDATE_COMPACT => PROTOCOL_VERSION_V8,
+        MSG_CURSOR_BATCH | MSG_CURSOR_BROADCAST_BATCH => PROTOCOL_VERSION_V9,
+        MSG_ERROR => PROTOCOL_VERSION_V11,
+        _ => PROTOCOL_VERSION_V1,
+    }
+}
+
+fn direction(msg_type: u8) -> Direction {
+    if BIDIRECTIONAL_MESSAGE_TYPES.contains(&msg_type) {
+        Direction::Bidirectional
+    } else if is_client_message_type(msg_type) {
+        Direction::ClientToServer
+    } else {
+        Direction::ServerToClient
+    }
+}
+
+/// Frame size range for a list of fields, including the type byte
+fn frame_size(fields: &[FieldSchema], frame_max: usize) -> (usize, usize) {
+    fields.iter().fold((1, 1), |(min, max), field| {
+        let (field_min, field_max) = field.size_range(frame_max);
+        (min + field_min, max + field_max)
+    })
+}
+
+/// Describe every message type this server knows
+pub fn protocol_schema() -> ProtocolSchema {
+    let known: Vec<(u8, &'static str)> = (0..=u8::MAX)
+        .filter_map(|msg_type| message_type_name(msg_type).map(|name| (msg_type, name)))
+        .collect();
+
+    // A correlated frame may wrap any plain frame, and a sequenced frame any
+    // frame but another sequenced one
+    let largest_plain_frame = known
+        .iter()
+        .filter(|(msg_type, _)| !matches!(*msg_type, MSG_SEQUENCED | MSG_CORRELATED))
+        .map(|(msg_type, _)| frame_size(&fields(*msg_type), 0).1)
+        .max()
+        .unwrap_or(0);
+    let largest_correlated_frame = frame_size(&fields(MSG_CORRELATED), largest_plain_frame).1;
+
+    let messages = known
+        .into_iter()
+        .map(|(msg_type, name)| {
+            let fields = fields(msg_type);
+            let frame_max = match msg_type {
+                MSG_SEQUENCED => largest_correlated_frame,
+                _ => largest_plain_frame,
+            };
+            let (min_size, max_size) = frame_size(&fields, frame_max);
+            MessageSchema {
+                name,
+                msg_type,
+                direction: direction(msg_type),
+                since_version: since_version(msg_type),
+                min_size,
+                max_size,
+                fields,
+            }
+        })
+        .collect();
+
+    ProtocolSchema {
+        version: PROTOCOL_VERSION,
+        min_version: MIN_PROTOCOL_VERSION,
+        byte_order: "big_endian",
+        messages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::messages::BinaryMessage;
+
+    fn find(schema: &ProtocolSchema, msg_type: u8) -> &MessageSchema {
+        schema
+            .messages
+            .iter()
+            .find(|message| message.msg_type == msg_type)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schema_reports_protocol_version() {
+        let schema = protocol_schema();
+        assert_eq!(schema.version, PROTOCOL_VERSION);
+        assert_eq!(schema.min_version, MIN_PROTOCOL_VERSION);
+
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["version"], PROTOCOL_VERSION);
+        assert_eq!(json["messages"][0]["type"], MSG_CURSOR_UPDATE);
+        assert_eq!(json["messages"][0]["name"], "cursor_update");
+        assert_eq!(json["messages"][0]["direction"], "client_to_server");
+    }
+
+    #[test]
+    fn test_schema_covers_every_message_type() {
+        let schema = protocol_schema();
+        let types: Vec<u8> = schema.messages.iter().map(|m| m.msg_type).collect();
+        let named: Vec<u8> = (0..=u8::MAX)
+            .filter(|t| message_type_name(*t).is_some())
+            .collect();
+        assert_eq!(types, named);
+
+        for client_type in CLIENT_MESSAGE_TYPES {
+            assert_ne!(
+                find(&schema, *client_type).direction,
+                Direction::ServerToClient
+            );
+        }
+        assert_eq!(find(&schema, MSG_PING).direction, Direction::ServerToClient);
+        assert_eq!(
+            find(&schema, MSG_HEARTBEAT).direction,
+            Direction::Bidirectional
+        );
+    }
+
+    #[test]
+    fn test_schema_sizes_match_encodings() {
+        let schema = protocol_schema();
+        let samples = [
+            BinaryMessage::CursorUpdate {
+                board_id: 1,
+                x: 2,
+                y: 3,
+            },
+            BinaryMessage::CursorBroadcast {
+                board_id: 1,
+                user_id: 2,
+                x: 3,
+                y: 4,
+            },
+            BinaryMessage::Join {
+                board_id: 1,
+                username: "a".repeat(MAX_USERNAME_LENGTH),
+                reconnect_token: Some([1; RECONNECT_TOKEN_LENGTH]),
+            },
+            BinaryMessage::Leave { board_id: 1 },
+            BinaryMessage::UserJoined {
+                board_id: 1,
+                user_id: 2,
+                username: "a".repeat(MAX_USERNAME_LENGTH),
+                color: [1, 2, 3],
+            },
+            BinaryMessage::UserLeft {
+                board_id: 1,
+                user_id: 2,
+            },
+            BinaryMessage::PresenceUpdate {
+                board_id: 1,
+                count: 2,
+            },
+            BinaryMessage::Heartbeat,
+            BinaryMessage::Hello {
+                version: PROTOCOL_VERSION,
+                cursor_interval_ms: Some(50),
+            },
+            BinaryMessage::CursorBroadcastTimed {
+                board_id: 1,
+                user_id: 2,
+                x: 3,
+                y: 4,
+                tick: 5,
+            },
+            BinaryMessage::FocusCard {
+                board_id: 1,
+                card_slot: 2,
+            },
+            BinaryMessage::FocusBroadcast {
+                board_id: 1,
+                user_id: 2,
+                card_slot: 3,
+            },
+            BinaryMessage::Sequenced {
+                seq: 1,
+                message: Box::new(BinaryMessage::Heartbeat),
+            },
+            BinaryMessage::Announcement {
+                board_id: 1,
+                text: "a".repeat(MAX_ANNOUNCEMENT_LENGTH),
+            },
+            BinaryMessage::Ping { nonce: 1 },
+            BinaryMessage::Pong { nonce: 1 },
+            BinaryMessage::QueryPresence { board_id: 1 },
+            BinaryMessage::Correlated {
+                msg_id: 1,
+                message: Box::new(BinaryMessage::Heartbeat),
+            },
+            BinaryMessage::CursorUpdateCompact {
+                board_id: 1,
+                x: 2,
+                y: 3,
+            },
+            BinaryMessage::CursorBatch {
+                board_id: 1,
+                points: vec![(2, 3); MAX_CURSOR_BATCH_POINTS],
+            },
+            BinaryMessage::CursorBroadcastBatch {
+                board_id: 1,
+                user_id: 2,
+                points: vec![(3, 4); MAX_CURSOR_BATCH_POINTS],
+            },
+            BinaryMessage::ActivityUpdate {
+                board_id: 1,
+                card_ref: 2,
+                active: true,
+            },
+            BinaryMessage::ActivityBroadcast {
+                board_id: 1,
+                user_id: 2,
+                card_ref: 3,
+                active: false,
+            },
+            BinaryMessage::ClaimCard {
+                board_id: 1,
+                card_slot: 2,
+            },
+            BinaryMessage::ReleaseCard {
+                board_id: 1,
+                card_slot: 2,
+            },
+            BinaryMessage::CardClaimed {
+                board_id: 1,
+                user_id: 2,
+                card_slot: 3,
+            },
+            BinaryMessage::CardReleased {
+                board_id: 1,
+                user_id: 2,
+                card_slot: 3,
+            },
+            BinaryMessage::Error {
+                code: ERROR_INVALID_LENGTH,
+            },
+        ];
+
+        for sample in &samples {
+            let encoded = sample.encode();
+            let message = find(&schema, encoded[0]);
+            let has_variable_field = message.fields.iter().any(|f| {
+                f.optional
+                    || matches!(
+                        f.field_type,
+                        FieldType::String | FieldType::Points | FieldType::Frame
+                    )
+            });
+
+            if has_variable_field {
+                // Samples use the longest strings and batches, every optional
+                // field and the smallest inner frame
+                assert!(
+                    encoded.len() == message.max_size || encoded.len() == message.min_size,
+                    "{}: {} bytes outside {}..={}",
+                    message.name,
+                    encoded.len(),
+                    message.min_size,
+                    message.max_size
+                );
+            } else {
+                assert_eq!(encoded.len(), message.min_size, "{}", message.name);
+                assert_eq!(encoded.len(), message.max_size, "{}", message.name);
+            }
+        }
+        assert_eq!(samples.len(), schema.messages.len());
+
+        let hello = find(&schema, MSG_HELLO);
+        assert_eq!((hello.min_size, hello.max_size), (2, 4));
+
+        let correlated = find(&schema, MSG_CORRELATED);
+        // Type byte and msg_id, around the largest plain frame (a full
+        // CursorBroadcastBatch)
+        assert_eq!(correlated.max_size, 3 + MAX_CURSOR_BROADCAST_BATCH_SIZE);
+        assert_eq!(correlated.since_version, PROTOCOL_VERSION_V5);
+
+        let sequenced = find(&schema, MSG_SEQUENCED);
+        // Type byte and seq, around the largest correlated frame
+        assert_eq!(sequenced.max_size, 5 + correlated.max_size);
+
+        let compact = find(&schema, MSG_CURSOR_UPDATE_COMPACT);
+        assert_eq!(compact.max_size, 8);
+        assert_eq!(compact.since_version, PROTOCOL_VERSION_V8);
+
+        let batch = find(&schema, MSG_CURSOR_BATCH);
+        assert_eq!(
+            (batch.min_size, batch.max_size),
+            (10, MAX_CURSOR_BATCH_SIZE)
+        );
+        assert_eq!(batch.since_version, PROTOCOL_VERSION_V9);
+
+        let error = find(&schema, MSG_ERROR);
+        assert_eq!(error.direction, Direction::ServerToClient);
+        assert_eq!(error.since_version, PROTOCOL_VERSION_V11);
+    }
+}
@@ -3,29 +3,267 @@
 //! Each message type is identified by a single byte that appears as the first byte
 //! of every encoded message. This allows for efficient message routing and parsing.
 
-/// Client → Server: Cursor position update (5 bytes total)
+/// Client → Server: Cursor position update (9 bytes total)
 pub const MSG_CURSOR_UPDATE: u8 = 0x01;
 
-/// Server → Client: Broadcast cursor position to other users (7 bytes total)
+/// Server → Client: Broadcast cursor position to other users (10 bytes total)
 pub const MSG_CURSOR_BROADCAST: u8 = 0x02;
 
-/// Client → Server: Join a board (4-36 bytes total)
+/// Client → Server: Join a board (6 to `MAX_JOIN_SIZE` bytes total)
 pub const MSG_JOIN: u8 = 0x03;
 
-/// Client → Server: Leave a board (3 bytes total)
+/// Client → Server: Leave a board (5 bytes total)
 pub const MSG_LEAVE: u8 = 0x04;
 
-/// Server → Client: Notify that a user joined (7-40 bytes total)
+/// Server → Client: Notify that a user joined (10 to `MAX_USER_JOINED_SIZE` bytes total)
 pub const MSG_USER_JOINED: u8 = 0x05;
 
-/// Server → Client: Notify that a user left (4 bytes total)
+/// Server → Client: Notify that a user left (6 bytes total)
 pub const MSG_USER_LEFT: u8 = 0x06;
 
-/// Server → Client: Update presence count for a board (4 bytes total)
+/// Server → Client: Update presence count for a board (7 bytes total)
 pub const MSG_PRESENCE_UPDATE: u8 = 0x07;
 
 /// Bidirectional: Heartbeat/keepalive (1 byte total)
 pub const MSG_HEARTBEAT: u8 = 0x08;
 
+/// Bidirectional: Protocol version negotiation, optionally with a cursor interval (2 or 4 bytes total)
+pub const MSG_HELLO: u8 = 0x09;
+
+/// Server → Client: Cursor broadcast with a server tick, v2+ only (14 bytes total)
+pub const MSG_CURSOR_BROADCAST_TIMED: u8 = 0x0A;
+
+/// Client → Server: Set the card the user has open (7 bytes total)
+pub const MSG_FOCUS_CARD: u8 = 0x0B;
+
+/// Server → Client: Notify that a user's focused card changed (8 bytes total)
+pub const MSG_FOCUS_BROADCAST: u8 = 0x0C;
+
+/// Server → Client: Sequence-numbered envelope around another frame, v3+ only (5+ bytes total)
+pub const MSG_SEQUENCED: u8 = 0x0D;
+
+/// Server → Client: Text announcement for a board (6-261 bytes total)
+pub const MSG_ANNOUNCEMENT: u8 = 0x0E;
+
+/// Server → Client: Round-trip probe carrying a nonce, v4+ only (5 bytes total)
+pub const MSG_PING: u8 = 0x0F;
+
+/// Client → Server: Echo of a Ping nonce, v4+ only (5 bytes total)
+pub const MSG_PONG: u8 = 0x10;
+
+/// Client → Server: Ask for a board's presence count without joining (5 bytes total)
+pub const MSG_QUERY_PRESENCE: u8 = 0x11;
+
+/// Bidirectional: Client message id around a request or its reply, v5+ only (4+ bytes total)
+pub const MSG_CORRELATED: u8 = 0x12;
+
+/// Client → Server: Cursor position with 12-bit coordinates, v8+ (8 bytes total)
+pub const MSG_CURSOR_UPDATE_COMPACT: u8 = 0x13;
+
+/// Client → Server: Several cursor positions in one frame, v9+ (10 to `MAX_CURSOR_BATCH_SIZE` bytes total)
+pub const MSG_CURSOR_BATCH: u8 = 0x14;
+
+/// Server → Client: Broadcast a batch of cursor positions, v9+ only (11 to `MAX_CURSOR_BROADCAST_BATCH_SIZE` bytes total)
+pub const MSG_CURSOR_BROADCAST_BATCH: u8 = 0x15;
+
+/// Client → Server: Start or stop editing a card (10 bytes total)
+pub const MSG_ACTIVITY_UPDATE: u8 = 0x16;
+
+/// Server → Client: A user started or stopped editing a card (11 bytes total)
+pub const MSG_ACTIVITY_BROADCAST: u8 = 0x17;
+
+/// Client → Server: Claim a card for editing (7 bytes total)
+pub const MSG_CLAIM_CARD: u8 = 0x18;
+
+/// Client → Server: Release a claimed card (7 bytes total)
+pub const MSG_RELEASE_CARD: u8 = 0x19;
+
+/// Server → Client: A user holds the claim on a card (8 bytes total)
+pub const MSG_CARD_CLAIMED: u8 = 0x1A;
+
+/// Server → Client: A user's claim on a card ended (8 bytes total)
+pub const MSG_CARD_RELEASED: u8 = 0x1B;
+
+/// Server → Client: A frame from the client was rejected, v11+ only (2 bytes total)
+pub const MSG_ERROR: u8 = 0x1C;
+
+// Error codes carried by `MSG_ERROR`
+//
+// | Code | Constant                     | Sent when the client's frame...        |
+// |------|------------------------------|----------------------------------------|
+// | 1    | `ERROR_UNKNOWN_MESSAGE_TYPE` | starts with an unassigned type byte     |
+// | 2    | `ERROR_INVALID_LENGTH`       | has the wrong length for its type       |
+// | 3    | `ERROR_USERNAME_TOO_LONG`    | carries a username over the limit       |
+//
+// Codes are never reused; clients should treat unknown codes as a generic error.
+
+/// Error code: the frame's type byte is not a known message type
+pub const ERROR_UNKNOWN_MESSAGE_TYPE: u8 = 1;
+
+/// Error code: the frame is too short or too long for its message type
+pub const ERROR_INVALID_LENGTH: u8 = 2;
+
+/// Error code: the username is longer than `MAX_USERNAME_LENGTH`
+pub const ERROR_USERNAME_TOO_LONG: u8 = 3;
+
+/// Largest coordinate in a compact cursor update (12 bits)
+pub const MAX_COMPACT_COORD: u16 = 0x0FFF;
+
+/// `card_slot` value meaning no card is focused
+pub const NO_CARD_FOCUSED: u16 = 0;
+
+/// Protocol version assumed for clients that never send a Hello
+pub const PROTOCOL_VERSION_V1: u8 = 1;
+
+/// Adds the server tick to cursor broadcasts
+pub const PROTOCOL_VERSION_V2: u8 = 2;
+
+/// Wraps every server frame in a per-connection sequence number
+pub const PROTOCOL_VERSION_V3: u8 = 3;
+
+/// Follows each heartbeat reply with a Ping so the server can measure RTT
+pub const PROTOCOL_VERSION_V4: u8 = 4;
+
+/// Echoes the client's message id on replies to Correlated requests
+pub const PROTOCOL_VERSION_V5: u8 = 5;
+
+/// Lets clients ask in their Hello for their cursor updates to be coalesced
+pub const PROTOCOL_VERSION_V6: u8 = 6;
+
+/// Widens `board_id` from u16 to u32 in every board-scoped frame
+///
+/// The layouts changed without a fallback, so clients built for an earlier
+/// version cannot be served; see `MIN_PROTOCOL_VERSION`.
+#[allow(dead_code)]
+pub const PROTOCOL_VERSION_V7: u8 = 7;
+
+/// Accepts compact cursor updates carrying 12-bit coordinates
+pub const PROTOCOL_VERSION_V8: u8 = 8;
+
+/// Accepts cursor batches and forwards them whole instead of their last point
+pub const PROTOCOL_VERSION_V9: u8 = 9;
+
+/// Widens the `PresenceUpdate` count from u8 to u16
+///
+/// Like v7 this changed a layout without a fallback, so it raised
+/// `MIN_PROTOCOL_VERSION`.
+pub const PROTOCOL_VERSION_V10: u8 = 10;
+
+/// Answers frames that fail to decode with an `Error` naming the problem
+pub const PROTOCOL_VERSION_V11: u8 = 11;
+
+/// Highest protocol version this server speaks
+pub const PROTOCOL_VERSION: u8 = PROTOCOL_VERSION_V11;
+
+/// Lowest version a Hello may request
+///
+/// Clients asking for less were built against the u16 `board_id` layouts or
+/// the u8 presence count; they are closed with a protocol error instead of
+/// having their frames misread.
+pub const MIN_PROTOCOL_VERSION: u8 = PROTOCOL_VERSION_V10;
+
+/// Pick the protocol version to use with a client that requested `requested`
+///
+/// Returns `None` if the client is too old to be served.
+pub fn negotiate_version(requested: u8) -> Option<u8> {
+    (requested >= MIN_PROTOCOL_VERSION).then(|| requested.min(PROTOCOL_VERSION))
+}
+
+/// Message types clients are allowed to send; everything else is server-only
+pub const CLIENT_MESSAGE_TYPES: &[u8] = &[
+    MSG_CURSOR_UPDATE,
+    MSG_JOIN,
+    MSG_LEAVE,
+    MSG_HEARTBEAT,
+    MSG_HELLO,
+    MSG_FOCUS_CARD,
+    MSG_PONG,
+    MSG_QUERY_PRESENCE,
+    MSG_CORRELATED,
+    MSG_CURSOR_UPDATE_COMPACT,
+    MSG_CURSOR_BATCH,
+    MSG_ACTIVITY_UPDATE,
+    MSG_CLAIM_CARD,
+    MSG_RELEASE_CARD,
+];
+
+/// Check whether a message type may be sent by clients
+pub fn is_client_message_type(msg_type: u8) -> bool {
+    CLIENT_MESSAGE_TYPES.contains(&msg_type)
+}
+
+/// Snake-case name of a message type, used as a metrics label
+pub fn message_type_name(msg_type: u8) -> Option<&'static str> {
+    match msg_type {
+        MSG_CURSOR_UPDATE => Some("cursor_update"),
+        MSG_CURSOR_BROADCAST => Some("cursor_broadcast"),
+        MSG_JOIN => Some("join"),
+        MSG_LEAVE => Some("leave"),
+        MSG_USER_JOINED => Some("user_joined"),
+        MSG_USER_LEFT => Some("user_left"),
+        MSG_PRESENCE_UPDATE => Some("presence_update"),
+        MSG_HEARTBEAT => Some("heartbeat"),
+        MSG_HELLO => Some("hello"),
+        MSG_CURSOR_BROADCAST_TIMED => Some("cursor_broadcast_timed"),
+        MSG_FOCUS_CARD => Some("focus_card"),
+        MSG_FOCUS_BROADCAST => Some("focus_broadcast"),
+        MSG_SEQUENCED => Some("sequenced"),
+        MSG_ANNOUNCEMENT => Some("announcement"),
+        MSG_PING => Some("ping"),
+        MSG_PONG => Some("pong"),
+        MSG_QUERY_PRESENCE => Some("query_presence"),
+        MSG_CORRELATED => Some("correlated"),
+        MSG_CURSOR_UPDATE_COMPACT => Some("cursor_update_compact"),
+        MSG_CURSOR_BATCH => Some("cursor_batch"),
+        MSG_CURSOR_BROADCAST_BATCH => Some("cursor_broadcast_batch"),
+        MSG_ACTIVITY_UPDATE => Some("activity_update"),
+        MSG_ACTIVITY_BROADCAST => Some("activity_broadcast"),
+        MSG_CLAIM_CARD => Some("claim_card"),
+        MSG_RELEASE_CARD => Some("release_card"),
+        MSG_CARD_CLAIMED => Some("card_claimed"),
+        MSG_CARD_RELEASED => Some("card_released"),
+        MSG_ERROR => Some("error"),
+        _ => None,
+    }
+}
+
 /// Maximum username length in bytes (UTF-8 encoded)
+///
+/// Enforced when encoding and decoding Join/UserJoined frames and when
+/// sanitizing usernames. The backend advertises the same value through
+/// `GET /api/capabilities`.
+#[cfg(not(test))]
 pub const MAX_USERNAME_LENGTH: usize = 32;
+
+/// Unit tests run with a different limit so anything still assuming 32 fails
+#[cfg(test)]
+pub const MAX_USERNAME_LENGTH: usize = 20;
+
+/// Length of the reconnect token a Join may carry
+pub const RECONNECT_TOKEN_LENGTH: usize = 16;
+
+/// Client-generated token identifying a user across reconnects
+pub type ReconnectToken = [u8; RECONNECT_TOKEN_LENGTH];
+
+/// Largest Join frame: type, board_id, length prefix, username and reconnect token
+pub const MAX_JOIN_SIZE: usize = 6 + MAX_USERNAME_LENGTH + RECONNECT_TOKEN_LENGTH;
+
+/// Largest UserJoined frame: type, board_id, user_id, length prefix, username and color
+pub const MAX_USER_JOINED_SIZE: usize = 10 + MAX_USERNAME_LENGTH;
+
+/// Maximum announcement text length in bytes (UTF-8 encoded)
+pub const MAX_ANNOUNCEMENT_LENGTH: usize = 255;
+
+/// Maximum number of points in a cursor batch
+pub const MAX_CURSOR_BATCH_POINTS: usize = 255;
+
+/// Largest CursorBatch frame: type, board_id, count and 4 bytes per point
+pub const MAX_CURSOR_BATCH_SIZE: usize = 6 + 4 * MAX_CURSOR_BATCH_POINTS;
+
+/// Largest CursorBroadcastBatch frame: a CursorBatch plus the user_id
+pub const MAX_CURSOR_BROADCAST_BATCH_SIZE: usize = 7 + 4 * MAX_CURSOR_BATCH_POINTS;
+
+// Lengths travel as a single-byte prefix
+const _: () = assert!(MAX_USERNAME_LENGTH <= u8::MAX as usize);
+const _: () = assert!(MAX_ANNOUNCEMENT_LENGTH <= u8::MAX as usize);
+const _: () = assert!(MAX_CURSOR_BATCH_POINTS <= u8::MAX as usize);
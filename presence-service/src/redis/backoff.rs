@@ -0,0 +1,215 @@
+//! Reconnection backoff for the Redis subscriber loop.
+//!
+//! Retries back off exponentially with jitter so that many instances losing
+//! Redis at the same moment do not reconnect in lockstep. After repeated
+//! failures a circuit breaker opens and retries slow to a fixed, jittered
+//! interval until a subscription stays up again.
+
+use rand::Rng;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Delay before the first retry
+pub const BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest delay while the circuit breaker is closed
+pub const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Consecutive failures before the circuit breaker opens
+const BREAKER_THRESHOLD: u32 = 8;
+
+/// Delay between retries while the circuit breaker is open
+const BREAKER_DELAY: Duration = Duration::from_secs(60);
+
+/// How long a subscription must stay up before failures are forgotten
+pub const STABLE_SUBSCRIPTION: Duration = Duration::from_secs(30);
+
+/// State of the reconnect circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Retrying with exponential backoff
+    Closed,
+    /// Too many consecutive failures; retrying slowly
+    Open,
+}
+
+/// Jittered exponential backoff with a circuit breaker
+#[derive(Debug)]
+pub struct ReconnectBackoff {
+    base: Duration,
+    max: Duration,
+    breaker_threshold: u32,
+    breaker_delay: Duration,
+    consecutive_failures: u32,
+    state: BreakerState,
+}
+
+impl ReconnectBackoff {
+    /// Create a backoff with the default delays
+    pub fn new() -> Self {
+        Self {
+            base: BASE_DELAY,
+            max: MAX_DELAY,
+            breaker_threshold: BREAKER_THRESHOLD,
+            breaker_delay: BREAKER_DELAY,
+            consecutive_failures: 0,
+            state: BreakerState::Closed,
+        }
+    }
+
+    /// Start retries at `base` and grow them up to `max` (`max` is raised to `base` if smaller)
+    ///
+    /// The circuit breaker keeps its own, longer interval.
+    pub fn with_delays(mut self, base: Duration, max: Duration) -> Self {
+        self.base = base;
+        self.max = max.max(base);
+        self
+    }
+
+    /// Record a failed attempt and return how long to wait before retrying
+    ///
+    /// The delay is drawn from the upper half of the current backoff window
+    /// ("equal jitter"), so it keeps growing while still spreading instances
+    /// apart.
+    pub fn on_failure(&mut self, rng: &mut impl Rng) -> Duration {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+
+        if self.state == BreakerState::Closed && self.consecutive_failures >= self.breaker_threshold
+        {
+            self.state = BreakerState::Open;
+            warn!(
+                "Redis reconnect circuit breaker opened after {} consecutive failures, retrying every ~{:?}",
+                self.consecutive_failures, self.breaker_delay
+            );
+        }
+
+        let window = match self.state {
+            BreakerState::Open => self.breaker_delay,
+            BreakerState::Closed => {
+                let exponent = (self.consecutive_failures - 1).min(31);
+                self.base.saturating_mul(1 << exponent).min(self.max)
+            }
+        };
+
+        let half = window / 2;
+        half + rng.gen_range(Duration::ZERO..=window - half)
+    }
+
+    /// Record a subscription that stayed up, closing the circuit breaker
+    pub fn on_success(&mut self) {
+        if self.state == BreakerState::Open {
+            info!(
+                "Redis reconnect circuit breaker closed after {} consecutive failures",
+                self.consecutive_failures
+            );
+        }
+        self.consecutive_failures = 0;
+        self.state = BreakerState::Closed;
+    }
+
+    /// Current circuit breaker state
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    /// Failures since the last stable subscription
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_backoff_grows_with_jitter() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut backoff = ReconnectBackoff::new();
+
+        let mut delays = Vec::new();
+        for attempt in 0..BREAKER_THRESHOLD - 1 {
+            let window = BASE_DELAY.saturating_mul(1 << attempt).min(MAX_DELAY);
+            let delay = backoff.on_failure(&mut rng);
+
+            assert!(delay >= window / 2 && delay <= window, "{:?}", delay);
+            delays.push(delay);
+        }
+
+        // Each window starts where the previous one ended, so delays never shrink
+        assert!(delays.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(delays.last().unwrap() > &(delays[0] * 8));
+        assert_eq!(backoff.state(), BreakerState::Closed);
+
+        // Two backoffs fed the same failures pick different delays
+        let mut other = ReconnectBackoff::new();
+        let mut other_rng = StdRng::seed_from_u64(8);
+        let other_delays: Vec<Duration> = (0..BREAKER_THRESHOLD - 1)
+            .map(|_| other.on_failure(&mut other_rng))
+            .collect();
+        assert_ne!(delays, other_delays);
+    }
+
+    #[test]
+    fn test_configured_delays_bound_backoff() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let base = Duration::from_millis(500);
+        let max = Duration::from_secs(4);
+        let mut backoff = ReconnectBackoff::new().with_delays(base, max);
+
+        // Jitter can pick a shorter delay than the one before once the window
+        // stops growing, so only the windows themselves are monotonic
+        let windows: Vec<Duration> = (0..BREAKER_THRESHOLD - 1)
+            .map(|attempt| base.saturating_mul(1 << attempt).min(max))
+            .collect();
+        for window in &windows {
+            let delay = backoff.on_failure(&mut rng);
+            assert!(delay >= *window / 2 && delay <= *window, "{:?}", delay);
+        }
+        assert_eq!(windows[0], base);
+        assert!(windows.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_eq!(*windows.last().unwrap(), max);
+
+        // A maximum below the base is raised to it
+        let mut flat = ReconnectBackoff::new().with_delays(base, Duration::from_millis(1));
+        for _ in 0..BREAKER_THRESHOLD - 1 {
+            let delay = flat.on_failure(&mut rng);
+            assert!(delay >= base / 2 && delay <= base);
+        }
+    }
+
+    #[test]
+    fn test_breaker_opens_and_closes() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut backoff = ReconnectBackoff::new();
+
+        for _ in 0..BREAKER_THRESHOLD - 1 {
+            backoff.on_failure(&mut rng);
+        }
+        assert_eq!(backoff.state(), BreakerState::Closed);
+
+        let delay = backoff.on_failure(&mut rng);
+        assert_eq!(backoff.state(), BreakerState::Open);
+        assert!(delay >= BREAKER_DELAY / 2 && delay <= BREAKER_DELAY);
+
+        // Stays open, at the slow interval, until a subscription holds
+        for _ in 0..20 {
+            let delay = backoff.on_failure(&mut rng);
+            assert!(delay >= BREAKER_DELAY / 2 && delay <= BREAKER_DELAY);
+        }
+        assert_eq!(backoff.state(), BreakerState::Open);
+
+        backoff.on_success();
+        assert_eq!(backoff.state(), BreakerState::Closed);
+        assert_eq!(backoff.consecutive_failures(), 0);
+        assert!(backoff.on_failure(&mut rng) <= BASE_DELAY);
+    }
+}
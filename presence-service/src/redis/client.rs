@@ -3,7 +3,7 @@
 use redis::aio::ConnectionManager;
 use redis::Client;
 use thiserror::Error;
-use tracing::{error, info};
+use tracing::info;
 
 /// Errors that can occur during Redis operations
 #[derive(Debug, Error)]
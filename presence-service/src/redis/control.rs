@@ -0,0 +1,75 @@
+//! Board state changes published by the backend.
+//!
+//! The backend publishes a small JSON document on the control channel when a
+//! board is deleted or locked, e.g. `{"board_id": 1234, "event": "deleted"}`.
+//! Unlike presence traffic these payloads are not wrapped in a `RedisMessage`,
+//! so publishers do not need to speak the binary protocol. `board_id` is the
+//! board's stored presence ID, the same one clients use when joining.
+
+use serde::{Deserialize, Serialize};
+
+/// Channel the backend publishes board state changes on
+pub const CONTROL_CHANNEL: &str = "presence:control";
+
+/// What happened to the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardStateChange {
+    /// The board no longer exists
+    Deleted,
+    /// The board was locked and no longer accepts edits
+    Locked,
+}
+
+impl BoardStateChange {
+    /// Text sent to the room's members before they are removed
+    pub fn announcement(self) -> &'static str {
+        match self {
+            BoardStateChange::Deleted => "This board has been deleted.",
+            BoardStateChange::Locked => "This board has been locked by its owner.",
+        }
+    }
+}
+
+/// A board state change received on the control channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlMessage {
+    pub board_id: u32,
+    pub event: BoardStateChange,
+}
+
+impl ControlMessage {
+    /// Encode the message as JSON
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("control messages always serialize")
+    }
+
+    /// Decode a control channel payload
+    pub fn decode(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_message_wire_format() {
+        let message = ControlMessage::decode(br#"{"board_id": 1234, "event": "deleted"}"#).unwrap();
+        assert_eq!(
+            message,
+            ControlMessage {
+                board_id: 1234,
+                event: BoardStateChange::Deleted,
+            }
+        );
+        assert_eq!(ControlMessage::decode(&message.encode()).unwrap(), message);
+
+        let locked = ControlMessage::decode(br#"{"board_id": 7, "event": "locked"}"#).unwrap();
+        assert_eq!(locked.event, BoardStateChange::Locked);
+
+        assert!(ControlMessage::decode(br#"{"board_id": 7, "event": "renamed"}"#).is_err());
+        assert!(ControlMessage::decode(br#"{"board_id": 4294967296, "event": "locked"}"#).is_err());
+    }
+}
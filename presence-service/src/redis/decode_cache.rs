@@ -0,0 +1,212 @@
+//! Small cache of decoded Redis payloads.
+//!
+//! Busy boards publish the same few frames over and over (presence counts,
+//! idle cursors), and every instance decodes each of them. The subscriber loop
+//! keeps a short-lived, per-channel LRU of recently decoded payloads so an
+//! identical payload arriving again within the window is not decoded twice.
+
+use crate::protocol::messages::{BinaryMessage, ProtocolError};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Decoded payloads remembered per channel
+const CAPACITY_PER_CHANNEL: usize = 16;
+
+/// How long a decoded payload stays reusable
+const ENTRY_TTL: Duration = Duration::from_secs(1);
+
+/// Channels tracked before stale ones are dropped
+const MAX_CHANNELS: usize = 1024;
+
+/// Hit and miss counters, shared with the admin metrics endpoint
+#[derive(Debug, Default)]
+pub struct DecodeCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Point-in-time copy of the decode cache counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DecodeCacheSnapshot {
+    /// Payloads served from the cache (decode calls saved)
+    pub hits: u64,
+    /// Payloads that had to be decoded
+    pub misses: u64,
+}
+
+impl DecodeCacheStats {
+    /// Read the current counters
+    pub fn snapshot(&self) -> DecodeCacheSnapshot {
+        DecodeCacheSnapshot {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A decoded payload
+#[derive(Debug)]
+struct Entry {
+    hash: u64,
+    payload: Vec<u8>,
+    message: BinaryMessage,
+    decoded_at: Instant,
+}
+
+/// Per-channel LRU of decoded payloads
+///
+/// Owned by the Redis subscriber loop, so it needs no locking.
+#[derive(Debug)]
+pub struct DecodeCache {
+    channels: HashMap<String, VecDeque<Entry>>,
+    stats: Arc<DecodeCacheStats>,
+}
+
+impl DecodeCache {
+    /// Create an empty cache that reports into `stats`
+    pub fn new(stats: Arc<DecodeCacheStats>) -> Self {
+        Self {
+            channels: HashMap::new(),
+            stats,
+        }
+    }
+
+    /// Decode a payload received on `channel`, reusing a recent result if possible
+    pub fn decode(
+        &mut self,
+        channel: &str,
+        payload: &[u8],
+    ) -> Result<BinaryMessage, ProtocolError> {
+        self.decode_at(channel, payload, Instant::now())
+    }
+
+    fn decode_at(
+        &mut self,
+        channel: &str,
+        payload: &[u8],
+        now: Instant,
+    ) -> Result<BinaryMessage, ProtocolError> {
+        let hash = payload_hash(payload);
+
+        if let Some(entries) = self.channels.get_mut(channel) {
+            entries.retain(|entry| now.duration_since(entry.decoded_at) < ENTRY_TTL);
+
+            let position = entries
+                .iter()
+                .position(|entry| entry.hash == hash && entry.payload == payload);
+            if let Some(entry) = position.and_then(|position| entries.remove(position)) {
+                let message = entry.message.clone();
+                entries.push_front(entry);
+                self.stats.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(message);
+            }
+        }
+
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        let message = BinaryMessage::decode(payload)?;
+
+        if !self.channels.contains_key(channel) && self.channels.len() >= MAX_CHANNELS {
+            self.channels.retain(|_, entries| {
+                entries
+                    .front()
+                    .is_some_and(|entry| now.duration_since(entry.decoded_at) < ENTRY_TTL)
+            });
+        }
+
+        let entries = self.channels.entry(channel.to_string()).or_default();
+        entries.push_front(Entry {
+            hash,
+            payload: payload.to_vec(),
+            message: message.clone(),
+            decoded_at: now,
+        });
+        entries.truncate(CAPACITY_PER_CHANNEL);
+
+        Ok(message)
+    }
+}
+
+fn payload_hash(payload: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    payload.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presence(count: u16) -> Vec<u8> {
+        BinaryMessage::PresenceUpdate { board_id: 3, count }.encode()
+    }
+
+    #[test]
+    fn test_repeated_payloads_hit_cache() {
+        let stats = Arc::new(DecodeCacheStats::default());
+        let mut cache = DecodeCache::new(Arc::clone(&stats));
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            let message = cache.decode_at("board:3", &presence(2), now).unwrap();
+            assert_eq!(
+                message,
+                BinaryMessage::PresenceUpdate {
+                    board_id: 3,
+                    count: 2
+                }
+            );
+        }
+        assert_eq!(stats.snapshot(), DecodeCacheSnapshot { hits: 4, misses: 1 });
+
+        // A different payload, or the same one on another channel, is decoded
+        cache.decode_at("board:3", &presence(3), now).unwrap();
+        cache.decode_at("board:4", &presence(2), now).unwrap();
+        assert_eq!(stats.snapshot(), DecodeCacheSnapshot { hits: 4, misses: 3 });
+    }
+
+    #[test]
+    fn test_entries_expire_and_evict() {
+        let stats = Arc::new(DecodeCacheStats::default());
+        let mut cache = DecodeCache::new(Arc::clone(&stats));
+        let now = Instant::now();
+
+        cache.decode_at("board:3", &presence(0), now).unwrap();
+        cache
+            .decode_at("board:3", &presence(0), now + ENTRY_TTL)
+            .unwrap();
+        assert_eq!(stats.snapshot().hits, 0);
+
+        // Filling the channel pushes out the least recently used payload
+        for count in 1..=CAPACITY_PER_CHANNEL as u16 {
+            cache
+                .decode_at("board:3", &presence(count), now + ENTRY_TTL)
+                .unwrap();
+        }
+        cache
+            .decode_at("board:3", &presence(0), now + ENTRY_TTL)
+            .unwrap();
+        cache
+            .decode_at(
+                "board:3",
+                &presence(CAPACITY_PER_CHANNEL as u16),
+                now + ENTRY_TTL,
+            )
+            .unwrap();
+        assert_eq!(stats.snapshot().hits, 1);
+    }
+
+    #[test]
+    fn test_decode_errors_not_cached() {
+        let stats = Arc::new(DecodeCacheStats::default());
+        let mut cache = DecodeCache::new(Arc::clone(&stats));
+
+        assert!(cache.decode("board:3", &[0xFF]).is_err());
+        assert!(cache.decode("board:3", &[0xFF]).is_err());
+        assert_eq!(stats.snapshot(), DecodeCacheSnapshot { hits: 0, misses: 2 });
+    }
+}
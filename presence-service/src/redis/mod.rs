@@ -1,3 +1,6 @@
+pub mod backoff;
 pub mod client;
+pub mod control;
+pub mod decode_cache;
 pub mod pubsub;
 pub mod store;
@@ -2,9 +2,10 @@
 
 use crate::protocol::messages::BinaryMessage;
 use crate::redis::client::{RedisClient, RedisError};
+use crate::redis::control::CONTROL_CHANNEL;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tracing::{debug, error, info, warn};
 
 /// Wrapper for Redis messages with instance ID to prevent echo
@@ -58,10 +59,21 @@ impl RedisMessage {
 /// Stream of incoming Redis pub/sub messages
 pub type PubSubStream = mpsc::UnboundedReceiver<(String, RedisMessage)>;
 
+/// Stream of incoming Redis pub/sub payloads, not yet decoded
+pub type RawPubSubStream = mpsc::UnboundedReceiver<(String, Vec<u8>)>;
+
+/// Where published messages go and subscriptions read from
+#[derive(Clone)]
+enum Transport {
+    Redis(RedisClient),
+    /// Channels shared only by clones of one `RedisPubSub`, for running without Redis
+    InMemory(broadcast::Sender<(String, Vec<u8>)>),
+}
+
 /// Redis pub/sub manager for broadcasting presence updates
 #[derive(Clone)]
 pub struct RedisPubSub {
-    client: RedisClient,
+    transport: Transport,
 }
 
 impl RedisPubSub {
@@ -76,7 +88,21 @@ impl RedisPubSub {
     /// A `Result` containing the `RedisPubSub` instance or a `RedisError`
     pub async fn new(client: RedisClient) -> Result<Self, RedisError> {
         info!("Initializing Redis pub/sub");
-        Ok(Self { client })
+        Ok(Self {
+            transport: Transport::Redis(client),
+        })
+    }
+
+    /// Create a pub/sub manager that delivers messages within the process
+    ///
+    /// Clones publish to and subscribe from the same channels, so managers
+    /// built from clones see each other's messages as if they were separate
+    /// instances sharing a Redis.
+    pub fn in_memory() -> Self {
+        let (tx, _) = broadcast::channel(1024);
+        Self {
+            transport: Transport::InMemory(tx),
+        }
     }
 
     /// Publish a message to a Redis channel
@@ -92,7 +118,15 @@ impl RedisPubSub {
     pub async fn publish(&self, channel: &str, message: &[u8]) -> Result<(), RedisError> {
         use redis::AsyncCommands;
 
-        let mut conn = self.client.get_connection().await?;
+        let client = match &self.transport {
+            Transport::Redis(client) => client,
+            Transport::InMemory(tx) => {
+                // Like Redis, a message nobody is subscribed to is dropped
+                let _ = tx.send((channel.to_string(), message.to_vec()));
+                return Ok(());
+            }
+        };
+        let mut conn = client.get_connection().await?;
 
         conn.publish::<_, _, ()>(channel, message)
             .await
@@ -115,10 +149,59 @@ impl RedisPubSub {
     ///
     /// A `Result` containing a stream of (channel, message) tuples or a `RedisError`
     pub async fn subscribe(&self, channels: Vec<String>) -> Result<PubSubStream, RedisError> {
+        let mut raw = self.subscribe_raw(channels).await?;
+
+        // Create a channel for streaming messages to the caller
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        // Spawn a task to decode incoming payloads
+        tokio::spawn(async move {
+            while let Some((channel, payload)) = raw.recv().await {
+                // Decode Redis message
+                let redis_msg = match RedisMessage::decode(&payload) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        error!("Failed to decode Redis message: {}", e);
+                        continue;
+                    }
+                };
+
+                // Send to channel
+                if let Err(e) = tx.send((channel.clone(), redis_msg)) {
+                    error!("Failed to send message to channel {}: {}", channel, e);
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// Subscribe to Redis channels and return a stream of undecoded payloads
+    ///
+    /// Used for channels whose payloads are not `RedisMessage`s, such as the
+    /// control channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channels` - List of channel names to subscribe to
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a stream of (channel, payload) tuples or a `RedisError`
+    pub async fn subscribe_raw(
+        &self,
+        channels: Vec<String>,
+    ) -> Result<RawPubSubStream, RedisError> {
         info!("Subscribing to channels: {:?}", channels);
 
+        let client = match &self.transport {
+            Transport::Redis(client) => client,
+            Transport::InMemory(tx) => return Ok(subscribe_in_memory(tx, channels)),
+        };
+
         // Get a dedicated connection for pub/sub
-        let mut pubsub = self.client.client().get_async_pubsub().await?;
+        let mut pubsub = client.client().get_async_pubsub().await?;
 
         // Subscribe to all channels
         for channel in &channels {
@@ -144,17 +227,8 @@ impl RedisPubSub {
                     }
                 };
 
-                // Decode Redis message
-                let redis_msg = match RedisMessage::decode(&payload) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        error!("Failed to decode Redis message: {}", e);
-                        continue;
-                    }
-                };
-
                 // Send to channel
-                if let Err(e) = tx.send((channel.clone(), redis_msg)) {
+                if let Err(e) = tx.send((channel.clone(), payload)) {
                     error!("Failed to send message to channel {}: {}", channel, e);
                     break;
                 }
@@ -167,7 +241,7 @@ impl RedisPubSub {
     }
 
     /// Get the channel name for a specific board
-    pub fn board_channel(board_id: u16) -> String {
+    pub fn board_channel(board_id: u32) -> String {
         format!("presence:board:{}", board_id)
     }
 
@@ -175,6 +249,39 @@ impl RedisPubSub {
     pub fn global_channel() -> String {
         "presence:global".to_string()
     }
+
+    /// Get the channel the backend publishes board state changes on
+    pub fn control_channel() -> String {
+        CONTROL_CHANNEL.to_string()
+    }
+}
+
+/// Forward the in-memory messages published on `channels` to a new stream
+fn subscribe_in_memory(
+    tx: &broadcast::Sender<(String, Vec<u8>)>,
+    channels: Vec<String>,
+) -> RawPubSubStream {
+    let mut published = tx.subscribe();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            match published.recv().await {
+                Ok((channel, payload)) if channels.contains(&channel) => {
+                    if tx.send((channel, payload)).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("In-memory subscriber skipped {} messages", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    rx
 }
 
 #[cfg(test)]
@@ -206,6 +313,31 @@ mod tests {
     fn test_channel_names() {
         assert_eq!(RedisPubSub::board_channel(123), "presence:board:123");
         assert_eq!(RedisPubSub::global_channel(), "presence:global");
+        assert_eq!(RedisPubSub::control_channel(), "presence:control");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_publish_subscribe() {
+        let pubsub = RedisPubSub::in_memory();
+        let mut stream = pubsub
+            .clone()
+            .subscribe_raw(vec![RedisPubSub::board_channel(1)])
+            .await
+            .unwrap();
+
+        pubsub
+            .publish(&RedisPubSub::board_channel(2), b"other board")
+            .await
+            .unwrap();
+        pubsub
+            .publish(&RedisPubSub::board_channel(1), b"hello")
+            .await
+            .unwrap();
+
+        let (channel, payload) = stream.recv().await.unwrap();
+        assert_eq!(channel, "presence:board:1");
+        assert_eq!(payload, b"hello");
+        assert!(stream.try_recv().is_err());
     }
 
     #[tokio::test]
@@ -1 +1,124 @@
-// TODO: Implement Redis store
+//! Presence statistics kept in Redis for other services to read.
+//!
+//! Each day's peaks live in a sorted set keyed by the day, counted in days
+//! since the Unix epoch (UTC), e.g. `presence:peak:20376`. Members are board
+//! IDs (the same 32-bit board hash clients join with) and scores the most
+//! users seen at once on that board during the day. Instances only ever raise
+//! a score, so with several instances a board's peak is the highest any one
+//! instance saw.
+
+use crate::redis::client::{RedisClient, RedisError};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prefix of the per-day peak presence keys
+pub const PEAK_KEY_PREFIX: &str = "presence:peak:";
+
+/// How long a day's peaks are kept after they were last written
+pub const PEAK_RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Days since the Unix epoch at `now`
+pub fn epoch_day(now: SystemTime) -> u64 {
+    now.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+/// Key holding the peaks of `day`
+pub fn peak_key(day: u64) -> String {
+    format!("{}{}", PEAK_KEY_PREFIX, day)
+}
+
+/// Writes per-board peak presence to Redis
+#[derive(Clone)]
+pub struct PeakStore {
+    client: RedisClient,
+}
+
+impl PeakStore {
+    /// Create a store writing through `client`
+    pub fn new(client: RedisClient) -> Self {
+        Self { client }
+    }
+
+    /// Raise the stored peaks of `day` to `peaks` where they are higher
+    ///
+    /// # Arguments
+    ///
+    /// * `day` - Day the peaks were seen, from `epoch_day`
+    /// * `peaks` - Most users seen at once, by board ID
+    pub async fn record_peaks(
+        &self,
+        day: u64,
+        peaks: &HashMap<u32, usize>,
+    ) -> Result<(), RedisError> {
+        if peaks.is_empty() {
+            return Ok(());
+        }
+
+        let key = peak_key(day);
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (board_id, peak) in peaks {
+            // GT only ever raises a board's score, and still adds new boards
+            pipe.cmd("ZADD")
+                .arg(&key)
+                .arg("GT")
+                .arg(*peak)
+                .arg(*board_id)
+                .ignore();
+        }
+        pipe.cmd("EXPIRE")
+            .arg(&key)
+            .arg(PEAK_RETENTION.as_secs())
+            .ignore();
+
+        let mut conn = self.client.get_connection().await?;
+        pipe.query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_key_per_utc_day() {
+        let day = epoch_day(UNIX_EPOCH + Duration::from_secs(20_376 * SECONDS_PER_DAY + 5));
+        assert_eq!(day, 20_376);
+        assert_eq!(peak_key(day), "presence:peak:20376");
+
+        let last_second = UNIX_EPOCH + Duration::from_secs(20_377 * SECONDS_PER_DAY - 1);
+        assert_eq!(epoch_day(last_second), 20_376);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires running Redis instance
+    async fn test_record_peaks_only_raises() {
+        use redis::AsyncCommands;
+
+        let client = RedisClient::new("redis://localhost:6379").await.unwrap();
+        let store = PeakStore::new(client.clone());
+        let day = u64::MAX;
+
+        store
+            .record_peaks(day, &HashMap::from([(1, 5)]))
+            .await
+            .unwrap();
+        store
+            .record_peaks(day, &HashMap::from([(1, 2), (2, 3)]))
+            .await
+            .unwrap();
+
+        let mut conn = client.get_connection().await.unwrap();
+        let peak: Option<u32> = conn.zscore(peak_key(day), 1).await.unwrap();
+        assert_eq!(peak, Some(5));
+        let peak: Option<u32> = conn.zscore(peak_key(day), 2).await.unwrap();
+        assert_eq!(peak, Some(3));
+
+        let _: () = conn.del(peak_key(day)).await.unwrap();
+    }
+}
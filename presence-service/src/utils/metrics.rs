@@ -1 +1,372 @@
-// TODO: Implement metrics
+//! Traffic metrics for the presence service.
+//!
+//! Counts messages and bytes per message type in each direction, plus a
+//! payload-size histogram, so the binary protocol's wire savings can be
+//! checked against real traffic. Broadcasts are counted separately, once per
+//! message, with a histogram of how many clients each reached.
+
+use crate::protocol::{message_type_name, BinaryMessage};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Upper bounds (inclusive, in bytes) of the payload-size histogram buckets
+///
+/// Chosen to line up with the fixed message sizes of the protocol, with a
+/// few wider buckets for cursor batches; larger payloads fall into a final
+/// overflow bucket.
+pub const PAYLOAD_SIZE_BUCKETS: &[usize] = &[1, 2, 5, 6, 8, 9, 10, 14, 16, 32, 64, 256, 1024];
+
+/// Upper bounds (inclusive, in recipients) of the broadcast fan-out histogram buckets
+///
+/// A room holds at most 255 local users, so the last bucket catches every
+/// broadcast.
+pub const FANOUT_BUCKETS: &[usize] = &[1, 2, 5, 10, 25, 50, 100, 255];
+
+/// Message and byte counters for one message type
+#[derive(Debug, Default)]
+struct TypeCounters {
+    messages: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// Counters for one traffic direction
+#[derive(Debug)]
+struct DirectionMetrics {
+    by_type: [TypeCounters; 256],
+    /// One slot per bucket in `PAYLOAD_SIZE_BUCKETS`, plus overflow
+    size_buckets: Vec<AtomicU64>,
+}
+
+impl DirectionMetrics {
+    fn new() -> Self {
+        Self {
+            by_type: std::array::from_fn(|_| TypeCounters::default()),
+            size_buckets: (0..=PAYLOAD_SIZE_BUCKETS.len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+        }
+    }
+
+    fn record(&self, frame: &[u8]) {
+        let Some(&msg_type) = frame.first() else {
+            return;
+        };
+
+        let counters = &self.by_type[msg_type as usize];
+        counters.messages.fetch_add(1, Ordering::Relaxed);
+        counters
+            .bytes
+            .fetch_add(frame.len() as u64, Ordering::Relaxed);
+
+        let bucket = PAYLOAD_SIZE_BUCKETS
+            .iter()
+            .position(|&bound| frame.len() <= bound)
+            .unwrap_or(PAYLOAD_SIZE_BUCKETS.len());
+        self.size_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> DirectionSnapshot {
+        let mut snapshot = DirectionSnapshot::default();
+
+        for (msg_type, counters) in self.by_type.iter().enumerate() {
+            let messages = counters.messages.load(Ordering::Relaxed);
+            if messages == 0 {
+                continue;
+            }
+            let bytes = counters.bytes.load(Ordering::Relaxed);
+
+            snapshot.total_messages += messages;
+            snapshot.total_bytes += bytes;
+
+            let name = message_type_name(msg_type as u8).unwrap_or("unknown");
+            let entry = snapshot.by_type.entry(name.to_string()).or_default();
+            entry.messages += messages;
+            entry.bytes += bytes;
+        }
+
+        snapshot.size_histogram = self
+            .size_buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| SizeBucket {
+                le: PAYLOAD_SIZE_BUCKETS
+                    .get(i)
+                    .map(|bound| bound.to_string())
+                    .unwrap_or_else(|| "+Inf".to_string()),
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect();
+
+        snapshot
+    }
+}
+
+/// Message and byte totals for one message type
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TypeSnapshot {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+/// Number of payloads whose size is at most `le` bytes (and above the previous bucket)
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SizeBucket {
+    pub le: String,
+    pub count: u64,
+}
+
+/// Point-in-time view of one traffic direction
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DirectionSnapshot {
+    pub total_messages: u64,
+    pub total_bytes: u64,
+    pub by_type: BTreeMap<String, TypeSnapshot>,
+    pub size_histogram: Vec<SizeBucket>,
+}
+
+/// Point-in-time view of all traffic metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct TrafficSnapshot {
+    pub sent: DirectionSnapshot,
+    pub received: DirectionSnapshot,
+}
+
+/// Traffic counters shared by all connections
+#[derive(Debug)]
+pub struct TrafficMetrics {
+    sent: DirectionMetrics,
+    received: DirectionMetrics,
+}
+
+impl TrafficMetrics {
+    pub fn new() -> Self {
+        Self {
+            sent: DirectionMetrics::new(),
+            received: DirectionMetrics::new(),
+        }
+    }
+
+    /// Record an encoded frame sent to a client
+    pub fn record_sent(&self, frame: &[u8]) {
+        self.sent.record(frame);
+    }
+
+    /// Record a binary frame received from a client
+    pub fn record_received(&self, frame: &[u8]) {
+        self.received.record(frame);
+    }
+
+    /// Take a snapshot of all counters
+    pub fn snapshot(&self) -> TrafficSnapshot {
+        TrafficSnapshot {
+            sent: self.sent.snapshot(),
+            received: self.received.snapshot(),
+        }
+    }
+}
+
+impl Default for TrafficMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Point-in-time view of the broadcast counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BroadcastSnapshot {
+    /// Messages fanned out to local clients, once per message
+    pub total_messages: u64,
+    /// Broadcasts per message type
+    pub by_type: BTreeMap<String, u64>,
+    /// Broadcasts by number of clients reached; `count` is per bucket, not cumulative
+    pub fanout_histogram: Vec<SizeBucket>,
+    /// Clients reached by all broadcasts together
+    pub fanout_sum: u64,
+}
+
+/// Broadcast counters shared by all rooms
+#[derive(Debug)]
+pub struct BroadcastMetrics {
+    by_type: [AtomicU64; 256],
+    /// One slot per bucket in `FANOUT_BUCKETS`, plus overflow
+    fanout_buckets: Vec<AtomicU64>,
+    fanout_sum: AtomicU64,
+}
+
+impl BroadcastMetrics {
+    pub fn new() -> Self {
+        Self {
+            by_type: std::array::from_fn(|_| AtomicU64::new(0)),
+            fanout_buckets: (0..=FANOUT_BUCKETS.len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            fanout_sum: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a message broadcast to `fanout` local clients
+    pub fn record(&self, message: &BinaryMessage, fanout: usize) {
+        self.by_type[message.message_type() as usize].fetch_add(1, Ordering::Relaxed);
+
+        let bucket = FANOUT_BUCKETS
+            .iter()
+            .position(|&bound| fanout <= bound)
+            .unwrap_or(FANOUT_BUCKETS.len());
+        self.fanout_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.fanout_sum.fetch_add(fanout as u64, Ordering::Relaxed);
+    }
+
+    /// Take a snapshot of all counters
+    pub fn snapshot(&self) -> BroadcastSnapshot {
+        let mut snapshot = BroadcastSnapshot::default();
+
+        for (msg_type, count) in self.by_type.iter().enumerate() {
+            let count = count.load(Ordering::Relaxed);
+            if count == 0 {
+                continue;
+            }
+            snapshot.total_messages += count;
+            let name = message_type_name(msg_type as u8).unwrap_or("unknown");
+            *snapshot.by_type.entry(name.to_string()).or_default() += count;
+        }
+
+        snapshot.fanout_histogram = self
+            .fanout_buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| SizeBucket {
+                le: FANOUT_BUCKETS
+                    .get(i)
+                    .map(|bound| bound.to_string())
+                    .unwrap_or_else(|| "+Inf".to_string()),
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect();
+        snapshot.fanout_sum = self.fanout_sum.load(Ordering::Relaxed);
+
+        snapshot
+    }
+}
+
+impl Default for BroadcastMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::BinaryMessage;
+
+    fn bucket_count(snapshot: &DirectionSnapshot, le: &str) -> u64 {
+        snapshot
+            .size_histogram
+            .iter()
+            .find(|bucket| bucket.le == le)
+            .map(|bucket| bucket.count)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cursor_update_recorded() {
+        let metrics = TrafficMetrics::new();
+        let frame = BinaryMessage::CursorUpdate {
+            board_id: 1,
+            x: 10,
+            y: 20,
+        }
+        .encode();
+
+        metrics.record_received(&frame);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.received.by_type["cursor_update"],
+            TypeSnapshot {
+                messages: 1,
+                bytes: 9
+            }
+        );
+        assert_eq!(snapshot.received.total_bytes, 9);
+        assert_eq!(bucket_count(&snapshot.received, "9"), 1);
+        assert_eq!(bucket_count(&snapshot.received, "10"), 0);
+
+        // Sent counters are tracked separately
+        assert_eq!(snapshot.sent.total_messages, 0);
+    }
+
+    #[test]
+    fn test_totals_across_types() {
+        let metrics = TrafficMetrics::new();
+
+        metrics.record_sent(&BinaryMessage::Heartbeat.encode());
+        metrics.record_sent(&BinaryMessage::Heartbeat.encode());
+        metrics.record_sent(
+            &BinaryMessage::UserLeft {
+                board_id: 1,
+                user_id: 2,
+            }
+            .encode(),
+        );
+
+        let sent = metrics.snapshot().sent;
+        assert_eq!(sent.total_messages, 3);
+        assert_eq!(sent.total_bytes, 8);
+        assert_eq!(sent.by_type["heartbeat"].messages, 2);
+        assert_eq!(bucket_count(&sent, "1"), 2);
+        assert_eq!(bucket_count(&sent, "6"), 1);
+    }
+
+    #[test]
+    fn test_unknown_and_oversized_frames() {
+        let metrics = TrafficMetrics::new();
+
+        // Cursor batches fill the 256 and 1024 buckets, so only frames past
+        // the largest batch overflow
+        metrics.record_received(&[0xFF; 100]);
+        metrics.record_received(&[0xFF; 2000]);
+        metrics.record_received(&[]);
+
+        let received = metrics.snapshot().received;
+        assert_eq!(received.total_messages, 2);
+        assert_eq!(received.by_type["unknown"].bytes, 2100);
+        assert_eq!(bucket_count(&received, "256"), 1);
+        assert_eq!(bucket_count(&received, "+Inf"), 1);
+    }
+
+    #[test]
+    fn test_broadcasts_by_type_and_fanout() {
+        let metrics = BroadcastMetrics::new();
+        let cursor = BinaryMessage::CursorBroadcast {
+            board_id: 1,
+            user_id: 2,
+            x: 3,
+            y: 4,
+        };
+
+        metrics.record(&cursor, 1);
+        metrics.record(&cursor, 30);
+        metrics.record(&BinaryMessage::Heartbeat, 255);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.total_messages, 3);
+        assert_eq!(snapshot.by_type["cursor_broadcast"], 2);
+        assert_eq!(snapshot.by_type["heartbeat"], 1);
+        assert_eq!(snapshot.fanout_sum, 286);
+
+        let bucket = |le: &str| {
+            snapshot
+                .fanout_histogram
+                .iter()
+                .find(|bucket| bucket.le == le)
+                .map(|bucket| bucket.count)
+                .unwrap()
+        };
+        assert_eq!(bucket("1"), 1);
+        assert_eq!(bucket("50"), 1);
+        assert_eq!(bucket("255"), 1);
+        assert_eq!(bucket("+Inf"), 0);
+    }
+}
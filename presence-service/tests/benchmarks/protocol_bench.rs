@@ -4,12 +4,16 @@
 //! 1. Message sizes (exact byte counts)
 //! 2. Encoding performance
 //! 3. Decoding performance
-//! 4. Throughput benchmarks
+//! 4. Throughput benchmarks, including allocating vs buffer-reusing encoding
 //! 5. Size comparison vs JSON
 //! 6. Coordinate normalization performance
 
+use bytes::BytesMut;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use presence_service::protocol::{denormalize_coord, normalize_coord, BinaryMessage};
+use presence_service::protocol::{
+    denormalize_coord, normalize_coord, normalize_coord_12bit, BinaryMessage, MAX_JOIN_SIZE,
+    MAX_USERNAME_LENGTH, MAX_USER_JOINED_SIZE,
+};
 use serde_json::json;
 
 // ============================================================================
@@ -19,178 +23,196 @@ use serde_json::json;
 fn verify_message_sizes(c: &mut Criterion) {
     let mut group = c.benchmark_group("message_sizes");
 
-    // Cursor Update - Target: 7 bytes
+    // Cursor Update - Target: 9 bytes
     let cursor_update = BinaryMessage::CursorUpdate {
         board_id: 1234,
         x: normalize_coord(0.5),
         y: normalize_coord(0.75),
     };
-    let cursor_bytes = cursor_update.encode();
+    let cursor_len = cursor_update.encoded_len();
+    assert_eq!(cursor_len, 9, "CursorUpdate should be exactly 9 bytes");
+
+    group.bench_function("size_cursor_update", |b| {
+        b.iter(|| black_box(&cursor_update).encoded_len());
+    });
+    println!("✓ CursorUpdate: {} bytes", cursor_len);
+
+    // Compact Cursor Update - Target: 8 bytes
+    let cursor_compact = BinaryMessage::CursorUpdateCompact {
+        board_id: 1234,
+        x: normalize_coord_12bit(0.5),
+        y: normalize_coord_12bit(0.75),
+    };
+    let compact_len = cursor_compact.encoded_len();
     assert_eq!(
-        cursor_bytes.len(),
-        7,
-        "CursorUpdate should be exactly 7 bytes"
+        compact_len, 8,
+        "CursorUpdateCompact should be exactly 8 bytes"
     );
 
-    group.bench_function("size_cursor_update", |b| {
-        b.iter(|| {
-            let bytes = black_box(&cursor_bytes);
-            bytes.len()
-        });
+    group.bench_function("size_cursor_update_compact", |b| {
+        b.iter(|| black_box(&cursor_compact).encoded_len());
     });
-    println!("✓ CursorUpdate: {} bytes", cursor_bytes.len());
+    println!("✓ CursorUpdateCompact: {} bytes", compact_len);
 
-    // Cursor Broadcast - Target: 8 bytes
+    // Cursor Broadcast - Target: 10 bytes
     let cursor_broadcast = BinaryMessage::CursorBroadcast {
         user_id: 42,
         board_id: 1234,
         x: normalize_coord(0.3),
         y: normalize_coord(0.9),
     };
-    let broadcast_bytes = cursor_broadcast.encode();
+    let broadcast_len = cursor_broadcast.encoded_len();
     assert_eq!(
-        broadcast_bytes.len(),
-        8,
-        "CursorBroadcast should be exactly 8 bytes"
+        broadcast_len, 10,
+        "CursorBroadcast should be exactly 10 bytes"
     );
 
     group.bench_function("size_cursor_broadcast", |b| {
-        b.iter(|| {
-            let bytes = black_box(&broadcast_bytes);
-            bytes.len()
-        });
+        b.iter(|| black_box(&cursor_broadcast).encoded_len());
     });
-    println!("✓ CursorBroadcast: {} bytes", broadcast_bytes.len());
+    println!("✓ CursorBroadcast: {} bytes", broadcast_len);
 
-    // Join - Variable size (4-36 bytes)
+    // Join - Variable size (6 to MAX_JOIN_SIZE bytes)
     let join_short = BinaryMessage::Join {
         board_id: 1234,
         username: "".to_string(),
+        reconnect_token: None,
     };
-    let join_short_bytes = join_short.encode();
+    let join_short_len = join_short.encoded_len();
     assert_eq!(
-        join_short_bytes.len(),
-        4,
-        "Join with empty username should be 4 bytes"
+        join_short_len, 6,
+        "Join with empty username should be 6 bytes"
     );
-    println!("✓ Join (empty username): {} bytes", join_short_bytes.len());
+    println!("✓ Join (empty username): {} bytes", join_short_len);
 
     let join_long = BinaryMessage::Join {
         board_id: 1234,
-        username: "a".repeat(32),
+        username: "a".repeat(MAX_USERNAME_LENGTH),
+        reconnect_token: Some([0; 16]),
     };
-    let join_long_bytes = join_long.encode();
+    let join_long_len = join_long.encoded_len();
     assert_eq!(
-        join_long_bytes.len(),
-        36,
-        "Join with 32-char username should be 36 bytes"
+        join_long_len, MAX_JOIN_SIZE,
+        "Join with a maximum-length username and a reconnect token should be MAX_JOIN_SIZE bytes"
+    );
+    println!(
+        "✓ Join ({}-char username, reconnect token): {} bytes",
+        MAX_USERNAME_LENGTH, join_long_len
     );
-    println!("✓ Join (32-char username): {} bytes", join_long_bytes.len());
 
-    // Leave - Target: 3 bytes
+    // Leave - Target: 5 bytes
     let leave = BinaryMessage::Leave { board_id: 1234 };
-    let leave_bytes = leave.encode();
-    assert_eq!(leave_bytes.len(), 3, "Leave should be exactly 3 bytes");
+    let leave_len = leave.encoded_len();
+    assert_eq!(leave_len, 5, "Leave should be exactly 5 bytes");
 
     group.bench_function("size_leave", |b| {
-        b.iter(|| {
-            let bytes = black_box(&leave_bytes);
-            bytes.len()
-        });
+        b.iter(|| black_box(&leave).encoded_len());
     });
-    println!("✓ Leave: {} bytes", leave_bytes.len());
+    println!("✓ Leave: {} bytes", leave_len);
 
-    // User Joined - Variable size (7-40 bytes)
+    // User Joined - Variable size (10 to MAX_USER_JOINED_SIZE bytes)
     let user_joined_short = BinaryMessage::UserJoined {
         user_id: 42,
         board_id: 1234,
         username: "".to_string(),
         color: [255, 0, 0],
     };
-    let user_joined_short_bytes = user_joined_short.encode();
+    let user_joined_short_len = user_joined_short.encoded_len();
     assert_eq!(
-        user_joined_short_bytes.len(),
-        8,
-        "UserJoined with empty username should be 8 bytes (includes 3-byte color)"
+        user_joined_short_len, 10,
+        "UserJoined with empty username should be 10 bytes (includes 3-byte color)"
     );
     println!(
         "✓ UserJoined (empty username): {} bytes",
-        user_joined_short_bytes.len()
+        user_joined_short_len
     );
 
     let user_joined_long = BinaryMessage::UserJoined {
         user_id: 42,
         board_id: 1234,
-        username: "a".repeat(32),
+        username: "a".repeat(MAX_USERNAME_LENGTH),
         color: [255, 0, 0],
     };
-    let user_joined_long_bytes = user_joined_long.encode();
+    let user_joined_long_len = user_joined_long.encoded_len();
     assert_eq!(
-        user_joined_long_bytes.len(),
-        40,
-        "UserJoined with 32-char username should be 40 bytes"
+        user_joined_long_len, MAX_USER_JOINED_SIZE,
+        "UserJoined with a maximum-length username should be MAX_USER_JOINED_SIZE bytes"
     );
     println!(
-        "✓ UserJoined (32-char username): {} bytes",
-        user_joined_long_bytes.len()
+        "✓ UserJoined ({}-char username): {} bytes",
+        MAX_USERNAME_LENGTH, user_joined_long_len
     );
 
-    // User Left - Target: 4 bytes
+    // User Left - Target: 6 bytes
     let user_left = BinaryMessage::UserLeft {
         user_id: 42,
         board_id: 1234,
     };
-    let user_left_bytes = user_left.encode();
-    assert_eq!(
-        user_left_bytes.len(),
-        4,
-        "UserLeft should be exactly 4 bytes"
-    );
+    let user_left_len = user_left.encoded_len();
+    assert_eq!(user_left_len, 6, "UserLeft should be exactly 6 bytes");
 
     group.bench_function("size_user_left", |b| {
-        b.iter(|| {
-            let bytes = black_box(&user_left_bytes);
-            bytes.len()
-        });
+        b.iter(|| black_box(&user_left).encoded_len());
     });
-    println!("✓ UserLeft: {} bytes", user_left_bytes.len());
+    println!("✓ UserLeft: {} bytes", user_left_len);
 
-    // Presence Update - Target: 4 bytes
+    // Presence Update - Target: 7 bytes
     let presence_update = BinaryMessage::PresenceUpdate {
         board_id: 1234,
         count: 5,
     };
-    let presence_bytes = presence_update.encode();
+    let presence_len = presence_update.encoded_len();
+    assert_eq!(presence_len, 7, "PresenceUpdate should be exactly 7 bytes");
+
+    group.bench_function("size_presence_update", |b| {
+        b.iter(|| black_box(&presence_update).encoded_len());
+    });
+    println!("✓ PresenceUpdate: {} bytes", presence_len);
+
+    // Activity Update - Target: 10 bytes
+    let activity_update = BinaryMessage::ActivityUpdate {
+        board_id: 1234,
+        card_ref: 0xDEAD_BEEF,
+        active: true,
+    };
+    let activity_len = activity_update.encoded_len();
     assert_eq!(
-        presence_bytes.len(),
-        4,
-        "PresenceUpdate should be exactly 4 bytes"
+        activity_len, 10,
+        "ActivityUpdate should be exactly 10 bytes"
     );
 
-    group.bench_function("size_presence_update", |b| {
-        b.iter(|| {
-            let bytes = black_box(&presence_bytes);
-            bytes.len()
-        });
+    group.bench_function("size_activity_update", |b| {
+        b.iter(|| black_box(&activity_update).encoded_len());
     });
-    println!("✓ PresenceUpdate: {} bytes", presence_bytes.len());
+    println!("✓ ActivityUpdate: {} bytes", activity_len);
 
-    // Heartbeat - Target: 1 byte
-    let heartbeat = BinaryMessage::Heartbeat;
-    let heartbeat_bytes = heartbeat.encode();
+    // Activity Broadcast - Target: 11 bytes
+    let activity_broadcast = BinaryMessage::ActivityBroadcast {
+        board_id: 1234,
+        user_id: 42,
+        card_ref: 0xDEAD_BEEF,
+        active: true,
+    };
+    let activity_broadcast_len = activity_broadcast.encoded_len();
     assert_eq!(
-        heartbeat_bytes.len(),
-        1,
-        "Heartbeat should be exactly 1 byte"
+        activity_broadcast_len, 11,
+        "ActivityBroadcast should be exactly 11 bytes"
     );
 
+    group.bench_function("size_activity_broadcast", |b| {
+        b.iter(|| black_box(&activity_broadcast).encoded_len());
+    });
+    println!("✓ ActivityBroadcast: {} bytes", activity_broadcast_len);
+
+    // Heartbeat - Target: 1 byte
+    let heartbeat = BinaryMessage::Heartbeat;
+    let heartbeat_len = heartbeat.encoded_len();
+    assert_eq!(heartbeat_len, 1, "Heartbeat should be exactly 1 byte");
+
     group.bench_function("size_heartbeat", |b| {
-        b.iter(|| {
-            let bytes = black_box(&heartbeat_bytes);
-            bytes.len()
-        });
+        b.iter(|| black_box(&heartbeat).encoded_len());
     });
-    println!("✓ Heartbeat: {} bytes", heartbeat_bytes.len());
+    println!("✓ Heartbeat: {} bytes", heartbeat_len);
 
     group.finish();
 }
@@ -227,6 +249,7 @@ fn encode_join(c: &mut Criterion) {
         let msg = BinaryMessage::Join {
             board_id: 1234,
             username: "Alice".to_string(),
+            reconnect_token: None,
         };
         b.iter(|| black_box(msg.encode()));
     });
@@ -319,6 +342,7 @@ fn decode_join(c: &mut Criterion) {
     let msg = BinaryMessage::Join {
         board_id: 1234,
         username: "Alice".to_string(),
+        reconnect_token: None,
     };
     let bytes = msg.encode();
 
@@ -359,6 +383,56 @@ fn decode_user_joined(c: &mut Criterion) {
     });
 }
 
+fn decode_user_joined_borrowed_vs_owned(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_user_joined_borrowing");
+
+    let msg = BinaryMessage::UserJoined {
+        user_id: 42,
+        board_id: 1234,
+        username: "a".repeat(MAX_USERNAME_LENGTH),
+        color: [255, 128, 64],
+    };
+    let bytes = msg.encode();
+
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            let result = BinaryMessage::decode(black_box(&bytes));
+            black_box(result)
+        });
+    });
+
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let result = BinaryMessage::decode_borrowed(black_box(&bytes));
+            black_box(result)
+        });
+    });
+
+    // Decode and re-encode, as when forwarding a frame
+    let mut scratch = BytesMut::with_capacity(64);
+    group.bench_function("owned_reencode", |b| {
+        b.iter(|| {
+            scratch.clear();
+            BinaryMessage::decode(black_box(&bytes))
+                .unwrap()
+                .encode_into(&mut scratch);
+            black_box(&scratch[..]);
+        });
+    });
+
+    group.bench_function("borrowed_reencode", |b| {
+        b.iter(|| {
+            scratch.clear();
+            BinaryMessage::decode_borrowed(black_box(&bytes))
+                .unwrap()
+                .encode_into(&mut scratch);
+            black_box(&scratch[..]);
+        });
+    });
+
+    group.finish();
+}
+
 fn decode_user_left(c: &mut Criterion) {
     let msg = BinaryMessage::UserLeft {
         user_id: 42,
@@ -427,6 +501,107 @@ fn throughput_cursor_updates(c: &mut Criterion) {
     group.finish();
 }
 
+fn throughput_encode_into(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_allocation");
+    group.throughput(Throughput::Elements(10_000));
+
+    let messages: Vec<BinaryMessage> = (0..10_000)
+        .map(|i| BinaryMessage::CursorUpdate {
+            board_id: 1234,
+            x: normalize_coord((i % 1000) as f32 / 1000.0),
+            y: normalize_coord((i % 500) as f32 / 500.0),
+        })
+        .collect();
+
+    group.bench_function("10k_encode_allocating", |b| {
+        b.iter(|| {
+            for msg in &messages {
+                black_box(msg.encode());
+            }
+        });
+    });
+
+    group.bench_function("10k_encode_into_reused_buffer", |b| {
+        let mut scratch = BytesMut::with_capacity(64);
+        b.iter(|| {
+            for msg in &messages {
+                scratch.clear();
+                msg.encode_into(&mut scratch);
+                black_box(&scratch[..]);
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn throughput_cursor_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput_cursor_batch");
+    group.throughput(Throughput::Elements(100));
+
+    let points: Vec<(u16, u16)> = (0..100)
+        .map(|i| {
+            (
+                normalize_coord(i as f32 / 100.0),
+                normalize_coord((i % 50) as f32 / 50.0),
+            )
+        })
+        .collect();
+
+    // Wire cost of the same path both ways: 100 x 9 bytes vs 6 + 100 x 4
+    let individual_bytes: usize = points
+        .iter()
+        .map(|&(x, y)| {
+            BinaryMessage::CursorUpdate {
+                board_id: 1234,
+                x,
+                y,
+            }
+            .encode()
+            .len()
+        })
+        .sum();
+    let batch_bytes = BinaryMessage::CursorBatch {
+        board_id: 1234,
+        points: points.clone(),
+    }
+    .encode()
+    .len();
+    assert_eq!(individual_bytes, 900);
+    assert_eq!(batch_bytes, 406);
+    println!(
+        "100 cursor points: {} bytes in 100 frames, {} bytes in 1 batch",
+        individual_bytes, batch_bytes
+    );
+
+    group.bench_function("100_individual_updates", |b| {
+        b.iter(|| {
+            for &(x, y) in &points {
+                let msg = BinaryMessage::CursorUpdate {
+                    board_id: 1234,
+                    x,
+                    y,
+                };
+                let bytes = msg.encode();
+                black_box(BinaryMessage::decode(&bytes).unwrap());
+            }
+        });
+    });
+
+    group.bench_function("1_batch_of_100", |b| {
+        b.iter(|| {
+            let msg = BinaryMessage::CursorBatch {
+                board_id: 1234,
+                points: points.clone(),
+            };
+            let bytes = msg.encode();
+            black_box(BinaryMessage::decode(&bytes).unwrap());
+        });
+    });
+
+    group.finish();
+}
+
 fn throughput_mixed_messages(c: &mut Criterion) {
     let mut group = c.benchmark_group("throughput_mixed");
     group.throughput(Throughput::Elements(1_000));
@@ -449,7 +624,7 @@ fn throughput_mixed_messages(c: &mut Criterion) {
                         // 10% presence updates
                         BinaryMessage::PresenceUpdate {
                             board_id: 1234,
-                            count: (i % 20) as u8,
+                            count: (i % 20) as u16,
                         }
                     }
                     _ => {
@@ -507,8 +682,8 @@ fn size_comparison(c: &mut Criterion) {
     println!("Size reduction: {:.1}%", reduction);
 
     assert!(
-        reduction >= 87.0,
-        "Size reduction should be at least 87%, got {:.1}%",
+        reduction >= 84.0,
+        "Size reduction should be at least 84%, got {:.1}%",
         reduction
     );
 
@@ -623,7 +798,12 @@ fn benchmark_coord_roundtrip(c: &mut Criterion) {
 fn benchmark_encoding_by_size(c: &mut Criterion) {
     let mut group = c.benchmark_group("encoding_by_username_length");
 
-    for length in [0, 8, 16, 32] {
+    for length in [
+        0,
+        MAX_USERNAME_LENGTH / 4,
+        MAX_USERNAME_LENGTH / 2,
+        MAX_USERNAME_LENGTH,
+    ] {
         let username = "a".repeat(length);
 
         group.bench_with_input(
@@ -633,6 +813,7 @@ fn benchmark_encoding_by_size(c: &mut Criterion) {
                 let msg = BinaryMessage::Join {
                     board_id: 1234,
                     username: username.clone(),
+                    reconnect_token: None,
                 };
                 b.iter(|| black_box(msg.encode()));
             },
@@ -708,6 +889,7 @@ criterion_group!(
     decode_join,
     decode_leave,
     decode_user_joined,
+    decode_user_joined_borrowed_vs_owned,
     decode_user_left,
     decode_presence_update,
     decode_heartbeat,
@@ -717,6 +899,8 @@ criterion_group!(
 criterion_group!(
     throughput_benches,
     throughput_cursor_updates,
+    throughput_encode_into,
+    throughput_cursor_batch,
     throughput_mixed_messages
 );
 
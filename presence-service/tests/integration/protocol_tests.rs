@@ -1 +1,918 @@
-// TODO: Implement protocol integration tests
+//! Protocol conformance vectors.
+//!
+//! Canonical encodings of every `BinaryMessage` variant, as hex, next to the
+//! value they decode to. Client implementations in other languages can port
+//! this table verbatim: a conforming client produces exactly these bytes and
+//! reads them back to the same fields. Any change to a vector here is a wire
+//! format change and needs a protocol version bump.
+//!
+//! Each variant has a minimum, a typical and a maximum case. The maximum
+//! username, announcement and cursor batch vectors are written out for the
+//! current `MAX_USERNAME_LENGTH` (32), `MAX_ANNOUNCEMENT_LENGTH` (255) and
+//! `MAX_CURSOR_BATCH_POINTS` (255).
+
+use presence_service::protocol::{
+    message_type_name, BinaryMessage, MAX_ANNOUNCEMENT_LENGTH, MAX_CURSOR_BATCH_POINTS,
+    MAX_CURSOR_BATCH_SIZE, MAX_CURSOR_BROADCAST_BATCH_SIZE, MAX_JOIN_SIZE, MAX_USERNAME_LENGTH,
+    MAX_USER_JOINED_SIZE, NO_CARD_FOCUSED,
+};
+use std::collections::HashSet;
+
+/// One canonical frame and the message it encodes
+pub struct ConformanceVector {
+    pub name: &'static str,
+    /// Lowercase hex of the complete frame
+    pub hex: &'static str,
+    pub message: BinaryMessage,
+}
+
+/// The full conformance table
+pub fn vectors() -> Vec<ConformanceVector> {
+    vec![
+        ConformanceVector {
+            name: "cursor_update_min",
+            hex: "010000000000000000",
+            message: BinaryMessage::CursorUpdate {
+                board_id: 0,
+                x: 0,
+                y: 0,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_update_typical",
+            hex: "01000004d280004000",
+            message: BinaryMessage::CursorUpdate {
+                board_id: 1234,
+                x: 32768,
+                y: 16384,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_update_max",
+            hex: "01ffffffffffffffff",
+            message: BinaryMessage::CursorUpdate {
+                board_id: 4294967295,
+                x: 65535,
+                y: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_min",
+            hex: "02000000000000000000",
+            message: BinaryMessage::CursorBroadcast {
+                board_id: 0,
+                user_id: 0,
+                x: 0,
+                y: 0,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_typical",
+            hex: "02000004d20780004000",
+            message: BinaryMessage::CursorBroadcast {
+                board_id: 1234,
+                user_id: 7,
+                x: 32768,
+                y: 16384,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_max",
+            hex: "02ffffffffffffffffff",
+            message: BinaryMessage::CursorBroadcast {
+                board_id: 4294967295,
+                user_id: 255,
+                x: 65535,
+                y: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "join_min",
+            hex: "030000000000",
+            message: BinaryMessage::Join {
+                board_id: 0,
+                username: String::new(),
+                reconnect_token: None,
+            },
+        },
+        ConformanceVector {
+            name: "join_typical",
+            hex: "03000004d205416c696365",
+            message: BinaryMessage::Join {
+                board_id: 1234,
+                username: "Alice".to_string(),
+                reconnect_token: None,
+            },
+        },
+        ConformanceVector {
+            name: "join_utf8",
+            hex: "030000002a045a6fc3ab",
+            message: BinaryMessage::Join {
+                board_id: 42,
+                username: "Zoë".to_string(),
+                reconnect_token: None,
+            },
+        },
+        ConformanceVector {
+            name: "join_reconnect_token",
+            hex: "03000004d205416c696365000102030405060708090a0b0c0d0e0f",
+            message: BinaryMessage::Join {
+                board_id: 1234,
+                username: "Alice".to_string(),
+                reconnect_token: Some([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]),
+            },
+        },
+        ConformanceVector {
+            name: "join_max",
+            hex: concat!(
+                "03ffffffff206161616161616161616161616161616161616161616161616161",
+                "616161616161ffffffffffffffffffffffffffffffff",
+            ),
+            message: BinaryMessage::Join {
+                board_id: 4294967295,
+                username: "a".repeat(32),
+                reconnect_token: Some([0xFF; 16]),
+            },
+        },
+        ConformanceVector {
+            name: "leave_min",
+            hex: "0400000000",
+            message: BinaryMessage::Leave { board_id: 0 },
+        },
+        ConformanceVector {
+            name: "leave_typical",
+            hex: "04000004d2",
+            message: BinaryMessage::Leave { board_id: 1234 },
+        },
+        ConformanceVector {
+            name: "leave_max",
+            hex: "04ffffffff",
+            message: BinaryMessage::Leave {
+                board_id: 4294967295,
+            },
+        },
+        ConformanceVector {
+            name: "user_joined_min",
+            hex: "05000000000000000000",
+            message: BinaryMessage::UserJoined {
+                board_id: 0,
+                user_id: 0,
+                username: String::new(),
+                color: [0, 0, 0],
+            },
+        },
+        ConformanceVector {
+            name: "user_joined_typical",
+            hex: "05000004d20703426f62ff5733",
+            message: BinaryMessage::UserJoined {
+                board_id: 1234,
+                user_id: 7,
+                username: "Bob".to_string(),
+                color: [255, 87, 51],
+            },
+        },
+        ConformanceVector {
+            name: "user_joined_max",
+            hex: concat!(
+                "05ffffffffff2061616161616161616161616161616161616161616161616161",
+                "61616161616161ffffff",
+            ),
+            message: BinaryMessage::UserJoined {
+                board_id: 4294967295,
+                user_id: 255,
+                username: "a".repeat(32),
+                color: [255, 255, 255],
+            },
+        },
+        ConformanceVector {
+            name: "user_left_min",
+            hex: "060000000000",
+            message: BinaryMessage::UserLeft {
+                board_id: 0,
+                user_id: 0,
+            },
+        },
+        ConformanceVector {
+            name: "user_left_typical",
+            hex: "06000004d207",
+            message: BinaryMessage::UserLeft {
+                board_id: 1234,
+                user_id: 7,
+            },
+        },
+        ConformanceVector {
+            name: "user_left_max",
+            hex: "06ffffffffff",
+            message: BinaryMessage::UserLeft {
+                board_id: 4294967295,
+                user_id: 255,
+            },
+        },
+        ConformanceVector {
+            name: "presence_update_min",
+            hex: "07000000000000",
+            message: BinaryMessage::PresenceUpdate {
+                board_id: 0,
+                count: 0,
+            },
+        },
+        ConformanceVector {
+            name: "presence_update_typical",
+            hex: "07000004d20003",
+            message: BinaryMessage::PresenceUpdate {
+                board_id: 1234,
+                count: 3,
+            },
+        },
+        ConformanceVector {
+            name: "presence_update_max",
+            hex: "07ffffffffffff",
+            message: BinaryMessage::PresenceUpdate {
+                board_id: 4294967295,
+                count: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "heartbeat",
+            hex: "08",
+            message: BinaryMessage::Heartbeat,
+        },
+        ConformanceVector {
+            name: "hello_min",
+            hex: "0900",
+            message: BinaryMessage::Hello {
+                version: 0,
+                cursor_interval_ms: None,
+            },
+        },
+        ConformanceVector {
+            name: "hello_typical",
+            hex: "0904",
+            message: BinaryMessage::Hello {
+                version: 4,
+                cursor_interval_ms: None,
+            },
+        },
+        ConformanceVector {
+            name: "hello_max",
+            hex: "09ff",
+            message: BinaryMessage::Hello {
+                version: 255,
+                cursor_interval_ms: None,
+            },
+        },
+        ConformanceVector {
+            name: "hello_cursor_interval",
+            hex: "09060032",
+            message: BinaryMessage::Hello {
+                version: 6,
+                cursor_interval_ms: Some(50),
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_timed_min",
+            hex: "0a00000000000000000000000000",
+            message: BinaryMessage::CursorBroadcastTimed {
+                board_id: 0,
+                user_id: 0,
+                x: 0,
+                y: 0,
+                tick: 0,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_timed_typical",
+            hex: "0a000004d207800040000001d4c0",
+            message: BinaryMessage::CursorBroadcastTimed {
+                board_id: 1234,
+                user_id: 7,
+                x: 32768,
+                y: 16384,
+                tick: 120000,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_timed_max",
+            hex: "0affffffffffffffffffffffffff",
+            message: BinaryMessage::CursorBroadcastTimed {
+                board_id: 4294967295,
+                user_id: 255,
+                x: 65535,
+                y: 65535,
+                tick: 4294967295,
+            },
+        },
+        ConformanceVector {
+            name: "focus_card_min",
+            hex: "0b000000000000",
+            message: BinaryMessage::FocusCard {
+                board_id: 0,
+                card_slot: NO_CARD_FOCUSED,
+            },
+        },
+        ConformanceVector {
+            name: "focus_card_typical",
+            hex: "0b000004d2002a",
+            message: BinaryMessage::FocusCard {
+                board_id: 1234,
+                card_slot: 42,
+            },
+        },
+        ConformanceVector {
+            name: "focus_card_max",
+            hex: "0bffffffffffff",
+            message: BinaryMessage::FocusCard {
+                board_id: 4294967295,
+                card_slot: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "focus_broadcast_min",
+            hex: "0c00000000000000",
+            message: BinaryMessage::FocusBroadcast {
+                board_id: 0,
+                user_id: 0,
+                card_slot: NO_CARD_FOCUSED,
+            },
+        },
+        ConformanceVector {
+            name: "focus_broadcast_typical",
+            hex: "0c000004d207002a",
+            message: BinaryMessage::FocusBroadcast {
+                board_id: 1234,
+                user_id: 7,
+                card_slot: 42,
+            },
+        },
+        ConformanceVector {
+            name: "focus_broadcast_max",
+            hex: "0cffffffffffffff",
+            message: BinaryMessage::FocusBroadcast {
+                board_id: 4294967295,
+                user_id: 255,
+                card_slot: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "sequenced_min",
+            hex: "0d0000000008",
+            message: BinaryMessage::Sequenced {
+                seq: 0,
+                message: Box::new(BinaryMessage::Heartbeat),
+            },
+        },
+        ConformanceVector {
+            name: "sequenced_typical",
+            hex: "0d0000001107000004d20003",
+            message: BinaryMessage::Sequenced {
+                seq: 17,
+                message: Box::new(BinaryMessage::PresenceUpdate {
+                    board_id: 1234,
+                    count: 3,
+                }),
+            },
+        },
+        ConformanceVector {
+            name: "sequenced_max",
+            hex: concat!(
+                "0dffffffff05ffffffffff206161616161616161616161616161616161616161",
+                "616161616161616161616161ffffff",
+            ),
+            message: BinaryMessage::Sequenced {
+                seq: 4294967295,
+                message: Box::new(BinaryMessage::UserJoined {
+                    board_id: 4294967295,
+                    user_id: 255,
+                    username: "a".repeat(32),
+                    color: [255, 255, 255],
+                }),
+            },
+        },
+        ConformanceVector {
+            name: "announcement_min",
+            hex: "0e0000000000",
+            message: BinaryMessage::Announcement {
+                board_id: 0,
+                text: String::new(),
+            },
+        },
+        ConformanceVector {
+            name: "announcement_typical",
+            hex: "0e000004d20f4465706c6f792061742031373a3030",
+            message: BinaryMessage::Announcement {
+                board_id: 1234,
+                text: "Deploy at 17:00".to_string(),
+            },
+        },
+        ConformanceVector {
+            name: "announcement_max",
+            hex: concat!(
+                "0effffffffff6161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161616161616161616161616161616161616161616161616161616161",
+                "6161616161",
+            ),
+            message: BinaryMessage::Announcement {
+                board_id: 4294967295,
+                text: "a".repeat(255),
+            },
+        },
+        ConformanceVector {
+            name: "ping_min",
+            hex: "0f00000000",
+            message: BinaryMessage::Ping { nonce: 0 },
+        },
+        ConformanceVector {
+            name: "ping_typical",
+            hex: "0fdeadbeef",
+            message: BinaryMessage::Ping { nonce: 3735928559 },
+        },
+        ConformanceVector {
+            name: "ping_max",
+            hex: "0fffffffff",
+            message: BinaryMessage::Ping { nonce: 4294967295 },
+        },
+        ConformanceVector {
+            name: "pong_min",
+            hex: "1000000000",
+            message: BinaryMessage::Pong { nonce: 0 },
+        },
+        ConformanceVector {
+            name: "pong_typical",
+            hex: "10deadbeef",
+            message: BinaryMessage::Pong { nonce: 3735928559 },
+        },
+        ConformanceVector {
+            name: "pong_max",
+            hex: "10ffffffff",
+            message: BinaryMessage::Pong { nonce: 4294967295 },
+        },
+        ConformanceVector {
+            name: "query_presence_min",
+            hex: "1100000000",
+            message: BinaryMessage::QueryPresence { board_id: 0 },
+        },
+        ConformanceVector {
+            name: "query_presence_typical",
+            hex: "11000004d2",
+            message: BinaryMessage::QueryPresence { board_id: 1234 },
+        },
+        ConformanceVector {
+            name: "query_presence_max",
+            hex: "11ffffffff",
+            message: BinaryMessage::QueryPresence {
+                board_id: 4294967295,
+            },
+        },
+        ConformanceVector {
+            name: "correlated_min",
+            hex: "12000008",
+            message: BinaryMessage::Correlated {
+                msg_id: 0,
+                message: Box::new(BinaryMessage::Heartbeat),
+            },
+        },
+        ConformanceVector {
+            name: "correlated_typical",
+            hex: "12002a03000004d205416c696365",
+            message: BinaryMessage::Correlated {
+                msg_id: 42,
+                message: Box::new(BinaryMessage::Join {
+                    board_id: 1234,
+                    username: "Alice".to_string(),
+                    reconnect_token: None,
+                }),
+            },
+        },
+        ConformanceVector {
+            name: "correlated_max",
+            hex: concat!(
+                "12ffff05ffffffffff2061616161616161616161616161616161616161616161",
+                "61616161616161616161ffffff",
+            ),
+            message: BinaryMessage::Correlated {
+                msg_id: 65535,
+                message: Box::new(BinaryMessage::UserJoined {
+                    board_id: 4294967295,
+                    user_id: 255,
+                    username: "a".repeat(32),
+                    color: [255, 255, 255],
+                }),
+            },
+        },
+        ConformanceVector {
+            name: "cursor_update_compact_min",
+            hex: "1300000000000000",
+            message: BinaryMessage::CursorUpdateCompact {
+                board_id: 0,
+                x: 0,
+                y: 0,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_update_compact_typical",
+            hex: "13000004d2800400",
+            message: BinaryMessage::CursorUpdateCompact {
+                board_id: 1234,
+                x: 2048,
+                y: 1024,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_update_compact_max",
+            hex: "13ffffffffffffff",
+            message: BinaryMessage::CursorUpdateCompact {
+                board_id: 4294967295,
+                x: 4095,
+                y: 4095,
+            },
+        },
+        ConformanceVector {
+            name: "cursor_batch_min",
+            hex: "14000000000100000000",
+            message: BinaryMessage::CursorBatch {
+                board_id: 0,
+                points: vec![(0, 0)],
+            },
+        },
+        ConformanceVector {
+            name: "cursor_batch_typical",
+            hex: "14000004d2027fff3fff80004000",
+            message: BinaryMessage::CursorBatch {
+                board_id: 1234,
+                points: vec![(32767, 16383), (32768, 16384)],
+            },
+        },
+        ConformanceVector {
+            name: "cursor_batch_max",
+            hex: concat!(
+                "14ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffff",
+            ),
+            message: BinaryMessage::CursorBatch {
+                board_id: 4294967295,
+                points: vec![(65535, 65535); MAX_CURSOR_BATCH_POINTS],
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_batch_min",
+            hex: "1500000000000100000000",
+            message: BinaryMessage::CursorBroadcastBatch {
+                board_id: 0,
+                user_id: 0,
+                points: vec![(0, 0)],
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_batch_typical",
+            hex: "15000004d205027fff3fff80004000",
+            message: BinaryMessage::CursorBroadcastBatch {
+                board_id: 1234,
+                user_id: 5,
+                points: vec![(32767, 16383), (32768, 16384)],
+            },
+        },
+        ConformanceVector {
+            name: "cursor_broadcast_batch_max",
+            hex: concat!(
+                "15ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "ffffff",
+            ),
+            message: BinaryMessage::CursorBroadcastBatch {
+                board_id: 4294967295,
+                user_id: 255,
+                points: vec![(65535, 65535); MAX_CURSOR_BATCH_POINTS],
+            },
+        },
+        ConformanceVector {
+            name: "activity_update_min",
+            hex: "16000000000000000000",
+            message: BinaryMessage::ActivityUpdate {
+                board_id: 0,
+                card_ref: 0,
+                active: false,
+            },
+        },
+        ConformanceVector {
+            name: "activity_update_typical",
+            hex: "16000004d2deadbeef01",
+            message: BinaryMessage::ActivityUpdate {
+                board_id: 1234,
+                card_ref: 3735928559,
+                active: true,
+            },
+        },
+        ConformanceVector {
+            name: "activity_update_max",
+            hex: "16ffffffffffffffff01",
+            message: BinaryMessage::ActivityUpdate {
+                board_id: 4294967295,
+                card_ref: 4294967295,
+                active: true,
+            },
+        },
+        ConformanceVector {
+            name: "activity_broadcast_min",
+            hex: "1700000000000000000000",
+            message: BinaryMessage::ActivityBroadcast {
+                board_id: 0,
+                user_id: 0,
+                card_ref: 0,
+                active: false,
+            },
+        },
+        ConformanceVector {
+            name: "activity_broadcast_typical",
+            hex: "17000004d2050000002a01",
+            message: BinaryMessage::ActivityBroadcast {
+                board_id: 1234,
+                user_id: 5,
+                card_ref: 42,
+                active: true,
+            },
+        },
+        ConformanceVector {
+            name: "activity_broadcast_max",
+            hex: "17ffffffffffffffffff01",
+            message: BinaryMessage::ActivityBroadcast {
+                board_id: 4294967295,
+                user_id: 255,
+                card_ref: 4294967295,
+                active: true,
+            },
+        },
+        ConformanceVector {
+            name: "claim_card_min",
+            hex: "18000000000001",
+            message: BinaryMessage::ClaimCard {
+                board_id: 0,
+                card_slot: 1,
+            },
+        },
+        ConformanceVector {
+            name: "claim_card_typical",
+            hex: "18000004d2002a",
+            message: BinaryMessage::ClaimCard {
+                board_id: 1234,
+                card_slot: 42,
+            },
+        },
+        ConformanceVector {
+            name: "claim_card_max",
+            hex: "18ffffffffffff",
+            message: BinaryMessage::ClaimCard {
+                board_id: 4294967295,
+                card_slot: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "release_card_min",
+            hex: "19000000000001",
+            message: BinaryMessage::ReleaseCard {
+                board_id: 0,
+                card_slot: 1,
+            },
+        },
+        ConformanceVector {
+            name: "release_card_typical",
+            hex: "19000004d2002a",
+            message: BinaryMessage::ReleaseCard {
+                board_id: 1234,
+                card_slot: 42,
+            },
+        },
+        ConformanceVector {
+            name: "release_card_max",
+            hex: "19ffffffffffff",
+            message: BinaryMessage::ReleaseCard {
+                board_id: 4294967295,
+                card_slot: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "card_claimed_min",
+            hex: "1a00000000000001",
+            message: BinaryMessage::CardClaimed {
+                board_id: 0,
+                user_id: 0,
+                card_slot: 1,
+            },
+        },
+        ConformanceVector {
+            name: "card_claimed_typical",
+            hex: "1a000004d207002a",
+            message: BinaryMessage::CardClaimed {
+                board_id: 1234,
+                user_id: 7,
+                card_slot: 42,
+            },
+        },
+        ConformanceVector {
+            name: "card_claimed_max",
+            hex: "1affffffffffffff",
+            message: BinaryMessage::CardClaimed {
+                board_id: 4294967295,
+                user_id: 255,
+                card_slot: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "card_released_min",
+            hex: "1b00000000000001",
+            message: BinaryMessage::CardReleased {
+                board_id: 0,
+                user_id: 0,
+                card_slot: 1,
+            },
+        },
+        ConformanceVector {
+            name: "card_released_typical",
+            hex: "1b000004d207002a",
+            message: BinaryMessage::CardReleased {
+                board_id: 1234,
+                user_id: 7,
+                card_slot: 42,
+            },
+        },
+        ConformanceVector {
+            name: "card_released_max",
+            hex: "1bffffffffffffff",
+            message: BinaryMessage::CardReleased {
+                board_id: 4294967295,
+                user_id: 255,
+                card_slot: 65535,
+            },
+        },
+        ConformanceVector {
+            name: "error_min",
+            hex: "1c00",
+            message: BinaryMessage::Error { code: 0 },
+        },
+        ConformanceVector {
+            name: "error_typical",
+            hex: "1c02",
+            message: BinaryMessage::Error { code: 2 },
+        },
+        ConformanceVector {
+            name: "error_max",
+            hex: "1cff",
+            message: BinaryMessage::Error { code: 255 },
+        },
+    ]
+}
+
+/// Decode a lowercase hex string into bytes
+pub fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    assert_eq!(hex.len() % 2, 0, "odd-length hex string");
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("invalid hex digit"))
+        .collect()
+}
+
+#[test]
+fn test_encode_matches_vectors() {
+    for vector in vectors() {
+        assert_eq!(
+            vector.message.encode(),
+            hex_to_bytes(vector.hex),
+            "encoding of {} changed",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn test_decode_matches_vectors() {
+    for vector in vectors() {
+        let decoded = BinaryMessage::decode(&hex_to_bytes(vector.hex))
+            .unwrap_or_else(|e| panic!("{} failed to decode: {}", vector.name, e));
+        assert_eq!(
+            decoded, vector.message,
+            "decoding of {} changed",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn test_vector_names_unique() {
+    let mut names = HashSet::new();
+    for vector in vectors() {
+        assert!(
+            names.insert(vector.name),
+            "duplicate vector {}",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn test_every_message_type_has_vectors() {
+    let covered: HashSet<u8> = vectors()
+        .iter()
+        .map(|vector| vector.message.message_type())
+        .collect();
+
+    for msg_type in 0..=u8::MAX {
+        if let Some(name) = message_type_name(msg_type) {
+            assert!(
+                covered.contains(&msg_type),
+                "no conformance vector for {} (0x{:02X})",
+                name,
+                msg_type
+            );
+        }
+    }
+}
+
+#[test]
+fn test_max_vectors_follow_limits() {
+    // The written-out maximum cases must be updated if the limits change
+    assert_eq!(MAX_USERNAME_LENGTH, 32);
+    assert_eq!(MAX_ANNOUNCEMENT_LENGTH, 255);
+    assert_eq!(MAX_CURSOR_BATCH_POINTS, 255);
+
+    let len_of = |name: &str| {
+        let vector = vectors()
+            .into_iter()
+            .find(|vector| vector.name == name)
+            .unwrap();
+        hex_to_bytes(vector.hex).len()
+    };
+    assert_eq!(len_of("join_max"), MAX_JOIN_SIZE);
+    assert_eq!(len_of("user_joined_max"), MAX_USER_JOINED_SIZE);
+    assert_eq!(len_of("announcement_max"), 6 + MAX_ANNOUNCEMENT_LENGTH);
+    assert_eq!(len_of("cursor_batch_max"), MAX_CURSOR_BATCH_SIZE);
+    assert_eq!(
+        len_of("cursor_broadcast_batch_max"),
+        MAX_CURSOR_BROADCAST_BATCH_SIZE
+    );
+}